@@ -0,0 +1,869 @@
+//! Command-line arguments: the subcommands and the values their options take
+
+use clap::{Parser, ValueEnum};
+use licor_core::{DEFAULT_ANOMALY_THRESHOLD, NonFinite, DuplicateColumns, StabilityWindow, RobustStatistic, QcThresholds, SmoothingSpec, ClockAnchor, Harmonization, ColumnOrder, RowOrder, BoundaryLayerModel, UnitStyle, MetadataStandard, OutputProfile, ParquetCodec, SCHEMA_VERSION};
+use std::path::Path;
+
+// Parsed once per run, so the size of `Convert` doesn't matter
+#[allow(clippy::large_enum_variant)]
+#[derive(Parser)]
+pub(crate) enum Commands {
+    Convert {
+        /// Device type
+        #[arg(long, value_enum)]
+        device: Device,
+        
+        /// Measurement configuration
+        #[arg(long, value_enum)]
+        config: Config,
+        
+        /// Input files (supports glob patterns)
+        #[arg(long)]
+        input: String,
+        
+        /// Output directory for converted files
+        #[arg(long, required_unless_present_any = ["dsn", "dry_run"])]
+        output: Option<String>,
+        
+        /// Output file format
+        #[arg(long, value_enum, default_value = "parquet")]
+        format: OutputFormat,
+        
+        /// PostgreSQL connection string for --format postgres
+        #[arg(long, required_if_eq("format", "postgres"))]
+        dsn: Option<String>,
+        
+        /// Keep only the end of each step: "60s" for the last 60 seconds, or "5" for
+        /// the last 5 stable observations
+        #[arg(long, value_name = "WINDOW")]
+        trim_to_stable: Option<StabilityWindow>,
+        
+        /// Write one row per step instead of every observation: each variable's
+        /// `median` or `trimmed[:FRACTION]` mean, with its scaled MAD as `<VAR>_mad`
+        #[arg(long, value_name = "STAT")]
+        step_summary: Option<RobustStatistic>,
+        
+        /// Check the analyzers and air supply for leaks, an exhausted desiccant, a dry
+        /// humidifier or CO2 off its setpoint, adding a `qc_<CHECK>` column per check and
+        /// reporting flagged observations per file. Thresholds change as NAME=VALUE,...
+        #[arg(long, value_name = "THRESHOLDS", num_args = 0..=1, default_missing_value = "")]
+        qc: Option<QcThresholds>,
+        
+        /// Add a `stable_<VAR>` column for each criterion of the stability
+        /// definition, from the logged slopes and standard deviations
+        #[arg(long)]
+        stability_columns: bool,
+        
+        /// Map remarks into columns: `NAME=VALUE` pairs (separated by `;` or `,`)
+        /// become `remark_<NAME>`, other remarks `remark`, each holding the value in
+        /// effect on the rows logged after it
+        #[arg(long)]
+        remark_columns: bool,
+        
+        /// Add a smoothed copy of a variable as `<VAR>_smooth`: VAR:METHOD:WINDOW[:ORDER]
+        /// with METHOD mean, median or savgol (repeatable)
+        #[arg(long = "smooth", value_name = "SPEC")]
+        smoothing: Vec<SmoothingSpec>,
+        
+        /// Add derived columns: `wue` (A/E) and/or `iwue` (A/gsw), comma-separated,
+        /// or `NAME = EXPRESSION` over the other columns (repeatable)
+        #[arg(long, value_name = "VARS")]
+        derive: Vec<String>,
+        
+        /// Repeat a header value on every row: a metadata field such as
+        /// `device_serial` or `chamber_type`, or a raw header key (repeatable)
+        #[arg(long = "header-column", value_name = "KEY")]
+        header_columns: Vec<String>,
+        
+        /// Correct instrument clock drift: `REFERENCE=COLUMN VALUE`, e.g.
+        /// "2025-05-30T10:00:00Z=hhmmss 09:48:12" (repeat for a linear drift fit)
+        #[arg(long = "clock-anchor", value_name = "ANCHOR")]
+        clock_anchors: Vec<ClockAnchor>,
+        
+        /// TOML file naming the bits of status columns such as DIAG or GPIO,
+        /// each decoded into its own boolean column
+        #[arg(long, value_name = "FILE")]
+        decode_table: Option<String>,
+        
+        /// TOML file of per-console defaults (leaf area, operator, timezone,
+        /// variable values, calibration certificates) keyed by serial, applied
+        /// to files from each listed console (defaults to $LICOR_INSTRUMENTS)
+        #[arg(long, value_name = "FILE")]
+        instruments: Option<String>,
+        
+        /// Recompute A, E, gsw, gtc and Ci from the logged measurements as
+        /// `<VAR>_rc` columns, with the CO2 compensation point as GammaStar
+        #[arg(long)]
+        recompute: bool,
+        
+        /// Oxygen in the air stream (%) for --recompute, instead of the file's
+        /// SysConst:Oxygen
+        #[arg(long, value_name = "PERCENT", requires = "recompute")]
+        oxygen: Option<f64>,
+        
+        /// Boundary layer conductance for --recompute: logged, constant:GBW or
+        /// convection:SPEED:DIMENSION (air speed in m/s at 10000 rpm, leaf size in m)
+        #[arg(long, value_name = "MODEL", requires = "recompute")]
+        gbw_model: Option<BoundaryLayerModel>,
+        
+        /// Flag observations whose modeled gbw differs from the logged one by
+        /// more than this fraction (default 0.2)
+        #[arg(long, value_name = "FRACTION", requires = "gbw_model")]
+        gbw_tolerance: Option<f64>,
+        
+        /// Cuticular conductance to water vapor (mol m-2 s-1) to remove from
+        /// gsw before --recompute computes Ci
+        #[arg(long, value_name = "GCW", requires = "recompute")]
+        gcw: Option<f64>,
+        
+        /// Append a `source_file` column with the path of each input file
+        #[arg(long)]
+        add_source_column: bool,
+        
+        /// Write long ("tidy") data: one row per observation and variable
+        #[arg(long)]
+        long: bool,
+        
+        /// Rename and convert columns for an analysis tool: photogea, msuracifit or plantecophys
+        #[arg(long, value_name = "PROFILE", conflicts_with = "long")]
+        profile: Option<OutputProfile>,
+        
+        /// Order of the instrument's columns: original (as logged), canonical
+        /// (by variable category) or alpha
+        #[arg(long, value_name = "ORDER", default_value = "original")]
+        column_order: ColumnOrder,
+        
+        /// Order of the rows: input (as logged), time or obs; sorting is stable
+        #[arg(long, value_name = "ORDER", default_value = "input")]
+        sort: RowOrder,
+        
+        /// Spelling of units in XLSX and --long output: original,
+        /// pretty (µmol m⁻² s⁻¹) or ascii (umol m-2 s-1)
+        #[arg(long, value_name = "STYLE", default_value = "original")]
+        units: UnitStyle,
+        
+        /// Rename columns and convert units with a crosswalk file (.toml or .csv)
+        /// mapping internal names to publication names
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["long", "profile"])]
+        crosswalk: Option<String>,
+        
+        /// Rename columns when writing, e.g. `--rename A=Photo gsw=Cond`, or
+        /// from a file of one OLD=NEW per line
+        #[arg(long = "rename", value_name = "OLD=NEW", num_args = 1.., conflicts_with_all = ["profile", "crosswalk"])]
+        renames: Vec<String>,
+        
+        /// Harmonize LI-6400 and LI-6800 names before writing: 6800-names
+        /// (Photo → A, Cond → gsw, ...) or 6400-names
+        #[arg(long, value_name = "NAMES")]
+        harmonize: Option<Harmonization>,
+        
+        /// With --format hive, also partition the dataset by console serial
+        #[arg(long)]
+        partition_by_device: bool,
+        
+        /// Write one file per group of rows sharing these columns' values, such
+        /// as `curve_id` or a --header-column (comma-separated)
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+        split_by: Vec<String>,
+        
+        /// File name template for --split-by groups, with `{file}` and
+        /// `{<column>}` placeholders (default `{file}_{<column>}...`)
+        #[arg(long, value_name = "TEMPLATE", requires = "split_by")]
+        split_name: Option<String>,
+        
+        /// Parquet compression codec (zstd, snappy or uncompressed)
+        #[arg(long, value_name = "CODEC", default_value = "zstd")]
+        compression: ParquetCodec,
+        
+        /// Parquet compression level (zstd only, 1-22)
+        #[arg(long, value_name = "LEVEL")]
+        compression_level: Option<i32>,
+        
+        /// Write Parquet columns without dictionary encoding
+        #[arg(long)]
+        no_dictionary: bool,
+        
+        /// Rows per Parquet row group
+        #[arg(long, value_name = "ROWS")]
+        row_group_size: Option<usize>,
+        
+        /// Sort rows by these columns before writing Parquet, comma-separated
+        #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+        sort_by: Vec<String>,
+        
+        /// Report the values that stopped columns from converting to their expected type
+        #[arg(long)]
+        coercion_report: bool,
+        
+        /// What non-finite cells (1.#INF, -1.#IND, 1e+309) in float columns become:
+        /// infinity (±inf, indeterminate ones null) or null
+        #[arg(long, value_name = "POLICY", default_value = "infinity")]
+        non_finite: NonFinite,
+        
+        /// What a column logged under an already used name becomes: number
+        /// (kept as NAME_1), merge (dropped when its values equal the first
+        /// column's, numbered otherwise) or fail
+        #[arg(long, value_name = "HANDLING", default_value = "number")]
+        duplicate_columns: DuplicateColumns,
+        
+        /// Robust z-score above which a file's mean A, gsw or leak is reported
+        /// as deviating from the rest of the batch
+        #[arg(long, value_name = "Z", default_value_t = DEFAULT_ANOMALY_THRESHOLD)]
+        anomaly_threshold: f64,
+        
+        /// After the batch, list every file with its status and warnings, and show a
+        /// file's error, warnings and offending lines when its number is entered
+        /// (in a terminal only)
+        #[arg(long)]
+        review: bool,
+        
+        /// Report each file's input and output size with the compression ratio,
+        /// and the batch totals
+        #[arg(long)]
+        size_report: bool,
+        
+        /// Parse and encode every file without writing anything, reporting the
+        /// output sizes it would take (implies --size-report)
+        #[arg(long)]
+        dry_run: bool,
+        
+        /// Replace existing output files without a warning
+        #[arg(long, conflicts_with_all = ["skip_existing", "fail_if_exists"])]
+        overwrite: bool,
+        
+        /// Leave existing output files untouched and skip those conversions
+        #[arg(long, conflicts_with = "fail_if_exists")]
+        skip_existing: bool,
+        
+        /// Count a file as failed when its output already exists
+        #[arg(long)]
+        fail_if_exists: bool,
+        
+        /// Enable verbose output
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Convert a single file to a temporary file and open it in a viewer
+    Open {
+        /// Device type
+        #[arg(long, value_enum)]
+        device: Device,
+        
+        /// Measurement configuration
+        #[arg(long, value_enum)]
+        config: Config,
+        
+        /// Input file
+        #[arg(long)]
+        input: String,
+        
+        /// Format of the temporary file handed to the viewer
+        #[arg(long, value_enum, default_value = "csv")]
+        format: ViewFormat,
+        
+        /// Viewer command, e.g. "vd" or "tad" (defaults to $LICOR_VIEWER, then the system opener)
+        #[arg(long)]
+        viewer: Option<String>,
+    },
+    /// Split input files into response curves and write one file per curve,
+    /// named `<sample>_<type>_<date>` for curve-fitting tools
+    ConcatCurves {
+        /// Device type
+        #[arg(long, value_enum)]
+        device: Device,
+        
+        /// Measurement configuration
+        #[arg(long, value_enum)]
+        config: Config,
+        
+        /// Input files (supports glob patterns)
+        #[arg(long)]
+        input: String,
+        
+        /// Output directory for the curve files
+        #[arg(long)]
+        output: String,
+        
+        /// Output file format
+        #[arg(long, value_enum, default_value = "csv")]
+        format: OutputFormat,
+        
+        /// Column (or metadata field such as `device_serial`) holding the sample ID;
+        /// defaults to the input file name
+        #[arg(long, value_name = "KEY")]
+        sample_id: Option<String>,
+        
+        /// Rename and convert columns for an analysis tool: photogea, msuracifit or plantecophys
+        #[arg(long, value_name = "PROFILE")]
+        profile: Option<OutputProfile>,        
+        /// Rename columns and convert units with a crosswalk file (.toml or .csv)
+        /// mapping internal names to publication names
+        #[arg(long, value_name = "FILE", conflicts_with = "profile")]
+        crosswalk: Option<String>,
+        
+        /// Enable verbose output
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Check files against a device and configuration, exiting nonzero on any problem
+    Validate {
+        /// Device type (detected from the file type when omitted)
+        #[arg(long, value_enum)]
+        device: Option<Device>,
+        
+        /// Measurement configuration (the best-matching one when omitted)
+        #[arg(long, value_enum)]
+        config: Option<Config>,
+        
+        /// Input files (supports glob patterns)
+        #[arg(long)]
+        input: String,
+        
+        /// List every malformed row and offending value
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// List the variable dictionary, or the known variables a file logs
+    Variables {
+        /// Only list the variables logged in this file
+        #[arg(long)]
+        input: Option<String>,
+        
+        /// Device that wrote --input (detected from the file type when omitted)
+        #[arg(long, value_enum, requires = "input")]
+        device: Option<Device>,
+        
+        /// Only list variables whose name, label or description matches these
+        /// words (tolerates typos), best match first
+        #[arg(long, value_name = "QUERY")]
+        search: Option<String>,
+        
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Watch a folder and convert LI-COR files as they appear or change
+    Watch {
+        /// Device type
+        #[arg(long, value_enum)]
+        device: Device,
+        
+        /// Measurement configuration
+        #[arg(long, value_enum)]
+        config: Config,
+        
+        /// Folder to watch, including subfolders
+        #[arg(long)]
+        input: String,
+        
+        /// Output directory for converted files
+        #[arg(long)]
+        output: String,
+        
+        /// Output file format
+        #[arg(long, value_enum, default_value = "parquet")]
+        format: OutputFormat,
+        
+        /// Seconds a file must go unchanged before it is converted, so copies
+        /// in progress are not read half-written
+        #[arg(long, value_name = "SECONDS", default_value_t = 2.0)]
+        settle: f64,
+        
+        /// Also convert the files already in the folder at startup
+        #[arg(long)]
+        existing: bool,
+        
+        /// Enable verbose output
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Combine many input files into one output file, with a `source_file` column
+    Merge {
+        /// Device type
+        #[arg(long, value_enum)]
+        device: Device,
+        
+        /// Measurement configuration
+        #[arg(long, value_enum)]
+        config: Config,
+        
+        /// Input files (supports glob patterns)
+        #[arg(long)]
+        input: String,
+        
+        /// Output file
+        #[arg(long)]
+        output: String,
+        
+        /// Output file format
+        #[arg(long, value_enum, default_value = "parquet")]
+        format: OutputFormat,
+        
+        /// Columns to sort the merged rows by (comma-separated; defaults to `time` when present)
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+        sort_by: Vec<String>,
+        
+        /// Enable verbose output
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Print the first observations of a file as a table, with units under the names
+    Head {
+        /// Device type (detected from the file type when omitted)
+        #[arg(long, value_enum)]
+        device: Option<Device>,
+        
+        /// Measurement configuration (the best-matching one when omitted)
+        #[arg(long, value_enum)]
+        config: Option<Config>,
+        
+        /// Input file
+        #[arg(long)]
+        input: String,
+        
+        /// Number of observations to print
+        #[arg(short = 'n', long, default_value_t = 10)]
+        rows: usize,
+        
+        /// Only print these columns (comma-separated), in this order
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+        columns: Vec<String>,
+    },
+    /// Show every observation of a file as a table in a pager, with units
+    /// under the names
+    Cat {
+        /// Device type (detected from the file type when omitted)
+        #[arg(long, value_enum)]
+        device: Option<Device>,
+        
+        /// Measurement configuration (the best-matching one when omitted)
+        #[arg(long, value_enum)]
+        config: Option<Config>,
+        
+        /// Input file
+        #[arg(long)]
+        input: String,
+        
+        /// Only show these columns (comma-separated), in this order
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+        columns: Vec<String>,
+        
+        /// Repeat the names and units every this many observations (0 for never)
+        #[arg(long, default_value_t = 40)]
+        page_size: usize,
+        
+        /// Print to stdout instead of the pager
+        #[arg(long)]
+        no_pager: bool,
+    },
+    /// Print one observation with each value's name, units and description,
+    /// to learn what the columns of a log mean
+    Explain {
+        /// Input file
+        input: String,
+        
+        /// Observation to explain (its `obs` number, or its position when the
+        /// file has no `obs` column)
+        #[arg(long, default_value_t = 1)]
+        obs: i64,
+        
+        /// Device type (detected from the file type when omitted)
+        #[arg(long, value_enum)]
+        device: Option<Device>,
+        
+        /// Measurement configuration (the best-matching one when omitted)
+        #[arg(long, value_enum)]
+        config: Option<Config>,
+        
+        /// Only explain these columns (comma-separated), in this order
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+        columns: Vec<String>,
+    },
+    /// Print per-variable statistics (count, missing, min, max, mean) of each
+    /// file, or of all files together, as a quick QC snapshot
+    Summary {
+        /// Device type (detected from the file type when omitted)
+        #[arg(long, value_enum)]
+        device: Option<Device>,
+        
+        /// Measurement configuration (the best-matching one when omitted)
+        #[arg(long, value_enum)]
+        config: Option<Config>,
+        
+        /// Input files (supports glob patterns)
+        #[arg(long)]
+        input: String,
+        
+        /// Only summarize these columns (comma-separated), in this order
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+        columns: Vec<String>,
+        
+        /// Summarize all files together instead of each file
+        #[arg(long)]
+        dataset: bool,
+        
+        /// Print JSON instead of tables
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the columns a device and configuration produce, with types and
+    /// units, as JSON or a SQL `CREATE TABLE` statement
+    Schema {
+        /// Device type
+        #[arg(long, value_enum)]
+        device: Device,
+        
+        /// Measurement configuration
+        #[arg(long, value_enum)]
+        config: Config,
+        
+        /// Output format
+        #[arg(long, value_enum, default_value = "json")]
+        format: SchemaFormat,
+        
+        /// Include every variable the device may log, not only the
+        /// configuration's required ones
+        #[arg(long)]
+        all: bool,
+        
+        /// Table name for --format sql
+        #[arg(long, default_value = "licor_data")]
+        table: String,
+    },
+    /// Draw an SVG chart of a file for field QC: variables against elapsed
+    /// time, or A against Ci
+    Plot {
+        /// Device type (detected from the file type when omitted)
+        #[arg(long, value_enum)]
+        device: Option<Device>,
+        
+        /// Measurement configuration (the best-matching one when omitted)
+        #[arg(long, value_enum)]
+        config: Option<Config>,
+        
+        /// Input file
+        #[arg(long)]
+        input: String,
+        
+        /// Output directory
+        #[arg(long, default_value = ".")]
+        output: String,
+        
+        /// Variables on the y axis (comma-separated)
+        #[arg(long, value_delimiter = ',', value_name = "COLUMNS", required_unless_present = "aci")]
+        y: Vec<String>,
+        
+        /// Column on the x axis, drawn as a scatter (elapsed time as lines when omitted)
+        #[arg(long, value_name = "COLUMN")]
+        x: Option<String>,
+        
+        /// Plot A against Ci as a scatter
+        #[arg(long, conflicts_with_all = ["y", "x"])]
+        aci: bool,
+    },
+    /// Find out why a file does not parse: encoding, missing sections or header
+    /// keys, cut-off data and rows with the wrong number of fields
+    Doctor {
+        /// File to check
+        input: String,
+        
+        /// Device type (detected from the file type when omitted)
+        #[arg(long, value_enum)]
+        device: Option<Device>,
+        
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Repair common damage (encoding, NUL bytes, mixed line endings, a repeated
+    /// header block, a cut-off last row) and write a cleaned copy
+    Fix {
+        /// File to repair
+        input: String,
+        
+        /// Repaired copy (default `<name>_fixed` next to the input)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+        
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Scan a directory tree and write a Parquet catalog of the LI-COR files
+    /// in it (path, serial, configuration, time range, observations)
+    Index {
+        /// Directory to scan
+        dir: String,
+        
+        /// Catalog file to write
+        #[arg(long, default_value = "licor_index.parquet")]
+        output: String,
+        
+        /// List each file as it is indexed, and the files skipped
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Rewrite the Parquet outputs of older versions under a directory in the
+    /// current schema and metadata layout
+    Migrate {
+        /// Directory (or single file) of converted Parquet outputs
+        dir: String,
+        
+        /// Schema to migrate to; only the current one, v2, can be written
+        #[arg(long, value_name = "VERSION", default_value = "v2", value_parser = parse_schema_version)]
+        to_schema: u32,
+        
+        /// List what would change without rewriting any file
+        #[arg(long)]
+        dry_run: bool,
+        
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write a catalog (a STAC-like JSON item collection) of the converted
+    /// Parquet outputs under a directory, listing each file's instruments,
+    /// time range, position and QC flags with a link to it
+    Catalog {
+        /// Directory of converted Parquet outputs
+        dir: String,
+        
+        /// Catalog file to write; links to the outputs are relative to it
+        #[arg(short, long, default_value = "catalog.json")]
+        output: String,
+        
+        /// Catalog identifier (the directory name by default)
+        #[arg(long)]
+        id: Option<String>,
+        
+        /// Description of the campaign
+        #[arg(long, default_value = "")]
+        description: String,
+        
+        /// Site identifier, added to every item
+        #[arg(long)]
+        site_id: Option<String>,
+        
+        /// Site latitude in decimal degrees, for outputs without logged positions
+        #[arg(long, allow_hyphen_values = true)]
+        latitude: Option<f64>,
+        
+        /// Site longitude in decimal degrees, for outputs without logged positions
+        #[arg(long, allow_hyphen_values = true)]
+        longitude: Option<f64>,
+    },
+    /// Convert a corpus with an older licor binary and with this one, and
+    /// summarize how the outputs' schemas and values differ, to check an
+    /// upgrade against your own data before adopting it
+    BisectOutput {
+        /// licor binary of the version to compare against
+        #[arg(long, value_name = "PATH")]
+        old_bin: String,
+        
+        /// licor binary of the new version (this one by default)
+        #[arg(long, value_name = "PATH")]
+        new_bin: Option<String>,
+        
+        /// Directory of files to convert (searched recursively), or a glob pattern
+        #[arg(long)]
+        input: String,
+        
+        /// Device type
+        #[arg(long, value_enum)]
+        device: Device,
+        
+        /// Measurement configuration
+        #[arg(long, value_enum)]
+        config: Config,
+        
+        /// Largest difference between two numbers, relative to the larger,
+        /// that still counts as the same value
+        #[arg(long, value_name = "FRACTION", default_value_t = 1e-9)]
+        tolerance: f64,
+        
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare the headers, settings (SysConst, stability) and columns of two
+    /// files, printing only what differs
+    Diff {
+        /// First file
+        left: String,
+        
+        /// Second file
+        right: String,
+        
+        /// Device type (detected from each file type when omitted)
+        #[arg(long, value_enum)]
+        device: Option<Device>,
+        
+        /// Print JSON instead of tables
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print a shell completion script for subcommands, flags and their values
+    Completions {
+        /// Shell to complete in
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Write instrument and site metadata for repository deposits
+    /// (ESS-DIVE `flmd.csv`/`dd.csv` or an AmeriFlux BIF file)
+    Metadata {
+        /// Device type
+        #[arg(long, value_enum)]
+        device: Device,
+        
+        /// Measurement configuration
+        #[arg(long, value_enum)]
+        config: Config,
+        
+        /// Input files (supports glob patterns)
+        #[arg(long)]
+        input: String,
+        
+        /// Output directory for the metadata files
+        #[arg(long)]
+        output: String,
+        
+        /// Reporting format: essdive or ameriflux
+        #[arg(long, value_name = "STANDARD")]
+        standard: MetadataStandard,
+        
+        /// Format the data files are deposited in, used for their file names
+        #[arg(long, value_enum, default_value = "csv")]
+        format: OutputFormat,
+        
+        /// Site identifier (AmeriFlux SITE_ID)
+        #[arg(long)]
+        site_id: Option<String>,
+        
+        /// Site latitude in decimal degrees
+        #[arg(long, allow_hyphen_values = true)]
+        latitude: Option<f64>,
+        
+        /// Site longitude in decimal degrees
+        #[arg(long, allow_hyphen_values = true)]
+        longitude: Option<f64>,        
+        /// Rename columns and convert units as `convert --crosswalk` did, so the
+        /// data dictionary lists the published names
+        #[arg(long, value_name = "FILE")]
+        crosswalk: Option<String>,
+        
+        /// Instrument profiles as given to `convert --instruments`, whose
+        /// calibration certificates are listed with each console (defaults to
+        /// $LICOR_INSTRUMENTS)
+        #[arg(long, value_name = "FILE")]
+        instruments: Option<String>,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub(crate) enum Device {
+    #[cfg(feature = "device-6800")]
+    #[value(name = "6800")]
+    Li6800,
+    #[cfg(feature = "device-6400")]
+    #[value(name = "6400")]
+    Li6400,
+}
+
+impl Device {
+    /// Device whose data comes in this kind of file: LI-6400 workbooks or LI-6800 text logs
+    pub(crate) fn detect(path: &str) -> Device {
+        let workbook = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("xls") || e.eq_ignore_ascii_case("xlsx"));
+        let variants = Device::value_variants();
+        variants.iter()
+            .find(|device| device.reads_workbooks() == workbook)
+            .unwrap_or(&variants[0])
+            .clone()
+    }
+    
+    pub(crate) fn reads_workbooks(&self) -> bool {
+        match self {
+            #[cfg(feature = "device-6800")]
+            Device::Li6800 => false,
+            #[cfg(feature = "device-6400")]
+            Device::Li6400 => true,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub(crate) enum Config {
+    Standard,
+    Fluorometer,
+    Aquatic,
+    Soil,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub(crate) enum OutputFormat {
+    Parquet,
+    Csv,
+    Xlsx,
+    Netcdf,
+    /// Hive-partitioned Parquet dataset (date=YYYY-MM-DD/...) under the output directory
+    Hive,
+    /// Append to a Delta Lake table at the output directory
+    Delta,
+    /// Load into a PostgreSQL database (requires the `postgres` feature)
+    Postgres,
+}
+
+impl OutputFormat {
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Parquet | OutputFormat::Hive => "parquet",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Xlsx => "xlsx",
+            OutputFormat::Netcdf => "nc",
+            OutputFormat::Delta | OutputFormat::Postgres => "",
+        }
+    }
+}
+
+/// Single-file formats `open` can hand to a viewer
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub(crate) enum ViewFormat {
+    Csv,
+    Parquet,
+    Xlsx,
+    Netcdf,
+}
+
+impl From<ViewFormat> for OutputFormat {
+    fn from(format: ViewFormat) -> Self {
+        match format {
+            ViewFormat::Csv => OutputFormat::Csv,
+            ViewFormat::Parquet => OutputFormat::Parquet,
+            ViewFormat::Xlsx => OutputFormat::Xlsx,
+            ViewFormat::Netcdf => OutputFormat::Netcdf,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub(crate) enum SchemaFormat {
+    Json,
+    /// PostgreSQL-compatible CREATE TABLE statement
+    Sql,
+}
+
+/// Command-line spelling of a value enum
+pub(crate) fn value_name<T: ValueEnum>(value: &T) -> String {
+    value.to_possible_value().map_or_else(String::new, |v| v.get_name().to_string())
+}
+
+fn parse_schema_version(s: &str) -> Result<u32, String> {
+    let version = s.trim().trim_start_matches(['v', 'V']).parse::<u32>()
+        .map_err(|_| format!("Invalid schema version '{}': use v{}", s, SCHEMA_VERSION))?;
+    if version != SCHEMA_VERSION {
+        return Err(format!("Can only migrate to the current schema, v{}", SCHEMA_VERSION));
+    }
+    Ok(version)
+}
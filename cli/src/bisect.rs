@@ -0,0 +1,239 @@
+//! `licor bisect-output`: find the first input whose output differs between two builds
+
+use licor_core::{OutputDiff, diff_outputs};
+use std::path::Path;
+use glob::glob;
+use crate::args::{Config, Device, value_name};
+use crate::output::{aligned_table, print_listing};
+
+/// How two versions converted one input file
+struct BisectedFile {
+    input: String,
+    old_error: Option<String>,
+    new_error: Option<String>,
+    /// Output file names only one version wrote
+    only_old: Vec<String>,
+    only_new: Vec<String>,
+    /// Differences of the outputs both wrote, by file name
+    outputs: Vec<(String, OutputDiff)>,
+}
+
+impl BisectedFile {
+    fn is_same(&self) -> bool {
+        self.old_error.is_some() == self.new_error.is_some()
+            && self.only_old.is_empty()
+            && self.only_new.is_empty()
+            && self.outputs.iter().all(|(_, diff)| diff.is_empty())
+    }
+}
+
+pub(crate) fn bisect_output(
+    old_bin: &str,
+    new_bin: Option<String>,
+    input: &str,
+    device: &Device,
+    config: &Config,
+    tolerance: f64,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let new_bin = match new_bin {
+        Some(path) => path,
+        None => {
+            // From the Python wheel this process is the interpreter
+            let exe = std::env::current_exe()?;
+            if !exe.file_stem().is_some_and(|stem| stem.to_string_lossy().starts_with("licor")) {
+                return Err("Pass the new licor binary with --new-bin".into());
+            }
+            exe.to_string_lossy().into_owned()
+        }
+    };
+    let inputs: Vec<std::path::PathBuf> = if Path::new(input).is_dir() {
+        let mut files = Vec::new();
+        let mut pending = vec![std::path::PathBuf::from(input)];
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+                    continue;
+                }
+                if path.is_dir() { pending.push(path) } else { files.push(path) }
+            }
+        }
+        files
+    } else {
+        glob(input)?.flatten().filter(|path| path.is_file()).collect()
+    };
+    let mut inputs = inputs;
+    inputs.sort();
+    if inputs.is_empty() {
+        return Err(format!("No files found in {}", input).into());
+    }
+    
+    let work = std::env::temp_dir().join(format!("licor-bisect-{}", std::process::id()));
+    let (device, config) = (value_name(device), value_name(config));
+    let mut files = Vec::new();
+    for (index, path) in inputs.iter().enumerate() {
+        // Each version converts into its own directory; the outputs are paired by name
+        let convert = |bin: &str, side: &str| -> Result<Vec<String>, String> {
+            let output = work.join(side).join(index.to_string());
+            std::fs::create_dir_all(&output).map_err(|e| e.to_string())?;
+            let run = std::process::Command::new(bin)
+                .args(["convert", "--device", &device, "--config", &config, "--input"])
+                .arg(glob::Pattern::escape(&path.to_string_lossy()))
+                .arg("--output")
+                .arg(&output)
+                .output()
+                .map_err(|e| format!("could not run {}: {}", bin, e))?;
+            if !run.status.success() {
+                let stderr = String::from_utf8_lossy(&run.stderr);
+                return Err(stderr.lines().rfind(|line| !line.trim().is_empty()).unwrap_or("failed").trim().to_string());
+            }
+            let mut names: Vec<String> = std::fs::read_dir(&output).map_err(|e| e.to_string())?
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|name| name.ends_with(".parquet"))
+                .collect();
+            names.sort();
+            Ok(names)
+        };
+        let (old, new) = (convert(old_bin, "old"), convert(&new_bin, "new"));
+        let mut file = BisectedFile {
+            input: path.to_string_lossy().into_owned(),
+            old_error: old.as_ref().err().cloned(),
+            new_error: new.as_ref().err().cloned(),
+            only_old: Vec::new(),
+            only_new: Vec::new(),
+            outputs: Vec::new(),
+        };
+        if let (Ok(old), Ok(new)) = (old, new) {
+            file.only_old = old.iter().filter(|name| !new.contains(name)).cloned().collect();
+            file.only_new = new.iter().filter(|name| !old.contains(name)).cloned().collect();
+            for name in old.iter().filter(|name| new.contains(name)) {
+                let read = |side: &str| -> Result<polars::prelude::DataFrame, Box<dyn std::error::Error>> {
+                    use polars::prelude::{ParquetReader, SerReader};
+                    let file = std::fs::File::open(work.join(side).join(index.to_string()).join(name))?;
+                    Ok(ParquetReader::new(file).finish()?)
+                };
+                file.outputs.push((name.clone(), diff_outputs(&read("old")?, &read("new")?, tolerance)?));
+            }
+        }
+        files.push(file);
+    }
+    let _ = std::fs::remove_dir_all(&work);
+    
+    // Changes by column across the corpus, most widespread first
+    let mut changes: Vec<(String, &str, usize)> = Vec::new();
+    for file in &files {
+        let mut seen = std::collections::BTreeSet::new();
+        for (_, diff) in &file.outputs {
+            seen.extend(diff.only_left.iter().map(|column| (column.clone(), "removed")));
+            seen.extend(diff.only_right.iter().map(|column| (column.clone(), "added")));
+            seen.extend(diff.types.iter().map(|d| (d.column.clone(), "type")));
+            seen.extend(diff.values.iter().map(|d| (d.column.clone(), "values")));
+        }
+        for (column, change) in seen {
+            match changes.iter_mut().find(|(c, k, _)| *c == column && *k == change) {
+                Some(entry) => entry.2 += 1,
+                None => changes.push((column, change, 1)),
+            }
+        }
+    }
+    changes.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)).then_with(|| a.1.cmp(b.1)));
+    let differing = files.iter().filter(|file| !file.is_same()).count();
+    
+    if json {
+        let entries: Vec<_> = files.iter().map(|file| serde_json::json!({
+            "input": file.input,
+            "same": file.is_same(),
+            "old_error": file.old_error,
+            "new_error": file.new_error,
+            "only_old": file.only_old,
+            "only_new": file.only_new,
+            "outputs": file.outputs.iter().map(|(name, diff)| serde_json::json!({
+                "output": name,
+                "rows": diff.rows,
+                "only_old": diff.only_left,
+                "only_new": diff.only_right,
+                "types": diff.types.iter().map(|d| serde_json::json!({ "column": d.column, "old": d.left, "new": d.right })).collect::<Vec<_>>(),
+                "values": diff.values.iter().map(|d| serde_json::json!({ "column": d.column, "rows": d.rows, "max_difference": d.max_difference })).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })).collect();
+        let columns: Vec<_> = changes.iter()
+            .map(|(column, change, count)| serde_json::json!({ "column": column, "change": change, "files": count }))
+            .collect();
+        let report = serde_json::json!({ "old_bin": old_bin, "new_bin": new_bin, "files": entries, "columns": columns });
+        return print_listing(format!("{}\n", serde_json::to_string_pretty(&report)?));
+    }
+    
+    let mut listing = String::new();
+    for file in files.iter().filter(|file| !file.is_same()) {
+        listing.push_str(&format!("{}:\n", file.input));
+        for (version, error) in [("old", &file.old_error), ("new", &file.new_error)] {
+            if let Some(error) = error {
+                listing.push_str(&format!("  Failed with the {} version: {}\n", version, error));
+            }
+        }
+        for (version, names) in [("old", &file.only_old), ("new", &file.only_new)] {
+            if !names.is_empty() {
+                listing.push_str(&format!("  Outputs only from the {} version: {}\n", version, names.join(", ")));
+            }
+        }
+        for (name, diff) in file.outputs.iter().filter(|(_, diff)| !diff.is_empty()) {
+            let indent = if file.outputs.len() > 1 {
+                listing.push_str(&format!("  {}:\n", name));
+                "    "
+            } else {
+                "  "
+            };
+            if let Some((old, new)) = diff.rows {
+                listing.push_str(&format!("{}Rows: {} -> {}\n", indent, old, new));
+            }
+            for (version, columns) in [("old", &diff.only_left), ("new", &diff.only_right)] {
+                if !columns.is_empty() {
+                    listing.push_str(&format!("{}Columns only in the {} output: {}\n", indent, version, columns.join(", ")));
+                }
+            }
+            for d in &diff.types {
+                listing.push_str(&format!("{}Type of {}: {} -> {}\n", indent, d.column, d.left, d.right));
+            }
+            for d in &diff.values {
+                let largest = d.max_difference.map_or_else(String::new, |max| format!(" (largest difference {})", max));
+                listing.push_str(&format!("{}Values of {} differ in {} rows{}\n", indent, d.column, d.rows, largest));
+            }
+        }
+    }
+    if !changes.is_empty() {
+        listing.push_str("\nChanged columns:\n");
+        let rows: Vec<Vec<String>> = std::iter::once(vec!["COLUMN".to_string(), "CHANGE".to_string(), "FILES".to_string()])
+            .chain(changes.iter().map(|(column, change, count)| vec![column.clone(), change.to_string(), count.to_string()]))
+            .collect();
+        listing.push_str(&aligned_table(&rows));
+    }
+    listing.push_str(&format!("Compared {} files: {} with the same output, {} differ\n", files.len(), files.len() - differing, differing));
+    print_listing(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bisected_file_is_same() {
+        let file = BisectedFile {
+            input: "a.txt".to_string(),
+            old_error: None,
+            new_error: None,
+            only_old: Vec::new(),
+            only_new: Vec::new(),
+            outputs: Vec::new(),
+        };
+        assert!(file.is_same());
+        // Both versions failing counts as the same, one failing doesn't
+        let both_failed = BisectedFile { old_error: Some("old".to_string()), new_error: Some("new".to_string()), ..file };
+        assert!(both_failed.is_same());
+        let old_failed = BisectedFile { new_error: None, ..both_failed };
+        assert!(!old_failed.is_same());
+        let renamed = BisectedFile { old_error: None, only_new: vec!["a.parquet".to_string()], ..old_failed };
+        assert!(!renamed.is_same());
+    }
+}
@@ -0,0 +1,534 @@
+//! `licor convert`: batch conversion of files to the output formats
+
+use licor_core::{LiCorData, FileMeans, DEFAULT_ANOMALY_THRESHOLD, batch_anomalies, ParseError, ParseOptions, Message, RobustStatistic, QcThresholds, ColumnCrosswalk, ColumnRename, Harmonization, UnitStyle, OutputProfile, ParquetOptions, split_groups, group_file_stem, write_delta, write_hive_dataset};
+use std::path::Path;
+use glob::glob;
+use indicatif::{ProgressBar, ProgressStyle};
+use crate::args::{Config, Device, OutputFormat};
+use crate::commands::{parse_input, sanitize_label, write_output};
+use crate::commands::validate::coercion_summary;
+use crate::{CliExit, Reporter, emit_json};
+use crate::output::{compression_ratio, format_bytes};
+use crate::output::review::review_batch;
+
+/// What `convert` does when an output file already exists
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ExistingOutput {
+    /// Replace it, and list how many files were replaced
+    #[default]
+    Warn,
+    Overwrite,
+    Skip,
+    Fail,
+}
+
+impl ExistingOutput {
+    pub(crate) fn from_flags(overwrite: bool, skip_existing: bool, fail_if_exists: bool) -> Self {
+        if overwrite {
+            ExistingOutput::Overwrite
+        } else if skip_existing {
+            ExistingOutput::Skip
+        } else if fail_if_exists {
+            ExistingOutput::Fail
+        } else {
+            ExistingOutput::Warn
+        }
+    }
+}
+
+/// How `convert` parses, reshapes and writes each file of a batch, built once
+/// from its arguments
+pub(crate) struct ConvertSettings {
+    pub(crate) device: Device,
+    pub(crate) config: Config,
+    /// Input files (a glob pattern), or the folder `watch` watches
+    pub(crate) input: String,
+    pub(crate) output_dir: Option<String>,
+    pub(crate) format: OutputFormat,
+    pub(crate) dsn: Option<String>,
+    pub(crate) options: ParseOptions,
+    pub(crate) qc: Option<QcThresholds>,
+    pub(crate) step_summary: Option<RobustStatistic>,
+    pub(crate) split_by: Vec<String>,
+    pub(crate) split_name: Option<String>,
+    pub(crate) harmonize: Option<Harmonization>,
+    pub(crate) renames: Vec<ColumnRename>,
+    pub(crate) long_format: bool,
+    pub(crate) profile: Option<OutputProfile>,
+    pub(crate) crosswalk: Option<ColumnCrosswalk>,
+    pub(crate) unit_style: UnitStyle,
+    pub(crate) partition_by_device: bool,
+    pub(crate) parquet: ParquetOptions,
+    pub(crate) existing: ExistingOutput,
+    pub(crate) anomaly_threshold: f64,
+    pub(crate) review: bool,
+    pub(crate) size_report: bool,
+    pub(crate) dry_run: bool,
+    pub(crate) verbose: bool,
+}
+
+impl ConvertSettings {
+    /// Write each file as parsed, with default options, replacing existing outputs
+    pub(crate) fn new(device: Device, config: Config, output_dir: String, format: OutputFormat) -> Self {
+        ConvertSettings {
+            device,
+            config,
+            input: String::new(),
+            output_dir: Some(output_dir),
+            format,
+            dsn: None,
+            options: ParseOptions::default(),
+            qc: None,
+            step_summary: None,
+            split_by: Vec::new(),
+            split_name: None,
+            harmonize: None,
+            renames: Vec::new(),
+            long_format: false,
+            profile: None,
+            crosswalk: None,
+            unit_style: UnitStyle::Original,
+            partition_by_device: false,
+            parquet: ParquetOptions::default(),
+            existing: ExistingOutput::Overwrite,
+            anomaly_threshold: DEFAULT_ANOMALY_THRESHOLD,
+            review: false,
+            size_report: false,
+            dry_run: false,
+            verbose: false,
+        }
+    }
+}
+
+/// Outputs of converting one input file
+#[derive(Default)]
+pub(crate) struct ConvertedFile {
+    /// Outputs written
+    pub(crate) outputs: Vec<String>,
+    /// Existing outputs left in place by `--skip-existing`
+    pub(crate) skipped: Vec<String>,
+    /// Existing outputs that were replaced
+    replaced: Vec<String>,
+    means: Vec<FileMeans>,
+    /// QC flags, console messages and coercion failures, for `--review`
+    pub(crate) warnings: Vec<String>,
+    /// Bytes written (or, with `--dry-run`, that would be), unless an output
+    /// has no file size, as a database table does
+    pub(crate) output_bytes: Option<u64>,
+}
+
+impl ConvertedFile {
+    fn add_output_size(&mut self, bytes: Option<u64>) {
+        self.output_bytes = self.output_bytes.zip(bytes).map(|(total, bytes)| total + bytes);
+    }
+}
+
+/// How a file of a batch ended
+pub(crate) enum BatchStatus {
+    Converted,
+    Skipped,
+    Failed(String),
+}
+
+/// A file of a batch, as listed by `--review`
+pub(crate) struct BatchEntry {
+    pub(crate) file: String,
+    pub(crate) status: BatchStatus,
+    pub(crate) outputs: Vec<String>,
+    pub(crate) warnings: Vec<String>,
+}
+
+pub(crate) fn convert_files(settings: &ConvertSettings, reporter: Reporter) -> Result<(), Box<dyn std::error::Error>> {
+    if !settings.split_by.is_empty() && matches!(settings.format, OutputFormat::Postgres | OutputFormat::Delta) {
+        return Err("--split-by writes one file per group and does not apply to --format postgres or delta".into());
+    }
+    
+    // Ensure output directory exists
+    if let Some(output_dir) = settings.output_dir.as_ref().filter(|_| !settings.dry_run) {
+        std::fs::create_dir_all(output_dir)?;
+    }
+    
+    // Find input files using glob pattern
+    let input_files: Vec<_> = glob(&settings.input)?
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    if input_files.is_empty() {
+        return Err(CliExit::new(1, Message::NoFilesFound { pattern: &settings.input }.text(reporter.language)).into());
+    }
+    
+    if settings.verbose {
+        println!("Found {} files to convert", input_files.len());
+        println!("Device: {:?}", settings.device);
+        println!("Config: {:?}", settings.config);
+        println!("Format: {:?}", settings.format);
+        if let Some(output_dir) = &settings.output_dir {
+            println!("Output directory: {}", output_dir);
+        }
+        println!();
+    }
+    
+    let mut successfully_converted = 0;
+    let mut failed_conversions = Vec::new();
+    let mut skipped = 0;
+    let mut replaced = 0;
+    let mut batch_means = Vec::new();
+    let mut entries = Vec::new();
+    let mut batch_sizes = None;
+    let progress = batch_progress(input_files.len(), settings.verbose);
+    
+    for input_file in input_files {
+        let input_path = input_file.to_string_lossy();
+        progress.set_message(input_path.to_string());
+        
+        if settings.verbose {
+            println!("Converting: {}", input_path);
+        }
+        
+        match convert_single_file(settings, &input_path, &progress, reporter) {
+            Ok(converted) => {
+                let status = if converted.outputs.is_empty() && !converted.skipped.is_empty() {
+                    skipped += 1;
+                    BatchStatus::Skipped
+                } else {
+                    successfully_converted += 1;
+                    BatchStatus::Converted
+                };
+                replaced += converted.replaced.len();
+                batch_means.extend(converted.means);
+                if let Some(output_bytes) = converted.output_bytes.filter(|_| settings.size_report && matches!(status, BatchStatus::Converted)) {
+                    let input_bytes = std::fs::metadata(input_file.as_path())?.len();
+                    progress.suspend(|| println!("  {}: {} → {}{}", input_path, format_bytes(input_bytes), format_bytes(output_bytes), compression_ratio(input_bytes, output_bytes)));
+                    let (total_in, total_out) = batch_sizes.unwrap_or((0, 0));
+                    batch_sizes = Some((total_in + input_bytes, total_out + output_bytes));
+                }
+                if settings.verbose {
+                    for output_path in &converted.outputs {
+                        println!("  → {}", output_path);
+                    }
+                    for output_path in &converted.skipped {
+                        println!("  Skipped existing {}", output_path);
+                    }
+                }
+                entries.push(BatchEntry { file: input_path.to_string(), status, outputs: converted.outputs, warnings: converted.warnings });
+            }
+            Err(e) => {
+                let error = progress.suspend(|| reporter.failure(&input_path, &*e));
+                failed_conversions.push((input_path.to_string(), error.clone()));
+                entries.push(BatchEntry { file: input_path.to_string(), status: BatchStatus::Failed(error), outputs: Vec::new(), warnings: Vec::new() });
+            }
+        }
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+    
+    println!();
+    println!("{}", Message::ConversionComplete.text(reporter.language));
+    println!("  {}", Message::SucceededCount(successfully_converted).text(reporter.language));
+    println!("  {}", Message::FailedCount(failed_conversions.len()).text(reporter.language));
+    if skipped > 0 {
+        println!("  {}", Message::SkippedCount(skipped).text(reporter.language));
+    }
+    if let Some((input_bytes, output_bytes)) = batch_sizes {
+        let ratio = input_bytes as f64 / output_bytes.max(1) as f64;
+        println!("  {}", Message::BatchSizes { input: &format_bytes(input_bytes), output: &format_bytes(output_bytes), ratio, estimated: settings.dry_run }.text(reporter.language));
+    }
+    if settings.dry_run {
+        println!("  {}", Message::DryRun.text(reporter.language));
+    }
+    if replaced > 0 && settings.existing == ExistingOutput::Warn {
+        eprintln!("\n{}", Message::ReplacedOutputs(replaced).text(reporter.language));
+    }
+    
+    let anomalies = batch_anomalies(&batch_means, settings.anomaly_threshold);
+    if !anomalies.is_empty() {
+        println!("\n{}", Message::BatchAnomalies(anomalies.len()).text(reporter.language));
+        for anomaly in anomalies {
+            println!("  {}: {} mean {:.4} (batch median {:.4}, robust z {:+.1})",
+                anomaly.file, anomaly.metric, anomaly.mean, anomaly.batch_median, anomaly.score);
+        }
+    }
+    
+    if settings.review {
+        review_batch(&entries, reporter)?;
+    }
+    
+    reporter.check_failed(failed_conversions)?;
+    
+    Ok(())
+}
+
+pub(crate) fn convert_single_file(
+    settings: &ConvertSettings,
+    input_path: &str,
+    progress: &ProgressBar,
+    reporter: Reporter,
+) -> Result<ConvertedFile, Box<dyn std::error::Error>> {
+    // Determine output filename
+    let input_filename = Path::new(input_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output");
+    
+    let mut converted = ConvertedFile { output_bytes: Some(0), ..ConvertedFile::default() };
+    for (label, data) in parse_input(&settings.device, &settings.config, input_path, &settings.options, reporter)? {
+        let source_file = match &label {
+            Some(label) => format!("{}#{}", input_path, label),
+            None => input_path.to_string(),
+        };
+        converted.means.push(data.anomaly_means(&source_file)?);
+        let output_stem = match &label {
+            Some(label) => format!("{}_{}", input_filename, sanitize_label(label)),
+            None => input_filename.to_string(),
+        };
+        
+        if settings.verbose {
+            if let Some(label) = &label {
+                println!("  Sheet: {}", label);
+            }
+            println!("  Parsed {} rows, {} columns", data.dataframe.height(), data.dataframe.width());
+            println!("  Device: {} ({})", data.metadata.device_serial, data.metadata.console_version);
+            if let Some(correction) = &data.metadata.clock_correction {
+                println!("  Clock correction: {}", correction.describe());
+            }
+            if let Some(hash) = &data.metadata.content_hash {
+                println!("  Content hash: {}", hash);
+            }
+        }
+        
+        let checks = match &settings.qc {
+            Some(thresholds) => data.analyzer_qc(thresholds)?,
+            None => Vec::new(),
+        };
+        let flagged: Vec<String> = checks.iter()
+            .filter(|check| check.flagged() > 0)
+            .map(|check| format!("{} flagged {} of {} observations: {}", check.name, check.flagged(), check.checked(), check.description))
+            .collect();
+        let data = if settings.qc.is_some() { data.with_qc_columns(&checks)? } else { data };
+        converted.warnings.extend(flagged.iter().map(|message| format!("QC {}", message)));
+        converted.warnings.extend(data.metadata.console_messages.iter()
+            .map(|message| format!("Console message after row {}: {}", message.after_row, message.text)));
+        converted.warnings.extend(data.coercion_reports.iter().map(coercion_summary));
+        
+        progress.suspend(|| if reporter.json() {
+            for message in &flagged {
+                emit_json("warning", Some(&source_file), "qc", None, message);
+            }
+            // Console messages refer to the data row they follow
+            for message in &data.metadata.console_messages {
+                emit_json("warning", Some(&source_file), "console_message", Some(message.after_row), &message.text);
+            }
+            for report in &data.coercion_reports {
+                let values: Vec<String> = report.offending_values.iter()
+                    .map(|(value, count)| format!("{:?} × {}", value, count))
+                    .collect();
+                emit_json("warning", Some(&source_file), "coercion", None, &format!(
+                    "{}: {}", coercion_summary(report), values.join(", ")
+                ));
+            }
+        } else {
+            for message in &flagged {
+                println!("  QC {}", message);
+            }
+            for message in &data.metadata.console_messages {
+                println!("  Console message after row {}: {}", message.after_row, message.text);
+            }
+            
+            for report in &data.coercion_reports {
+                println!("  {}:", coercion_summary(report));
+                for (value, count) in &report.offending_values {
+                    println!("    {:?} × {}", value, count);
+                }
+            }
+        });
+        
+        let data = match settings.step_summary {
+            Some(statistic) => data.step_summary(statistic)?,
+            None => data,
+        };
+        
+        // Group keys go into file names, so they are made safe for them
+        let groups = if settings.split_by.is_empty() {
+            vec![(output_stem, data)]
+        } else {
+            split_groups(&data.dataframe, &settings.split_by)?
+                .into_iter()
+                .map(|(key, dataframe)| {
+                    let key: Vec<Option<String>> = key.iter().map(|value| value.as_deref().map(sanitize_label)).collect();
+                    let stem = group_file_stem(settings.split_name.as_deref(), &output_stem, &settings.split_by, &key)?;
+                    Ok((stem, LiCorData { dataframe, ..data.clone() }))
+                })
+                .collect::<Result<Vec<_>, ParseError>>()?
+        };
+        
+        for (output_stem, data) in groups {
+            let data = match settings.harmonize {
+                Some(harmonize) => data.harmonize(harmonize)?,
+                None => data,
+            };
+            let data = if settings.renames.is_empty() { data } else { data.rename_columns(&settings.renames)? };
+            let data = if settings.long_format {
+                data.with_unit_style(settings.unit_style).to_long()?
+            } else if let Some(profile) = settings.profile {
+                data.to_profile(profile)?
+            } else if let Some(crosswalk) = &settings.crosswalk {
+                data.apply_crosswalk(crosswalk)?
+            } else {
+                data
+            };
+            let data = data.with_unit_style(settings.unit_style);
+            
+            if settings.dry_run {
+                converted.add_output_size(estimate_output_size(&data, &settings.format, &settings.parquet)?);
+                continue;
+            }
+        
+            if let OutputFormat::Postgres = settings.format {
+                let dsn = settings.dsn.as_deref().ok_or("--dsn is required for --format postgres")?;
+                write_postgres_output(&data, dsn, &source_file)?;
+                converted.outputs.push(format!("postgres: {}", source_file));
+                converted.add_output_size(None);
+                continue;
+            }
+        
+            let output_dir = settings.output_dir.as_deref().ok_or("--output is required for file formats")?;
+        
+            if let OutputFormat::Hive = settings.format {
+                let paths = write_hive_dataset(&data, output_dir, &output_stem, settings.partition_by_device, &settings.parquet)?;
+                for path in &paths {
+                    converted.add_output_size(Some(std::fs::metadata(path)?.len()));
+                }
+                converted.outputs.extend(paths);
+                continue;
+            }
+        
+            if let OutputFormat::Delta = settings.format {
+                write_delta(&data, output_dir, &source_file, &settings.parquet)?;
+                converted.outputs.push(format!("delta: {} ({})", output_dir, source_file));
+                converted.add_output_size(None);
+                continue;
+            }
+        
+            let output_path = format!("{}/{}.{}", output_dir, output_stem, settings.format.extension());
+            if Path::new(&output_path).exists() {
+                match settings.existing {
+                    ExistingOutput::Skip => {
+                        converted.skipped.push(output_path);
+                        continue;
+                    }
+                    ExistingOutput::Fail => {
+                        return Err(format!("{} already exists (use --overwrite or --skip-existing)", output_path).into());
+                    }
+                    ExistingOutput::Warn | ExistingOutput::Overwrite => converted.replaced.push(output_path.clone()),
+                }
+            }
+            write_output(&data, &settings.format, &settings.parquet, &output_path)?;
+            converted.add_output_size(Some(std::fs::metadata(&output_path)?.len()));
+            converted.outputs.push(output_path);
+        }
+    }
+    
+    Ok(converted)
+}
+
+/// Progress bar with ETA for a batch of files, showing the file in progress
+///
+/// Hidden with `--verbose`, which lists each file instead, and when stderr is
+/// not a terminal.
+pub(crate) fn batch_progress(files: usize, verbose: bool) -> ProgressBar {
+    if verbose {
+        return ProgressBar::hidden();
+    }
+    let progress = ProgressBar::new(files as u64);
+    progress.set_style(
+        ProgressStyle::with_template("{bar:30} {pos}/{len} files, ETA {eta} {wide_msg}")
+            .expect("progress template is valid")
+            .progress_chars("=> ")
+    );
+    progress
+}
+
+/// Bytes `data` takes in `format`, found by writing it to a temporary file
+///
+/// Hive and Delta Lake outputs are sized as the Parquet files they consist
+/// of; PostgreSQL tables have no size.
+fn estimate_output_size(
+    data: &LiCorData,
+    format: &OutputFormat,
+    parquet: &ParquetOptions
+) -> Result<Option<u64>, Box<dyn std::error::Error>> {
+    let format = match format {
+        OutputFormat::Postgres => return Ok(None),
+        OutputFormat::Hive | OutputFormat::Delta => &OutputFormat::Parquet,
+        format => format,
+    };
+    let path = std::env::temp_dir().join(format!("licor-dry-run-{}.{}", std::process::id(), format.extension()));
+    let path = path.to_string_lossy();
+    let written = write_output(data, format, parquet, &path).and_then(|()| Ok(std::fs::metadata(path.as_ref())?.len()));
+    let _ = std::fs::remove_file(path.as_ref());
+    Ok(Some(written?))
+}
+
+#[cfg(feature = "postgres")]
+fn write_postgres_output(
+    data: &LiCorData,
+    dsn: &str,
+    source_file: &str
+) -> Result<(), Box<dyn std::error::Error>> {
+    licor_core::write_postgres(data, dsn, source_file)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "postgres"))]
+fn write_postgres_output(
+    _data: &LiCorData,
+    _dsn: &str,
+    _source_file: &str
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err("licor was built without the `postgres` feature".into())
+}
+
+/// Add the header values an output profile reads to the requested header columns
+pub(crate) fn with_profile_headers(mut header_columns: Vec<String>, profile: Option<OutputProfile>) -> Vec<String> {
+    for key in profile.map_or(&[][..], |p| p.header_keys()) {
+        if !header_columns.iter().any(|k| k == key) {
+            header_columns.push(key.to_string());
+        }
+    }
+    header_columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::parse_input;
+
+    #[test]
+    fn test_existing_output_flags() {
+        assert_eq!(ExistingOutput::from_flags(false, false, false), ExistingOutput::Warn);
+        assert_eq!(ExistingOutput::from_flags(false, false, true), ExistingOutput::Fail);
+        assert_eq!(ExistingOutput::from_flags(false, true, true), ExistingOutput::Skip);
+        assert_eq!(ExistingOutput::from_flags(true, true, true), ExistingOutput::Overwrite);
+    }
+
+    #[cfg(feature = "device-6800")]
+    #[test]
+    fn test_estimate_output_size() {
+        let (_, data) = parse_input(
+            &Device::Li6800,
+            &Config::Fluorometer,
+            "../example_data/2025-05-30-1203_logdata_F2",
+            &ParseOptions::default(),
+            Reporter::default(),
+        ).unwrap().remove(0);
+        let parquet = ParquetOptions::default();
+        let csv_size = estimate_output_size(&data, &OutputFormat::Csv, &parquet).unwrap().unwrap();
+        let parquet_size = estimate_output_size(&data, &OutputFormat::Parquet, &parquet).unwrap().unwrap();
+        assert!(csv_size > 0 && parquet_size > 0);
+        // Datasets are estimated by their Parquet file; databases can't be
+        assert_eq!(estimate_output_size(&data, &OutputFormat::Hive, &parquet).unwrap(), Some(parquet_size));
+        assert_eq!(estimate_output_size(&data, &OutputFormat::Postgres, &parquet).unwrap(), None);
+        // Nothing is left behind
+        let scratch = std::env::temp_dir().join(format!("licor-dry-run-{}.parquet", std::process::id()));
+        assert!(!scratch.exists());
+    }
+}
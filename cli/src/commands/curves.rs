@@ -0,0 +1,98 @@
+//! `licor concat-curves`: one file per response curve across inputs
+
+use licor_core::{LiCorData, Message, split_curves};
+use std::path::Path;
+use glob::glob;
+use crate::commands::{first_value, parse_input, sanitize_label, write_output};
+use crate::commands::convert::ConvertSettings;
+use crate::{CliExit, Reporter};
+
+pub(crate) fn concat_curves(settings: &ConvertSettings, sample_id: Option<&str>, reporter: Reporter) -> Result<(), Box<dyn std::error::Error>> {
+    let output_dir = settings.output_dir.as_deref().ok_or("--output is required")?;
+    std::fs::create_dir_all(output_dir)?;
+    
+    let input_files: Vec<_> = glob(&settings.input)?
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    if input_files.is_empty() {
+        return Err(CliExit::new(1, Message::NoFilesFound { pattern: &settings.input }.text(reporter.language)).into());
+    }
+    
+    let mut used_names = std::collections::HashSet::new();
+    let mut written = 0;
+    let mut failed_conversions = Vec::new();
+    
+    for input_file in input_files {
+        let input_path = input_file.to_string_lossy();
+        match export_file_curves(settings, &input_path, output_dir, sample_id, &mut used_names, reporter) {
+            Ok(output_paths) => {
+                written += output_paths.len();
+                if settings.verbose {
+                    println!("{}", input_path);
+                    for output_path in output_paths {
+                        println!("  → {}", output_path);
+                    }
+                }
+            }
+            Err(e) => {
+                let error = reporter.failure(&input_path, &*e);
+                failed_conversions.push((input_path.to_string(), error));
+            }
+        }
+    }
+    
+    println!();
+    println!("{}", Message::ConversionComplete.text(reporter.language));
+    println!("  {}", Message::SucceededCount(written).text(reporter.language));
+    println!("  {}", Message::FailedCount(failed_conversions.len()).text(reporter.language));
+    
+    reporter.check_failed(failed_conversions)?;
+    
+    Ok(())
+}
+
+/// Write each curve of one input file, returning the paths written
+fn export_file_curves(
+    settings: &ConvertSettings,
+    input_path: &str,
+    output_dir: &str,
+    sample_id: Option<&str>,
+    used_names: &mut std::collections::HashSet<String>,
+    reporter: Reporter,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let input_filename = Path::new(input_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output");
+    
+    let mut output_paths = Vec::new();
+    for (_, data) in parse_input(&settings.device, &settings.config, input_path, &settings.options, reporter)? {
+        for curve in split_curves(&data.dataframe)? {
+            let sample = sample_id
+                .and_then(|key| first_value(&curve.dataframe, key).or_else(|| data.metadata.field(key).map(str::to_string)))
+                .unwrap_or_else(|| input_filename.to_string());
+            let date = curve.date.as_deref().unwrap_or("undated");
+            let stem = sanitize_label(&format!("{}_{}_{}", sample, curve.curve_type.label(), date));
+            
+            // Repeated curves of a sample on the same day are numbered
+            let mut name = stem.clone();
+            let mut counter = 2;
+            while !used_names.insert(name.clone()) {
+                name = format!("{}_{}", stem, counter);
+                counter += 1;
+            }
+            
+            let output_path = format!("{}/{}.{}", output_dir, name, settings.format.extension());
+            let mut curve_data = LiCorData { dataframe: curve.dataframe, ..data.clone() };
+            if let Some(profile) = settings.profile {
+                curve_data = curve_data.to_profile(profile)?;
+            } else if let Some(crosswalk) = &settings.crosswalk {
+                curve_data = curve_data.apply_crosswalk(crosswalk)?;
+            }
+            write_output(&curve_data, &settings.format, &settings.parquet, &output_path)?;
+            output_paths.push(output_path);
+        }
+    }
+    
+    Ok(output_paths)
+}
@@ -0,0 +1,154 @@
+//! `licor index`, `migrate` and `catalog`: working on a folder of converted outputs
+
+use licor_core::{index_file, write_index, SiteInfo, migrate_parquet, catalog_item, catalog_json};
+use std::path::Path;
+use crate::{CliExit, Reporter};
+use crate::output::print_listing;
+
+pub(crate) fn build_index(dir: &str, output_path: &str, verbose: bool, reporter: Reporter) -> Result<(), Box<dyn std::error::Error>> {
+    let mut paths = Vec::new();
+    let mut pending = vec![std::path::PathBuf::from(dir)];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    
+    // Files that do not scan as LI-COR logs (outputs, notes) are left out
+    let output = Path::new(output_path);
+    let mut entries = Vec::new();
+    for path in paths.iter().filter(|path| output.canonicalize().ok() != path.canonicalize().ok()) {
+        let path = path.to_string_lossy();
+        match index_file(&path) {
+            Ok(entry) => {
+                if verbose {
+                    println!("{} ({}, {}, {} observations)", entry.path, entry.serial, entry.config, entry.n_obs);
+                }
+                entries.push(entry);
+            }
+            Err(e) if verbose => println!("Skipped {}: {}", path, reporter.localize(&e)),
+            Err(_) => {}
+        }
+    }
+    write_index(&entries, output_path)?;
+    println!("Indexed {} of {} files into {}", entries.len(), paths.len(), output_path);
+    Ok(())
+}
+
+/// Parquet files written by `convert` under a directory, or the file itself
+///
+/// Hive partitions (`date=.../device=...`) are walked into like any other
+/// directory. Delta tables are left out: rewriting their data files in place
+/// would bypass the transaction log.
+fn parquet_outputs(dir: &str) -> std::io::Result<Vec<std::path::PathBuf>> {
+    let mut paths = Vec::new();
+    let mut pending = vec![std::path::PathBuf::from(dir)];
+    while let Some(path) = pending.pop() {
+        if path.is_file() {
+            paths.push(path);
+            continue;
+        }
+        if path.join("_delta_log").is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(&path)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "parquet") {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+pub(crate) fn migrate_dataset(dir: &str, to_schema: u32, dry_run: bool, json: bool, reporter: Reporter) -> Result<(), Box<dyn std::error::Error>> {
+    let paths = parquet_outputs(dir)?;
+    let mut results = Vec::new();
+    let mut failed = 0;
+    for path in &paths {
+        let path = path.to_string_lossy();
+        let result = migrate_parquet(&path, to_schema, dry_run);
+        failed += usize::from(result.is_err());
+        results.push((path, result));
+    }
+    let migrated = results.iter().filter(|(_, result)| matches!(result, Ok(Some(_)))).count();
+    
+    let listing = if json {
+        let files: Vec<_> = results.iter().map(|(path, result)| match result {
+            Ok(Some(migration)) => serde_json::json!({ "file": path, "status": "migrated", "from": migration.from, "changes": migration.changes }),
+            Ok(None) => serde_json::json!({ "file": path, "status": "current" }),
+            Err(e) => serde_json::json!({ "file": path, "status": "failed", "error": reporter.localize(e), "code": e.code() }),
+        }).collect();
+        format!("{}\n", serde_json::to_string_pretty(&serde_json::json!({ "to_schema": to_schema, "dry_run": dry_run, "files": files }))?)
+    } else {
+        let mut listing = String::new();
+        for (path, result) in &results {
+            match result {
+                Ok(Some(migration)) => {
+                    listing.push_str(&format!("{} (v{} -> v{}):\n", path, migration.from, to_schema));
+                    for change in &migration.changes {
+                        listing.push_str(&format!("  {}\n", change));
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => listing.push_str(&format!("{}: failed: {}\n", path, reporter.localize(e))),
+            }
+        }
+        let verb = if dry_run { "Would migrate" } else { "Migrated" };
+        listing.push_str(&format!("{} {} of {} Parquet files to schema v{}\n", verb, migrated, paths.len(), to_schema));
+        listing
+    };
+    print_listing(listing)?;
+    if failed > 0 {
+        return Err(CliExit::reported(1, format!("{} of {} Parquet files failed to migrate", failed, paths.len())).into());
+    }
+    Ok(())
+}
+
+pub(crate) fn write_catalog(dir: &str, output_path: &str, id: Option<String>, description: &str, site: &SiteInfo, reporter: Reporter) -> Result<(), Box<dyn std::error::Error>> {
+    let root = Path::new(dir).canonicalize()?;
+    let output = Path::new(output_path);
+    let parent = match output.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(parent)?;
+    let base = parent.canonicalize()?;
+    // Relative links where the outputs sit under the catalog's directory
+    let link = |path: &Path| {
+        let relative = path.strip_prefix(&base).unwrap_or(path);
+        relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+    };
+    
+    let mut items = Vec::new();
+    let mut skipped = 0;
+    for path in parquet_outputs(dir)? {
+        let path = path.canonicalize()?;
+        let id = link(&path.strip_prefix(&root).unwrap_or(&path).with_extension(""));
+        let id = if id.is_empty() { path.file_stem().unwrap_or_default().to_string_lossy().to_string() } else { id };
+        match catalog_item(&path.to_string_lossy(), &id, &link(&path)) {
+            Ok(item) => items.push(item),
+            Err(e) => {
+                skipped += 1;
+                println!("Skipped {}: {}", path.display(), reporter.localize(&e));
+            }
+        }
+    }
+    let id = id.unwrap_or_else(|| root.file_name().map_or("catalog".to_string(), |name| name.to_string_lossy().to_string()));
+    let catalog = catalog_json(&id, description, &items, site);
+    std::fs::write(output, format!("{}\n", serde_json::to_string_pretty(&catalog)?))?;
+    println!("Cataloged {} Parquet files into {}", items.len(), output_path);
+    if skipped > 0 {
+        println!("Skipped {} files; outputs of older versions need licor migrate first", skipped);
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! `licor diff`: compare two files or two outputs
+
+use licor_core::{FileDiff, diff_files, RawLiCorFile};
+use crate::args::Device;
+use crate::commands::read_raw_tables;
+use crate::output::{aligned_table, print_listing};
+
+pub(crate) fn print_diff(
+    left: &str,
+    right: &str,
+    device: Option<Device>,
+    json: bool
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Workbooks are compared by their first sheet
+    let read = |path: &str| -> Result<RawLiCorFile, Box<dyn std::error::Error>> {
+        let device = device.clone().unwrap_or_else(|| Device::detect(path));
+        read_raw_tables(&device, path)?
+            .into_iter()
+            .next()
+            .map(|(_, raw)| raw)
+            .ok_or_else(|| format!("{} has no data", path).into())
+    };
+    let diff: FileDiff = diff_files(&read(left)?, &read(right)?);
+    
+    if json {
+        let entry = serde_json::json!({
+            "left": left,
+            "right": right,
+            "header": diff.header.iter().map(|d| serde_json::json!({
+                "key": d.key,
+                "left": d.left,
+                "right": d.right,
+            })).collect::<Vec<_>>(),
+            "only_left": diff.only_left,
+            "only_right": diff.only_right,
+            "units": diff.units.iter().map(|d| serde_json::json!({
+                "column": d.column,
+                "left": d.left,
+                "right": d.right,
+            })).collect::<Vec<_>>(),
+        });
+        return print_listing(format!("{}\n", serde_json::to_string_pretty(&entry)?));
+    }
+    
+    if diff.is_empty() {
+        return print_listing(format!("{} and {} have the same header and columns\n", left, right));
+    }
+    let mut listing = String::new();
+    let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "(absent)".to_string());
+    if !diff.header.is_empty() {
+        listing.push_str("Header:\n");
+        let rows: Vec<Vec<String>> = std::iter::once(vec!["KEY".to_string(), left.to_string(), right.to_string()])
+            .chain(diff.header.iter().map(|d| vec![d.key.clone(), value(&d.left), value(&d.right)]))
+            .collect();
+        listing.push_str(&aligned_table(&rows));
+        listing.push('\n');
+    }
+    for (path, columns) in [(left, &diff.only_left), (right, &diff.only_right)] {
+        if !columns.is_empty() {
+            listing.push_str(&format!("Columns only in {}: {}\n", path, columns.join(", ")));
+        }
+    }
+    if !diff.units.is_empty() {
+        listing.push_str("Units:\n");
+        let rows: Vec<Vec<String>> = std::iter::once(vec!["COLUMN".to_string(), left.to_string(), right.to_string()])
+            .chain(diff.units.iter().map(|d| vec![d.column.clone(), d.left.clone(), d.right.clone()]))
+            .collect();
+        listing.push_str(&aligned_table(&rows));
+    }
+    print_listing(listing)
+}
@@ -0,0 +1,100 @@
+//! `licor doctor` and `licor fix`: diagnosing and repairing damaged files
+
+use licor_core::{diagnose, Finding, Severity, repair, ParseOptions};
+use std::path::Path;
+use crate::args::Device;
+use crate::commands::parse_input;
+use crate::commands::validate::validate_best_config;
+use crate::{CliExit, Reporter};
+use crate::output::print_listing;
+
+pub(crate) fn print_doctor(input_path: &str, device: Option<Device>, json: bool, reporter: Reporter) -> Result<(), Box<dyn std::error::Error>> {
+    let device = device.unwrap_or_else(|| Device::detect(input_path));
+    let mut findings = if device.reads_workbooks() { Vec::new() } else { diagnose(&std::fs::read(input_path)?) };
+    
+    // Problems the checks do not cover show up when parsing
+    if findings.iter().all(|finding| finding.severity != Severity::Error) {
+        let parsed = validate_best_config(&device, input_path)
+            .and_then(|(config, _)| parse_input(&device, &config, input_path, &ParseOptions::default(), reporter));
+        if let Err(e) = parsed {
+            findings.push(Finding {
+                severity: Severity::Error,
+                line: None,
+                problem: reporter.localize(&*e),
+                fix: "Check the file against a log that converts; `licor diff` lists how their headers and columns differ".to_string(),
+            });
+        }
+    }
+    
+    let listing = if json {
+        let entries: Vec<_> = findings.iter().map(|finding| serde_json::json!({
+            "severity": finding.severity.name(),
+            "line": finding.line,
+            "problem": finding.problem,
+            "fix": finding.fix,
+        })).collect();
+        format!("{}\n", serde_json::to_string_pretty(&serde_json::json!({ "file": input_path, "findings": entries }))?)
+    } else if findings.is_empty() {
+        format!("{}: no problems found\n", input_path)
+    } else {
+        let mut listing = format!("{}:\n", input_path);
+        for finding in &findings {
+            let location = finding.line.map(|line| format!(" (line {})", line)).unwrap_or_default();
+            listing.push_str(&format!("  {}{}: {}\n    fix: {}\n", finding.severity.name(), location, finding.problem, finding.fix));
+        }
+        listing
+    };
+    print_listing(listing)?;
+    if findings.iter().any(|finding| finding.severity == Severity::Error) {
+        return Err(CliExit::reported(1, format!("{} has errors", input_path)).into());
+    }
+    Ok(())
+}
+
+pub(crate) fn fix_file(input_path: &str, output_path: Option<String>, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let input = Path::new(input_path);
+    let output_path = output_path.unwrap_or_else(|| {
+        let stem = input.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+        let name = match input.extension().and_then(|e| e.to_str()) {
+            Some(extension) => format!("{}_fixed.{}", stem, extension),
+            None => format!("{}_fixed", stem),
+        };
+        input.with_file_name(name).to_string_lossy().into_owned()
+    });
+    if Path::new(&output_path) == input {
+        return Err("--output must differ from the input, so the original is kept".into());
+    }
+    
+    let (repaired, repairs) = repair(&std::fs::read(input_path)?);
+    if !repairs.is_empty() {
+        std::fs::write(&output_path, &repaired)?;
+    }
+    // Damage the repair does not cover is left for `licor doctor` to explain
+    let remaining = diagnose(repaired.as_bytes()).iter()
+        .filter(|finding| finding.severity == Severity::Error)
+        .count();
+    
+    let listing = if json {
+        let changes: Vec<_> = repairs.iter().map(|repair| serde_json::json!({
+            "line": repair.line,
+            "change": repair.change,
+        })).collect();
+        let output = (!repairs.is_empty()).then_some(&output_path);
+        format!("{}\n", serde_json::to_string_pretty(&serde_json::json!({ "file": input_path, "output": output, "changes": changes, "remaining_errors": remaining }))?)
+    } else if repairs.is_empty() {
+        format!("{}: nothing to repair\n", input_path)
+    } else {
+        let mut listing = format!("{} -> {}:\n", input_path, output_path);
+        for repair in &repairs {
+            let location = repair.line.map(|line| format!(" (line {})", line)).unwrap_or_default();
+            listing.push_str(&format!("  {}{}\n", repair.change, location));
+        }
+        listing
+    };
+    print_listing(listing)?;
+    if remaining > 0 {
+        let message = format!("{} problem(s) remain; run `licor doctor {}` for details", remaining, if repairs.is_empty() { input_path } else { &output_path });
+        return Err(CliExit::new(1, message).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,293 @@
+//! `licor head`, `cat`, `explain`, `summary` and `schema`: looking at data without converting it
+
+use licor_core::{LiCorData, merge_data, peek, summarize, VariableSummary, SchemaColumn, output_schema, schema_ddl, ConfigStandard, ConfigFluorometer, ParseError, ParseOptions, Message};
+#[cfg(feature = "device-6800")]
+use licor_core::{ConfigAquatic, ConfigSoil, Device6800};
+#[cfg(feature = "device-6400")]
+use licor_core::Device6400;
+use glob::glob;
+use crate::args::{Config, Device, SchemaFormat};
+use crate::commands::{parse_input, parse_table, read_raw_tables};
+use crate::commands::validate::validate_best_config;
+use crate::{CliExit, Reporter};
+use crate::output::{aligned_table, format_cell, page_listing, print_listing};
+
+pub(crate) fn print_head(
+    device: Option<Device>,
+    config: Option<Config>,
+    input_path: String,
+    rows: usize,
+    columns: Vec<String>
+) -> Result<(), Box<dyn std::error::Error>> {
+    let device = device.unwrap_or_else(|| Device::detect(&input_path));
+    let config = match config {
+        Some(config) => config,
+        None => validate_best_config(&device, &input_path)?.0,
+    };
+    
+    // Text logs are only read as far as the rows printed
+    let tables = if device.reads_workbooks() {
+        read_raw_tables(&device, &input_path)?
+    } else {
+        vec![(None, peek(&input_path, rows)?)]
+    };
+    
+    let mut listing = String::new();
+    for (label, raw) in tables {
+        if let Some(label) = label {
+            listing.push_str(&format!("{}#{}\n", input_path, label));
+        }
+        let data = parse_table(&device, &config, raw)?;
+        let df = data.dataframe.head(Some(rows));
+        let df = if columns.is_empty() { df } else { df.select(&columns)? };
+        listing.push_str(&aligned_table(&data_table(&data, &df, 0)));
+    }
+    print_listing(listing)
+}
+
+pub(crate) fn print_cat(
+    device: Option<Device>,
+    config: Option<Config>,
+    input_path: String,
+    columns: Vec<String>,
+    page_size: usize,
+    no_pager: bool
+) -> Result<(), Box<dyn std::error::Error>> {
+    let device = device.unwrap_or_else(|| Device::detect(&input_path));
+    let config = match config {
+        Some(config) => config,
+        None => validate_best_config(&device, &input_path)?.0,
+    };
+    
+    let mut listing = String::new();
+    for (label, raw) in read_raw_tables(&device, &input_path)? {
+        if let Some(label) = label {
+            listing.push_str(&format!("{}#{}\n", input_path, label));
+        }
+        let data = parse_table(&device, &config, raw)?;
+        let df = if columns.is_empty() { data.dataframe.clone() } else { data.dataframe.select(&columns)? };
+        listing.push_str(&aligned_table(&data_table(&data, &df, page_size)));
+    }
+    if no_pager {
+        print_listing(listing)
+    } else {
+        page_listing(listing)
+    }
+}
+
+/// Names, units and cell text of `df`, with the names and units repeated
+/// every `page_size` rows when it is not zero
+pub(crate) fn print_explain(
+    device: Option<Device>,
+    config: Option<Config>,
+    input_path: String,
+    obs: i64,
+    columns: Vec<String>
+) -> Result<(), Box<dyn std::error::Error>> {
+    let device = device.unwrap_or_else(|| Device::detect(&input_path));
+    let config = match config {
+        Some(config) => config,
+        None => validate_best_config(&device, &input_path)?.0,
+    };
+    
+    let mut listing = String::new();
+    for (label, raw) in read_raw_tables(&device, &input_path)? {
+        let data = parse_table(&device, &config, raw)?;
+        let df = if columns.is_empty() { data.dataframe.clone() } else { data.dataframe.select(&columns)? };
+        let row = match data.dataframe.column("obs") {
+            Ok(column) => column.cast(&polars::prelude::DataType::Int64)?.i64()?.into_iter().position(|value| value == Some(obs)),
+            Err(_) => usize::try_from(obs - 1).ok().filter(|&row| row < df.height()),
+        };
+        let Some(row) = row else { continue };
+        
+        let source = match label {
+            Some(label) => format!("{}#{}", input_path, label),
+            None => input_path.clone(),
+        };
+        listing.push_str(&format!("Observation {} of {}\n\n", obs, source));
+        let mut table = vec![["Variable", "Value", "Units", "Description"].map(String::from).to_vec()];
+        for column in df.get_columns() {
+            let name = column.name().as_str();
+            let info = data.variable_info.iter().find(|v| v.internal_name == name);
+            let variable = match info.map(|info| info.display_label.as_str()) {
+                Some(display) if display != name => format!("{} ({})", display, name),
+                _ => name.to_string(),
+            };
+            table.push(vec![
+                variable,
+                column.get(row).map(format_cell).unwrap_or_default(),
+                info.and_then(|info| info.units.clone()).unwrap_or_default(),
+                info.and_then(|info| info.description.lines().next()).unwrap_or_default().to_string(),
+            ]);
+        }
+        listing.push_str(&aligned_table(&table));
+        listing.push('\n');
+    }
+    if listing.is_empty() {
+        return Err(format!("{} has no observation {}", input_path, obs).into());
+    }
+    print_listing(listing)
+}
+
+fn data_table(data: &LiCorData, df: &polars::prelude::DataFrame, page_size: usize) -> Vec<Vec<String>> {
+    let units = |name: &str| data.variable_info.iter()
+        .find(|v| v.internal_name == name)
+        .and_then(|v| v.units.clone())
+        .unwrap_or_default();
+    let names: Vec<String> = df.get_column_names().iter().map(|name| name.to_string()).collect();
+    let units: Vec<String> = names.iter().map(|name| units(name)).collect();
+    let mut table = Vec::new();
+    for row in 0..df.height() {
+        if row == 0 || (page_size > 0 && row % page_size == 0) {
+            table.push(names.clone());
+            table.push(units.clone());
+        }
+        table.push(df.get_columns().iter()
+            .map(|column| column.get(row).map(format_cell).unwrap_or_default())
+            .collect());
+    }
+    if table.is_empty() {
+        table.extend([names, units]);
+    }
+    table
+}
+
+pub(crate) fn print_summary(
+    device: Option<Device>,
+    config: Option<Config>,
+    input_pattern: String,
+    columns: Vec<String>,
+    dataset: bool,
+    json: bool,
+    reporter: Reporter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input_files: Vec<_> = glob(&input_pattern)?
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    if input_files.is_empty() {
+        return Err(CliExit::new(1, Message::NoFilesFound { pattern: &input_pattern }.text(reporter.language)).into());
+    }
+    
+    // (name, files, data) for each table summarized
+    let mut parsed: Vec<(String, usize, LiCorData)> = Vec::new();
+    for input_file in &input_files {
+        let input_path = input_file.to_string_lossy();
+        // Converted files carry their own metadata and variable info
+        if input_file.extension().is_some_and(|e| e.eq_ignore_ascii_case("parquet")) {
+            parsed.push((input_path.to_string(), 1, LiCorData::from_parquet(&input_path)?));
+            continue;
+        }
+        let device = device.clone().unwrap_or_else(|| Device::detect(&input_path));
+        let config = match &config {
+            Some(config) => config.clone(),
+            None => validate_best_config(&device, &input_path)?.0,
+        };
+        for (label, data) in parse_input(&device, &config, &input_path, &ParseOptions::default(), reporter)? {
+            let name = match label {
+                Some(label) => format!("{}#{}", input_path, label),
+                None => input_path.to_string(),
+            };
+            parsed.push((name, 1, data));
+        }
+    }
+    if dataset {
+        let files = input_files.len();
+        let merged = merge_data(parsed.into_iter().map(|(_, _, data)| data).collect(), &[])?;
+        parsed = vec![(input_pattern.clone(), files, merged)];
+    }
+    
+    let summaries: Vec<(String, usize, usize, Vec<VariableSummary>)> = parsed.iter()
+        .map(|(name, files, data)| Ok((name.clone(), *files, data.dataframe.height(), summarize(data, &columns)?)))
+        .collect::<Result<_, ParseError>>()?;
+    
+    if json {
+        let entries: Vec<serde_json::Value> = summaries.iter()
+            .map(|(name, files, observations, variables)| serde_json::json!({
+                "input": name,
+                "files": files,
+                "observations": observations,
+                "variables": variables.iter().map(|v| serde_json::json!({
+                    "name": v.name,
+                    "units": v.units,
+                    "count": v.count,
+                    "missing": v.missing,
+                    "min": v.min,
+                    "max": v.max,
+                    "mean": v.mean,
+                })).collect::<Vec<_>>(),
+            }))
+            .collect();
+        return print_listing(format!("{}\n", serde_json::to_string_pretty(&entries)?));
+    }
+    
+    let number = |value: Option<f64>| value.map(|v| format!("{:.6}", v).trim_end_matches('0').trim_end_matches('.').to_string()).unwrap_or_default();
+    let mut listing = String::new();
+    for (name, files, observations, variables) in &summaries {
+        if *files > 1 {
+            listing.push_str(&format!("{}: {} files, {} observations\n", name, files, observations));
+        } else {
+            listing.push_str(&format!("{}: {} observations\n", name, observations));
+        }
+        let header = ["VARIABLE", "UNITS", "N", "NA", "MIN", "MAX", "MEAN"].map(String::from).to_vec();
+        let rows: Vec<Vec<String>> = std::iter::once(header)
+            .chain(variables.iter().map(|v| vec![
+                v.name.clone(),
+                v.units.clone().unwrap_or_default(),
+                v.count.to_string(),
+                v.missing.to_string(),
+                number(v.min),
+                number(v.max),
+                number(v.mean),
+            ]))
+            .collect();
+        listing.push_str(&aligned_table(&rows));
+        listing.push('\n');
+    }
+    print_listing(listing)
+}
+
+pub(crate) fn print_schema(
+    device: Device,
+    config: Config,
+    format: SchemaFormat,
+    all: bool,
+    table: &str
+) -> Result<(), Box<dyn std::error::Error>> {
+    let columns: Vec<SchemaColumn> = match (&device, &config) {
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Standard) => output_schema::<Device6800, ConfigStandard>(all),
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Fluorometer) => output_schema::<Device6800, ConfigFluorometer>(all),
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Aquatic) => output_schema::<Device6800, ConfigAquatic>(all),
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Soil) => output_schema::<Device6800, ConfigSoil>(all),
+        #[cfg(feature = "device-6400")]
+        (Device::Li6400, Config::Standard) => output_schema::<Device6400, ConfigStandard>(all),
+        #[cfg(feature = "device-6400")]
+        (Device::Li6400, Config::Fluorometer) => output_schema::<Device6400, ConfigFluorometer>(all),
+        #[cfg(feature = "device-6400")]
+        (Device::Li6400, _) => {
+            return Err(format!("Config {:?} is not supported for the LI-6400", config).into());
+        }
+    };
+    
+    let listing = match format {
+        SchemaFormat::Json => {
+            let entries: Vec<serde_json::Value> = columns.iter()
+                .map(|c| serde_json::json!({
+                    "name": c.name,
+                    "dtype": c.data_type.arrow_name(),
+                    "type": c.data_type.name(),
+                    "units": c.units,
+                    "category": c.category,
+                    "description": c.description,
+                    "required": c.required,
+                }))
+                .collect();
+            format!("{}\n", serde_json::to_string_pretty(&entries)?)
+        }
+        SchemaFormat::Sql => schema_ddl(table, &columns),
+    };
+    print_listing(listing)
+}
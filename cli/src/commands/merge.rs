@@ -0,0 +1,75 @@
+//! `licor merge`: several files into one output
+
+use licor_core::{merge_data, ParseOptions, Message, ParquetOptions};
+use glob::glob;
+use crate::args::{Config, Device, OutputFormat};
+use crate::commands::{parse_input, write_output};
+use crate::commands::convert::batch_progress;
+use crate::{CliExit, Reporter};
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn merge_files(
+    device: Device,
+    config: Config,
+    input_pattern: String,
+    output_path: String,
+    format: OutputFormat,
+    sort_by: Vec<String>,
+    verbose: bool,
+    reporter: Reporter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input_files: Vec<_> = glob(&input_pattern)?
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    if input_files.is_empty() {
+        return Err(CliExit::new(1, Message::NoFilesFound { pattern: &input_pattern }.text(reporter.language)).into());
+    }
+    
+    let options = ParseOptions { include_source: true, ..ParseOptions::default() };
+    let mut datasets = Vec::new();
+    let mut merged_files = 0;
+    let mut failed_conversions = Vec::new();
+    let progress = batch_progress(input_files.len(), verbose);
+    for input_file in input_files {
+        let input_path = input_file.to_string_lossy();
+        progress.set_message(input_path.to_string());
+        match parse_input(&device, &config, &input_path, &options, reporter) {
+            Ok(parsed) => {
+                if verbose {
+                    let rows: usize = parsed.iter().map(|(_, data)| data.dataframe.height()).sum();
+                    println!("{}: {} rows", input_path, rows);
+                }
+                datasets.extend(parsed.into_iter().map(|(_, data)| data));
+                merged_files += 1;
+            }
+            Err(e) => {
+                let error = progress.suspend(|| reporter.failure(&input_path, &*e));
+                failed_conversions.push((input_path.to_string(), error));
+            }
+        }
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+    
+    if !datasets.is_empty() {
+        let sort_by = if sort_by.is_empty() && datasets.iter().all(|d| d.dataframe.column("time").is_ok()) {
+            vec!["time".to_string()]
+        } else {
+            sort_by
+        };
+        let merged = merge_data(datasets, &sort_by)?;
+        write_output(&merged, &format, &ParquetOptions::default(), &output_path)?;
+        if verbose {
+            println!("  → {} ({} rows, {} columns)", output_path, merged.dataframe.height(), merged.dataframe.width());
+        }
+    }
+    
+    println!();
+    println!("{}", Message::ConversionComplete.text(reporter.language));
+    println!("  {}", Message::SucceededCount(merged_files).text(reporter.language));
+    println!("  {}", Message::FailedCount(failed_conversions.len()).text(reporter.language));
+    
+    reporter.check_failed(failed_conversions)?;
+    
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//! `licor metadata`: metadata records in community standards
+
+use licor_core::{ParseOptions, Message, ColumnCrosswalk, MetadataStandard, SiteInfo, write_metadata_crosswalk};
+use std::path::Path;
+use glob::glob;
+use crate::args::{Config, Device, OutputFormat};
+use crate::commands::{parse_input, sanitize_label};
+use crate::{CliExit, Reporter};
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn export_metadata(
+    device: Device,
+    config: Config,
+    input_pattern: String,
+    output_dir: String,
+    standard: MetadataStandard,
+    format: OutputFormat,
+    site: &SiteInfo,
+    crosswalk: Option<&ColumnCrosswalk>,
+    options: &ParseOptions,
+    reporter: Reporter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input_files: Vec<_> = glob(&input_pattern)?
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    if input_files.is_empty() {
+        return Err(CliExit::new(1, Message::NoFilesFound { pattern: &input_pattern }.text(reporter.language)).into());
+    }
+    
+    // Name each dataset the way `convert` would
+    let mut files = Vec::new();
+    for input_file in input_files {
+        let input_path = input_file.to_string_lossy();
+        let input_filename = Path::new(input_path.as_ref())
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("output");
+        for (label, data) in parse_input(&device, &config, &input_path, options, reporter)? {
+            let stem = match &label {
+                Some(label) => format!("{}_{}", input_filename, sanitize_label(label)),
+                None => input_filename.to_string(),
+            };
+            let file_name = match format.extension() {
+                "" => stem,
+                extension => format!("{}.{}", stem, extension),
+            };
+            let data = match crosswalk {
+                Some(crosswalk) => data.apply_crosswalk(crosswalk)?,
+                None => data,
+            };
+            files.push((file_name, data));
+        }
+    }
+    
+    for path in write_metadata_crosswalk(&files, site, standard, &output_dir)? {
+        println!("→ {}", path);
+    }
+    
+    Ok(())
+}
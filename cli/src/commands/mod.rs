@@ -0,0 +1,214 @@
+//! The subcommands, and the parsing and writing they share
+
+pub(crate) mod convert;
+pub(crate) mod curves;
+pub(crate) mod dataset;
+pub(crate) mod diff;
+pub(crate) mod doctor;
+pub(crate) mod inspect;
+pub(crate) mod merge;
+pub(crate) mod metadata;
+pub(crate) mod plot;
+pub(crate) mod validate;
+pub(crate) mod variables;
+pub(crate) mod watch;
+
+use licor_core::{LiCorData, ParseOptions, RawLiCorFile, ValidationReport, ParquetOptions, write_xlsx, write_netcdf, write_parquet};
+#[cfg(feature = "device-6800")]
+use licor_core::{LiCor6800Standard, LiCor6800Fluorometer, LiCor6800Aquatic, LiCor6800Soil};
+#[cfg(feature = "device-6400")]
+use licor_core::{LiCor6400Standard, LiCor6400Fluorometer, Message, SheetResult};
+use crate::args::{Config, Device, OutputFormat};
+use crate::Reporter;
+#[cfg(feature = "device-6400")]
+use crate::emit_json;
+
+/// Parsed dataset with an optional label (the workbook sheet it came from)
+type LabelledData = (Option<String>, LiCorData);
+
+/// Validation report of a file or workbook sheet, labelled like `LabelledData`
+type LabelledReport = (Option<String>, ValidationReport);
+
+/// Unconverted data table, labelled like `LabelledData`
+type LabelledTable = (Option<String>, RawLiCorFile);
+
+/// Parse an input file into one or more datasets
+///
+/// Text logs produce a single unlabelled dataset; LI-6400 workbooks produce one
+/// dataset per data table, labelled by sheet.
+pub(crate) fn parse_input(
+    device: &Device,
+    config: &Config,
+    input_path: &str,
+    options: &ParseOptions,
+    // Only LI-6400 workbooks report skipped sheets
+    #[cfg_attr(not(feature = "device-6400"), allow(unused_variables))]
+    reporter: Reporter,
+) -> Result<Vec<LabelledData>, Box<dyn std::error::Error>> {
+    // Parse file based on device and config combination
+    let datasets = match (device, config) {
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Standard) => {
+            let parser = LiCor6800Standard::with_options(options.clone());
+            vec![(None, parser.parse_file(input_path)?)]
+        }
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Fluorometer) => {
+            let parser = LiCor6800Fluorometer::with_options(options.clone());
+            vec![(None, parser.parse_file(input_path)?)]
+        }
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Aquatic) => {
+            let parser = LiCor6800Aquatic::with_options(options.clone());
+            vec![(None, parser.parse_file(input_path)?)]
+        }
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Soil) => {
+            let parser = LiCor6800Soil::with_options(options.clone());
+            vec![(None, parser.parse_file(input_path)?)]
+        }
+        #[cfg(feature = "device-6400")]
+        (Device::Li6400, Config::Standard) => {
+            let parser = LiCor6400Standard::with_options(options.clone());
+            collect_sheets(parser.parse_xls(input_path)?, reporter)?
+        }
+        #[cfg(feature = "device-6400")]
+        (Device::Li6400, Config::Fluorometer) => {
+            let parser = LiCor6400Fluorometer::with_options(options.clone());
+            collect_sheets(parser.parse_xls(input_path)?, reporter)?
+        }
+        #[cfg(feature = "device-6400")]
+        (Device::Li6400, _) => {
+            return Err(format!("Config {:?} is not supported for the LI-6400", config).into());
+        }
+    };
+    
+    Ok(datasets)
+}
+
+/// Convert one already-read data table
+fn parse_table(
+    device: &Device,
+    config: &Config,
+    raw: RawLiCorFile
+) -> Result<LiCorData, Box<dyn std::error::Error>> {
+    let data = match (device, config) {
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Standard) => LiCor6800Standard::new().parse_raw(raw)?,
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Fluorometer) => LiCor6800Fluorometer::new().parse_raw(raw)?,
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Aquatic) => LiCor6800Aquatic::new().parse_raw(raw)?,
+        #[cfg(feature = "device-6800")]
+        (Device::Li6800, Config::Soil) => LiCor6800Soil::new().parse_raw(raw)?,
+        #[cfg(feature = "device-6400")]
+        (Device::Li6400, Config::Standard) => LiCor6400Standard::new().parse_raw(raw)?,
+        #[cfg(feature = "device-6400")]
+        (Device::Li6400, Config::Fluorometer) => LiCor6400Fluorometer::new().parse_raw(raw)?,
+        #[cfg(feature = "device-6400")]
+        (Device::Li6400, _) => {
+            return Err(format!("Config {:?} is not supported for the LI-6400", config).into());
+        }
+    };
+    
+    Ok(data)
+}
+
+/// Keep the workbook sheets that parsed, warning about the rest
+#[cfg(feature = "device-6400")]
+fn collect_sheets(
+    sheets: Vec<SheetResult>,
+    reporter: Reporter,
+) -> Result<Vec<LabelledData>, Box<dyn std::error::Error>> {
+    let mut parsed = Vec::new();
+    for (label, result) in sheets {
+        match result {
+            Ok(data) => parsed.push((Some(label), data)),
+            Err(e) => {
+                let error = e.localized(reporter.language);
+                if reporter.json() {
+                    emit_json("warning", Some(&label), "sheet_skipped", None, &error);
+                } else {
+                    eprintln!("  {}", Message::SkippingSheet { sheet: &label, error: &error }.text(reporter.language));
+                }
+            }
+        }
+    }
+    
+    if parsed.is_empty() {
+        return Err("No sheet in the workbook could be converted".into());
+    }
+    
+    Ok(parsed)
+}
+
+fn sanitize_label(label: &str) -> String {
+    label.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+pub(crate) fn write_output(
+    data: &LiCorData,
+    format: &OutputFormat,
+    parquet: &ParquetOptions,
+    output_path: &str
+) -> Result<(), Box<dyn std::error::Error>> {
+    use polars::prelude::*;
+    
+    match format {
+        OutputFormat::Parquet => {
+            write_parquet(data, output_path, parquet)?;
+        }
+        OutputFormat::Csv => {
+            let mut file = std::fs::File::create(output_path)?;
+            CsvWriter::new(&mut file)
+                .include_header(true)
+                .finish(&mut data.dataframe.clone())?;
+        }
+        OutputFormat::Xlsx => {
+            write_xlsx(data, output_path)?;
+        }
+        OutputFormat::Netcdf => {
+            write_netcdf(data, output_path)?;
+        }
+        OutputFormat::Hive => {
+            return Err("Hive output is a partitioned dataset, not a single file".into());
+        }
+        OutputFormat::Delta => {
+            return Err("Delta Lake output is appended to a table, not written to a file".into());
+        }
+        OutputFormat::Postgres => {
+            return Err("PostgreSQL output is written to a database, not a file".into());
+        }
+    }
+    
+    Ok(())
+}
+
+/// Unconverted data tables of a file, labelled by sheet for workbooks
+fn read_raw_tables(
+    device: &Device,
+    input_path: &str
+) -> Result<Vec<LabelledTable>, Box<dyn std::error::Error>> {
+    if device.reads_workbooks() {
+        #[cfg(feature = "device-6400")]
+        {
+            Ok(licor_core::xls::read_xls_sheets(input_path)?
+                .into_iter()
+                .map(|(label, raw)| (Some(label), raw))
+                .collect())
+        }
+        #[cfg(not(feature = "device-6400"))]
+        unreachable!("only the LI-6400 reads workbooks")
+    } else {
+        Ok(vec![(None, RawLiCorFile::parse(&std::fs::read_to_string(input_path)?)?)])
+    }
+}
+
+/// First non-null value of a column, as text
+fn first_value(df: &polars::prelude::DataFrame, column: &str) -> Option<String> {
+    let values = df.column(column).ok()?.cast(&polars::prelude::DataType::String).ok()?;
+    let value = values.str().ok()?.into_iter().flatten().next().map(str::to_string);
+    value.filter(|v| !v.trim().is_empty())
+}
@@ -0,0 +1,47 @@
+//! `licor plot`: quick-look SVG plots
+
+use licor_core::{ParseOptions, PlotSpec};
+use std::path::Path;
+use crate::args::{Config, Device};
+use crate::commands::{parse_input, sanitize_label};
+use crate::commands::validate::validate_best_config;
+use crate::Reporter;
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn plot_file(
+    device: Option<Device>,
+    config: Option<Config>,
+    input_path: &str,
+    output_dir: &str,
+    y: Vec<String>,
+    x: Option<String>,
+    aci: bool,
+    reporter: Reporter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let device = device.unwrap_or_else(|| Device::detect(input_path));
+    let config = match config {
+        Some(config) => config,
+        None => validate_best_config(&device, input_path)?.0,
+    };
+    let input_filename = Path::new(input_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output");
+    
+    std::fs::create_dir_all(output_dir)?;
+    for (label, data) in parse_input(&device, &config, input_path, &ParseOptions::default(), reporter)? {
+        let spec = match (aci, &x) {
+            (true, _) => PlotSpec::aci(&data),
+            (false, Some(x)) => PlotSpec { x: x.clone(), y: y.clone(), lines: false },
+            (false, None) => PlotSpec::time_series(&data, y.clone()),
+        };
+        let (title, output_stem) = match &label {
+            Some(label) => (format!("{}#{}", input_path, label), format!("{}_{}", input_filename, sanitize_label(label))),
+            None => (input_path.to_string(), input_filename.to_string()),
+        };
+        let output_path = Path::new(output_dir).join(format!("{}.svg", output_stem));
+        std::fs::write(&output_path, data.plot_svg(&spec, &title)?)?;
+        println!("{}", output_path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+//! `licor validate`: check files parse without writing anything
+
+use licor_core::{ValidationReport, CoercionReport, Message};
+#[cfg(feature = "device-6800")]
+use licor_core::{LiCor6800Standard, LiCor6800Fluorometer, LiCor6800Aquatic, LiCor6800Soil};
+#[cfg(feature = "device-6400")]
+use licor_core::{LiCor6400Standard, LiCor6400Fluorometer};
+use glob::glob;
+use crate::args::{Config, Device, value_name};
+use crate::commands::{LabelledReport, read_raw_tables};
+use crate::{CliExit, Reporter, emit_json, error_code};
+
+pub(crate) fn validate_files(
+    device: Option<Device>,
+    config: Option<Config>,
+    input_pattern: String,
+    verbose: bool,
+    reporter: Reporter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input_files: Vec<_> = glob(&input_pattern)?
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    if input_files.is_empty() {
+        return Err(CliExit::new(1, Message::NoFilesFound { pattern: &input_pattern }.text(reporter.language)).into());
+    }
+    
+    let total = input_files.len();
+    let mut failed = 0;
+    for input_file in input_files {
+        let input_path = input_file.to_string_lossy();
+        let device = device.clone().unwrap_or_else(|| Device::detect(&input_path));
+        let result = match &config {
+            Some(config) => validate_input(&device, config, &input_path).map(|tables| (config.clone(), tables)),
+            None => validate_best_config(&device, &input_path),
+        };
+        
+        match result {
+            Ok((config, tables)) => {
+                let mut valid = true;
+                for (label, report) in tables {
+                    let name = match &label {
+                        Some(label) => format!("{}#{}", input_path, label),
+                        None => input_path.to_string(),
+                    };
+                    println!("{} ({}, {}): {}", name, value_name(&device), value_name(&config),
+                        if report.is_valid() { "OK" } else { "problems found" });
+                    print_validation_report(&report, verbose);
+                    valid &= report.is_valid();
+                }
+                if !valid {
+                    failed += 1;
+                }
+            }
+            Err(e) if reporter.json() => {
+                emit_json("error", Some(&input_path), error_code(&*e), None, &reporter.localize(&*e));
+                failed += 1;
+            }
+            Err(e) => {
+                println!("{} ({}): {}", input_path, value_name(&device), reporter.localize(&*e));
+                failed += 1;
+            }
+        }
+    }
+    
+    println!();
+    println!("{} of {} files passed validation", total - failed, total);
+    if failed > 0 {
+        return Err(CliExit::reported(1, format!("{} of {} files failed validation", failed, total)).into());
+    }
+    
+    Ok(())
+}
+
+/// Validate every data table of a file
+fn validate_input(
+    device: &Device,
+    config: &Config,
+    input_path: &str
+) -> Result<Vec<LabelledReport>, Box<dyn std::error::Error>> {
+    let mut reports = Vec::new();
+    for (label, raw) in read_raw_tables(device, input_path)? {
+        let report = match (device, config) {
+            #[cfg(feature = "device-6800")]
+            (Device::Li6800, Config::Standard) => LiCor6800Standard::new().validate_raw(raw)?,
+            #[cfg(feature = "device-6800")]
+            (Device::Li6800, Config::Fluorometer) => LiCor6800Fluorometer::new().validate_raw(raw)?,
+            #[cfg(feature = "device-6800")]
+            (Device::Li6800, Config::Aquatic) => LiCor6800Aquatic::new().validate_raw(raw)?,
+            #[cfg(feature = "device-6800")]
+            (Device::Li6800, Config::Soil) => LiCor6800Soil::new().validate_raw(raw)?,
+            #[cfg(feature = "device-6400")]
+            (Device::Li6400, Config::Standard) => LiCor6400Standard::new().validate_raw(raw)?,
+            #[cfg(feature = "device-6400")]
+            (Device::Li6400, Config::Fluorometer) => LiCor6400Fluorometer::new().validate_raw(raw)?,
+            #[cfg(feature = "device-6400")]
+            (Device::Li6400, _) => {
+                return Err(format!("Config {:?} is not supported for the LI-6400", config).into());
+            }
+        };
+        reports.push((label, report));
+    }
+    
+    Ok(reports)
+}
+
+/// Validate against the configuration with the fewest missing variables
+/// (the first listed wins ties, so fluorometer logs are not reported as standard)
+pub(crate) fn validate_best_config(
+    device: &Device,
+    input_path: &str
+) -> Result<(Config, Vec<LabelledReport>), Box<dyn std::error::Error>> {
+    let mut best: Option<(Config, Vec<LabelledReport>)> = None;
+    let mut last_error = None;
+    for config in [Config::Fluorometer, Config::Standard, Config::Aquatic, Config::Soil] {
+        match validate_input(device, &config, input_path) {
+            Ok(tables) => {
+                let missing = |tables: &[LabelledReport]| -> usize {
+                    tables.iter().map(|(_, report)| report.missing_variables.len()).sum()
+                };
+                if best.as_ref().is_none_or(|(_, current)| missing(&tables) < missing(current)) {
+                    best = Some((config, tables));
+                }
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    best.ok_or_else(|| last_error.unwrap_or_else(|| "No configuration could be checked".into()))
+}
+
+/// Malformed rows and offending values listed without `--verbose`
+const VALIDATION_PREVIEW: usize = 5;
+
+fn print_validation_report(report: &ValidationReport, verbose: bool) {
+    let limit = if verbose { usize::MAX } else { VALIDATION_PREVIEW };
+    for variable in &report.missing_variables {
+        println!("  Missing variable: {}", variable);
+    }
+    for (row, fields) in report.malformed_rows.iter().take(limit) {
+        println!("  Row {}: {} fields, expected {}", row, fields, report.expected_fields);
+    }
+    if report.malformed_rows.len() > limit {
+        println!("  ... {} more malformed rows", report.malformed_rows.len() - limit);
+    }
+    for mismatch in &report.unit_mismatches {
+        println!("  Units of '{}': {} (expected {})", mismatch.column, mismatch.found, mismatch.expected);
+    }
+    for fallback in report.type_fallbacks.iter().chain(&report.rounded_columns) {
+        println!("  {}:", coercion_summary(fallback));
+        for (value, count) in fallback.offending_values.iter().take(limit) {
+            println!("    {:?} × {}", value, count);
+        }
+    }
+}
+
+/// What happened to a column whose values did not convert
+pub(crate) fn coercion_summary(report: &CoercionReport) -> String {
+    if report.rounded {
+        format!("Column '{}' rounded to integers", report.column)
+    } else {
+        format!("Column '{}' kept as text (expected {:?})", report.column, report.expected_type)
+    }
+}
@@ -0,0 +1,61 @@
+//! `licor variables`: the variable dictionary
+
+use licor_core::{search_variables, VariableDef, VARIABLE_DEFINITIONS};
+use crate::args::Device;
+use crate::commands::read_raw_tables;
+use crate::output::{aligned_table, print_listing};
+
+pub(crate) fn list_variables(
+    input: Option<String>,
+    device: Option<Device>,
+    search: Option<String>,
+    json: bool
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut variables: Vec<&VariableDef> = match &input {
+        Some(input_path) => {
+            let device = device.unwrap_or_else(|| Device::detect(input_path));
+            let mut found: Vec<&VariableDef> = Vec::new();
+            for (_, raw) in read_raw_tables(&device, input_path)? {
+                for (name, units) in raw.column_names.iter().zip(&raw.units) {
+                    if let Some(def) = VariableDef::lookup(name, units) {
+                        if !found.iter().any(|f| std::ptr::eq(*f, def)) {
+                            found.push(def);
+                        }
+                    }
+                }
+            }
+            found
+        }
+        None => VARIABLE_DEFINITIONS.iter().collect(),
+    };
+    if let Some(query) = &search {
+        let logged = variables;
+        variables = search_variables(query).into_iter()
+            .filter(|def| logged.iter().any(|l| std::ptr::eq(*l, *def)))
+            .collect();
+    }
+    
+    if json {
+        let entries: Vec<serde_json::Value> = variables.iter()
+            .map(|def| serde_json::json!({
+                "internal_name": def.internal_name,
+                "label": def.display_label,
+                "units": def.units,
+                "type": def.data_type.name(),
+                "category": def.category,
+                "description": def.description,
+            }))
+            .collect();
+        return print_listing(format!("{}\n", serde_json::to_string_pretty(&entries)?));
+    }
+    
+    let header = ["NAME", "LABEL", "UNITS", "TYPE", "CATEGORY"].map(String::from).to_vec();
+    let rows: Vec<Vec<String>> = std::iter::once(header)
+        .chain(variables.iter().map(|def| {
+            [def.internal_name, def.display_label, def.units.unwrap_or(""), def.data_type.name(), def.category]
+                .map(String::from)
+                .to_vec()
+        }))
+        .collect();
+    print_listing(aligned_table(&rows))
+}
@@ -0,0 +1,88 @@
+//! `licor watch`: convert files as they appear in a folder
+
+use std::path::Path;
+use glob::glob;
+use indicatif::ProgressBar;
+use crate::args::Device;
+use crate::commands::convert::{ConvertSettings, convert_single_file};
+use crate::Reporter;
+
+pub(crate) fn watch_folder(settings: &ConvertSettings, settle: f64, existing: bool, reporter: Reporter) -> Result<(), Box<dyn std::error::Error>> {
+    use notify::{EventKind, RecursiveMode, Watcher};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::mpsc::{channel, RecvTimeoutError};
+    use std::time::{Duration, Instant};
+    
+    let input_dir = &settings.input;
+    let output_dir = settings.output_dir.as_deref().ok_or("--output is required")?;
+    if !Path::new(input_dir).is_dir() {
+        return Err(format!("'{}' is not a folder", input_dir).into());
+    }
+    std::fs::create_dir_all(output_dir)?;
+    let settle = Duration::try_from_secs_f64(settle)
+        .map_err(|_| format!("Invalid settle time: {}", settle))?;
+    // Output written inside the watched folder must not be picked up again
+    let output_root = std::fs::canonicalize(output_dir)?;
+    let is_candidate = |path: &Path| {
+        path.is_file()
+            && !path.starts_with(&output_root)
+            && !path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with('.'))
+            && Device::detect(&path.to_string_lossy()).reads_workbooks() == settings.device.reads_workbooks()
+    };
+    
+    let (sender, receiver) = channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(&std::fs::canonicalize(input_dir)?, RecursiveMode::Recursive)?;
+    println!("Watching {} (Ctrl-C to stop)", input_dir);
+    
+    // Files waiting to settle, with the time of their last change
+    let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+    if existing {
+        let pattern = Path::new(input_dir).join("**").join("*");
+        for path in glob(&pattern.to_string_lossy())?.flatten() {
+            if let Ok(path) = std::fs::canonicalize(path) {
+                if is_candidate(&path) {
+                    pending.insert(path, Instant::now() - settle);
+                }
+            }
+        }
+    }
+    
+    loop {
+        match receiver.recv_timeout(Duration::from_millis(250)) {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    for path in event.paths {
+                        if is_candidate(&path) {
+                            pending.insert(path, Instant::now());
+                        }
+                    }
+                }
+            }
+            Ok(Err(e)) => eprintln!("Watch error: {}", e),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Err("The folder watcher stopped".into()),
+        }
+        
+        let mut ready: Vec<PathBuf> = pending.iter()
+            .filter(|(_, changed)| changed.elapsed() >= settle)
+            .map(|(path, _)| path.clone())
+            .collect();
+        ready.sort();
+        for path in ready {
+            pending.remove(&path);
+            let input_path = path.to_string_lossy();
+            match convert_single_file(settings, &input_path, &ProgressBar::hidden(), reporter) {
+                Ok(converted) => {
+                    for output_path in converted.outputs {
+                        println!("{} → {}", input_path, output_path);
+                    }
+                }
+                Err(e) => {
+                    reporter.failure(&input_path, &*e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! The `licor` command-line tool, as a library so other packages (the Python
+//! wheel) can ship it; the binary only calls [`run_cli`].
+
+mod args;
+mod bisect;
+mod commands;
+mod open;
+mod output;
+
+use clap::{CommandFactory, Parser};
+use licor_core::{ERROR_CODES, ParseError, ParseOptions, Language, Message, KeyValueRemarks, ColumnCrosswalk, ColumnRename, DecodeTable, InstrumentProfiles, RecomputeOptions, DerivedVariable, SiteInfo, ParquetOptions};
+use std::sync::Arc;
+use crate::args::Commands;
+use crate::bisect::bisect_output;
+use crate::commands::convert::{ConvertSettings, ExistingOutput, convert_files, with_profile_headers};
+use crate::commands::curves::concat_curves;
+use crate::commands::dataset::{build_index, migrate_dataset, write_catalog};
+use crate::commands::diff::print_diff;
+use crate::commands::doctor::{fix_file, print_doctor};
+use crate::commands::inspect::{print_cat, print_explain, print_head, print_schema, print_summary};
+use crate::commands::merge::merge_files;
+use crate::commands::metadata::export_metadata;
+use crate::commands::plot::plot_file;
+use crate::commands::validate::validate_files;
+use crate::commands::variables::list_variables;
+use crate::commands::watch::watch_folder;
+use crate::open::open_file;
+use crate::output::print_listing;
+
+#[cfg(not(any(feature = "device-6800", feature = "device-6400")))]
+compile_error!("licor needs at least one of the `device-6800` or `device-6400` features");
+
+#[derive(Parser)]
+#[command(name = "licor")]
+#[command(about = "Convert LI-COR instrument data to analysis-ready Parquet format")]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+    
+    /// Language of error messages and the convert summary: en, es or pt
+    /// (defaults to $LICOR_LANG, then English). Warnings, reports and
+    /// listings are always in English.
+    #[arg(long, global = true, value_name = "LANG")]
+    lang: Option<Language>,
+    
+    /// How failures and warnings are written to stderr: text, or json for one
+    /// JSON object per line (level, file, code, row, message)
+    #[arg(long, global = true, value_name = "FORMAT", default_value = "text")]
+    error_format: ErrorFormat,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ErrorFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// How a run reports failures and status messages, from `--lang` and
+/// `--error-format`; passed along rather than kept in a global so the CLI can
+/// run more than once in a process
+#[derive(Clone, Copy, Debug, Default)]
+struct Reporter {
+    language: Language,
+    format: ErrorFormat,
+}
+
+impl Reporter {
+    fn json(self) -> bool {
+        self.format == ErrorFormat::Json
+    }
+    
+    /// Error text in the selected language (licor-core errors are translated)
+    fn localize(self, e: &(dyn std::error::Error + 'static)) -> String {
+        match e.downcast_ref::<ParseError>() {
+            Some(e) => e.localized(self.language),
+            None => e.to_string(),
+        }
+    }
+    
+    /// Report a file that could not be processed, returning the message for the
+    /// end-of-run list
+    fn failure(self, file: &str, e: &(dyn std::error::Error + 'static)) -> String {
+        let error = self.localize(e);
+        if self.json() {
+            emit_json("error", Some(file), error_code(e), None, &error);
+        } else {
+            eprintln!("{}", Message::ConversionFailed { file, error: &error }.text(self.language));
+        }
+        error
+    }
+    
+    /// List the failed files (already reported one by one as JSON) and fail
+    /// with status 1, if any failed
+    fn check_failed(self, failed_conversions: Vec<(String, String)>) -> Result<(), CliExit> {
+        if failed_conversions.is_empty() {
+            return Ok(());
+        }
+        if !self.json() {
+            eprintln!("\n{}", Message::FailedConversions.text(self.language));
+            for (file, error) in &failed_conversions {
+                eprintln!("  {}: {}", file, error);
+            }
+        }
+        Err(CliExit::reported(1, format!("{} file(s) failed", failed_conversions.len())))
+    }
+    
+    /// Report the error that stopped a run, returning it with its exit status
+    fn exit(self, e: &(dyn std::error::Error + 'static)) -> CliExit {
+        if let Some(exit) = e.downcast_ref::<CliExit>() {
+            if !exit.reported {
+                self.report(error_code(e), &exit.message, &exit.message);
+            }
+            return CliExit::reported(exit.status, exit.message.clone());
+        }
+        let message = self.localize(e);
+        self.report(error_code(e), &format!("Error: {}", message), &message);
+        CliExit::reported(exit_status(e), message)
+    }
+    
+    fn report(self, code: &str, text: &str, message: &str) {
+        if self.json() {
+            emit_json("error", None, code, None, message);
+        } else {
+            eprintln!("{}", text);
+        }
+    }
+}
+
+/// An unsuccessful run of the CLI: the status the process should exit with and
+/// what went wrong, already written to stderr by [`run_cli`]
+#[derive(Debug, Clone)]
+pub struct CliExit {
+    /// Exit status: an `ERROR_CODES` number for licor-core and I/O errors, 2
+    /// for invalid arguments, 1 otherwise
+    pub status: i32,
+    /// What went wrong, in the selected language
+    pub message: String,
+    reported: bool,
+}
+
+impl CliExit {
+    /// A failure still to be written to stderr
+    fn new(status: i32, message: String) -> Self {
+        CliExit { status, message, reported: false }
+    }
+    
+    /// A failure whose details were already written to stderr
+    fn reported(status: i32, message: String) -> Self {
+        CliExit { status, message, reported: true }
+    }
+}
+
+impl std::fmt::Display for CliExit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CliExit {}
+
+/// Write one failure or warning as a JSON line on stderr (`--error-format json`);
+/// `row` is the 1-based data row the message refers to, if any
+fn emit_json(level: &str, file: Option<&str>, code: &str, row: Option<usize>, message: &str) {
+    eprintln!("{}", serde_json::json!({
+        "level": level,
+        "file": file,
+        "code": code,
+        "row": row,
+        "message": message,
+    }));
+}
+
+/// Stable identifier of an error for --error-format json
+fn error_code(e: &(dyn std::error::Error + 'static)) -> &'static str {
+    if let Some(e) = e.downcast_ref::<ParseError>() {
+        e.code()
+    } else if e.is::<std::io::Error>() {
+        "io"
+    } else {
+        "error"
+    }
+}
+
+/// Exit status for an error: the error's number for licor-core and I/O errors
+/// (see `ERROR_CODES`), 1 otherwise
+fn exit_status(e: &(dyn std::error::Error + 'static)) -> i32 {
+    let code = error_code(e);
+    ERROR_CODES.iter()
+        .find(|(name, _)| *name == code)
+        .map_or(1, |(_, number)| i32::from(*number))
+}
+
+/// Run the CLI with the given arguments, the first being the program name
+///
+/// Failures are written to stderr as the binary writes them and returned with
+/// the status to exit with; the process itself is never exited, so this can run
+/// repeatedly in one process. `--help` prints and returns `Ok`.
+pub fn run_cli<I, T>(args: I) -> Result<(), CliExit>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    let cli = match Cli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return match e.exit_code() {
+                0 => Ok(()),
+                status => Err(CliExit::reported(status, e.kind().to_string())),
+            };
+        }
+    };
+    let reporter = Reporter {
+        language: cli.lang.unwrap_or_else(Language::from_env),
+        format: cli.error_format,
+    };
+    run(cli.command, reporter).map_err(|e| reporter.exit(&*e))
+}
+
+fn run(command: Commands, reporter: Reporter) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Commands::Convert { device, config, input, output, format, dsn, trim_to_stable, step_summary, qc, stability_columns, remark_columns, smoothing, derive, header_columns, clock_anchors, decode_table, instruments, recompute, oxygen, gbw_model, gbw_tolerance, gcw, add_source_column, long, profile, column_order, sort, units, crosswalk, renames, harmonize, partition_by_device, split_by, split_name, compression, compression_level, no_dictionary, row_group_size, sort_by, coercion_report, non_finite, duplicate_columns, anomaly_threshold, review, size_report, dry_run, overwrite, skip_existing, fail_if_exists, verbose } => {
+            let options = ParseOptions {
+                coercion_report,
+                trim_to_stable,
+                include_source: add_source_column,
+                smoothing,
+                header_columns: with_profile_headers(header_columns, profile),
+                clock_anchors,
+                derived: DerivedVariable::from_args(&derive)?,
+                column_order,
+                row_order: sort,
+                decode_table: decode_table.as_deref().map(DecodeTable::from_file).transpose()?,
+                recompute: recompute.then(|| RecomputeOptions {
+                    oxygen,
+                    boundary_layer: gbw_model.unwrap_or_default(),
+                    gbw_tolerance,
+                    cuticular_conductance: gcw,
+                }),
+                stability_columns,
+                remark_parsers: if remark_columns { vec![Arc::new(KeyValueRemarks)] } else { Vec::new() },
+                instrument_profiles: instruments.or_else(|| std::env::var("LICOR_INSTRUMENTS").ok().filter(|path| !path.is_empty()))
+                    .as_deref()
+                    .map(InstrumentProfiles::from_file)
+                    .transpose()?
+                    .map(Arc::new),
+                hooks: Default::default(),
+                non_finite,
+                duplicate_columns,
+            };
+            let settings = ConvertSettings {
+                device,
+                config,
+                input,
+                output_dir: output,
+                format,
+                dsn,
+                options,
+                qc,
+                step_summary,
+                split_by,
+                split_name,
+                harmonize,
+                renames: ColumnRename::from_args(&renames)?,
+                long_format: long,
+                profile,
+                crosswalk: crosswalk.as_deref().map(ColumnCrosswalk::from_file).transpose()?,
+                unit_style: units,
+                partition_by_device,
+                parquet: ParquetOptions {
+                    codec: compression,
+                    level: compression_level,
+                    dictionary: !no_dictionary,
+                    row_group_size,
+                    sort_by,
+                },
+                existing: ExistingOutput::from_flags(overwrite, skip_existing, fail_if_exists),
+                anomaly_threshold,
+                review,
+                size_report: size_report || dry_run,
+                dry_run,
+                verbose,
+            };
+            convert_files(&settings, reporter)
+        }
+        Commands::Open { device, config, input, format, viewer } => {
+            open_file(device, config, input, format, viewer, reporter)?;
+            Ok(())
+        }
+        Commands::ConcatCurves { device, config, input, output, format, sample_id, profile, crosswalk, verbose } => {
+            let settings = ConvertSettings {
+                input,
+                options: ParseOptions {
+                    header_columns: with_profile_headers(Vec::new(), profile),
+                    ..ParseOptions::default()
+                },
+                profile,
+                crosswalk: crosswalk.as_deref().map(ColumnCrosswalk::from_file).transpose()?,
+                verbose,
+                ..ConvertSettings::new(device, config, output, format)
+            };
+            concat_curves(&settings, sample_id.as_deref(), reporter)
+        }
+        Commands::Validate { device, config, input, verbose } => {
+            validate_files(device, config, input, verbose, reporter)?;
+            Ok(())
+        }
+        Commands::Variables { input, device, search, json } => {
+            list_variables(input, device, search, json)?;
+            Ok(())
+        }
+        Commands::Watch { device, config, input, output, format, settle, existing, verbose } => {
+            let settings = ConvertSettings { input, verbose, ..ConvertSettings::new(device, config, output, format) };
+            watch_folder(&settings, settle, existing, reporter)
+        }
+        Commands::Merge { device, config, input, output, format, sort_by, verbose } => {
+            merge_files(device, config, input, output, format, sort_by, verbose, reporter)?;
+            Ok(())
+        }
+        Commands::Head { device, config, input, rows, columns } => {
+            print_head(device, config, input, rows, columns)?;
+            Ok(())
+        }
+        Commands::Cat { device, config, input, columns, page_size, no_pager } => {
+            print_cat(device, config, input, columns, page_size, no_pager)?;
+            Ok(())
+        }
+        Commands::Explain { input, obs, device, config, columns } => {
+            print_explain(device, config, input, obs, columns)
+        }
+        Commands::Summary { device, config, input, columns, dataset, json } => {
+            print_summary(device, config, input, columns, dataset, json, reporter)?;
+            Ok(())
+        }
+        Commands::Schema { device, config, format, all, table } => {
+            print_schema(device, config, format, all, &table)?;
+            Ok(())
+        }
+        Commands::Plot { device, config, input, output, y, x, aci } => {
+            plot_file(device, config, &input, &output, y, x, aci, reporter)
+        }
+        Commands::Doctor { input, device, json } => {
+            print_doctor(&input, device, json, reporter)
+        }
+        Commands::Fix { input, output, json } => {
+            fix_file(&input, output, json)
+        }
+        Commands::Index { dir, output, verbose } => {
+            build_index(&dir, &output, verbose, reporter)
+        }
+        Commands::Migrate { dir, to_schema, dry_run, json } => {
+            migrate_dataset(&dir, to_schema, dry_run, json, reporter)
+        }
+        Commands::Catalog { dir, output, id, description, site_id, latitude, longitude } => {
+            let site = SiteInfo { site_id, latitude, longitude };
+            write_catalog(&dir, &output, id, &description, &site, reporter)
+        }
+        Commands::BisectOutput { old_bin, new_bin, input, device, config, tolerance, json } => {
+            bisect_output(&old_bin, new_bin, &input, &device, &config, tolerance, json)
+        }
+        Commands::Diff { left, right, device, json } => {
+            print_diff(&left, &right, device, json)?;
+            Ok(())
+        }
+        Commands::Completions { shell } => {
+            let mut script = Vec::new();
+            clap_complete::generate(shell, &mut Cli::command(), "licor", &mut script);
+            print_listing(String::from_utf8(script)?)
+        }
+        Commands::Metadata { device, config, input, output, standard, format, site_id, latitude, longitude, crosswalk, instruments } => {
+            let site = SiteInfo { site_id, latitude, longitude };
+            let crosswalk = crosswalk.as_deref().map(ColumnCrosswalk::from_file).transpose()?;
+            let options = ParseOptions {
+                instrument_profiles: instruments.or_else(|| std::env::var("LICOR_INSTRUMENTS").ok().filter(|path| !path.is_empty()))
+                    .as_deref()
+                    .map(InstrumentProfiles::from_file)
+                    .transpose()?
+                    .map(Arc::new),
+                ..ParseOptions::default()
+            };
+            export_metadata(device, config, input, output, standard, format, &site, crosswalk.as_ref(), &options, reporter)?;
+            Ok(())
+        }
+    }
+}
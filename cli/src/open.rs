@@ -0,0 +1,69 @@
+//! `licor open`: convert a file to a temporary copy and hand it to a viewer
+
+use licor_core::{ParseOptions, ParquetOptions};
+use std::path::Path;
+use crate::args::{Config, Device, OutputFormat, ViewFormat};
+use crate::commands::{parse_input, write_output};
+use crate::Reporter;
+
+pub(crate) fn open_file(
+    device: Device,
+    config: Config,
+    input_path: String,
+    format: ViewFormat,
+    viewer: Option<String>,
+    reporter: Reporter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (_, data) = parse_input(&device, &config, &input_path, &ParseOptions::default(), reporter)?
+        .into_iter()
+        .next()
+        .ok_or("Input contained no data")?;
+    
+    // A fresh, uniquely named temp file, kept after we exit so a viewer that
+    // returns at once (xdg-open, open) can still read it
+    let format = OutputFormat::from(format);
+    let input_filename = Path::new(&input_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("output");
+    let (_, temp_path) = tempfile::Builder::new()
+        .prefix(&format!("{}-", input_filename))
+        .suffix(&format!(".{}", format.extension()))
+        .tempfile()?
+        .keep()?;
+    let temp_path = temp_path.to_string_lossy().to_string();
+    
+    write_output(&data, &format, &ParquetOptions::default(), &temp_path)?;
+    
+    let viewer = viewer
+        .or_else(|| std::env::var("LICOR_VIEWER").ok())
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| default_opener().to_string());
+    
+    let mut parts = viewer.split_whitespace();
+    let program = parts.next().ok_or("Empty viewer command")?;
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(&temp_path)
+        .status()
+        .map_err(|e| format!("Failed to launch viewer '{}': {}", program, e))?;
+    
+    // explorer exits with 1 even when it opened the file
+    let explorer = cfg!(target_os = "windows") && program.eq_ignore_ascii_case("explorer");
+    if !status.success() && !explorer {
+        return Err(format!("Viewer '{}' exited with {}", program, status).into());
+    }
+    
+    Ok(())
+}
+
+/// Platform command that opens a file with its default application
+fn default_opener() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    }
+}
@@ -0,0 +1,92 @@
+//! Printing results: sizes, aligned tables and the pager
+
+pub(crate) mod review;
+
+/// Size in bytes with a decimal unit, e.g. `1.2 MB`
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while size >= 1000.0 && unit + 1 < UNITS.len() {
+        size /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// ` (ratio N.NN)`, input over output bytes, unless either is empty
+pub(crate) fn compression_ratio(input_bytes: u64, output_bytes: u64) -> String {
+    if input_bytes == 0 || output_bytes == 0 {
+        return String::new();
+    }
+    format!(" (ratio {:.2})", input_bytes as f64 / output_bytes as f64)
+}
+
+/// Table cell text: strings unquoted, nulls blank
+pub(crate) fn format_cell(value: polars::prelude::AnyValue) -> String {
+    use polars::prelude::AnyValue;
+    match value {
+        AnyValue::Null => String::new(),
+        AnyValue::String(s) => s.to_string(),
+        AnyValue::StringOwned(s) => s.to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// Rows as left-aligned columns separated by two spaces
+pub(crate) fn aligned_table(rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        widths.resize(widths.len().max(row.len()), 0);
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut table = String::new();
+    for row in rows {
+        let cells: Vec<String> = row.iter().zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+            .collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// Print to stdout, treating a closed pipe (e.g. `| head`) as success
+pub(crate) fn print_listing(listing: String) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+    match std::io::stdout().lock().write_all(listing.as_bytes()) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Show through `$PAGER` (`less -S` by default) when stdout is a terminal,
+/// printing directly otherwise or when the pager cannot be started
+pub(crate) fn page_listing(listing: String) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{IsTerminal, Write};
+    use std::process::{Command, Stdio};
+    if !std::io::stdout().is_terminal() {
+        return print_listing(listing);
+    }
+    let pager = std::env::var("PAGER").ok().filter(|pager| !pager.trim().is_empty());
+    let pager = pager.as_deref().unwrap_or("less -S");
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or("less");
+    let Ok(mut child) = Command::new(program).args(words).stdin(Stdio::piped()).spawn() else {
+        return print_listing(listing);
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes its input
+        match stdin.write_all(listing.as_bytes()) {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.into()),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
@@ -0,0 +1,152 @@
+//! The interactive review of a batch after `licor convert --review`
+
+use licor_core::diagnose;
+use crate::commands::convert::{BatchEntry, BatchStatus};
+use crate::Reporter;
+
+/// Interactive summary of a batch: the files with their status, and the
+/// details of a file when its number is entered
+///
+/// Only runs with a terminal on stdin and stdout, and not with `--json-errors`.
+pub(crate) fn review_batch(entries: &[BatchEntry], reporter: Reporter) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufRead, IsTerminal, Write};
+    if entries.is_empty() || reporter.json() || !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Ok(());
+    }
+    let mut failed_only = false;
+    let mut stdin = std::io::stdin().lock();
+    loop {
+        println!("\n{}", review_listing(entries, failed_only));
+        print!("File number for details, `f` to toggle failed files only, Enter to quit: ");
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer)? == 0 {
+            return Ok(());
+        }
+        match answer.trim() {
+            "" | "q" => return Ok(()),
+            "f" => failed_only = !failed_only,
+            answer => match answer.parse::<usize>().ok().and_then(|number| entries.get(number.wrapping_sub(1))) {
+                Some(entry) => {
+                    println!("\n{}", review_detail(entry));
+                    print!("Press Enter to return to the list: ");
+                    std::io::stdout().flush()?;
+                    stdin.read_line(&mut String::new())?;
+                }
+                None => println!("No file numbered '{}'", answer),
+            },
+        }
+    }
+}
+
+/// One line per file: number, status, file and the error or warning count
+fn review_listing(entries: &[BatchEntry], failed_only: bool) -> String {
+    let mut listing = String::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let (status, note) = match &entry.status {
+            BatchStatus::Failed(error) => ("failed", error.lines().next().unwrap_or_default().to_string()),
+            _ if failed_only => continue,
+            BatchStatus::Converted => ("converted", String::new()),
+            BatchStatus::Skipped => ("skipped", String::new()),
+        };
+        let note = match entry.warnings.len() {
+            0 => note,
+            1 => "1 warning".to_string(),
+            count => format!("{} warnings", count),
+        };
+        let line = format!("{:>5}  {:<9}  {}  {}", index + 1, status, entry.file, note);
+        listing.push_str(line.trim_end());
+        listing.push('\n');
+    }
+    listing.trim_end().to_string()
+}
+
+/// A file's status, outputs and warnings, and for a failed text log its
+/// error with the lines `licor doctor` finds problems on
+fn review_detail(entry: &BatchEntry) -> String {
+    let mut detail = format!("{}\n", entry.file);
+    for output in &entry.outputs {
+        detail.push_str(&format!("  → {}\n", output));
+    }
+    for warning in &entry.warnings {
+        detail.push_str(&format!("  warning: {}\n", warning));
+    }
+    if let BatchStatus::Failed(error) = &entry.status {
+        detail.push_str(&format!("  error: {}\n", error));
+        if let Ok(bytes) = std::fs::read(&entry.file) {
+            let content = String::from_utf8_lossy(&bytes);
+            let lines: Vec<&str> = content.lines().collect();
+            for finding in diagnose(&bytes) {
+                let location = finding.line.map(|line| format!(" (line {})", line)).unwrap_or_default();
+                detail.push_str(&format!("  {}{}: {}\n    fix: {}\n", finding.severity.name(), location, finding.problem, finding.fix));
+                if let Some(line) = finding.line.and_then(|line| lines.get(line - 1)) {
+                    let text: String = line.replace('\t', " | ").chars().take(120).collect();
+                    detail.push_str(&format!("    > {}\n", text));
+                }
+            }
+        }
+    } else if entry.warnings.is_empty() {
+        detail.push_str("  no warnings\n");
+    }
+    detail.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorFormat;
+
+    fn entries() -> Vec<BatchEntry> {
+        vec![
+            BatchEntry {
+                file: "a.txt".to_string(),
+                status: BatchStatus::Converted,
+                outputs: vec!["out/a.parquet".to_string()],
+                warnings: Vec::new(),
+            },
+            BatchEntry {
+                file: "b.txt".to_string(),
+                status: BatchStatus::Skipped,
+                outputs: Vec::new(),
+                warnings: vec!["first".to_string(), "second".to_string()],
+            },
+            BatchEntry {
+                file: "/nonexistent/licor-review/c.txt".to_string(),
+                status: BatchStatus::Failed("Invalid header format\nmore detail".to_string()),
+                outputs: Vec::new(),
+                warnings: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_review_listing() {
+        let entries = entries();
+        assert_eq!(review_listing(&entries, false), [
+            "    1  converted  a.txt",
+            "    2  skipped    b.txt  2 warnings",
+            "    3  failed     /nonexistent/licor-review/c.txt  Invalid header format",
+        ].join("\n"));
+        // Files keep their numbers when only the failed ones are listed
+        assert_eq!(review_listing(&entries, true), "    3  failed     /nonexistent/licor-review/c.txt  Invalid header format");
+    }
+
+    #[test]
+    fn test_review_detail() {
+        let entries = entries();
+        assert_eq!(review_detail(&entries[0]), "a.txt\n  → out/a.parquet\n  no warnings");
+        assert_eq!(review_detail(&entries[1]), "b.txt\n  warning: first\n  warning: second");
+        assert_eq!(
+            review_detail(&entries[2]),
+            "/nonexistent/licor-review/c.txt\n  error: Invalid header format\nmore detail"
+        );
+    }
+
+    #[test]
+    fn test_review_batch_needs_a_terminal() {
+        // Nothing to review, and JSON errors, both return without prompting
+        assert!(review_batch(&[], Reporter::default()).is_ok());
+        let reporter = Reporter { format: ErrorFormat::Json, ..Reporter::default() };
+        assert!(review_batch(&entries(), reporter).is_ok());
+    }
+}
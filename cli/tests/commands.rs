@@ -0,0 +1,384 @@
+//! Subcommands run through the `licor` binary, checking exit status and output
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const SAMPLE: &str = "../example_data/2025-05-30-1203_logdata_F2";
+
+fn licor(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_licor"))
+        .args(args)
+        .env("LICOR_LANG", "en")
+        .output()
+        .expect("Should run the licor binary")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// A fresh directory under the system temp dir, removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("licor_cli_{}_{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+
+    fn join(&self, name: &str) -> String {
+        self.0.join(name).to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+#[test]
+fn test_migrate_walks_hive_partitions() {
+    let dir = TempDir::new("migrate_hive");
+    let dataset = dir.join("dataset");
+    let output = licor(&["convert", "--input", SAMPLE, "--output", &dataset, "--device", "6800", "--config", "fluorometer", "--format", "hive", "--partition-by-device"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = licor(&["migrate", &dataset, "--to-schema", "v2", "--dry-run", "--json"]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["to_schema"], 2);
+    let files = report["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert!(files[0]["file"].as_str().unwrap().contains("date=2025-05-29"));
+    assert_eq!(files[0]["status"], "current");
+
+    // Delta tables are left to their transaction log
+    let table = dir.join("table");
+    assert!(licor(&["convert", "--input", SAMPLE, "--output", &table, "--device", "6800", "--config", "fluorometer", "--format", "delta"]).status.success());
+    let output = licor(&["migrate", &table, "--json"]);
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert!(report["files"].as_array().unwrap().is_empty());
+    assert!(dir.path().join("table/_delta_log").is_dir());
+
+    let output = licor(&["migrate", &dataset, "--to-schema", "v1"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_open_hands_a_temp_file_to_the_viewer() {
+    // `test -s` succeeds only if it is handed a file that is not empty
+    let output = licor(&["open", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--format", "parquet", "--viewer", "test -s"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = licor(&["open", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--viewer", "false"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Viewer 'false' exited with"));
+
+    // Datasets and databases are not files a viewer can open
+    let output = licor(&["open", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--format", "hive", "--viewer", "true"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_convert_existing_outputs() {
+    let dir = TempDir::new("existing");
+    let output_dir = dir.join("out");
+    let convert = |flag: Option<&str>| {
+        let mut args = vec!["convert", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--output", &output_dir];
+        args.extend(flag);
+        licor(&args)
+    };
+    assert!(convert(None).status.success());
+
+    // Replaced by default, with a note saying how to keep them
+    let output = convert(None);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Replaced 1 existing output file(s)"));
+
+    let output = convert(Some("--overwrite"));
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Replaced"));
+
+    let output = convert(Some("--skip-existing"));
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("Skipped (output exists): 1"));
+
+    let output = convert(Some("--fail-if-exists"));
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+}
+
+#[test]
+fn test_convert_estimates_sizes() {
+    let dir = TempDir::new("dry_run");
+    let output_dir = dir.join("out");
+    let output = licor(&["convert", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--output", &output_dir, "--dry-run"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("Estimated size: 37.3 kB in"));
+    assert!(stdout(&output).contains("Dry run: no output was written"));
+    assert!(!dir.path().join("out").exists());
+
+    let output = licor(&["convert", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--output", &output_dir, "--size-report"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("Size: 37.3 kB in"));
+    assert!(dir.path().join("out/2025-05-30-1203_logdata_F2.parquet").is_file());
+}
+
+#[test]
+fn test_concat_curves() {
+    let dir = TempDir::new("concat_curves");
+    let output = licor(&["concat-curves", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--output", &dir.join("curves"), "--format", "csv"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains("Successfully converted: 1"));
+    assert!(dir.path().join("curves/2025-05-30-1203_logdata_F2_other_2025-05-29.csv").is_file());
+
+    let output = licor(&["concat-curves", "--device", "6800", "--config", "fluorometer", "--input", &dir.join("*.txt"), "--output", &dir.join("curves")]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_validate() {
+    let output = licor(&["validate", "--input", SAMPLE]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("(6800, fluorometer): OK"));
+    assert!(stdout(&output).contains("1 of 1 files passed validation"));
+
+    // Not a LI-COR file
+    let output = licor(&["validate", "--device", "6800", "--config", "standard", "--input", "Cargo.toml"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_variables() {
+    let output = licor(&["variables", "--search", "assimilation"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).lines().any(|line| line.starts_with("A ")));
+
+    let output = licor(&["variables", "--input", SAMPLE, "--json"]);
+    assert!(output.status.success());
+    let variables: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(variables[0]["label"], "obs");
+
+    // Exit status 20 is the `io` error code
+    let output = licor(&["variables", "--input", "/nonexistent/licor-variables"]);
+    assert_eq!(output.status.code(), Some(20));
+}
+
+#[test]
+fn test_watch_converts_existing_files() {
+    let dir = TempDir::new("watch");
+    std::fs::create_dir(dir.path().join("incoming")).unwrap();
+    std::fs::copy(SAMPLE, dir.path().join("incoming/sample")).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_licor"))
+        .args(["watch", "--device", "6800", "--config", "fluorometer", "--input", &dir.join("incoming"), "--output", &dir.join("out"), "--existing", "--settle", "0"])
+        .env("LICOR_LANG", "en")
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let converted = dir.path().join("out/sample.parquet");
+    let start = std::time::Instant::now();
+    while !converted.exists() && start.elapsed() < std::time::Duration::from_secs(30) {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    child.kill().unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(converted.is_file());
+    assert!(stdout(&output).contains("(Ctrl-C to stop)"));
+
+    let output = licor(&["watch", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--output", &dir.join("out")]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is not a folder"));
+}
+
+#[test]
+fn test_merge() {
+    let dir = TempDir::new("merge");
+    let merged = dir.join("merged.csv");
+    let output = licor(&["merge", "--device", "6800", "--config", "fluorometer", "--input", "../example_data/*", "--output", &merged, "--format", "csv"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains("Successfully converted: 2"));
+    assert!(std::fs::metadata(&merged).unwrap().len() > 0);
+
+    let output = licor(&["merge", "--device", "6800", "--config", "fluorometer", "--input", &dir.join("*.txt"), "--output", &merged]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_head_and_cat() {
+    let output = licor(&["head", "--input", SAMPLE, "-n", "3", "--columns", "obs,A"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("obs") && lines[1].contains("µmol m-2 s-1"));
+    assert!(lines[4].starts_with("3 "));
+
+    let output = licor(&["cat", "--input", SAMPLE, "--columns", "obs,A", "--no-pager", "--page-size", "0"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output).lines().count(), 12);
+
+    let output = licor(&["head", "--input", SAMPLE, "--columns", "no_such_column"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_explain() {
+    let output = licor(&["explain", SAMPLE, "--obs", "2", "--columns", "A"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert!(text.starts_with("Observation 2 of"));
+    assert!(text.lines().any(|line| line.starts_with("A ") && line.contains("-1.963655") && line.contains("Assimilation rate")));
+
+    let output = licor(&["explain", SAMPLE, "--obs", "99"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_summary() {
+    let output = licor(&["summary", "--input", SAMPLE, "--columns", "A", "--json"]);
+    assert!(output.status.success());
+    let summary: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(summary[0]["observations"], 10);
+    let a = &summary[0]["variables"][0];
+    assert_eq!(a["name"], "A");
+    assert_eq!(a["count"], 10);
+    assert!(a["min"].as_f64().unwrap() <= a["mean"].as_f64().unwrap());
+
+    let output = licor(&["summary", "--input", "../example_data/*", "--dataset", "--columns", "A"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_schema() {
+    let output = licor(&["schema", "--device", "6800", "--config", "fluorometer", "--format", "sql", "--table", "gasex"]);
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert!(text.starts_with("CREATE TABLE IF NOT EXISTS \"gasex\" ("));
+    assert!(text.contains("\"obs\" BIGINT"));
+
+    let output = licor(&["schema", "--device", "6800", "--config", "fluorometer"]);
+    assert!(output.status.success());
+    let schema: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(schema[0]["name"], "obs");
+    assert_eq!(schema[0]["dtype"], "Int64");
+}
+
+#[test]
+fn test_plot() {
+    let dir = TempDir::new("plot");
+    let output = licor(&["plot", "--input", SAMPLE, "--output", &dir.join("plots"), "--y", "A"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let svg = dir.path().join("plots/2025-05-30-1203_logdata_F2.svg");
+    assert_eq!(stdout(&output).trim(), svg.to_string_lossy());
+    assert!(std::fs::read_to_string(&svg).unwrap().contains("<svg"));
+
+    // Exit status 12 is the `unknown_variable` error code
+    let output = licor(&["plot", "--input", SAMPLE, "--output", &dir.join("plots"), "--y", "no_such_column"]);
+    assert_eq!(output.status.code(), Some(12));
+}
+
+#[test]
+fn test_doctor_and_fix() {
+    let output = licor(&["doctor", SAMPLE, "--json"]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert!(report["findings"].as_array().unwrap().is_empty());
+
+    let dir = TempDir::new("fix");
+    let output = licor(&["fix", SAMPLE, "-o", &dir.join("fixed"), "--json"]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert!(report["changes"].as_array().unwrap().is_empty());
+    assert_eq!(report["remaining_errors"], 0);
+    assert!(report["output"].is_null());
+
+    let output = licor(&["doctor", "/nonexistent/licor-doctor"]);
+    assert_eq!(output.status.code(), Some(20));
+}
+
+#[test]
+fn test_index() {
+    let dir = TempDir::new("index");
+    let catalog = dir.join("index.parquet");
+    let output = licor(&["index", "../example_data", "--output", &catalog]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains("Indexed 2 of 2 files"));
+    assert!(dir.path().join("index.parquet").is_file());
+
+    let output = licor(&["index", "/nonexistent/licor-index", "--output", &catalog]);
+    assert_eq!(output.status.code(), Some(20));
+}
+
+#[test]
+fn test_catalog() {
+    let dir = TempDir::new("catalog");
+    let outputs = dir.join("out");
+    assert!(licor(&["convert", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--output", &outputs]).status.success());
+    let catalog = dir.join("catalog.json");
+    let output = licor(&["catalog", &outputs, "-o", &catalog, "--site-id", "marsh"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains("Cataloged 1 Parquet files"));
+    let catalog: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&catalog).unwrap()).unwrap();
+    assert_eq!(catalog["features"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_bisect_output() {
+    let licor_bin = env!("CARGO_BIN_EXE_licor");
+    let output = licor(&["bisect-output", "--old-bin", licor_bin, "--new-bin", licor_bin, "--input", SAMPLE, "--device", "6800", "--config", "fluorometer", "--json"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(report["files"][0]["same"], true);
+    assert_eq!(report["files"][0]["outputs"][0]["output"], "2025-05-30-1203_logdata_F2.parquet");
+
+    // A version that fails every file differs from one that converts it
+    let output = licor(&["bisect-output", "--old-bin", "false", "--new-bin", licor_bin, "--input", SAMPLE, "--device", "6800", "--config", "fluorometer"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("Compared 1 files: 0 with the same output, 1 differ"));
+
+    let output = licor(&["bisect-output", "--old-bin", licor_bin, "--new-bin", licor_bin, "--input", "/nonexistent/licor-bisect/*", "--device", "6800", "--config", "fluorometer"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_diff() {
+    let kinetics = "../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1";
+    let output = licor(&["diff", SAMPLE, kinetics, "--json"]);
+    assert!(output.status.success());
+    let diff: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(diff["header"][0]["key"], "File opened");
+    assert!(diff["only_left"].as_array().unwrap().is_empty());
+
+    let output = licor(&["diff", SAMPLE, "/nonexistent/licor-diff"]);
+    assert_eq!(output.status.code(), Some(20));
+}
+
+#[test]
+fn test_completions() {
+    let output = licor(&["completions", "bash"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("_licor()"));
+
+    let output = licor(&["completions", "no-such-shell"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_metadata() {
+    let dir = TempDir::new("metadata");
+    let output = licor(&["metadata", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--output", &dir.join("meta"), "--standard", "essdive"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout(&output).contains("flmd.csv"));
+    assert!(dir.path().join("meta/flmd.csv").is_file());
+    assert!(dir.path().join("meta/dd.csv").is_file());
+
+    let output = licor(&["metadata", "--device", "6800", "--config", "fluorometer", "--input", SAMPLE, "--output", &dir.join("meta"), "--standard", "efi"]);
+    assert_eq!(output.status.code(), Some(2));
+}
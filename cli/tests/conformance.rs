@@ -0,0 +1,143 @@
+//! Cross-binding conformance tests
+//!
+//! Each case in `conformance/cases/*.json` names a fixture file, the device and
+//! configuration to parse it with, and the summary every language surface must
+//! reproduce. This file checks licor-core and the `licor` CLI; the Python and R
+//! packages run the same cases from their own test suites.
+//!
+//! Set `LICOR_BLESS=1` to rewrite the expected summaries from licor-core output.
+
+use licor_core::{LiCor6800Standard, LiCor6800Fluorometer, LiCor6800Aquatic, LiCor6800Soil, LiCorData};
+use polars::prelude::*;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Relative tolerance for comparing column means
+const TOLERANCE: f64 = 1e-9;
+
+fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+fn load_cases() -> Vec<(PathBuf, Value)> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(repo_root().join("conformance/cases"))
+        .expect("conformance/cases should exist")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "No conformance cases found");
+
+    paths.into_iter()
+        .map(|path| {
+            let case = serde_json::from_str(&std::fs::read_to_string(&path).unwrap())
+                .unwrap_or_else(|e| panic!("Invalid case {}: {}", path.display(), e));
+            (path, case)
+        })
+        .collect()
+}
+
+fn parse_with_core(fixture: &Path, device: &str, config: &str) -> LiCorData {
+    let path = fixture.to_str().unwrap();
+    let result = match (device, config) {
+        ("6800", "standard") => LiCor6800Standard::new().parse_file(path),
+        ("6800", "fluorometer") => LiCor6800Fluorometer::new().parse_file(path),
+        ("6800", "aquatic") => LiCor6800Aquatic::new().parse_file(path),
+        ("6800", "soil") => LiCor6800Soil::new().parse_file(path),
+        _ => panic!("Unsupported conformance case: {} {}", device, config),
+    };
+    result.unwrap_or_else(|e| panic!("Failed to parse {}: {}", fixture.display(), e))
+}
+
+/// Summary of the columns listed in the case: non-null count and mean
+fn summarize_columns(df: &DataFrame, columns: &[String]) -> Value {
+    let mut summaries = serde_json::Map::new();
+    for name in columns {
+        let column = df.column(name)
+            .unwrap_or_else(|_| panic!("Missing column '{}'", name))
+            .cast(&DataType::Float64)
+            .unwrap();
+        let values = column.f64().unwrap();
+        summaries.insert(name.clone(), json!({
+            "non_null": values.len() - values.null_count(),
+            "mean": values.mean(),
+        }));
+    }
+    Value::Object(summaries)
+}
+
+fn assert_dataframe_matches(surface: &str, case: &Value, df: &DataFrame) {
+    let fixture = case["fixture"].as_str().unwrap();
+    assert_eq!(df.height() as u64, case["rows"].as_u64().unwrap(), "{}: row count for {}", surface, fixture);
+    assert_eq!(df.width() as u64, case["columns"].as_u64().unwrap(), "{}: column count for {}", surface, fixture);
+
+    let expected = case["column_summaries"].as_object().unwrap();
+    let names: Vec<String> = expected.keys().cloned().collect();
+    let actual = summarize_columns(df, &names);
+    for (name, expected) in expected {
+        let actual = &actual[name];
+        assert_eq!(actual["non_null"], expected["non_null"], "{}: non-null count of '{}' in {}", surface, name, fixture);
+        match (actual["mean"].as_f64(), expected["mean"].as_f64()) {
+            (Some(actual), Some(expected)) => assert!(
+                (actual - expected).abs() <= TOLERANCE * expected.abs().max(1.0),
+                "{}: mean of '{}' in {} was {}, expected {}", surface, name, fixture, actual, expected
+            ),
+            (actual, expected) => assert_eq!(actual, expected, "{}: mean of '{}' in {}", surface, name, fixture),
+        }
+    }
+}
+
+#[test]
+fn test_core_conformance() {
+    let bless = std::env::var("LICOR_BLESS").is_ok_and(|v| v == "1");
+
+    for (path, mut case) in load_cases() {
+        let fixture = repo_root().join(case["fixture"].as_str().unwrap());
+        let data = parse_with_core(&fixture, case["device"].as_str().unwrap(), case["config"].as_str().unwrap());
+
+        if bless {
+            let names: Vec<String> = case["column_summaries"].as_object().unwrap().keys().cloned().collect();
+            case["rows"] = json!(data.dataframe.height());
+            case["columns"] = json!(data.dataframe.width());
+            case["metadata"] = json!({
+                "device_serial": data.metadata.device_serial,
+                "console_version": data.metadata.console_version,
+            });
+            case["column_summaries"] = summarize_columns(&data.dataframe, &names);
+            std::fs::write(&path, serde_json::to_string_pretty(&case).unwrap() + "\n").unwrap();
+            continue;
+        }
+
+        assert_dataframe_matches("core", &case, &data.dataframe);
+        assert_eq!(data.metadata.device_serial, case["metadata"]["device_serial"].as_str().unwrap());
+        assert_eq!(data.metadata.console_version, case["metadata"]["console_version"].as_str().unwrap());
+    }
+}
+
+#[test]
+fn test_cli_conformance() {
+    let output_dir = std::env::temp_dir().join(format!("licor_conformance_{}", std::process::id()));
+
+    for (_, case) in load_cases() {
+        let fixture = repo_root().join(case["fixture"].as_str().unwrap());
+        let output = Command::new(env!("CARGO_BIN_EXE_licor"))
+            .args(["convert", "--device", case["device"].as_str().unwrap()])
+            .args(["--config", case["config"].as_str().unwrap()])
+            .arg("--input").arg(&fixture)
+            .arg("--output").arg(&output_dir)
+            .output()
+            .expect("Should run licor");
+        assert!(
+            output.status.success(),
+            "licor convert failed for {}: {}", fixture.display(), String::from_utf8_lossy(&output.stderr)
+        );
+
+        let stem = fixture.file_stem().unwrap().to_str().unwrap();
+        let file = std::fs::File::open(output_dir.join(format!("{}.parquet", stem))).unwrap();
+        let df = ParquetReader::new(file).finish().unwrap();
+        assert_dataframe_matches("cli", &case, &df);
+    }
+
+    std::fs::remove_dir_all(&output_dir).ok();
+}
@@ -0,0 +1,20 @@
+//! Running the CLI in-process, as the Python wheel does
+
+#[test]
+fn test_run_cli_returns_instead_of_exiting() {
+    assert!(licor::run_cli(["licor", "--help"]).is_ok());
+    
+    let exit = licor::run_cli(["licor", "convert", "--no-such-flag"]).unwrap_err();
+    assert_eq!(exit.status, 2);
+}
+
+#[test]
+fn test_run_cli_options_apply_per_call() {
+    let pattern = "/nonexistent/licor-run-cli/*.txt";
+    let english = licor::run_cli(["licor", "validate", "--device", "6800", "--config", "standard", "--input", pattern, "--lang", "en"]).unwrap_err();
+    assert_eq!(english.status, 1);
+    assert_eq!(english.message, format!("No files found matching pattern: {}", pattern));
+    
+    let spanish = licor::run_cli(["licor", "validate", "--device", "6800", "--config", "standard", "--input", pattern, "--lang", "es"]).unwrap_err();
+    assert_eq!(spanish.message, format!("Ningún archivo coincide con el patrón: {}", pattern));
+}
@@ -0,0 +1,134 @@
+//! Across-file comparison of a batch of conversions
+//!
+//! Each file is reduced to the mean of a few health metrics, and a file is
+//! flagged when its mean lies far from the rest of the batch. Distance is a
+//! robust z-score, `0.6745 * (x - median) / MAD`, so the faulty file itself
+//! does not widen the spread it is compared against.
+
+use crate::{ParseError, LiCorData};
+use crate::smoothing::median;
+use polars::prelude::*;
+
+/// Metrics compared across a batch, with the column names that can hold each
+/// (LI-6800 first, then LI-6400)
+pub const ANOMALY_METRICS: &[(&str, &[&str])] = &[
+    ("A", &["A", "Photo"]),
+    ("gsw", &["gsw", "Cond"]),
+    ("Leak", &["Leak"]),
+    ("LeakPct", &["LeakPct"]),
+];
+
+/// Robust z-score above which a file is flagged by default
+pub const DEFAULT_ANOMALY_THRESHOLD: f64 = 3.5;
+
+/// Files needed before a batch has a distribution to compare against
+const MIN_BATCH_SIZE: usize = 3;
+
+/// Per-file means of the `ANOMALY_METRICS`, in the same order
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileMeans {
+    pub file: String,
+    pub means: Vec<Option<f64>>,
+}
+
+/// A file whose mean of one metric deviates strongly from the batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchAnomaly {
+    pub file: String,
+    pub metric: &'static str,
+    pub mean: f64,
+    pub batch_median: f64,
+    pub score: f64,
+}
+
+impl LiCorData {
+    /// Means of the `ANOMALY_METRICS`, `None` where a metric is not logged
+    pub fn anomaly_means(&self, file: &str) -> Result<FileMeans, ParseError> {
+        let mut means = Vec::new();
+        for (_, candidates) in ANOMALY_METRICS {
+            let column = candidates.iter().find_map(|name| self.dataframe.column(name).ok());
+            let mean = match column {
+                Some(column) => column.cast(&polars::datatypes::DataType::Float64)
+                    .map_err(anomaly_error)?
+                    .f64()
+                    .map_err(anomaly_error)?
+                    .mean(),
+                None => None,
+            };
+            means.push(mean.filter(|m| m.is_finite()));
+        }
+        Ok(FileMeans { file: file.to_string(), means })
+    }
+}
+
+/// Files whose metric means lie more than `threshold` robust z-scores from the
+/// batch median, most extreme first
+///
+/// Metrics logged by fewer than three files are skipped.
+pub fn batch_anomalies(files: &[FileMeans], threshold: f64) -> Vec<BatchAnomaly> {
+    let mut anomalies = Vec::new();
+    for (index, (metric, _)) in ANOMALY_METRICS.iter().enumerate() {
+        let values: Vec<(&str, f64)> = files.iter()
+            .filter_map(|f| f.means.get(index).copied().flatten().map(|m| (f.file.as_str(), m)))
+            .collect();
+        if values.len() < MIN_BATCH_SIZE {
+            continue;
+        }
+
+        let batch_median = median(&mut values.iter().map(|(_, m)| *m).collect::<Vec<_>>());
+        let mad = median(&mut values.iter().map(|(_, m)| (m - batch_median).abs()).collect::<Vec<_>>());
+        // More than half the files agree exactly; fall back to the mean deviation
+        let spread = if mad > 0.0 {
+            mad / 0.6745
+        } else {
+            values.iter().map(|(_, m)| (m - batch_median).abs()).sum::<f64>() / values.len() as f64 * 1.2533
+        };
+        if spread == 0.0 {
+            continue;
+        }
+
+        for (file, mean) in values {
+            let score = (mean - batch_median) / spread;
+            if score.abs() > threshold {
+                anomalies.push(BatchAnomaly { file: file.to_string(), metric, mean, batch_median, score });
+            }
+        }
+    }
+    anomalies.sort_by(|a, b| b.score.abs().total_cmp(&a.score.abs()));
+    anomalies
+}
+
+fn anomaly_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to summarize batch: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn means(file: &str, a: f64, leak: Option<f64>) -> FileMeans {
+        FileMeans { file: file.to_string(), means: vec![Some(a), Some(0.2), leak, None] }
+    }
+
+    #[test]
+    fn test_batch_anomalies() {
+        let mut files = vec![
+            means("a", 12.1, Some(0.10)),
+            means("b", 11.8, Some(0.12)),
+            means("c", 12.4, Some(0.11)),
+            means("d", 12.0, None),
+            means("gasket", 4.2, Some(2.5)),
+        ];
+        let anomalies = batch_anomalies(&files, DEFAULT_ANOMALY_THRESHOLD);
+        assert_eq!(anomalies.len(), 2);
+        assert!(anomalies.iter().all(|a| a.file == "gasket"));
+        assert_eq!(anomalies[0].metric, "Leak");
+        assert!(anomalies.iter().any(|a| a.metric == "A" && a.score < 0.0 && a.batch_median == 12.0));
+
+        // Too few files to judge
+        files.truncate(2);
+        assert!(batch_anomalies(&files, DEFAULT_ANOMALY_THRESHOLD).is_empty());
+    }
+}
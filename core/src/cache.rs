@@ -0,0 +1,132 @@
+//! Parsed files kept in memory for interactive sessions
+//!
+//! A notebook or Shiny app re-running a cell or re-rendering a plot asks for
+//! the same large file again and again. The language bindings keep a
+//! [`ParseCache`] so those calls return the data parsed the first time, until
+//! the file changes on disk.
+
+use crate::LiCorData;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A file as it was when parsed, and how it was parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+    /// Device, configuration and any options that change the result
+    options: String,
+}
+
+impl CacheKey {
+    /// Key of the file at `path` as it is now
+    pub fn new(path: &str, options: impl Into<String>) -> std::io::Result<Self> {
+        let path = Path::new(path);
+        let metadata = std::fs::metadata(path)?;
+        Ok(CacheKey {
+            path: path.canonicalize()?,
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            options: options.into(),
+        })
+    }
+}
+
+/// Least-recently-used cache of parsed files, holding at most `capacity`
+/// (none when 0)
+#[derive(Debug, Default)]
+pub struct ParseCache {
+    capacity: usize,
+    /// Most recently used last
+    entries: Vec<(CacheKey, LiCorData)>,
+}
+
+impl ParseCache {
+    pub const fn new(capacity: usize) -> Self {
+        ParseCache { capacity, entries: Vec::new() }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the capacity, dropping the least recently used files over it
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let excess = self.entries.len().saturating_sub(capacity);
+        self.entries.drain(..excess);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The data cached for a key, marked as most recently used
+    pub fn get(&mut self, key: &CacheKey) -> Option<LiCorData> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(index);
+        let data = entry.1.clone();
+        self.entries.push(entry);
+        Some(data)
+    }
+
+    /// Cache parsed data, replacing an older parse of the same file
+    pub fn insert(&mut self, key: CacheKey, data: LiCorData) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|(k, _)| k.path != key.path || k.options != key.options);
+        if self.entries.len() == self.capacity {
+            self.entries.remove(0);
+        }
+        self.entries.push((key, data));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cache() {
+        let parse = |path: &str| crate::LiCor6800Fluorometer::new().parse_file(path).unwrap();
+        let first = "../example_data/2025-05-30-1203_logdata_F2";
+        let second = "../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1";
+        let key = |path: &str| CacheKey::new(path, "6800/fluorometer").unwrap();
+
+        let mut cache = ParseCache::new(0);
+        cache.insert(key(first), parse(first));
+        assert!(cache.is_empty());
+
+        cache.set_capacity(1);
+        cache.insert(key(first), parse(first));
+        assert_eq!(cache.get(&key(first)).unwrap().metadata.content_hash, parse(first).metadata.content_hash);
+        assert!(cache.get(&CacheKey::new(first, "6800/standard").unwrap()).is_none());
+        cache.insert(key(second), parse(second));
+        assert!(cache.get(&key(first)).is_none());
+        assert_eq!(cache.len(), 1);
+
+        // A file written since it was cached is parsed again
+        let path = std::env::temp_dir().join(format!("licor_test_cache_{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        std::fs::copy(first, path).unwrap();
+        let cached = key(path);
+        cache.insert(cached.clone(), parse(path));
+        std::fs::write(path, std::fs::read_to_string(second).unwrap()).unwrap();
+        assert!(cache.get(&key(path)).is_none());
+        assert!(cache.get(&cached).is_some());
+        std::fs::remove_file(path).unwrap();
+
+        cache.set_capacity(0);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+//! Catalog of converted outputs for data portals and lab data managers
+//!
+//! [`catalog_item`] reads what a portal indexes from one Parquet output of
+//! `licor convert` (instruments, time range, position, QC flags) from its
+//! metadata and a few columns, and [`catalog_json`] gathers the items into one
+//! JSON document laid out like a STAC item collection: each output is a
+//! `Feature` whose `data` asset links to the Parquet file.
+
+use crate::writers::parquet::{key_values, METADATA_KEY};
+use crate::{LiCorMetadata, ParseError, SiteInfo};
+use polars::prelude::*;
+use serde_json::{json, Map, Value};
+
+const STAC_VERSION: &str = "1.0.0";
+const PARQUET_MEDIA_TYPE: &str = "application/vnd.apache.parquet";
+/// Columns holding logged positions, e.g. from GPS remarks
+const LATITUDE_COLUMNS: &[&str] = &["latitude", "lat", "remark_latitude", "remark_lat"];
+const LONGITUDE_COLUMNS: &[&str] = &["longitude", "lon", "lng", "remark_longitude", "remark_lon"];
+
+/// One converted output in the catalog
+#[derive(Debug, Clone)]
+pub struct CatalogItem {
+    /// Identifier, unique within the catalog
+    pub id: String,
+    /// Link to the Parquet file, relative to the catalog
+    pub href: String,
+    pub metadata: LiCorMetadata,
+    pub n_obs: usize,
+    /// First and last observation, as RFC 3339 UTC timestamps
+    pub start: Option<String>,
+    pub end: Option<String>,
+    /// Bounding box `[west, south, east, north]` of the logged positions
+    pub bbox: Option<[f64; 4]>,
+    /// Observations flagged by each `qc_<check>` column, by check
+    pub qc: Vec<(String, usize)>,
+}
+
+/// Read the catalog entry of a Parquet output written by `licor convert`
+///
+/// Only the `time`, position and `qc_*` columns are read.
+pub fn catalog_item(path: &str, id: &str, href: &str) -> Result<CatalogItem, ParseError> {
+    let invalid = |message: String| ParseError::InvalidHeaderFormat {
+        message: format!("{}: {}", path, message),
+    };
+    let mut reader = ParquetReader::new(std::fs::File::open(path)?);
+    let key_values = key_values(&mut reader).map_err(|e| invalid(e.to_string()))?;
+    let metadata = key_values.iter()
+        .find(|(key, _)| key == METADATA_KEY)
+        .ok_or_else(|| invalid(format!("no {} metadata; run licor migrate on older outputs", METADATA_KEY)))?;
+    let metadata: LiCorMetadata = serde_json::from_str(&metadata.1)
+        .map_err(|e| invalid(format!("invalid {}: {}", METADATA_KEY, e)))?;
+    let n_obs = reader.num_rows().map_err(|e| invalid(e.to_string()))?;
+
+    let schema = reader.schema().map_err(|e| invalid(e.to_string()))?;
+    let names: Vec<String> = schema.iter_names().map(|name| name.to_string()).collect();
+    let find = |candidates: &[&str]| candidates.iter().find(|name| names.iter().any(|n| n == *name)).map(|name| name.to_string());
+    let (latitude, longitude) = (find(LATITUDE_COLUMNS), find(LONGITUDE_COLUMNS));
+    let wanted: Vec<String> = names.iter()
+        .filter(|name| *name == "time" || name.starts_with("qc_") || Some(*name) == latitude.as_ref() || Some(*name) == longitude.as_ref())
+        .cloned()
+        .collect();
+    let df = reader.with_columns(Some(wanted)).finish().map_err(|e| invalid(e.to_string()))?;
+
+    let numbers = |name: &str| -> Result<Vec<Option<f64>>, ParseError> {
+        let values = df.column(name)
+            .and_then(|column| column.cast(&polars::datatypes::DataType::Float64))
+            .map_err(|e| invalid(e.to_string()))?;
+        Ok(values.f64().map_err(|e| invalid(e.to_string()))?.into_iter().collect())
+    };
+    let (start, end) = if df.column("time").is_ok() {
+        let times: Vec<f64> = numbers("time")?.into_iter().flatten().filter(|t| t.is_finite()).collect();
+        let first = times.iter().copied().reduce(f64::min);
+        let last = times.iter().copied().reduce(f64::max);
+        (first.map(crate::clock::format_timestamp), last.map(crate::clock::format_timestamp))
+    } else {
+        (None, None)
+    };
+    let bbox = match (&latitude, &longitude) {
+        (Some(latitude), Some(longitude)) => numbers(latitude)?.into_iter()
+            .zip(numbers(longitude)?)
+            .filter_map(|(lat, lon)| Some((lat?, lon?)))
+            .filter(|(lat, lon)| (-90.0..=90.0).contains(lat) && (-180.0..=180.0).contains(lon))
+            .fold(None, |bbox: Option<[f64; 4]>, (lat, lon)| Some(match bbox {
+                Some([west, south, east, north]) => [west.min(lon), south.min(lat), east.max(lon), north.max(lat)],
+                None => [lon, lat, lon, lat],
+            })),
+        _ => None,
+    };
+    let qc = df.get_columns().iter()
+        .filter_map(|column| Some((column.name().strip_prefix("qc_")?.to_string(), column.bool().ok()?.sum()? as usize)))
+        .collect();
+
+    Ok(CatalogItem { id: id.to_string(), href: href.to_string(), metadata, n_obs, start, end, bbox, qc })
+}
+
+/// The catalog document of a campaign's outputs
+///
+/// Outputs without logged positions are placed at the site's coordinates
+/// when it has them.
+pub fn catalog_json(id: &str, description: &str, items: &[CatalogItem], site: &SiteInfo) -> Value {
+    let site_bbox = site.latitude.zip(site.longitude).map(|(lat, lon)| [lon, lat, lon, lat]);
+    let features: Vec<Value> = items.iter().map(|item| feature(item, item.bbox.or(site_bbox), site)).collect();
+
+    let boxes: Vec<[f64; 4]> = items.iter().filter_map(|item| item.bbox.or(site_bbox)).collect();
+    let spatial = boxes.iter().copied().reduce(|a, b| [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])]);
+    let start = items.iter().filter_map(|item| item.start.as_deref()).min();
+    let end = items.iter().filter_map(|item| item.end.as_deref()).max();
+    let links: Vec<Value> = items.iter()
+        .map(|item| json!({ "rel": "item", "href": item.href, "type": PARQUET_MEDIA_TYPE, "title": item.id }))
+        .collect();
+
+    json!({
+        "type": "FeatureCollection",
+        "stac_version": STAC_VERSION,
+        "id": id,
+        "description": description,
+        "extent": {
+            "spatial": { "bbox": spatial.map(|bbox| vec![bbox]).unwrap_or_default() },
+            "temporal": { "interval": [[start, end]] },
+        },
+        "licor:observations": items.iter().map(|item| item.n_obs).sum::<usize>(),
+        "features": features,
+        "links": links,
+    })
+}
+
+fn feature(item: &CatalogItem, bbox: Option<[f64; 4]>, site: &SiteInfo) -> Value {
+    let geometry = bbox.map(|[west, south, east, north]| if west == east && south == north {
+        json!({ "type": "Point", "coordinates": [west, south] })
+    } else {
+        json!({ "type": "Polygon", "coordinates": [[[west, south], [east, south], [east, north], [west, north], [west, south]]] })
+    });
+
+    let metadata = &item.metadata;
+    let mut properties = Map::new();
+    // STAC allows a null datetime only with a range
+    let single = item.start.is_some() && item.start == item.end;
+    properties.insert("datetime".to_string(), json!(if single { item.start.as_deref() } else { None }));
+    if !single {
+        properties.insert("start_datetime".to_string(), json!(item.start));
+        properties.insert("end_datetime".to_string(), json!(item.end));
+    }
+    properties.insert("licor:device_serial".to_string(), json!(metadata.device_serial));
+    properties.insert("licor:console_version".to_string(), json!(metadata.console_version));
+    let optional_fields = [
+        ("licor:head_serial", &metadata.head_serial),
+        ("licor:chamber_type", &metadata.chamber_type),
+        ("licor:chamber_serial", &metadata.chamber_serial),
+        ("licor:fluorometer_serial", &metadata.fluorometer_serial),
+        ("licor:calibration_date", &metadata.calibration_date),
+        ("licor:content_hash", &metadata.content_hash),
+    ];
+    for (key, value) in optional_fields {
+        if let Some(value) = value {
+            properties.insert(key.to_string(), json!(value));
+        }
+    }
+    if let Some(site_id) = &site.site_id {
+        properties.insert("licor:site_id".to_string(), json!(site_id));
+    }
+    if !metadata.calibration_certificates.is_empty() {
+        properties.insert("licor:calibration_certificates".to_string(), json!(metadata.calibration_certificates));
+    }
+    properties.insert("licor:observations".to_string(), json!(item.n_obs));
+    if !item.qc.is_empty() {
+        let qc: Map<String, Value> = item.qc.iter().map(|(check, flagged)| (check.clone(), json!(flagged))).collect();
+        properties.insert("licor:qc_flagged".to_string(), Value::Object(qc));
+    }
+
+    let mut feature = json!({
+        "type": "Feature",
+        "stac_version": STAC_VERSION,
+        "id": item.id,
+        "geometry": geometry,
+        "properties": properties,
+        "assets": {
+            "data": { "href": item.href, "type": PARQUET_MEDIA_TYPE, "roles": ["data"] },
+        },
+        "links": [],
+    });
+    if let Some(bbox) = bbox {
+        feature["bbox"] = json!(bbox);
+    }
+    feature
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog() {
+        let data = crate::LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        let checks = data.analyzer_qc(&crate::QcThresholds::default()).unwrap();
+        let mut data = data.with_qc_columns(&checks).unwrap();
+        let height = data.dataframe.height();
+        data.dataframe.with_column(Column::new("remark_lat".into(), vec![38.5; height])).unwrap();
+        data.dataframe.with_column(Column::new("remark_lon".into(), (0..height).map(|i| -121.0 - i as f64 / 100.0).collect::<Vec<_>>())).unwrap();
+        let path = std::env::temp_dir().join(format!("licor_test_catalog_{}.parquet", std::process::id()));
+        let path = path.to_str().unwrap();
+        crate::write_parquet(&data, path, &crate::ParquetOptions::default()).unwrap();
+
+        let item = catalog_item(path, "F2", "F2.parquet").unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(item.n_obs, height);
+        assert_eq!(item.metadata.device_serial, "68C-901292");
+        assert_eq!(item.start.as_deref(), Some("2025-05-29T16:09:24Z"));
+        assert!(item.end > item.start);
+        assert_eq!(item.bbox, Some([-121.0 - (height - 1) as f64 / 100.0, 38.5, -121.0, 38.5]));
+        assert_eq!(item.qc[0], ("leak_flow".to_string(), height));
+
+        let site = SiteInfo { site_id: Some("US-Xyz".to_string()), latitude: Some(40.0), longitude: Some(-100.0) };
+        let unplaced = CatalogItem { id: "other".to_string(), bbox: None, ..item.clone() };
+        let catalog = catalog_json("campaign", "Test campaign", &[item, unplaced], &site);
+        assert_eq!(catalog["features"][0]["geometry"]["type"], "Polygon");
+        assert_eq!(catalog["features"][1]["geometry"]["coordinates"], json!([-100.0, 40.0]));
+        assert_eq!(catalog["features"][0]["assets"]["data"]["href"], "F2.parquet");
+        assert_eq!(catalog["features"][0]["properties"]["licor:site_id"], "US-Xyz");
+        assert_eq!(catalog["extent"]["spatial"]["bbox"][0][3], json!(40.0));
+        assert_eq!(catalog["links"][1]["title"], "other");
+    }
+}
@@ -0,0 +1,385 @@
+use crate::{ParseError, VariableInfo, DataType};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Reference time for one observation, used to correct the instrument clock
+///
+/// Written `REFERENCE=COLUMN VALUE`, e.g. `2025-05-30T10:00:00Z=hhmmss 09:48:12`:
+/// the observation whose `hhmmss` column reads `09:48:12` actually happened
+/// at 10:00:00 UTC.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockAnchor {
+    /// True time of the observation, in seconds since the Unix epoch
+    pub reference: f64,
+    /// Column identifying the observation (`hhmmss`, `date`, `time`, `obs`, ...)
+    pub column: String,
+    /// Value of `column` in that observation
+    pub value: String,
+}
+
+impl FromStr for ClockAnchor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| format!(
+            "Invalid clock anchor '{}': {} (expected e.g. '2025-05-30T10:00:00Z=hhmmss 09:48:12')",
+            s, reason
+        );
+        let (reference, instrument) = s.split_once('=').ok_or_else(|| invalid("missing '='"))?;
+        let reference = parse_timestamp(reference.trim()).ok_or_else(|| invalid("reference is not an ISO 8601 timestamp"))?;
+        let (column, value) = instrument.trim().split_once(char::is_whitespace)
+            .ok_or_else(|| invalid("expected a column name and value after '='"))?;
+        Ok(ClockAnchor {
+            reference,
+            column: column.to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+/// Linear model mapping instrument time onto reference time
+///
+/// `corrected = t + offset + drift * (t - origin)`, with all times in epoch
+/// seconds. A single anchor gives a pure offset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClockCorrection {
+    /// Seconds added at `origin`
+    pub offset: f64,
+    /// Extra seconds gained per instrument second (positive when the instrument runs slow)
+    pub drift: f64,
+    /// Instrument time the offset refers to (mean of the anchors)
+    pub origin: f64,
+    /// Number of anchors the model was fitted to
+    pub anchors: usize,
+}
+
+impl ClockCorrection {
+    /// Fit the model to `(instrument, reference)` time pairs
+    pub fn fit(pairs: &[(f64, f64)]) -> Option<Self> {
+        if pairs.is_empty() {
+            return None;
+        }
+        let n = pairs.len() as f64;
+        let origin = pairs.iter().map(|(t, _)| t).sum::<f64>() / n;
+        let offset = pairs.iter().map(|(t, r)| r - t).sum::<f64>() / n;
+
+        // Least-squares slope of (reference - instrument) against instrument time
+        let spread: f64 = pairs.iter().map(|(t, _)| (t - origin).powi(2)).sum();
+        let drift = if spread > 0.0 {
+            pairs.iter().map(|(t, r)| (t - origin) * (r - t - offset)).sum::<f64>() / spread
+        } else {
+            0.0
+        };
+
+        Some(ClockCorrection { offset, drift, origin, anchors: pairs.len() })
+    }
+
+    /// Corrected time for an instrument time
+    pub fn correct(&self, time: f64) -> f64 {
+        time + self.offset + self.drift * (time - self.origin)
+    }
+
+    /// Short human-readable summary, e.g. `offset +708.000 s at 1748526912.000, drift 0.00 ppm, 1 anchor`
+    pub fn describe(&self) -> String {
+        format!(
+            "offset {:+.3} s at {:.3}, drift {:.2} ppm, {} anchor{}",
+            self.offset,
+            self.origin,
+            self.drift * 1e6,
+            self.anchors,
+            if self.anchors == 1 { "" } else { "s" }
+        )
+    }
+}
+
+/// Correct the epoch `time` column against reference anchors
+///
+/// The original values move to `time_instrument` and `time` holds the corrected
+/// times. The local clock readings `date` and `hhmmss` (with any numbered
+/// copies such as `hhmmss_1`) are moved by each row's correction, to the
+/// nearest second; other epoch columns (`TIME`, event times) stay as logged.
+/// Each anchor is matched to the first observation whose column equals its
+/// value. Returns `None` (and leaves the data untouched) without anchors.
+pub fn apply_clock_correction(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    anchors: &[ClockAnchor],
+) -> Result<Option<ClockCorrection>, ParseError> {
+    if anchors.is_empty() {
+        return Ok(None);
+    }
+    let times = float_values(df.column("time")
+        .map_err(|_| ParseError::UnknownVariable { variable: "time".to_string() })?)?;
+
+    let mut pairs = Vec::with_capacity(anchors.len());
+    for anchor in anchors {
+        let row = find_row(df, anchor)?;
+        let time = times[row].ok_or_else(|| clock_error(format!(
+            "observation with {} = {} has no time", anchor.column, anchor.value
+        )))?;
+        pairs.push((time, anchor.reference));
+    }
+    let correction = ClockCorrection::fit(&pairs).expect("at least one anchor");
+    let description = format!("Time corrected to reference clock ({})", correction.describe());
+    apply_correction(df, variable_info, &correction, description)?;
+    let shifts: Vec<Option<f64>> = times.iter().map(|t| t.map(|t| correction.correct(t) - t)).collect();
+    shift_clock_readings(df, &shifts)?;
+    Ok(Some(correction))
+}
+
+/// Move the `date` and `hhmmss` readings of each row by its correction
+fn shift_clock_readings(df: &mut DataFrame, shifts: &[Option<f64>]) -> Result<(), ParseError> {
+    let names: Vec<String> = df.get_column_names_str().into_iter()
+        .filter(|name| *name == "date" || *name == "hhmmss"
+            || name.strip_prefix("hhmmss_").is_some_and(|n| n.parse::<usize>().is_ok()))
+        .map(str::to_string)
+        .collect();
+    for name in names {
+        let column = df.column(&name).map_err(polars_error)?;
+        let Ok(values) = column.str() else {
+            continue;
+        };
+        let shifted: Vec<Option<String>> = values.into_iter()
+            .zip(shifts)
+            .map(|(value, shift)| value.map(|value| shift
+                .and_then(|shift| shift_reading(value, shift.round() as i64))
+                .unwrap_or_else(|| value.to_string())))
+            .collect();
+        df.with_column(Column::new(name.as_str().into(), shifted)).map_err(polars_error)?;
+    }
+    Ok(())
+}
+
+/// A `YYYYMMDD HH:MM:SS` or `HH:MM:SS` reading moved by whole seconds, or
+/// `None` if it is in neither layout
+fn shift_reading(value: &str, seconds: i64) -> Option<String> {
+    let value = value.trim();
+    let (date, clock) = match value.split_once(' ') {
+        Some((date, clock)) => (Some(date), clock),
+        None => (None, value),
+    };
+    let mut clock_parts = clock.split(':').map(|p| p.parse::<i64>().ok());
+    let (hours, minutes, secs) = (clock_parts.next()??, clock_parts.next()??, clock_parts.next()??);
+    if clock_parts.next().is_some() {
+        return None;
+    }
+    let mut total = hours * 3600 + minutes * 60 + secs + seconds;
+    if let Some(date) = date {
+        if date.len() != 8 || !date.is_ascii() {
+            return None;
+        }
+        total += days_from_civil(date[..4].parse().ok()?, date[4..6].parse().ok()?, date[6..].parse().ok()?) * 86_400;
+    }
+
+    let time = total.rem_euclid(86_400);
+    let clock = format!("{:02}:{:02}:{:02}", time / 3600, time % 3600 / 60, time % 60);
+    Some(match date {
+        Some(_) => {
+            let (year, month, day) = civil_from_days(total.div_euclid(86_400));
+            format!("{:04}{:02}{:02} {}", year, month, day, clock)
+        }
+        None => clock,
+    })
+}
+
+/// Shift the epoch `time` column of a console clock set to a UTC offset
+/// (seconds east of UTC) to UTC, keeping the original as `time_instrument`
+pub(crate) fn apply_utc_offset(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    utc_offset: i64,
+) -> Result<ClockCorrection, ParseError> {
+    let correction = ClockCorrection { offset: -utc_offset as f64, drift: 0.0, origin: 0.0, anchors: 0 };
+    let description = format!("Time converted to UTC from a console clock at UTC{}", format_utc_offset(utc_offset));
+    apply_correction(df, variable_info, &correction, description)?;
+    Ok(correction)
+}
+
+/// Offset east of UTC as `+HH:MM`
+pub(crate) fn format_utc_offset(seconds: i64) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    format!("{}{:02}:{:02}", sign, seconds.abs() / 3600, seconds.abs() % 3600 / 60)
+}
+
+fn apply_correction(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    correction: &ClockCorrection,
+    description: String,
+) -> Result<(), ParseError> {
+    let times = float_values(df.column("time")
+        .map_err(|_| ParseError::UnknownVariable { variable: "time".to_string() })?)?;
+    let corrected: Vec<Option<f64>> = times.iter().map(|t| t.map(|t| correction.correct(t))).collect();
+    let mut original = df.column("time").map_err(polars_error)?.clone();
+    original.rename("time_instrument".into());
+    df.with_column(original).map_err(polars_error)?;
+    df.with_column(Column::new("time".into(), corrected)).map_err(polars_error)?;
+
+    let source = variable_info.iter().find(|v| v.internal_name == "time").cloned();
+    variable_info.push(VariableInfo {
+        internal_name: "time_instrument".to_string(),
+        display_label: "time_instrument".to_string(),
+        units: source.as_ref().and_then(|v| v.units.clone()),
+        units_pretty: source.as_ref().and_then(|v| v.units_pretty.clone()),
+        units_ascii: source.as_ref().and_then(|v| v.units_ascii.clone()),
+        description: "Uncorrected instrument time".to_string(),
+        data_type: DataType::Float,
+        column_category: source.map(|v| v.column_category).unwrap_or_default(),
+        derived_from: Some("time".to_string()),
+    });
+    if let Some(time) = variable_info.iter_mut().find(|v| v.internal_name == "time") {
+        time.description = description;
+    }
+    Ok(())
+}
+
+/// Index of the first row whose anchor column matches the anchor value
+fn find_row(df: &DataFrame, anchor: &ClockAnchor) -> Result<usize, ParseError> {
+    let column = df.column(&anchor.column)
+        .map_err(|_| ParseError::UnknownVariable { variable: anchor.column.clone() })?;
+    let row = if column.dtype().is_primitive_numeric() {
+        let target: f64 = anchor.value.parse()
+            .map_err(|_| clock_error(format!("'{}' is not a number for column {}", anchor.value, anchor.column)))?;
+        float_values(column)?.iter().position(|v| *v == Some(target))
+    } else {
+        let values = column.cast(&polars::datatypes::DataType::String).map_err(polars_error)?;
+        let position = values.str().map_err(polars_error)?
+            .into_iter()
+            .position(|v| v.map(str::trim) == Some(anchor.value.as_str()));
+        position
+    };
+    row.ok_or_else(|| clock_error(format!("no observation with {} = {}", anchor.column, anchor.value)))
+}
+
+/// Parse `YYYY-MM-DDTHH:MM:SS[.fff][Z|±HH:MM]` to epoch seconds (UTC when no zone is given)
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let (date, time) = value.split_once(['T', ' '])?;
+    let mut date_parts = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date_parts.next()??, date_parts.next()??, date_parts.next()??);
+    if date_parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let (clock, zone_offset) = if let Some(clock) = time.strip_suffix('Z') {
+        (clock, 0)
+    } else if let Some(index) = time.rfind(['+', '-']) {
+        let sign = if time[index..].starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = time[index + 1..].split_once(':')?;
+        (&time[..index], sign * (hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60))
+    } else {
+        (time, 0)
+    };
+
+    let mut clock_parts = clock.split(':');
+    let hours: i64 = clock_parts.next()?.parse().ok()?;
+    let minutes: i64 = clock_parts.next()?.parse().ok()?;
+    let seconds: f64 = clock_parts.next().map_or(Some(0.0), |s| s.parse().ok())?;
+    if clock_parts.next().is_some() || hours > 23 || minutes > 59 || !(0.0..61.0).contains(&seconds) {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    Some((days * 86_400 + hours * 3600 + minutes * 60 - zone_offset) as f64 + seconds)
+}
+
+/// Epoch seconds as an RFC 3339 UTC timestamp, `YYYY-MM-DDTHH:MM:SSZ`
+/// (fractions of a second are dropped)
+pub(crate) fn format_timestamp(seconds: f64) -> String {
+    let seconds = seconds.floor() as i64;
+    let (days, time) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, time / 3600, time % 3600 / 60, time % 60)
+}
+
+/// Proleptic Gregorian date of days since 1970-01-01, the inverse of
+/// [`days_from_civil`]
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn float_values(column: &Column) -> Result<Vec<Option<f64>>, ParseError> {
+    let values = column.cast(&polars::datatypes::DataType::Float64).map_err(polars_error)?;
+    Ok(values.f64().map_err(polars_error)?.into_iter().collect())
+}
+
+fn polars_error(e: PolarsError) -> ParseError {
+    clock_error(e.to_string())
+}
+
+fn clock_error(message: String) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to apply clock correction: {}", message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_anchor() {
+        let anchor: ClockAnchor = "2025-05-30T10:00:00Z=hhmmss 09:48:12".parse().unwrap();
+        assert_eq!(anchor.reference, 1748599200.0);
+        assert_eq!(anchor.column, "hhmmss");
+        assert_eq!(anchor.value, "09:48:12");
+
+        let offset: ClockAnchor = "2025-05-30T06:00:00.5-04:00=obs 3".parse().unwrap();
+        assert_eq!(offset.reference, 1748599200.5);
+        assert!("yesterday=obs 3".parse::<ClockAnchor>().is_err());
+        assert!("2025-05-30T10:00:00Z=hhmmss".parse::<ClockAnchor>().is_err());
+        
+        assert_eq!(format_timestamp(1748599200.5), "2025-05-30T10:00:00Z");
+        assert_eq!(format_timestamp(951_782_400.0), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(-1.0), "1969-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn test_clock_correction() {
+        let mut df = df! {
+            "obs" => [1i64, 2, 3],
+            "time" => [1000.0, 2000.0, 3000.0],
+            "hhmmss" => ["09:00:00", "09:16:40", "09:33:20"],
+            "date" => ["20250529 09:00:00", "20250529 23:59:50", "20251231 23:59:59"],
+        }.unwrap();
+        let mut info = Vec::new();
+
+        // Instrument is 10 s behind at obs 1 and 12 s behind at obs 3
+        let anchors = ["1970-01-01T00:16:50Z=obs 1".parse().unwrap(), "1970-01-01T00:50:12Z=hhmmss 09:33:20".parse().unwrap()];
+        let correction = apply_clock_correction(&mut df, &mut info, &anchors).unwrap().unwrap();
+        assert!((correction.drift - 0.001).abs() < 1e-12);
+
+        let time: Vec<f64> = df.column("time").unwrap().f64().unwrap().into_no_null_iter().collect();
+        for (actual, expected) in time.iter().zip([1010.0, 2011.0, 3012.0]) {
+            assert!((actual - expected).abs() < 1e-9);
+        }
+        assert_eq!(df.column("time_instrument").unwrap().f64().unwrap().get(0), Some(1000.0));
+
+        // The local clock readings move with it, across midnight and the year
+        let readings = |name: &str| df.column(name).unwrap().str().unwrap().into_no_null_iter().map(str::to_string).collect::<Vec<_>>();
+        assert_eq!(readings("hhmmss"), ["09:00:10", "09:16:51", "09:33:32"]);
+        assert_eq!(readings("date"), ["20250529 09:00:10", "20250530 00:00:01", "20260101 00:00:11"]);
+
+        let missing = apply_clock_correction(&mut df, &mut info, &["1970-01-01T00:00:00Z=obs 9".parse().unwrap()]);
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,490 @@
+//! User-supplied mapping from internal variable names to publication column names
+//!
+//! A crosswalk file lists, in output order, each column to publish: the
+//! internal `source` name, the published `target` name and optionally its
+//! `units`. Values are converted when the units differ from the source's
+//! (SI prefixes such as `mol` → `mmol`, `kPa` → `bar`, and `°C` ↔ `K`), or by
+//! an explicit `scale` and `offset` (`target = source * scale + offset`).
+//!
+//! TOML:
+//!
+//! ```toml
+//! keep_unmapped = false
+//!
+//! [[column]]
+//! source = "E"
+//! target = "transpiration"
+//! units = "mmol m-2 s-1"
+//! ```
+//!
+//! CSV, with a header row naming the same fields (`source` and `target` required):
+//!
+//! ```text
+//! source,target,units,scale,offset,optional,description
+//! E,transpiration,mmol m-2 s-1,,,,
+//! ```
+//!
+//! For plain renames that keep every column in place, such as LI-6400 names
+//! for scripts written against them, [`ColumnRename`]s are simpler.
+
+use crate::{ParseError, LiCorData, VariableInfo, DataType};
+use crate::validate::normalize_units;
+use polars::prelude::*;
+use serde::Deserialize;
+use std::path::Path;
+use std::str::FromStr;
+
+/// One published column
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ColumnMapping {
+    /// Internal variable name
+    pub source: String,
+    /// Published column name
+    pub target: String,
+    /// Published units; values are converted from the source units if they differ
+    #[serde(default)]
+    pub units: Option<String>,
+    /// Explicit conversion factor, overriding unit-based conversion
+    #[serde(default)]
+    pub scale: Option<f64>,
+    /// Explicit offset added after scaling
+    #[serde(default)]
+    pub offset: Option<f64>,
+    /// Leave the column out, instead of failing, when the source is missing
+    #[serde(default)]
+    pub optional: bool,
+    /// Published description, replacing the variable definition's
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Mapping from internal names to publication column names
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ColumnCrosswalk {
+    /// Keep columns the crosswalk does not mention, after the mapped ones
+    #[serde(default)]
+    pub keep_unmapped: bool,
+    #[serde(default, rename = "column")]
+    pub columns: Vec<ColumnMapping>,
+}
+
+impl ColumnCrosswalk {
+    /// Read a crosswalk from a `.toml` or `.csv` file
+    pub fn from_file(path: &str) -> Result<Self, ParseError> {
+        let content = std::fs::read_to_string(path)?;
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("toml") => Self::from_toml_str(&content),
+            Some("csv") => Self::from_csv_str(&content),
+            _ => Err(crosswalk_error(format!("'{}' is not a .toml or .csv file", path))),
+        }
+    }
+
+    /// Parse a crosswalk in TOML form
+    pub fn from_toml_str(content: &str) -> Result<Self, ParseError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Parse a crosswalk in CSV form; only mapped columns are kept
+    pub fn from_csv_str(content: &str) -> Result<Self, ParseError> {
+        let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+        let header = split_csv_line(lines.next().ok_or_else(|| crosswalk_error("empty crosswalk file".to_string()))?);
+        let field = |name: &str| header.iter().position(|h| h.eq_ignore_ascii_case(name));
+        let (Some(source), Some(target)) = (field("source"), field("target")) else {
+            return Err(crosswalk_error("the header needs 'source' and 'target' columns".to_string()));
+        };
+        let (units, scale, offset, optional, description) =
+            (field("units"), field("scale"), field("offset"), field("optional"), field("description"));
+
+        let mut columns = Vec::new();
+        for (line_no, line) in lines.enumerate() {
+            let cells = split_csv_line(line);
+            let cell = |index: Option<usize>| index
+                .and_then(|i| cells.get(i))
+                .filter(|value| !value.is_empty())
+                .cloned();
+            let number = |index: Option<usize>, name: &str| -> Result<Option<f64>, ParseError> {
+                cell(index)
+                    .map(|value| value.parse::<f64>().map_err(|_| crosswalk_error(format!(
+                        "row {}: {} '{}' is not a number", line_no + 1, name, value
+                    ))))
+                    .transpose()
+            };
+            let (Some(source), Some(target)) = (cell(Some(source)), cell(Some(target))) else {
+                return Err(crosswalk_error(format!("row {}: source and target are required", line_no + 1)));
+            };
+            columns.push(ColumnMapping {
+                source,
+                target,
+                units: cell(units),
+                scale: number(scale, "scale")?,
+                offset: number(offset, "offset")?,
+                optional: cell(optional).is_some_and(|v| matches!(v.to_ascii_lowercase().as_str(), "true" | "yes" | "1")),
+                description: cell(description),
+            });
+        }
+
+        Ok(ColumnCrosswalk { keep_unmapped: false, columns })
+    }
+}
+
+/// A column renamed at write time, written `OLD=NEW`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnRename {
+    pub from: String,
+    pub to: String,
+}
+
+impl FromStr for ColumnRename {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => Ok(ColumnRename {
+                from: from.trim().to_string(),
+                to: to.trim().to_string(),
+            }),
+            _ => Err(crosswalk_error(format!("'{}' is not a rename of the form OLD=NEW", s))),
+        }
+    }
+}
+
+impl ColumnRename {
+    /// Renames given as `OLD=NEW`, or as files of one `OLD=NEW` per line
+    /// (blank lines and `#` comments are skipped)
+    pub fn from_args(args: &[String]) -> Result<Vec<Self>, ParseError> {
+        let mut renames = Vec::new();
+        for arg in args {
+            if arg.contains('=') {
+                renames.push(arg.parse()?);
+                continue;
+            }
+            for line in std::fs::read_to_string(arg)?.lines() {
+                let line = line.split('#').next().unwrap_or_default().trim();
+                if !line.is_empty() {
+                    renames.push(line.parse()?);
+                }
+            }
+        }
+        Ok(renames)
+    }
+}
+
+impl LiCorData {
+    /// Rename columns in place, keeping their order, values and definitions
+    ///
+    /// A missing source column is an error, as is a new name that another
+    /// column already has.
+    pub fn rename_columns(&self, renames: &[ColumnRename]) -> Result<LiCorData, ParseError> {
+        let mut data = self.clone();
+        let mut names: Vec<String> = data.dataframe.get_column_names_str().iter().map(|name| name.to_string()).collect();
+        for rename in renames {
+            let Some(index) = names.iter().position(|name| *name == rename.from) else {
+                return Err(ParseError::MissingRequiredVariable {
+                    variable: rename.from.clone(),
+                    config: "rename".to_string(),
+                });
+            };
+            if names.contains(&rename.to) && rename.to != rename.from {
+                return Err(crosswalk_error(format!("cannot rename '{}' to '{}', which already exists", rename.from, rename.to)));
+            }
+            names[index] = rename.to.clone();
+            for info in data.variable_info.iter_mut().filter(|v| v.internal_name == rename.from) {
+                info.internal_name = rename.to.clone();
+                info.display_label = rename.to.clone();
+                info.derived_from = Some(rename.from.clone());
+            }
+        }
+        data.dataframe.set_column_names(names).map_err(polars_error)?;
+        Ok(data)
+    }
+
+    /// Rename, convert and select columns according to a crosswalk
+    ///
+    /// A missing source column is an error unless the mapping is `optional`.
+    pub fn apply_crosswalk(&self, crosswalk: &ColumnCrosswalk) -> Result<LiCorData, ParseError> {
+        let df = &self.dataframe;
+        let mut columns = Vec::new();
+        let mut variable_info = Vec::new();
+
+        for mapping in &crosswalk.columns {
+            let Ok(column) = df.column(&mapping.source) else {
+                if mapping.optional {
+                    continue;
+                }
+                return Err(ParseError::MissingRequiredVariable {
+                    variable: mapping.source.clone(),
+                    config: "crosswalk".to_string(),
+                });
+            };
+            let info = self.variable_info.iter().find(|v| v.internal_name == mapping.source);
+            let source_units = info.and_then(|v| v.units.clone());
+
+            let conversion = match (mapping.scale, mapping.offset, &mapping.units, &source_units) {
+                (None, None, Some(to), Some(from)) => Some(unit_conversion(from, to).ok_or_else(|| crosswalk_error(format!(
+                    "cannot convert '{}' from {} to {}; give a scale and offset", mapping.source, from, to
+                )))?),
+                (None, None, _, _) => None,
+                (scale, offset, _, _) => Some((scale.unwrap_or(1.0), offset.unwrap_or(0.0))),
+            };
+
+            let mut column = column.clone();
+            let mut data_type = info.map_or(DataType::Float, |v| v.data_type.clone());
+            if let Some((scale, offset)) = conversion.filter(|c| *c != (1.0, 0.0)) {
+                let values = column.cast(&polars::datatypes::DataType::Float64).map_err(polars_error)?;
+                let converted: Float64Chunked = values.f64().map_err(polars_error)?
+                    .apply_values(|v| v * scale + offset);
+                column = converted.into_column();
+                data_type = DataType::Float;
+            }
+            columns.push(column.with_name(mapping.target.as_str().into()));
+            let units = mapping.units.clone().or(source_units);
+            variable_info.push(VariableInfo {
+                internal_name: mapping.target.clone(),
+                display_label: mapping.target.clone(),
+                units_pretty: units.as_deref().map(crate::pretty_units),
+                units_ascii: units.as_deref().map(crate::ascii_units),
+                units,
+                description: mapping.description.clone()
+                    .or_else(|| info.map(|v| v.description.clone()))
+                    .unwrap_or_default(),
+                data_type,
+                column_category: info.map(|v| v.column_category.clone()).unwrap_or_default(),
+                derived_from: (mapping.source != mapping.target).then(|| mapping.source.clone()),
+            });
+        }
+
+        if crosswalk.keep_unmapped {
+            for column in df.get_columns() {
+                let name = column.name().as_str();
+                let mapped = crosswalk.columns.iter().any(|m| m.source == name || m.target == name);
+                if !mapped {
+                    columns.push(column.clone());
+                    variable_info.extend(self.variable_info.iter().find(|v| v.internal_name == name).cloned());
+                }
+            }
+        }
+
+        let dataframe = DataFrame::new(columns).map_err(polars_error)?;
+        Ok(LiCorData {
+            metadata: self.metadata.clone(),
+            dataframe,
+            variable_info,
+            coercion_reports: self.coercion_reports.clone(),
+        })
+    }
+}
+
+/// Units whose values convert by an SI prefix change: (unit, family, factor to the family's base)
+const BASE_UNITS: &[(&str, &str, f64)] = &[
+    ("mol", "mol", 1.0),
+    ("Pa", "Pa", 1.0),
+    ("bar", "Pa", 1e5),
+    ("g", "g", 1.0),
+    ("W", "W", 1.0),
+    ("J", "J", 1.0),
+];
+
+const PREFIXES: &[(&str, f64)] = &[
+    ("", 1.0),
+    ("k", 1e3),
+    ("h", 1e2),
+    ("m", 1e-3),
+    ("µ", 1e-6),
+    ("μ", 1e-6),
+    ("u", 1e-6),
+    ("n", 1e-9),
+];
+
+/// Scale and offset converting values in `from` units to `to` units, if known
+///
+/// Units convert when they differ only in the prefix of their first term
+/// (`mol m-2 s-1` → `mmol m-2 s-1`, `kPa` → `bar`), or between °C and K.
+pub fn unit_conversion(from: &str, to: &str) -> Option<(f64, f64)> {
+    let (from, to) = (normalize_units(from), normalize_units(to));
+    if from == to {
+        return Some((1.0, 0.0));
+    }
+    match (from.as_str(), to.as_str()) {
+        ("°C", "K") => return Some((1.0, 273.15)),
+        ("K", "°C") => return Some((1.0, -273.15)),
+        _ => {}
+    }
+
+    let (from_first, from_rest) = from.split_once(' ').unwrap_or((&from, ""));
+    let (to_first, to_rest) = to.split_once(' ').unwrap_or((&to, ""));
+    if from_rest != to_rest {
+        return None;
+    }
+    let (from_family, from_factor) = prefixed_unit(from_first)?;
+    let (to_family, to_factor) = prefixed_unit(to_first)?;
+    (from_family == to_family).then_some((from_factor / to_factor, 0.0))
+}
+
+/// Family and factor to the family's base unit of a possibly prefixed unit
+fn prefixed_unit(unit: &str) -> Option<(&'static str, f64)> {
+    BASE_UNITS.iter().find_map(|(base, family, base_factor)| {
+        let prefix = unit.strip_suffix(base)?;
+        let (_, prefix_factor) = PREFIXES.iter().find(|(p, _)| *p == prefix)?;
+        Some((*family, prefix_factor * base_factor))
+    })
+}
+
+/// Split a CSV line on commas, honouring double-quoted cells
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+fn crosswalk_error(message: String) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Invalid crosswalk: {}", message)
+    }
+}
+
+fn polars_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to apply crosswalk: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LiCorMetadata;
+
+    fn sample() -> LiCorData {
+        let info = |name: &str, units: &str| VariableInfo {
+            internal_name: name.to_string(),
+            display_label: name.to_string(),
+            units: Some(units.to_string()),
+            units_pretty: Some(crate::pretty_units(units)),
+            units_ascii: Some(crate::ascii_units(units)),
+            description: format!("{} description", name),
+            data_type: DataType::Float,
+            column_category: "GasEx".to_string(),
+            derived_from: None,
+        };
+        LiCorData {
+            metadata: LiCorMetadata {
+                device_serial: "68C-901292".to_string(),
+                console_version: "Bluestem v.2.1.13".to_string(),
+                ..Default::default()
+            },
+            dataframe: df! {
+                "obs" => [1i64, 2],
+                "E" => [0.004, 0.002],
+                "Tleaf" => [25.0, 30.0],
+                "Pa" => [97.0, 98.0],
+            }.unwrap(),
+            variable_info: vec![info("E", "mol m⁻² s⁻¹"), info("Tleaf", "°C"), info("Pa", "kPa")],
+            coercion_reports: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_apply_crosswalk() {
+        let crosswalk = ColumnCrosswalk::from_toml_str(r#"
+            [[column]]
+            source = "Tleaf"
+            target = "leaf_temp_K"
+            units = "K"
+
+            [[column]]
+            source = "E"
+            target = "transpiration"
+            units = "mmol m-2 s-1"
+
+            [[column]]
+            source = "Pa"
+            target = "pressure"
+            scale = 10.0
+            units = "hPa"
+
+            [[column]]
+            source = "Fs"
+            target = "fluorescence"
+            optional = true
+        "#).unwrap();
+        let data = sample().apply_crosswalk(&crosswalk).unwrap();
+
+        let names: Vec<&str> = data.dataframe.get_column_names().into_iter().map(|n| n.as_str()).collect();
+        assert_eq!(names, vec!["leaf_temp_K", "transpiration", "pressure"]);
+        let values = |name: &str| -> Vec<f64> {
+            data.dataframe.column(name).unwrap().f64().unwrap().into_no_null_iter().collect()
+        };
+        assert_eq!(values("leaf_temp_K"), vec![298.15, 303.15]);
+        assert!((values("transpiration")[0] - 4.0).abs() < 1e-12);
+        assert_eq!(values("pressure"), vec![970.0, 980.0]);
+        assert_eq!(data.variable_info[1].units.as_deref(), Some("mmol m-2 s-1"));
+        assert_eq!(data.variable_info[1].derived_from.as_deref(), Some("E"));
+        assert_eq!(data.variable_info[0].description, "Tleaf description");
+
+        // Unmapped columns follow the mapped ones when kept
+        let keep = ColumnCrosswalk { keep_unmapped: true, columns: crosswalk.columns[..1].to_vec() };
+        let kept = sample().apply_crosswalk(&keep).unwrap();
+        assert_eq!(kept.dataframe.get_column_names(), ["leaf_temp_K", "obs", "E", "Pa"]);
+
+        // Unknown conversions and missing sources are errors
+        let bad = ColumnCrosswalk::from_csv_str("source,target,units\nTleaf,leaf_temp,mol\n").unwrap();
+        assert!(sample().apply_crosswalk(&bad).is_err());
+        let missing = ColumnCrosswalk::from_csv_str("source,target\nFs,fluorescence\n").unwrap();
+        assert!(matches!(sample().apply_crosswalk(&missing), Err(ParseError::MissingRequiredVariable { .. })));
+    }
+
+    #[test]
+    fn test_rename_columns() {
+        let renames: Vec<ColumnRename> = ["E=Trmmol", " Tleaf = Tleaf_legacy "].iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let data = sample().rename_columns(&renames).unwrap();
+        assert_eq!(data.dataframe.get_column_names(), ["obs", "Trmmol", "Tleaf_legacy", "Pa"]);
+        assert_eq!(data.variable_info[0].internal_name, "Trmmol");
+        assert_eq!(data.variable_info[0].units.as_deref(), Some("mol m⁻² s⁻¹"));
+        assert_eq!(data.variable_info[1].derived_from.as_deref(), Some("Tleaf"));
+
+        assert!("E".parse::<ColumnRename>().is_err());
+        assert!("E=".parse::<ColumnRename>().is_err());
+        let missing = [ColumnRename { from: "Fs".to_string(), to: "F".to_string() }];
+        assert!(matches!(sample().rename_columns(&missing), Err(ParseError::MissingRequiredVariable { .. })));
+        let clash = [ColumnRename { from: "E".to_string(), to: "Pa".to_string() }];
+        assert!(sample().rename_columns(&clash).is_err());
+    }
+
+    #[test]
+    fn test_crosswalk_csv_and_units() {
+        let crosswalk = ColumnCrosswalk::from_csv_str(
+            "source,target,units,scale,offset,optional,description\n\
+             E,transpiration,,1000,,,\"Transpiration, per leaf area\"\n\
+             Fs,fluorescence,,,,yes,\n"
+        ).unwrap();
+        assert_eq!(crosswalk.columns.len(), 2);
+        assert_eq!(crosswalk.columns[0].scale, Some(1000.0));
+        assert_eq!(crosswalk.columns[0].description.as_deref(), Some("Transpiration, per leaf area"));
+        assert!(crosswalk.columns[1].optional);
+        assert!(ColumnCrosswalk::from_csv_str("name,target\nA,B\n").is_err());
+
+        assert_eq!(unit_conversion("kPa", "bar"), Some((0.01, 0.0)));
+        assert_eq!(unit_conversion("µmol/mol", "µmol mol⁻¹"), Some((1.0, 0.0)));
+        assert_eq!(unit_conversion("mol m-2 s-1", "mmol m-2 s-1"), Some((1000.0, 0.0)));
+        assert_eq!(unit_conversion("mol m-2 s-1", "mol m-2 h-1"), None);
+    }
+}
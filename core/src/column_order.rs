@@ -0,0 +1,117 @@
+use crate::{ParseError, VariableInfo, VARIABLE_DEFINITIONS};
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Order of the instrument's columns in the parsed data
+///
+/// Columns added by parse options (derived, smoothed, header and source
+/// columns) always follow the instrument's columns, in the order they were
+/// requested.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColumnOrder {
+    /// As logged by the instrument, with renamed duplicates (`Name_1`) left in
+    /// place of the original
+    #[default]
+    Original,
+    /// Order of the variable definitions (by category, then name); columns
+    /// without a definition follow in their original order
+    Canonical,
+    /// Alphabetical by name, ignoring case
+    Alpha,
+}
+
+impl FromStr for ColumnOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "original" => Ok(ColumnOrder::Original),
+            "canonical" => Ok(ColumnOrder::Canonical),
+            "alpha" | "alphabetical" => Ok(ColumnOrder::Alpha),
+            _ => Err(format!("Unknown column order '{}': use canonical, original or alpha", s)),
+        }
+    }
+}
+
+/// Rearrange the frame's columns, and their `VariableInfo`, into `order`
+///
+/// `variable_info` must describe the frame's columns in frame order.
+pub(crate) fn apply_column_order(
+    dataframe: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    order: ColumnOrder,
+) -> Result<(), ParseError> {
+    let mut indices: Vec<usize> = (0..variable_info.len()).collect();
+    match order {
+        ColumnOrder::Original => return Ok(()),
+        ColumnOrder::Canonical => {
+            // Stable, so undefined columns keep their relative order at the end
+            indices.sort_by_key(|&i| definition_rank(&variable_info[i].internal_name));
+        }
+        ColumnOrder::Alpha => {
+            indices.sort_by_cached_key(|&i| variable_info[i].internal_name.to_lowercase());
+        }
+    }
+
+    let names: Vec<PlSmallStr> = indices.iter()
+        .map(|&i| variable_info[i].internal_name.as_str().into())
+        .collect();
+    *dataframe = dataframe.select(names)
+        .map_err(|e| ParseError::InvalidHeaderFormat {
+            message: format!("Failed to reorder columns: {}", e)
+        })?;
+    let mut reordered: Vec<Option<VariableInfo>> = std::mem::take(variable_info).into_iter().map(Some).collect();
+    *variable_info = indices.iter().filter_map(|&i| reordered[i].take()).collect();
+    Ok(())
+}
+
+/// Position of a column's first definition, or `usize::MAX` when undefined
+fn definition_rank(name: &str) -> usize {
+    VARIABLE_DEFINITIONS.iter()
+        .position(|def| def.internal_name == name)
+        .unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataType;
+
+    fn info(name: &str) -> VariableInfo {
+        VariableInfo {
+            internal_name: name.to_string(),
+            display_label: name.to_string(),
+            units: None,
+            units_pretty: None,
+            units_ascii: None,
+            description: String::new(),
+            data_type: DataType::Float,
+            column_category: String::new(),
+            derived_from: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_column_order() {
+        let names = ["obs", "zzz_custom", "A", "aaa_custom", "E"];
+        let frame = DataFrame::new(names.iter().map(|n| Column::new((*n).into(), [1.0])).collect()).unwrap();
+        let infos: Vec<VariableInfo> = names.iter().map(|n| info(n)).collect();
+
+        let (mut df, mut vars) = (frame.clone(), infos.clone());
+        apply_column_order(&mut df, &mut vars, ColumnOrder::Original).unwrap();
+        assert_eq!(df.get_column_names(), names);
+
+        let (mut df, mut vars) = (frame.clone(), infos.clone());
+        apply_column_order(&mut df, &mut vars, ColumnOrder::Alpha).unwrap();
+        assert_eq!(df.get_column_names(), ["A", "aaa_custom", "E", "obs", "zzz_custom"]);
+        assert_eq!(vars[1].internal_name, "aaa_custom");
+
+        let (mut df, mut vars) = (frame, infos);
+        apply_column_order(&mut df, &mut vars, ColumnOrder::Canonical).unwrap();
+        let ordered = df.get_column_names();
+        assert_eq!(&ordered[3..], ["zzz_custom", "aaa_custom"]);
+        assert!(vars.iter().zip(ordered).all(|(v, n)| v.internal_name == n.as_str()));
+
+        assert!("positional".parse::<ColumnOrder>().is_err());
+    }
+}
@@ -9,18 +9,21 @@ pub trait LiCorConfig {
     
     /// Validate that required variables are present in the columns
     fn validate_columns(columns: &[String]) -> Result<(), ParseError> {
-        let expected = Self::expected_variables();
-        
-        for &required_var in expected {
-            if !columns.iter().any(|col| col == required_var) {
-                return Err(ParseError::MissingRequiredVariable {
-                    variable: required_var.to_string(),
-                    config: Self::CONFIG_NAME.to_string(),
-                });
-            }
+        match Self::missing_variables(columns).first() {
+            Some(required_var) => Err(ParseError::MissingRequiredVariable {
+                variable: required_var.to_string(),
+                config: Self::CONFIG_NAME.to_string(),
+            }),
+            None => Ok(()),
         }
-        
-        Ok(())
+    }
+    
+    /// Expected variables absent from the columns
+    fn missing_variables(columns: &[String]) -> Vec<&'static str> {
+        Self::expected_variables().iter()
+            .copied()
+            .filter(|required_var| !columns.iter().any(|col| col == required_var))
+            .collect()
     }
     
     /// Check if a variable is known (exists in our definitions)
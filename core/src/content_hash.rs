@@ -0,0 +1,123 @@
+use crate::ParseError;
+use polars::prelude::*;
+use sha2::{Digest, Sha256};
+
+/// Version tag hashed ahead of the data, changed whenever the encoding changes
+const ENCODING_VERSION: &[u8] = b"licor-content-hash-v1";
+
+/// SHA-256 of a DataFrame's schema and values, as lowercase hex
+///
+/// The data is hashed through a fixed encoding rather than any in-memory or
+/// file layout, so the same parsed output gives the same hash on any machine
+/// and across Polars versions. Columns are hashed in order as their name, a
+/// type tag and their values; floats are compared bitwise, with `-0.0` and all
+/// NaNs folded into one value each.
+pub fn content_hash(df: &DataFrame) -> Result<String, ParseError> {
+    let mut hasher = Sha256::new();
+    hasher.update(ENCODING_VERSION);
+    hasher.update((df.width() as u64).to_le_bytes());
+    hasher.update((df.height() as u64).to_le_bytes());
+
+    for column in df.get_columns() {
+        hash_bytes(&mut hasher, column.name().as_bytes());
+        match column.dtype() {
+            dtype if dtype.is_float() => {
+                hasher.update(b"f");
+                let values = column.cast(&polars::datatypes::DataType::Float64).map_err(hash_error)?;
+                for value in values.f64().map_err(hash_error)? {
+                    hash_value(&mut hasher, value.map(canonical_float).map(f64::to_bits).map(u64::to_le_bytes));
+                }
+            }
+            dtype if dtype.is_integer() => {
+                hasher.update(b"i");
+                let values = column.cast(&polars::datatypes::DataType::Int64).map_err(hash_error)?;
+                for value in values.i64().map_err(hash_error)? {
+                    hash_value(&mut hasher, value.map(i64::to_le_bytes));
+                }
+            }
+            polars::datatypes::DataType::Boolean => {
+                hasher.update(b"b");
+                for value in column.bool().map_err(hash_error)? {
+                    hash_value(&mut hasher, value.map(|v| [v as u8]));
+                }
+            }
+            _ => {
+                hasher.update(b"s");
+                let values = column.cast(&polars::datatypes::DataType::String).map_err(hash_error)?;
+                for value in values.str().map_err(hash_error)? {
+                    match value {
+                        Some(value) => {
+                            hasher.update([1]);
+                            hash_bytes(&mut hasher, value.as_bytes());
+                        }
+                        None => hasher.update([0]),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn hash_value<const N: usize>(hasher: &mut Sha256, value: Option<[u8; N]>) {
+    match value {
+        Some(bytes) => {
+            hasher.update([1]);
+            hasher.update(bytes);
+        }
+        None => hasher.update([0]),
+    }
+}
+
+/// Length-prefixed, so adjacent strings cannot run together
+fn hash_bytes(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+fn canonical_float(value: f64) -> f64 {
+    if value.is_nan() {
+        f64::NAN
+    } else if value == 0.0 {
+        0.0
+    } else {
+        value
+    }
+}
+
+fn hash_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to hash parsed data: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash() {
+        let df = df! {
+            "obs" => [1i64, 2],
+            "A" => [Some(12.5), None],
+            "hhmmss" => ["09:55:12", "09:56:12"],
+        }.unwrap();
+        let hash = content_hash(&df).unwrap();
+        assert_eq!(hash.len(), 64);
+
+        // Memory layout does not matter, but names, types and values do
+        let mut rechunked = df.slice(0, 1);
+        rechunked.vstack_mut(&df.slice(1, 1)).unwrap();
+        assert_eq!(content_hash(&rechunked).unwrap(), hash);
+        let renamed = df.clone().rename("A", "Photo".into()).unwrap().clone();
+        assert_ne!(content_hash(&renamed).unwrap(), hash);
+        let mut retyped = df.clone();
+        retyped.with_column(Column::new("obs".into(), [1.0, 2.0])).unwrap();
+        assert_ne!(content_hash(&retyped).unwrap(), hash);
+
+        let zeros = df! { "x" => [0.0, f64::NAN] }.unwrap();
+        let signed = df! { "x" => [-0.0, -f64::NAN] }.unwrap();
+        assert_eq!(content_hash(&zeros).unwrap(), content_hash(&signed).unwrap());
+    }
+}
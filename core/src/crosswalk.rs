@@ -0,0 +1,287 @@
+//! Instrument and site metadata mapped onto data repository reporting formats
+//!
+//! ESS-DIVE deposits use the CSV reporting format's file-level metadata
+//! (`flmd.csv`) and data dictionary (`dd.csv`). AmeriFlux uses BADM key-values in
+//! the BIF layout (`SITE_ID, GROUP_ID, VARIABLE_GROUP, VARIABLE, DATAVALUE`).
+//! Site details are not recorded by the instrument, so they come from [`SiteInfo`].
+
+use crate::{ParseError, LiCorData};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Missing-value code written to ESS-DIVE files
+const MISSING_VALUE: &str = "-9999";
+
+/// Repository reporting format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataStandard {
+    EssDive,
+    AmeriFlux,
+}
+
+impl FromStr for MetadataStandard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "essdive" => Ok(MetadataStandard::EssDive),
+            "ameriflux" | "bif" => Ok(MetadataStandard::AmeriFlux),
+            _ => Err(format!("Unknown metadata standard '{}': use essdive or ameriflux", s)),
+        }
+    }
+}
+
+/// Site details supplied by the user
+#[derive(Debug, Clone, Default)]
+pub struct SiteInfo {
+    pub site_id: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+/// Write metadata for converted data files in the given reporting format
+///
+/// `files` pairs each data file name (as deposited) with its parsed data.
+/// Returns the paths written under `output_dir`.
+pub fn write_metadata_crosswalk(
+    files: &[(String, LiCorData)],
+    site: &SiteInfo,
+    standard: MetadataStandard,
+    output_dir: &str,
+) -> Result<Vec<String>, ParseError> {
+    std::fs::create_dir_all(output_dir)?;
+    let tables = match standard {
+        MetadataStandard::EssDive => vec![
+            ("flmd.csv", file_level_metadata(files, site)?),
+            ("dd.csv", data_dictionary(files)),
+        ],
+        MetadataStandard::AmeriFlux => vec![("bif.csv", bif_records(files, site)?)],
+    };
+
+    let mut paths = Vec::new();
+    for (name, rows) in tables {
+        let path = Path::new(output_dir).join(name);
+        std::fs::write(&path, to_csv(&rows))?;
+        paths.push(path.to_string_lossy().to_string());
+    }
+    Ok(paths)
+}
+
+/// ESS-DIVE file-level metadata: one row per data file
+fn file_level_metadata(files: &[(String, LiCorData)], site: &SiteInfo) -> Result<Vec<Vec<String>>, ParseError> {
+    let mut rows = vec![[
+        "File_Name", "File_Description", "Standard", "Header_Rows", "Column_or_Row_Name_Position",
+        "Start_Date", "End_Date", "Latitude", "Longitude",
+        "Instrument_Model", "Instrument_Serial_Number", "Instrument_Software_Version",
+    ].map(String::from).to_vec()];
+
+    for (file_name, data) in files {
+        let (start, end) = date_range(data)?;
+        let metadata = &data.metadata;
+        rows.push(vec![
+            file_name.clone(),
+            format!(
+                "Leaf gas exchange observations ({} rows) from LI-COR console {}",
+                data.dataframe.height(), metadata.device_serial
+            ),
+            "ESS-DIVE CSV v1".to_string(),
+            "1".to_string(),
+            "1".to_string(),
+            start.unwrap_or_else(|| MISSING_VALUE.to_string()),
+            end.unwrap_or_else(|| MISSING_VALUE.to_string()),
+            optional_number(site.latitude),
+            optional_number(site.longitude),
+            instrument_model(data),
+            metadata.device_serial.clone(),
+            metadata.console_version.clone(),
+        ]);
+    }
+    Ok(rows)
+}
+
+/// ESS-DIVE data dictionary: one row per column across all files
+fn data_dictionary(files: &[(String, LiCorData)]) -> Vec<Vec<String>> {
+    let mut rows = vec![[
+        "Column_or_Row_Name", "Unit", "Definition", "Column_or_Row_Long_Name", "Data_Type", "Missing_Value_Code",
+    ].map(String::from).to_vec()];
+
+    let mut seen = std::collections::HashSet::new();
+    for (_, data) in files {
+        for column in data.dataframe.get_columns() {
+            let name = column.name().to_string();
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            let info = data.variable_info.iter().find(|v| v.internal_name == name);
+            rows.push(vec![
+                name.clone(),
+                info.and_then(|v| v.units.clone()).unwrap_or_else(|| "N/A".to_string()),
+                info.map(|v| v.description.clone()).filter(|d| !d.is_empty()).unwrap_or_else(|| "N/A".to_string()),
+                info.map(|v| v.display_label.clone()).unwrap_or(name),
+                ess_dive_type(column.dtype()).to_string(),
+                MISSING_VALUE.to_string(),
+            ]);
+        }
+    }
+    rows
+}
+
+/// AmeriFlux BADM records: site location plus one instrument group per console
+fn bif_records(files: &[(String, LiCorData)], site: &SiteInfo) -> Result<Vec<Vec<String>>, ParseError> {
+    let site_id = site.site_id.clone().unwrap_or_else(|| "UNKNOWN".to_string());
+    let mut rows = vec![["SITE_ID", "GROUP_ID", "VARIABLE_GROUP", "VARIABLE", "DATAVALUE"].map(String::from).to_vec()];
+    let mut group_id = 0;
+    let record = |rows: &mut Vec<Vec<String>>, group: usize, variable_group: &str, variable: &str, value: String| {
+        rows.push(vec![site_id.clone(), group.to_string(), variable_group.to_string(), variable.to_string(), value]);
+    };
+
+    if site.latitude.is_some() || site.longitude.is_some() {
+        group_id += 1;
+        record(&mut rows, group_id, "GRP_LOCATION", "LOCATION_LAT", optional_number(site.latitude));
+        record(&mut rows, group_id, "GRP_LOCATION", "LOCATION_LONG", optional_number(site.longitude));
+    }
+
+    let mut consoles = std::collections::HashSet::new();
+    for (_, data) in files {
+        let metadata = &data.metadata;
+        if !consoles.insert(metadata.device_serial.clone()) {
+            continue;
+        }
+        let (start, _) = date_range(data)?;
+        group_id += 1;
+        if let Some(start) = start {
+            record(&mut rows, group_id, "GRP_INSTOM", "INSTOM_DATE", start.replace('-', ""));
+        }
+        record(&mut rows, group_id, "GRP_INSTOM", "INSTOM_TYPE", "Installation".to_string());
+        record(&mut rows, group_id, "GRP_INSTOM", "INSTOM_MODEL", instrument_model(data));
+        record(&mut rows, group_id, "GRP_INSTOM", "INSTOM_SN", metadata.device_serial.clone());
+        let comment = format!(
+            "Software {}{}{}",
+            metadata.console_version,
+            metadata.head_serial.as_ref().map(|s| format!("; sensor head {}", s)).unwrap_or_default(),
+            metadata.calibration_certificates.iter().map(|c| format!("; calibration certificate {}", c.describe())).collect::<String>()
+        );
+        record(&mut rows, group_id, "GRP_INSTOM", "INSTOM_COMMENT", comment);
+    }
+    Ok(rows)
+}
+
+/// First and last measurement dates (`YYYY-MM-DD`)
+fn date_range(data: &LiCorData) -> Result<(Option<String>, Option<String>), ParseError> {
+    let dates = crate::writers::hive::row_dates(&data.dataframe)?;
+    let mut dates = dates.into_iter().flatten();
+    let first = dates.next();
+    let (start, end) = dates.fold((first.clone(), first), |(start, end), date| {
+        (start.min(Some(date.clone())), end.max(Some(date)))
+    });
+    Ok((start, end))
+}
+
+/// Instrument model inferred from the console serial and software
+fn instrument_model(data: &LiCorData) -> String {
+    let metadata = &data.metadata;
+    if metadata.device_serial.starts_with("68C") {
+        "LI-COR LI-6800".to_string()
+    } else if metadata.console_version.starts_with("OPEN") {
+        "LI-COR LI-6400".to_string()
+    } else {
+        "LI-COR".to_string()
+    }
+}
+
+fn ess_dive_type(dtype: &polars::datatypes::DataType) -> &'static str {
+    use polars::datatypes::DataType as PolarsType;
+    match dtype {
+        PolarsType::Float32 | PolarsType::Float64 => "decimal",
+        dtype if dtype.is_integer() => "integer",
+        PolarsType::Boolean => "boolean",
+        PolarsType::Date | PolarsType::Datetime(_, _) => "date",
+        _ => "text",
+    }
+}
+
+fn optional_number(value: Option<f64>) -> String {
+    value.map_or_else(|| MISSING_VALUE.to_string(), |v| v.to_string())
+}
+
+fn to_csv(rows: &[Vec<String>]) -> String {
+    let mut csv = String::new();
+    for row in rows {
+        let cells: Vec<String> = row.iter()
+            .map(|cell| {
+                if cell.contains([',', '"', '\n']) {
+                    format!("\"{}\"", cell.replace('"', "\"\""))
+                } else {
+                    cell.clone()
+                }
+            })
+            .collect();
+        csv.push_str(&cells.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LiCorMetadata, VariableInfo, DataType};
+    use polars::prelude::*;
+
+    fn sample() -> LiCorData {
+        LiCorData {
+            metadata: LiCorMetadata {
+                device_serial: "68C-901292".to_string(),
+                console_version: "Bluestem v.2.1.13".to_string(),
+                head_serial: Some("68H-581292".to_string()),
+                ..Default::default()
+            },
+            dataframe: df! {
+                "obs" => [1i64, 2],
+                "date" => ["20250529 12:09:24", "20250530 08:00:00"],
+                "A" => [12.5, 13.0],
+            }.unwrap(),
+            variable_info: vec![VariableInfo {
+                internal_name: "A".to_string(),
+                display_label: "A".to_string(),
+                units: Some("µmol m-2 s-1".to_string()),
+                units_pretty: Some("µmol m⁻² s⁻¹".to_string()),
+                units_ascii: Some("umol m-2 s-1".to_string()),
+                description: "Assimilation rate".to_string(),
+                data_type: DataType::Float,
+                column_category: "GasEx".to_string(),
+                derived_from: None,
+            }],
+            coercion_reports: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_ess_dive_tables() {
+        let files = vec![("leaf1.csv".to_string(), sample())];
+        let site = SiteInfo { latitude: Some(38.5), ..SiteInfo::default() };
+
+        let flmd = file_level_metadata(&files, &site).unwrap();
+        assert_eq!(flmd[1][0], "leaf1.csv");
+        assert_eq!(flmd[1][5..8], ["2025-05-29", "2025-05-30", "38.5"]);
+        assert_eq!(flmd[1][9], "LI-COR LI-6800");
+
+        let dd = data_dictionary(&files);
+        assert_eq!(dd.len(), 4);
+        assert_eq!(dd[3], vec!["A", "µmol m-2 s-1", "Assimilation rate", "A", "decimal", "-9999"]);
+        assert_eq!(dd[1][4], "integer");
+    }
+
+    #[test]
+    fn test_bif_records() {
+        let files = vec![("a.csv".to_string(), sample()), ("b.csv".to_string(), sample())];
+        let site = SiteInfo { site_id: Some("US-Xyz".to_string()), latitude: Some(38.5), longitude: Some(-121.7) };
+        let rows = bif_records(&files, &site).unwrap();
+
+        // Location group plus one instrument group for the single console
+        assert_eq!(rows.len(), 1 + 2 + 5);
+        assert_eq!(rows[3], vec!["US-Xyz", "2", "GRP_INSTOM", "INSTOM_DATE", "20250529"]);
+        assert_eq!(rows[7][4], "Software Bluestem v.2.1.13; sensor head 68H-581292");
+        assert_eq!(to_csv(&[vec!["a,b".to_string(), "c".to_string()]]), "\"a,b\",c\n");
+    }
+}
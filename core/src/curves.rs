@@ -0,0 +1,275 @@
+use crate::ParseError;
+use polars::prelude::*;
+
+/// Columns driven during a curve, with the smallest range that counts as a sweep
+/// (LI-6800 name first, then LI-6400)
+const CO2_COLUMNS: (&[&str], f64) = (&["CO2_r", "CO2R"], 100.0);
+const LIGHT_COLUMNS: (&[&str], f64) = (&["Qin", "PARi"], 100.0);
+
+/// LI-6800 setpoints (CO₂ reference, light source) whose steps mark curves in
+/// logs without `curve_id`
+pub(crate) const SETPOINT_COLUMNS: [&str; 2] = ["CO2_r_sp", "Q"];
+
+/// Kind of response curve, judged from which environmental driver was swept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveType {
+    /// CO₂ response (A/Ci)
+    Aci,
+    /// Light response (A/Q)
+    LightResponse,
+    /// Neither CO₂ nor light was swept (survey points, induction, time courses)
+    Other,
+}
+
+impl CurveType {
+    /// Short label used in file names
+    pub fn label(&self) -> &'static str {
+        match self {
+            CurveType::Aci => "ACi",
+            CurveType::LightResponse => "AQ",
+            CurveType::Other => "other",
+        }
+    }
+}
+
+/// Rows of one response curve
+#[derive(Debug, Clone)]
+pub struct Curve {
+    /// AutoProgram run the rows came from, if the data records runs
+    pub curve_id: Option<String>,
+    pub curve_type: CurveType,
+    /// Measurement date (`YYYY-MM-DD`) of the first observation
+    pub date: Option<String>,
+    pub dataframe: DataFrame,
+}
+
+/// Split observations into curves
+///
+/// Rows are grouped by `curve_id` (AutoProgram runs in workbook exports), in
+/// order of first appearance; rows outside any run form their own group.
+///
+/// LI-6800 logs record no AutoProgram runs, so without a `curve_id` column the
+/// rows are split on setpoint changes instead: a curve is a run of steps in
+/// which the same setpoint (`CO2_r_sp` or the light source `Q`) is swept, and a
+/// new curve starts where a different setpoint, or both at once, change. Data
+/// without either column is a single curve.
+pub fn split_curves(df: &DataFrame) -> Result<Vec<Curve>, ParseError> {
+    let groups = if df.column("curve_id").is_ok() {
+        split_groups(df, &["curve_id".to_string()])?
+    } else {
+        split_setpoint_runs(df)?.into_iter().map(|dataframe| (vec![None], dataframe)).collect()
+    };
+    groups.into_iter()
+        .map(|(mut key, dataframe)| {
+            let date = crate::writers::hive::row_dates(&dataframe)?.into_iter().flatten().next();
+            Ok(Curve {
+                curve_id: key.pop().flatten(),
+                curve_type: classify_curve(&dataframe)?,
+                date,
+                dataframe,
+            })
+        })
+        .collect()
+}
+
+/// Consecutive rows sweeping the same setpoint
+fn split_setpoint_runs(df: &DataFrame) -> Result<Vec<DataFrame>, ParseError> {
+    let mut setpoints = Vec::new();
+    for name in SETPOINT_COLUMNS {
+        if let Ok(column) = df.column(name) {
+            let column = column.cast(&polars::datatypes::DataType::Float64).map_err(curve_error)?;
+            setpoints.push(column.f64().map_err(curve_error)?.into_iter().collect::<Vec<_>>());
+        }
+    }
+
+    let mut starts = vec![0];
+    let mut swept: Option<Vec<bool>> = None;
+    for row in 1..df.height() {
+        let changed: Vec<bool> = setpoints.iter().map(|column| column[row] != column[row - 1]).collect();
+        match &swept {
+            _ if !changed.contains(&true) => {}
+            Some(previous) if *previous == changed => {}
+            None if changed.iter().filter(|c| **c).count() == 1 => swept = Some(changed),
+            _ => {
+                starts.push(row);
+                swept = None;
+            }
+        }
+    }
+    starts.push(df.height());
+    Ok(starts.windows(2)
+        .map(|bounds| df.slice(bounds[0] as i64, bounds[1] - bounds[0]))
+        .collect())
+}
+
+/// Rows sharing a key: one value per grouping column, `None` for nulls
+pub type Group = (Vec<Option<String>>, DataFrame);
+
+/// Split rows into groups sharing the values of `columns`, in order of first
+/// appearance
+pub fn split_groups(df: &DataFrame, columns: &[String]) -> Result<Vec<Group>, ParseError> {
+    let mut values = Vec::new();
+    for name in columns {
+        let column = df.column(name).map_err(|_| ParseError::MissingRequiredVariable {
+            variable: name.clone(),
+            config: "split".to_string(),
+        })?;
+        let column = column.cast(&polars::datatypes::DataType::String).map_err(curve_error)?;
+        values.push(column.str().map_err(curve_error)?
+            .into_iter()
+            .map(|v| v.map(str::to_string))
+            .collect::<Vec<_>>());
+    }
+
+    // Row indices of each key in one pass, keys in order of first appearance
+    let mut index: std::collections::HashMap<Vec<Option<String>>, usize> = std::collections::HashMap::new();
+    let mut groups: Vec<(Vec<Option<String>>, Vec<IdxSize>)> = Vec::new();
+    for row in 0..df.height() {
+        let key: Vec<Option<String>> = values.iter().map(|column| column[row].clone()).collect();
+        let group = *index.entry(key.clone()).or_insert_with(|| {
+            groups.push((key, Vec::new()));
+            groups.len() - 1
+        });
+        groups[group].1.push(row as IdxSize);
+    }
+    groups.into_iter()
+        .map(|(key, rows)| {
+            let rows = IdxCa::from_vec("group".into(), rows);
+            Ok((key, df.take(&rows).map_err(curve_error)?))
+        })
+        .collect()
+}
+
+/// File name stem of a group: `{file}` in the template becomes `file` and
+/// `{<column>}` the group's value of that column (`none` for nulls). Without a
+/// template the values follow the file name, as in `{file}_{curve_id}`.
+pub fn group_file_stem(template: Option<&str>, file: &str, columns: &[String], key: &[Option<String>]) -> Result<String, ParseError> {
+    let default = std::iter::once("{file}".to_string())
+        .chain(columns.iter().map(|column| format!("{{{}}}", column)))
+        .collect::<Vec<_>>()
+        .join("_");
+    let mut stem = template.unwrap_or(&default).replace("{file}", file);
+    for (column, value) in columns.iter().zip(key) {
+        stem = stem.replace(&format!("{{{}}}", column), value.as_deref().unwrap_or("none"));
+    }
+    if let Some(start) = stem.find('{') {
+        let placeholder = stem[start..].split_inclusive('}').next().unwrap_or_default();
+        return Err(ParseError::InvalidHeaderFormat {
+            message: format!("Unknown placeholder '{}' in file name template: use {{file}} or a --split-by column", placeholder)
+        });
+    }
+    Ok(stem)
+}
+
+/// Classify a curve by whichever driver (CO₂ or light) spans the larger relative range
+pub fn classify_curve(df: &DataFrame) -> Result<CurveType, ParseError> {
+    let co2 = relative_sweep(df, CO2_COLUMNS)?;
+    let light = relative_sweep(df, LIGHT_COLUMNS)?;
+    Ok(match (co2, light) {
+        (Some(co2), Some(light)) if light > co2 => CurveType::LightResponse,
+        (Some(_), _) => CurveType::Aci,
+        (None, Some(_)) => CurveType::LightResponse,
+        (None, None) => CurveType::Other,
+    })
+}
+
+/// Range relative to the maximum of the first driver column present, if it
+/// spans at least the threshold
+fn relative_sweep(df: &DataFrame, (names, threshold): (&[&str], f64)) -> Result<Option<f64>, ParseError> {
+    let Some(column) = names.iter().find_map(|name| df.column(name).ok()) else {
+        return Ok(None);
+    };
+    let values = column.cast(&polars::datatypes::DataType::Float64).map_err(curve_error)?;
+    let values = values.f64().map_err(curve_error)?;
+    let (Some(min), Some(max)) = (values.min(), values.max()) else {
+        return Ok(None);
+    };
+    let range = max - min;
+    Ok((range >= threshold && max > 0.0).then(|| range / max))
+}
+
+fn curve_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to split curves: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_and_classify() {
+        let df = df! {
+            "curve_id" => [Some("1"), Some("1"), Some("1"), None, Some("2"), Some("2")],
+            "date" => ["20250529 09:00:00", "20250529 09:02:00", "20250529 09:04:00", "20250529 09:10:00", "20250530 08:00:00", "20250530 08:05:00"],
+            "CO2_r" => [400.0, 200.0, 1200.0, 400.0, 420.0, 415.0],
+            "Qin" => [1500.0, 1500.0, 1500.0, 1500.0, 2000.0, 50.0],
+        }.unwrap();
+
+        let curves = split_curves(&df).unwrap();
+        assert_eq!(curves.len(), 3);
+        assert_eq!(curves[0].curve_id.as_deref(), Some("1"));
+        assert_eq!(curves[0].curve_type, CurveType::Aci);
+        assert_eq!(curves[0].dataframe.height(), 3);
+        assert_eq!(curves[1].curve_id, None);
+        assert_eq!(curves[1].curve_type, CurveType::Other);
+        assert_eq!(curves[2].curve_type, CurveType::LightResponse);
+        assert_eq!(curves[2].date.as_deref(), Some("2025-05-30"));
+
+        // Without curve_id or setpoints the data is one curve
+        let single = split_curves(&df.drop("curve_id").unwrap()).unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].dataframe.height(), 6);
+
+        // Groups on several columns, named from their keys
+        let columns = ["curve_id".to_string(), "Qin".to_string()];
+        let groups = split_groups(&df, &columns).unwrap();
+        assert_eq!(groups.len(), 4);
+        assert_eq!(groups[0].0, [Some("1".to_string()), Some("1500.0".to_string())]);
+        assert_eq!(groups[0].1.height(), 3);
+        assert_eq!(group_file_stem(None, "F2", &columns, &groups[1].0).unwrap(), "F2_none_1500.0");
+        assert_eq!(group_file_stem(Some("{curve_id}-{file}"), "F2", &columns, &groups[0].0).unwrap(), "1-F2");
+        assert!(group_file_stem(Some("{sample}"), "F2", &columns, &groups[0].0).is_err());
+        assert!(matches!(split_groups(&df, &["sample_id".to_string()]), Err(ParseError::MissingRequiredVariable { .. })));
+
+        // A key that comes back later joins its first group, rows kept in order
+        let by_co2 = split_groups(&df, &["CO2_r".to_string()]).unwrap();
+        assert_eq!(by_co2.len(), 5);
+        assert_eq!(by_co2[1].0, [Some("200.0".to_string())]);
+        assert_eq!(by_co2[0].1.column("date").unwrap().str().unwrap().into_no_null_iter().collect::<Vec<_>>(), ["20250529 09:00:00", "20250529 09:10:00"]);
+    }
+
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_split_on_setpoints() {
+        let data = crate::LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        let mut df = data.dataframe;
+        assert!(df.column("curve_id").is_err());
+
+        // Setpoints held constant throughout: one curve
+        let curves = split_curves(&df).unwrap();
+        assert_eq!(curves.len(), 1);
+        assert_eq!(curves[0].dataframe.height(), df.height());
+        assert_eq!(curves[0].curve_type, CurveType::Other);
+
+        // A CO₂ sweep, then a light sweep at a new CO₂ setpoint
+        assert_eq!(df.height(), 10);
+        let co2 = [400.0, 300.0, 200.0, 100.0, 50.0, 400.0, 400.0, 400.0, 400.0, 400.0];
+        let light = [1500.0, 1500.0, 1500.0, 1500.0, 1500.0, 2000.0, 1000.0, 500.0, 100.0, 0.0];
+        for (names, values) in [(["CO2_r_sp", "CO2_r"], co2), (["Q", "Qin"], light)] {
+            for name in names {
+                df.with_column(Series::new(name.into(), values)).unwrap();
+            }
+        }
+        let curves = split_curves(&df).unwrap();
+        assert_eq!(curves.len(), 2);
+        assert_eq!(curves[0].dataframe.height(), 5);
+        assert_eq!(curves[0].curve_type, CurveType::Aci);
+        assert_eq!(curves[1].dataframe.height(), 5);
+        assert_eq!(curves[1].curve_type, CurveType::LightResponse);
+        assert_eq!(curves[1].date.as_deref(), Some("2025-05-29"));
+    }
+}
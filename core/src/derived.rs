@@ -0,0 +1,240 @@
+use crate::{ParseError, VariableInfo, DataType};
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Columns holding an input variable, with the factor converting each to the
+/// LI-6800 units (LI-6400 names and the LI-6800 mmol transpiration included)
+const ASSIMILATION: &[(&str, f64)] = &[("A", 1.0), ("Photo", 1.0)];
+const TRANSPIRATION: &[(&str, f64)] = &[("E", 1.0), ("Emm", 1e-3), ("Trmmol", 1e-3)];
+const CONDUCTANCE: &[(&str, f64)] = &[("gsw", 1.0), ("Cond", 1.0)];
+
+/// Units recorded for columns computed from a user expression
+pub const USER_DEFINED_UNITS: &str = "user-defined";
+
+/// Variable computed from the gas-exchange results
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DerivedVariable {
+    /// Water-use efficiency, A / E (µmol CO₂ per mmol H₂O)
+    Wue,
+    /// Intrinsic water-use efficiency, A / gsw (µmol CO₂ per mol H₂O)
+    Iwue,
+    /// `NAME = EXPRESSION`, a polars SQL expression over the other columns
+    Expression { name: String, expression: String },
+}
+
+impl DerivedVariable {
+    /// Name of the output column
+    pub fn column_name(&self) -> &str {
+        match self {
+            DerivedVariable::Wue => "WUE",
+            DerivedVariable::Iwue => "iWUE",
+            DerivedVariable::Expression { name, .. } => name,
+        }
+    }
+
+    fn units(&self) -> &'static str {
+        match self {
+            DerivedVariable::Wue => "µmol mmol-1",
+            DerivedVariable::Iwue => "µmol mol-1",
+            DerivedVariable::Expression { .. } => USER_DEFINED_UNITS,
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            DerivedVariable::Wue => "Water-use efficiency (A/E)".to_string(),
+            DerivedVariable::Iwue => "Intrinsic water-use efficiency (A/gsw)".to_string(),
+            DerivedVariable::Expression { expression, .. } => format!("User-defined ({})", expression),
+        }
+    }
+
+    /// Derived variables given as `NAME = EXPRESSION`, or as comma-separated
+    /// `wue`/`iwue` names
+    pub fn from_args(args: &[String]) -> Result<Vec<Self>, ParseError> {
+        let mut derived = Vec::new();
+        for arg in args {
+            let parts: Vec<&str> = if arg.contains('=') { vec![arg] } else { arg.split(',').collect() };
+            for part in parts {
+                derived.push(part.parse().map_err(|message| ParseError::InvalidHeaderFormat { message })?);
+            }
+        }
+        Ok(derived)
+    }
+}
+
+impl FromStr for DerivedVariable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((name, expression)) = s.split_once('=') {
+            let (name, expression) = (name.trim(), expression.trim());
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(format!("Invalid derived column name in '{}': use NAME = EXPRESSION", s));
+            }
+            if let Err(e) = polars::sql::sql_expr(expression) {
+                return Err(format!("Invalid expression '{}': {}", expression, e));
+            }
+            return Ok(DerivedVariable::Expression { name: name.to_string(), expression: expression.to_string() });
+        }
+        match s.trim().to_ascii_lowercase().as_str() {
+            "wue" => Ok(DerivedVariable::Wue),
+            "iwue" => Ok(DerivedVariable::Iwue),
+            _ => Err(format!("Unknown derived variable '{}': use wue, iwue or NAME = EXPRESSION", s)),
+        }
+    }
+}
+
+/// Append a column for each derived variable
+///
+/// Rows where an input is missing, or the denominator is zero, negative or not
+/// finite, get a null instead of a meaningless ratio. Expressions are
+/// evaluated in order, so one can use a column an earlier one added.
+pub fn add_derived_variables(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    derived: &[DerivedVariable],
+) -> Result<(), ParseError> {
+    for variable in derived {
+        if let DerivedVariable::Expression { name, expression } = variable {
+            add_expression(df, variable_info, variable, name, expression)?;
+            continue;
+        }
+        let (numerator, numerator_name) = input_values(df, ASSIMILATION)?;
+        let (denominator, denominator_name) = match variable {
+            // A in µmol, E in mol: scale E to mmol
+            DerivedVariable::Wue => {
+                let (values, name) = input_values(df, TRANSPIRATION)?;
+                (values.into_iter().map(|v| v.map(|e| e * 1e3)).collect(), name)
+            }
+            _ => input_values(df, CONDUCTANCE)?,
+        };
+
+        let ratio: Vec<Option<f64>> = numerator.iter().zip(&denominator)
+            .map(|(a, b)| match (a, b) {
+                (Some(a), Some(b)) if b.is_finite() && *b > 0.0 => Some(a / b).filter(|r| r.is_finite()),
+                _ => None,
+            })
+            .collect();
+
+        let name = variable.column_name();
+        df.with_column(Column::new(name.into(), ratio)).map_err(derived_error)?;
+        variable_info.push(VariableInfo {
+            internal_name: name.to_string(),
+            display_label: name.to_string(),
+            units: Some(variable.units().to_string()),
+            units_pretty: Some(crate::pretty_units(variable.units())),
+            units_ascii: Some(crate::ascii_units(variable.units())),
+            description: variable.description(),
+            data_type: DataType::Float,
+            column_category: "Derived".to_string(),
+            derived_from: Some(format!("{}/{}", numerator_name, denominator_name)),
+        });
+    }
+    Ok(())
+}
+
+/// Evaluate a user expression against the columns so far
+fn add_expression(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    variable: &DerivedVariable,
+    name: &str,
+    expression: &str,
+) -> Result<(), ParseError> {
+    let expr = polars::sql::sql_expr(expression).map_err(derived_error)?;
+    let result = df.clone().lazy()
+        .select([expr.alias(name)])
+        .collect()
+        .map_err(derived_error)?;
+    let column = result.column(name).map_err(derived_error)?.clone();
+    let data_type = match column.dtype() {
+        polars::datatypes::DataType::Boolean => DataType::Boolean,
+        dtype if dtype.is_integer() => DataType::Integer,
+        dtype if dtype.is_primitive_numeric() => DataType::Float,
+        _ => DataType::String,
+    };
+    // A constant expression evaluates to a single value
+    let column = if column.len() == 1 && df.height() != 1 { column.new_from_index(0, df.height()) } else { column };
+    df.with_column(column).map_err(derived_error)?;
+    variable_info.push(VariableInfo {
+        internal_name: name.to_string(),
+        display_label: name.to_string(),
+        units: Some(USER_DEFINED_UNITS.to_string()),
+        units_pretty: Some(USER_DEFINED_UNITS.to_string()),
+        units_ascii: Some(USER_DEFINED_UNITS.to_string()),
+        description: variable.description(),
+        data_type,
+        column_category: "Derived".to_string(),
+        derived_from: Some(expression.to_string()),
+    });
+    Ok(())
+}
+
+/// Values of the first candidate column present, converted to the reference units
+fn input_values(df: &DataFrame, candidates: &[(&str, f64)]) -> Result<(Vec<Option<f64>>, String), ParseError> {
+    let (name, scale) = candidates.iter()
+        .find(|(name, _)| df.column(name).is_ok())
+        .ok_or_else(|| ParseError::UnknownVariable { variable: candidates[0].0.to_string() })?;
+    let values = df.column(name).map_err(derived_error)?
+        .cast(&polars::datatypes::DataType::Float64)
+        .map_err(derived_error)?;
+    let values = values.f64().map_err(derived_error)?
+        .into_iter()
+        .map(|v| v.map(|v| v * scale))
+        .collect();
+    Ok((values, name.to_string()))
+}
+
+fn derived_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to compute derived variables: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_water_use_efficiency() {
+        let mut df = df! {
+            "A" => [Some(12.0), Some(-1.5), None, Some(10.0)],
+            "E" => [Some(0.004), Some(0.001), Some(0.002), Some(0.0)],
+            "gsw" => [Some(0.25), Some(-0.01), Some(0.1), Some(0.25)],
+        }.unwrap();
+        let mut info = Vec::new();
+        add_derived_variables(&mut df, &mut info, &[DerivedVariable::Wue, DerivedVariable::Iwue]).unwrap();
+
+        let wue: Vec<_> = df.column("WUE").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(wue, vec![Some(3.0), Some(-1.5), None, None]);
+        let iwue: Vec<_> = df.column("iWUE").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(iwue, vec![Some(48.0), None, None, Some(40.0)]);
+        assert_eq!(info[0].units.as_deref(), Some("µmol mmol-1"));
+        assert_eq!(info[1].derived_from.as_deref(), Some("A/gsw"));
+
+        // LI-6400 names, with transpiration in mmol
+        let mut df = df! { "Photo" => [9.0], "Trmmol" => [3.0] }.unwrap();
+        add_derived_variables(&mut df, &mut Vec::new(), &["wue".parse().unwrap()]).unwrap();
+        let wue = df.column("WUE").unwrap().f64().unwrap().get(0).unwrap();
+        assert!((wue - 3.0).abs() < 1e-12);
+
+        let missing = add_derived_variables(&mut df, &mut Vec::new(), &[DerivedVariable::Iwue]);
+        assert!(matches!(missing, Err(ParseError::UnknownVariable { .. })));
+
+        // Expressions, which can build on each other
+        let mut df = df! { "A" => [12.0, 6.0], "gsw" => [0.25, 0.1] }.unwrap();
+        let mut info = Vec::new();
+        let args = ["iWUE2 = A / gsw".to_string(), "half = iWUE2 / 2".to_string(), "wue,iwue".to_string()];
+        let derived = DerivedVariable::from_args(&args).unwrap();
+        assert_eq!(derived.len(), 4);
+        add_derived_variables(&mut df, &mut info, &derived[..2]).unwrap();
+        let half: Vec<_> = df.column("half").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(half, vec![Some(24.0), Some(30.0)]);
+        assert_eq!(info[0].units.as_deref(), Some(USER_DEFINED_UNITS));
+        assert_eq!(info[0].derived_from.as_deref(), Some("A / gsw"));
+        assert!("bad name = A".parse::<DerivedVariable>().is_err());
+        assert!("x = A /".parse::<DerivedVariable>().is_err());
+        let unknown = add_derived_variables(&mut df, &mut Vec::new(), &["x = Photo * 2".parse().unwrap()]);
+        assert!(unknown.is_err());
+    }
+}
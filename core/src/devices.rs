@@ -1,4 +1,5 @@
 use crate::ParseError;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Trait for device-specific parsing and validation
@@ -10,10 +11,24 @@ pub trait LiCorDevice {
     
     /// Parse device-specific metadata from header
     fn parse_metadata(header: &HashMap<String, String>) -> Result<LiCorMetadata, ParseError>;
+    
+    /// Pairs of (device column name, LI-6800 column name) for devices whose
+    /// logs name the same quantities differently
+    fn column_aliases() -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+    
+    /// LI-6800 equivalent of a column name, used for configuration validation
+    fn canonical_column_name(column: &str) -> &str {
+        Self::column_aliases().iter()
+            .find(|(alias, _)| *alias == column)
+            .map(|(_, canonical)| *canonical)
+            .unwrap_or(column)
+    }
 }
 
 /// Device metadata extracted from file headers
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LiCorMetadata {
     pub device_serial: String,
     pub console_version: String,
@@ -23,11 +38,44 @@ pub struct LiCorMetadata {
     pub chamber_serial: Option<String>,
     pub fluorometer_serial: Option<String>,
     pub calibration_date: Option<String>,
+    /// Instrument clock correction applied to the `time` column, if any
+    pub clock_correction: Option<crate::ClockCorrection>,
+    /// SHA-256 of the parsed data (see `content_hash`), set once parsing finishes
+    pub content_hash: Option<String>,
+    /// Console messages found among the data rows, kept out of the data
+    pub console_messages: Vec<crate::ConsoleMessage>,
+    /// Fluorometer settings at file open, then each change during the log
+    #[serde(default)]
+    pub fluorometer_settings: Vec<crate::FluorometerSettings>,
+    /// Calibration certificates of the instruments, from the instrument profiles
+    #[serde(default)]
+    pub calibration_certificates: Vec<crate::CalibrationCertificate>,
+}
+
+impl LiCorMetadata {
+    /// Look up a metadata field by its field name, e.g. `"chamber_type"`
+    ///
+    /// Returns `None` for unknown names and for optional fields that are unset.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        match name {
+            "device_serial" => Some(&self.device_serial),
+            "console_version" => Some(&self.console_version),
+            "head_serial" => self.head_serial.as_deref(),
+            "head_version" => self.head_version.as_deref(),
+            "chamber_type" => self.chamber_type.as_deref(),
+            "chamber_serial" => self.chamber_serial.as_deref(),
+            "fluorometer_serial" => self.fluorometer_serial.as_deref(),
+            "calibration_date" => self.calibration_date.as_deref(),
+            _ => None,
+        }
+    }
 }
 
 /// LI-6800 Portable Photosynthesis System
+#[cfg(feature = "device-6800")]
 pub struct Device6800;
 
+#[cfg(feature = "device-6800")]
 impl LiCorDevice for Device6800 {
     const DEVICE_NAME: &'static str = "LI-6800";
     
@@ -77,27 +125,79 @@ impl LiCorDevice for Device6800 {
             chamber_serial: header.get("Chamber s/n").cloned(),
             fluorometer_serial: header.get("Fluorometer").cloned(),
             calibration_date: header.get("Factory cal date").cloned(),
+            ..Default::default()
         })
     }
 }
 
-/// LI-6400 Portable Photosynthesis System (future implementation)
+/// LI-6400 Portable Photosynthesis System
+///
+/// Header keys follow the legacy layout: `Console ver` holds the "OPEN x.y.z"
+/// software line and `Unit` the console serial number.
+#[cfg(feature = "device-6400")]
 pub struct Device6400;
 
+#[cfg(feature = "device-6400")]
 impl LiCorDevice for Device6400 {
     const DEVICE_NAME: &'static str = "LI-6400";
     
-    fn validate_header(_header: &HashMap<String, String>) -> Result<(), ParseError> {
-        // TODO: Implement LI-6400 validation when we have sample files
-        Err(ParseError::InvalidFileFormat { 
-            device: "LI-6400 support not yet implemented".to_string() 
-        })
+    fn validate_header(header: &HashMap<String, String>) -> Result<(), ParseError> {
+        let version = header.get("Console ver")
+            .ok_or_else(|| ParseError::MissingRequiredHeader { 
+                field: "Console ver".to_string() 
+            })?;
+        
+        // Validate that this is actually a 6400 (OPEN software)
+        if !version.starts_with("OPEN") {
+            return Err(ParseError::InvalidFileFormat { 
+                device: Self::DEVICE_NAME.to_string() 
+            });
+        }
+        
+        if !header.contains_key("Unit") {
+            return Err(ParseError::MissingRequiredHeader { 
+                field: "Unit".to_string() 
+            });
+        }
+        
+        Ok(())
     }
     
-    fn parse_metadata(_header: &HashMap<String, String>) -> Result<LiCorMetadata, ParseError> {
-        // TODO: Implement LI-6400 metadata parsing
-        Err(ParseError::InvalidFileFormat { 
-            device: "LI-6400 support not yet implemented".to_string() 
+    fn parse_metadata(header: &HashMap<String, String>) -> Result<LiCorMetadata, ParseError> {
+        let device_serial = header.get("Unit")
+            .ok_or_else(|| ParseError::MissingRequiredHeader { 
+                field: "Unit".to_string() 
+            })?
+            .clone();
+            
+        let console_version = header.get("Console ver")
+            .ok_or_else(|| ParseError::MissingRequiredHeader { 
+                field: "Console ver".to_string() 
+            })?
+            .clone();
+            
+        Ok(LiCorMetadata {
+            device_serial,
+            console_version,
+            chamber_type: header.get("LightSource").cloned(),
+            ..Default::default()
         })
     }
-}
\ No newline at end of file
+    
+    fn column_aliases() -> &'static [(&'static str, &'static str)] {
+        &[
+            ("Obs", "obs"),
+            ("Photo", "A"),
+            ("Trmmol", "E"),
+            ("CO2R", "Ca"),
+            ("Cond", "gsw"),
+            ("BLCond", "gbw"),
+            ("Tleaf", "Tleaf"),
+            ("Tair", "Tair"),
+            ("Flow", "Flow"),
+            ("Press", "Pa"),
+            ("Fs", "F"),
+            ("PhiPS2", "PhiPS2"),
+        ]
+    }
+}
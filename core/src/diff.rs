@@ -0,0 +1,275 @@
+use crate::{ParseError, RawLiCorFile};
+use polars::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+/// Header key under which the stability definition is compared; the file
+/// logs it under the time it was set
+pub const STABILITY_KEY: &str = "Stability Definition";
+
+/// A header entry whose value differs between two files, or that only one has
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderDifference {
+    pub key: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+/// A column both files log, in different units
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitsDifference {
+    pub column: String,
+    pub left: String,
+    pub right: String,
+}
+
+/// Differences between the headers and columns of two files
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileDiff {
+    /// Settings first (`SysConst`, then the stability definition, then other
+    /// constants such as `ChambConst`), then the rest of the header
+    pub header: Vec<HeaderDifference>,
+    /// Columns only the left file logs, in its order
+    pub only_left: Vec<String>,
+    /// Columns only the right file logs, in its order
+    pub only_right: Vec<String>,
+    pub units: Vec<UnitsDifference>,
+}
+
+impl FileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.header.is_empty() && self.only_left.is_empty() && self.only_right.is_empty() && self.units.is_empty()
+    }
+}
+
+/// Compare the headers, column sets and column units of two files
+pub fn diff_files(left: &RawLiCorFile, right: &RawLiCorFile) -> FileDiff {
+    let (left_header, right_header) = (settings(&left.header), settings(&right.header));
+    let keys: BTreeSet<&String> = left_header.keys().chain(right_header.keys()).collect();
+    let mut header: Vec<HeaderDifference> = keys.into_iter()
+        .filter(|key| left_header.get(*key) != right_header.get(*key))
+        .map(|key| HeaderDifference {
+            key: key.clone(),
+            left: left_header.get(key).cloned(),
+            right: right_header.get(key).cloned(),
+        })
+        .collect();
+    header.sort_by_key(|difference| setting_rank(&difference.key));
+
+    let only = |a: &RawLiCorFile, b: &RawLiCorFile| a.column_names.iter()
+        .filter(|name| !name.is_empty() && !b.column_names.contains(name))
+        .cloned()
+        .collect();
+    // Names logged more than once are paired by occurrence
+    let units = left.column_names.iter()
+        .zip(&left.units)
+        .enumerate()
+        .filter_map(|(index, (column, left_units))| {
+            let occurrence = left.column_names[..index].iter().filter(|name| *name == column).count();
+            let index = right.column_names.iter()
+                .enumerate()
+                .filter(|(_, name)| *name == column)
+                .nth(occurrence)?
+                .0;
+            let right_units = right.units.get(index)?;
+            (left_units != right_units).then(|| UnitsDifference {
+                column: column.clone(),
+                left: left_units.clone(),
+                right: right_units.clone(),
+            })
+        })
+        .collect();
+
+    FileDiff { header, only_left: only(left, right), only_right: only(right, left), units }
+}
+
+/// A column both outputs have, with different types
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDifference {
+    pub column: String,
+    pub left: String,
+    pub right: String,
+}
+
+/// A column both outputs have, with different values in some rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueDifference {
+    pub column: String,
+    /// Rows whose values differ, including where only one is missing
+    pub rows: usize,
+    /// Largest difference between two numbers, for numeric columns
+    pub max_difference: Option<f64>,
+}
+
+/// Differences between two outputs of the same file, such as those written
+/// by two versions of the converter
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutputDiff {
+    /// Columns only the left output has, in its order
+    pub only_left: Vec<String>,
+    /// Columns only the right output has, in its order
+    pub only_right: Vec<String>,
+    pub types: Vec<TypeDifference>,
+    /// Row counts, when they differ (values are then not compared)
+    pub rows: Option<(usize, usize)>,
+    pub values: Vec<ValueDifference>,
+}
+
+impl OutputDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.types.is_empty()
+            && self.rows.is_none() && self.values.is_empty()
+    }
+}
+
+/// Compare the schemas and values of two outputs
+///
+/// Columns are paired by name. Numbers are equal when they differ by at most
+/// `tolerance` relative to the larger, so `Int64` and `Float64` columns of the
+/// same values only differ in type; other columns are compared as text.
+pub fn diff_outputs(left: &DataFrame, right: &DataFrame, tolerance: f64) -> Result<OutputDiff, ParseError> {
+    let only = |a: &DataFrame, b: &DataFrame| a.get_column_names().into_iter()
+        .filter(|name| b.column(name).is_err())
+        .map(|name| name.to_string())
+        .collect();
+    let mut diff = OutputDiff {
+        only_left: only(left, right),
+        only_right: only(right, left),
+        rows: (left.height() != right.height()).then(|| (left.height(), right.height())),
+        ..OutputDiff::default()
+    };
+    for column in left.get_columns() {
+        let Ok(other) = right.column(column.name()) else { continue };
+        if column.dtype() != other.dtype() {
+            diff.types.push(TypeDifference {
+                column: column.name().to_string(),
+                left: column.dtype().to_string(),
+                right: other.dtype().to_string(),
+            });
+        }
+        if diff.rows.is_some() {
+            continue;
+        }
+        let difference = if column.dtype().is_primitive_numeric() && other.dtype().is_primitive_numeric() {
+            let (a, b) = (cast_values(column, &DataType::Float64)?, cast_values(other, &DataType::Float64)?);
+            let (mut rows, mut max_difference) = (0, 0.0f64);
+            for (a, b) in a.f64().map_err(output_error)?.iter().zip(b.f64().map_err(output_error)?.iter()) {
+                match (a, b) {
+                    (Some(a), Some(b)) if a == b || (a.is_nan() && b.is_nan()) => {}
+                    (Some(a), Some(b)) => {
+                        let difference = (a - b).abs();
+                        if difference > tolerance * a.abs().max(b.abs()) {
+                            rows += 1;
+                            max_difference = max_difference.max(difference);
+                        }
+                    }
+                    (None, None) => {}
+                    _ => rows += 1,
+                }
+            }
+            (rows > 0).then(|| ValueDifference { column: column.name().to_string(), rows, max_difference: Some(max_difference) })
+        } else {
+            let (a, b) = (cast_values(column, &DataType::String)?, cast_values(other, &DataType::String)?);
+            let rows = a.str().map_err(output_error)?.iter()
+                .zip(b.str().map_err(output_error)?.iter())
+                .filter(|(a, b)| a != b)
+                .count();
+            (rows > 0).then(|| ValueDifference { column: column.name().to_string(), rows, max_difference: None })
+        };
+        diff.values.extend(difference);
+    }
+    Ok(diff)
+}
+
+fn cast_values(column: &Column, dtype: &DataType) -> Result<Column, ParseError> {
+    column.cast(dtype).map_err(output_error)
+}
+
+fn output_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to compare outputs: {}", e)
+    }
+}
+
+/// Header with the stability definition under [`STABILITY_KEY`]
+fn settings(header: &HashMap<String, String>) -> HashMap<String, String> {
+    header.iter()
+        .map(|(key, value)| match value.strip_prefix("Stability Definition:") {
+            Some(definition) => (STABILITY_KEY.to_string(), definition.trim().to_string()),
+            None => (key.clone(), value.clone()),
+        })
+        .collect()
+}
+
+/// Position of a header key in a diff; keys of equal rank stay sorted by name
+fn setting_rank(key: &str) -> u8 {
+    match key.split_once(':') {
+        Some(("SysConst", _)) => 0,
+        _ if key == STABILITY_KEY => 1,
+        Some((group, _)) if group.ends_with("Const") => 2,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_files() {
+        let content = std::fs::read_to_string("../example_data/2025-05-30-1203_logdata_F2").unwrap();
+        let left = RawLiCorFile::parse(&content).unwrap();
+        assert!(diff_files(&left, &left).is_empty());
+
+        let changed = content
+            .replace("SysConst:Oxygen\t21.0", "SysConst:Oxygen\t2.0")
+            .replace("12:04:20\tStability Definition:\tF (FlrLS): Slp<1", "13:00:00\tStability Definition:\tF (FlrLS): Slp<2")
+            .replace("ChambConst:blc_a\t0.25\n", "")
+            .replace("File opened\t2025-05-30 12:04:20", "File opened\t2025-05-31 08:00:00");
+        let right = RawLiCorFile::parse(&changed).unwrap();
+        let diff = diff_files(&left, &right);
+        let keys: Vec<&str> = diff.header.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, ["SysConst:Oxygen", STABILITY_KEY, "ChambConst:blc_a", "File opened"]);
+        assert_eq!(diff.header[0].right.as_deref(), Some("2.0"));
+        assert_eq!(diff.header[2].right, None);
+        assert!(diff.only_left.is_empty() && diff.units.is_empty());
+
+        let mut fewer = left.clone();
+        fewer.column_names[7] = "Etr".to_string();
+        fewer.units[8] = "mol m⁻² s⁻¹".to_string();
+        let diff = diff_files(&left, &fewer);
+        assert_eq!(diff.only_left, ["E"]);
+        assert_eq!(diff.only_right, ["Etr"]);
+        assert_eq!(diff.units[0].column, "Emm");
+    }
+
+    #[test]
+    fn test_diff_outputs() {
+        let left = df! {
+            "obs" => ["1", "2", "3"],
+            "A" => [1.0, 2.0, 3.0],
+            "E" => [0.1, 0.2, 0.3],
+            "remark" => [Some("a"), None, Some("c")],
+        }.unwrap();
+        assert!(diff_outputs(&left, &left, 0.0).unwrap().is_empty());
+
+        let right = df! {
+            "obs" => [1i64, 2, 3],
+            "A" => [1.0, 2.0 + 1e-12, 3.5],
+            "remark" => [Some("a"), Some("b"), Some("c")],
+            "gsw" => [0.1, 0.1, 0.1],
+        }.unwrap();
+        let diff = diff_outputs(&left, &right, 1e-9).unwrap();
+        assert_eq!(diff.only_left, ["E"]);
+        assert_eq!(diff.only_right, ["gsw"]);
+        assert_eq!(diff.types, [TypeDifference { column: "obs".to_string(), left: "str".to_string(), right: "i64".to_string() }]);
+        assert_eq!(diff.values, [
+            ValueDifference { column: "A".to_string(), rows: 1, max_difference: Some(0.5) },
+            ValueDifference { column: "remark".to_string(), rows: 1, max_difference: None },
+        ]);
+
+        let shorter = left.head(Some(2));
+        let diff = diff_outputs(&left, &shorter, 0.0).unwrap();
+        assert_eq!(diff.rows, Some((3, 2)));
+        assert!(diff.values.is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+//! Diagnosis of text logs that fail to parse
+//!
+//! Where parsing stops at the first error, [`diagnose`] looks at the raw bytes
+//! and reports every problem it can find, each with the line it is on and a
+//! suggested fix.
+
+use crate::RawLiCorFile;
+
+/// Header keys the LI-6800 parser requires
+const REQUIRED_HEADER_KEYS: &[&str] = &["Console s/n", "Console ver", "Head s/n"];
+
+/// Number of malformed rows listed individually before they are summarized
+const ROWS_LISTED: usize = 5;
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The file cannot be parsed until this is fixed
+    Error,
+    /// The file parses, but some values are lost or may be wrong
+    Warning,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// A problem found in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Line of the file (1-based) the problem is on, if it is on one
+    pub line: Option<usize>,
+    pub problem: String,
+    pub fix: String,
+}
+
+impl Finding {
+    fn new(severity: Severity, line: Option<usize>, problem: impl Into<String>, fix: impl Into<String>) -> Self {
+        Finding { severity, line, problem: problem.into(), fix: fix.into() }
+    }
+}
+
+/// Check a file's encoding, sections, header keys and column counts
+pub fn diagnose(bytes: &[u8]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if bytes.is_empty() {
+        findings.push(Finding::new(Severity::Error, None, "The file is empty",
+            "Copy the file from the instrument again"));
+        return findings;
+    }
+    if bytes.starts_with(&[0xFF, 0xFE]) || bytes.starts_with(&[0xFE, 0xFF]) {
+        findings.push(Finding::new(Severity::Error, Some(1), "The file is UTF-16 text, as saved by some Windows editors",
+            "Save the file as UTF-8, or copy the original from the instrument"));
+        return findings;
+    }
+    if let Some(offset) = bytes.iter().position(|&b| b == 0) {
+        findings.push(Finding::new(Severity::Error, Some(line_of(bytes, offset)), "The file contains NUL bytes, as left by an interrupted write",
+            "Copy the file from the instrument again; if the console lost power while logging, delete the lines from here on"));
+    }
+    let content = match std::str::from_utf8(bytes) {
+        Ok(content) => content.to_string(),
+        Err(e) => {
+            findings.push(Finding::new(Severity::Warning, Some(line_of(bytes, e.valid_up_to())), "The file is not valid UTF-8, as when it was re-saved as Latin-1 or Windows-1252",
+                "Re-save the file as UTF-8 (e.g. `iconv -f latin1 -t utf-8`); characters such as µ and ° are garbled until then"));
+            String::from_utf8_lossy(bytes).into_owned()
+        }
+    };
+    let content = content.strip_prefix('\u{feff}').unwrap_or(&content);
+    let lines: Vec<&str> = content.lines().collect();
+
+    let header_start = lines.iter().position(|line| line.trim() == "[Header]");
+    let data_start = lines.iter().position(|line| line.trim() == "[Data]");
+    match (header_start, data_start) {
+        (None, _) => findings.push(Finding::new(Severity::Error, None, "No [Header] line",
+            "Check that this is an LI-COR log; the file should start with a line reading [Header]")),
+        (Some(header), Some(data)) if data < header => findings.push(Finding::new(Severity::Error, Some(data + 1), "[Data] comes before [Header]",
+            "Put the header section first; the file may be two logs pasted together")),
+        _ => {}
+    }
+    let Some(data_start) = data_start else {
+        findings.push(Finding::new(Severity::Error, None, "No [Data] line",
+            "The file was cut off in the header; copy it from the instrument again"));
+        return findings;
+    };
+
+    // Header keys are checked in the header section only
+    let header_lines = &lines[header_start.map_or(0, |start| start + 1)..data_start];
+    for key in REQUIRED_HEADER_KEYS {
+        if !header_lines.iter().any(|line| line.split('\t').next().map(str::trim) == Some(*key)) {
+            findings.push(Finding::new(Severity::Error, None, format!("Header key '{}' is missing", key),
+                format!("Add a '{}<TAB>value' line to the header, copying the value from another log of the same console", key)));
+        }
+    }
+
+    let table = &lines[data_start + 1..];
+    if table.len() < 3 {
+        findings.push(Finding::new(Severity::Error, Some(data_start + 1 + table.len()), "The data section ends before the column names and units",
+            "The file was cut off; copy it from the instrument again"));
+        return findings;
+    }
+    let widths: Vec<usize> = table[..3].iter().map(|line| line.split('\t').count()).collect();
+    if widths[0] != widths[1] || widths[1] != widths[2] {
+        findings.push(Finding::new(Severity::Warning, Some(data_start + 2),
+            format!("The category, name and unit rows have {}, {} and {} columns", widths[0], widths[1], widths[2]),
+            "Columns without a name or units are given empty ones; check that no tab was added or deleted in these rows"));
+    }
+
+    let columns = widths.into_iter().max().unwrap_or_default();
+    let mut observations = 0;
+    let mut malformed = Vec::new();
+    for (index, line) in table.iter().enumerate().skip(3) {
+        if line.trim().is_empty() || RawLiCorFile::is_console_message(line, columns) {
+            continue;
+        }
+        observations += 1;
+        let fields = line.split('\t').count();
+        if fields != columns {
+            malformed.push((data_start + 2 + index, fields));
+        }
+    }
+    if observations == 0 {
+        findings.push(Finding::new(Severity::Error, Some(data_start + 4), "The data section has no observations",
+            "The log was closed before anything was logged; there is nothing to convert"));
+    }
+
+    // A short last line with no line ending is a cut-off copy, not a bad row
+    let truncated = malformed.last()
+        .filter(|(line, fields)| *line == data_start + 1 + table.len() && *fields < columns && !content.ends_with('\n'))
+        .copied();
+    if let Some((line, fields)) = truncated {
+        malformed.pop();
+        findings.push(Finding::new(Severity::Warning, Some(line), format!("The last observation is cut off after {} of {} fields", fields, columns),
+            "The file was copied while the console was still logging; copy it again, or delete the last line"));
+    }
+    for (line, fields) in malformed.iter().take(ROWS_LISTED) {
+        findings.push(Finding::new(Severity::Warning, Some(*line), format!("Row has {} fields, not {}", fields, columns),
+            if *fields < columns { "Missing cells are read as empty; check the row for deleted tabs" } else { "Extra cells are dropped; check the row for inserted tabs" }));
+    }
+    if malformed.len() > ROWS_LISTED {
+        findings.push(Finding::new(Severity::Warning, None, format!("{} more rows have the wrong number of fields", malformed.len() - ROWS_LISTED),
+            "The file may have been edited in a spreadsheet; convert the original from the instrument instead"));
+    }
+    findings
+}
+
+/// Line (1-based) a byte offset is on
+fn line_of(bytes: &[u8], offset: usize) -> usize {
+    bytes[..offset].iter().filter(|&&b| b == b'\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose() {
+        let content = std::fs::read_to_string("../example_data/2025-05-30-1203_logdata_F2").unwrap();
+        assert_eq!(diagnose(content.as_bytes()), Vec::new());
+
+        // Cut off mid-row, without a header key, with one short row
+        let mut broken = content.replace("Head s/n\t68H-581292\n", "");
+        let first = broken.find("\n1\t").unwrap() + 1;
+        let tab = first + broken[first..].find('\t').unwrap();
+        broken.replace_range(tab..tab + 1, "");
+        broken.truncate(broken.trim_end().len() - 200);
+        let findings = diagnose(broken.as_bytes());
+        let problems: Vec<&str> = findings.iter().map(|f| f.problem.as_str()).collect();
+        assert_eq!(findings.len(), 3, "{:?}", problems);
+        assert_eq!(findings[0].problem, "Header key 'Head s/n' is missing");
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert!(findings[1].problem.starts_with("The last observation is cut off"));
+        assert!(findings[2].problem.starts_with("Row has"));
+        assert_eq!(findings[2].line, Some(broken[..first].lines().count() + 1));
+
+        let latin1: Vec<u8> = content.chars().map(|c| u8::try_from(c).unwrap_or(b'?')).collect();
+        assert_eq!(diagnose(&latin1)[0].severity, Severity::Warning);
+        assert_eq!(diagnose(b"")[0].problem, "The file is empty");
+        assert_eq!(diagnose(&[0xFF, 0xFE, b'[', 0])[0].problem, "The file is UTF-16 text, as saved by some Windows editors");
+    }
+}
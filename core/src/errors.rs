@@ -35,4 +35,87 @@ pub enum ParseError {
     
     #[error("Empty or invalid data section")]
     EmptyDataSection,
-}
\ No newline at end of file
+    
+    #[error("Failed to write {format} output: {message}")]
+    WriteError { format: String, message: String },
+}
+
+/// Every error kind as `(code, number)`, in number order
+///
+/// Codes and numbers are stable: a kind keeps both across releases, and new
+/// kinds get new numbers. The CLI exits with the number of the error that
+/// stopped it.
+pub const ERROR_CODES: &[(&str, u8)] = &[
+    ("invalid_file_format", 10),
+    ("missing_required_header", 11),
+    ("unknown_variable", 12),
+    ("missing_required_variable", 13),
+    ("malformed_data_section", 14),
+    ("data_type_error", 15),
+    ("invalid_header_format", 16),
+    ("empty_data_section", 17),
+    ("toml_parse", 18),
+    ("io", 20),
+    ("write_error", 21),
+];
+
+impl ParseError {
+    /// Stable numeric code of the error kind (see [`ERROR_CODES`]), used as
+    /// the CLI's exit status
+    pub fn number(&self) -> u8 {
+        let code = self.code();
+        ERROR_CODES.iter()
+            .find(|(name, _)| *name == code)
+            .map_or(1, |(_, number)| *number)
+    }
+
+    /// Stable snake_case identifier of the error kind, for machine-readable output
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::InvalidFileFormat { .. } => "invalid_file_format",
+            ParseError::MissingRequiredHeader { .. } => "missing_required_header",
+            ParseError::UnknownVariable { .. } => "unknown_variable",
+            ParseError::MissingRequiredVariable { .. } => "missing_required_variable",
+            ParseError::MalformedDataSection { .. } => "malformed_data_section",
+            ParseError::DataTypeError { .. } => "data_type_error",
+            ParseError::Io(_) => "io",
+            ParseError::TomlParse(_) => "toml_parse",
+            ParseError::InvalidHeaderFormat { .. } => "invalid_header_format",
+            ParseError::EmptyDataSection => "empty_data_section",
+            ParseError::WriteError { .. } => "write_error",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(ParseError::EmptyDataSection.code(), "empty_data_section");
+        let io = ParseError::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(io.code(), "io");
+        assert_eq!(io.number(), 20);
+        let write = ParseError::WriteError { format: "csv".to_string(), message: String::new() };
+        assert_eq!(write.number(), 21);
+
+        // Numbers are unique and leave 1 and 2 to generic and usage errors
+        let mut numbers: Vec<u8> = ERROR_CODES.iter().map(|(_, number)| *number).collect();
+        numbers.dedup();
+        assert_eq!(numbers.len(), ERROR_CODES.len());
+        assert!(numbers.windows(2).all(|pair| pair[0] < pair[1]) && numbers[0] > 2);
+        let kinds = [
+            ParseError::InvalidFileFormat { device: String::new() },
+            ParseError::MissingRequiredHeader { field: String::new() },
+            ParseError::UnknownVariable { variable: String::new() },
+            ParseError::MissingRequiredVariable { variable: String::new(), config: String::new() },
+            ParseError::MalformedDataSection { expected: 0, found: 0 },
+            ParseError::DataTypeError { value: String::new(), expected_type: String::new(), variable: String::new() },
+            ParseError::InvalidHeaderFormat { message: String::new() },
+            ParseError::EmptyDataSection,
+            ParseError::TomlParse(toml::from_str::<toml::Value>("=").unwrap_err()),
+        ];
+        assert!(kinds.iter().all(|e| e.number() != 1));
+    }
+}
@@ -0,0 +1,262 @@
+//! Fluorometer settings from the header and from changes logged mid-log
+//!
+//! The LI-6800 writes the fluorometer configuration as a timestamped
+//! `Flr Settings:` line of `Name=Value` entries, in the header when the log is
+//! opened and among the data rows when a setting is changed while logging.
+
+use crate::{ConsoleMessage, DataType, ParseError, VariableInfo};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+/// Labels that start a settings line
+const LABELS: [&str; 2] = ["Flr Settings:", "Fluorometer Settings:"];
+
+/// Kind of saturating flash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlashType {
+    Rectangular,
+    /// Multiphase flash
+    Multiphase,
+}
+
+impl FromStr for FlashType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "rect" | "rectangular" => Ok(FlashType::Rectangular),
+            "mpf" | "multiphase" => Ok(FlashType::Multiphase),
+            _ => Err(format!("Unknown flash type '{}'", s)),
+        }
+    }
+}
+
+impl FlashType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FlashType::Rectangular => "rectangular",
+            FlashType::Multiphase => "multiphase",
+        }
+    }
+}
+
+/// Fluorometer configuration in effect from some point of a log
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FluorometerSettings {
+    /// Data rows logged before these settings took effect; 0 for the header's
+    pub after_row: usize,
+    /// Console time the settings were written, as `HH:MM:SS`
+    pub time: Option<String>,
+    /// Measuring beam intensity, µmol m⁻² s⁻¹
+    pub measuring_intensity: Option<f64>,
+    /// Modulation rate in the dark, Hz
+    pub dark_modulation_rate: Option<f64>,
+    /// Modulation rate in the light, Hz
+    pub light_modulation_rate: Option<f64>,
+    pub flash_type: Option<FlashType>,
+    /// Flash target intensity, µmol m⁻² s⁻¹
+    pub flash_intensity: Option<f64>,
+    /// Rectangular flash duration, ms
+    pub flash_duration: Option<f64>,
+    /// Multiphase flash intensity ramp (phase 2), %
+    pub mpf_ramp: Option<f64>,
+    /// Entries not matched to a field, by name as logged
+    pub other: BTreeMap<String, String>,
+}
+
+impl FluorometerSettings {
+    /// Settings from the entries of a settings line, after its label; `None`
+    /// when there are none
+    pub fn parse_entries(entries: &str) -> Option<Self> {
+        let mut settings = FluorometerSettings::default();
+        let pairs = entries.split('\t').flat_map(|part| -> Vec<(String, String)> {
+            if part.contains('=') {
+                part.split_whitespace()
+                    .filter_map(|token| token.split_once('='))
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect()
+            } else {
+                part.split_once(':')
+                    .map(|(name, value)| vec![(name.trim().to_string(), value.trim().to_string())])
+                    .unwrap_or_default()
+            }
+        });
+        let mut any = false;
+        for (name, value) in pairs {
+            any = true;
+            let key: String = name.chars().filter(char::is_ascii_alphanumeric).collect::<String>().to_ascii_lowercase();
+            let number = leading_number(&value);
+            match key.as_str() {
+                "measint" | "measintensity" | "measuringintensity" | "qmod" => settings.measuring_intensity = number,
+                "darkmod" | "darkmodrate" | "darkrate" => settings.dark_modulation_rate = number,
+                "lightmod" | "lightmodrate" | "lightrate" => settings.light_modulation_rate = number,
+                "flash" | "flashtype" => settings.flash_type = value.parse().ok(),
+                "flashint" | "flashintensity" | "target" | "rectint" => settings.flash_intensity = number,
+                "flashdur" | "flashduration" | "rectdur" => settings.flash_duration = number,
+                "ramp" | "mpframp" | "p2ramp" => settings.mpf_ramp = number,
+                _ => {
+                    settings.other.insert(name, value);
+                }
+            }
+        }
+        any.then_some(settings)
+    }
+
+    /// Settings of a header, then each change logged among the data rows, in
+    /// log order; the changes are taken out of `console_messages`
+    pub fn from_log(header: &HashMap<String, String>, console_messages: &mut Vec<ConsoleMessage>) -> Vec<Self> {
+        let mut initial: Vec<(&String, Self)> = header.iter()
+            .filter_map(|(key, value)| Some((key, Self::parse_line(value)?)))
+            .collect();
+        initial.sort_by(|a, b| a.0.cmp(b.0));
+        let mut settings: Vec<Self> = initial.into_iter()
+            .map(|(key, settings)| FluorometerSettings { time: Some(timestamp(key).to_string()), ..settings })
+            .collect();
+
+        console_messages.retain(|message| {
+            // Messages are joined with spaces: `12:30:00 Flr Settings: ...`
+            let (time, text) = match message.text.split_once(' ') {
+                Some((time, text)) if is_time(time) => (Some(time.to_string()), text),
+                _ => (None, message.text.as_str()),
+            };
+            match Self::parse_line(text) {
+                Some(change) => {
+                    settings.push(FluorometerSettings { after_row: message.after_row, time, ..change });
+                    false
+                }
+                None => true,
+            }
+        });
+        settings
+    }
+
+    fn parse_line(text: &str) -> Option<Self> {
+        let entries = LABELS.iter().find_map(|label| text.trim_start().strip_prefix(label))?;
+        Self::parse_entries(entries)
+    }
+
+    /// Column name, units and value of each field, for [`add_settings_columns`]
+    fn columns(&self) -> [(&'static str, Option<&'static str>, Option<String>); 7] {
+        let number = |value: Option<f64>| value.map(|v| v.to_string());
+        [
+            ("flr_measuring_intensity", Some("µmol m-2 s-1"), number(self.measuring_intensity)),
+            ("flr_dark_modulation_rate", Some("Hz"), number(self.dark_modulation_rate)),
+            ("flr_light_modulation_rate", Some("Hz"), number(self.light_modulation_rate)),
+            ("flr_flash_type", None, self.flash_type.map(|t| t.name().to_string())),
+            ("flr_flash_intensity", Some("µmol m-2 s-1"), number(self.flash_intensity)),
+            ("flr_flash_duration", Some("ms"), number(self.flash_duration)),
+            ("flr_mpf_ramp", Some("%"), number(self.mpf_ramp)),
+        ]
+    }
+}
+
+/// Add a `flr_<setting>` column for each setting changed during the log,
+/// holding the value in effect on every row
+///
+/// `settings` must be in log order, as from [`FluorometerSettings::from_log`],
+/// and the rows as read (before any trimming or sorting). Settings that never
+/// change stay in the metadata only.
+pub(crate) fn add_settings_columns(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    settings: &[FluorometerSettings],
+) -> Result<(), ParseError> {
+    let Some(first) = settings.first() else {
+        return Ok(());
+    };
+    let columns: Vec<_> = settings.iter().map(FluorometerSettings::columns).collect();
+    for (index, (name, units, _)) in first.columns().into_iter().enumerate() {
+        if columns.iter().all(|fields| fields[index].2 == columns[0][index].2) {
+            continue;
+        }
+        // The latest settings logged before each row
+        let mut current = 0;
+        let values: Vec<Option<String>> = (0..df.height())
+            .map(|row| {
+                while current + 1 < settings.len() && settings[current + 1].after_row <= row {
+                    current += 1;
+                }
+                (settings[current].after_row <= row).then(|| columns[current][index].2.clone()).flatten()
+            })
+            .collect();
+        let is_number = name != "flr_flash_type";
+        let column = if is_number {
+            Column::new(name.into(), values.iter().map(|v| v.as_deref().and_then(|v| v.parse::<f64>().ok())).collect::<Vec<_>>())
+        } else {
+            Column::new(name.into(), values)
+        };
+        df.with_column(column).map_err(|e| ParseError::InvalidHeaderFormat {
+            message: format!("Failed to add fluorometer setting column: {}", e)
+        })?;
+        variable_info.push(VariableInfo {
+            internal_name: name.to_string(),
+            display_label: name.to_string(),
+            units: units.map(str::to_string),
+            units_pretty: units.map(crate::pretty_units),
+            units_ascii: units.map(crate::ascii_units),
+            description: format!("Fluorometer setting in effect: {}", name.trim_start_matches("flr_").replace('_', " ")),
+            data_type: if is_number { DataType::Float } else { DataType::String },
+            column_category: "FlrSettings".to_string(),
+            derived_from: Some("Flr Settings".to_string()),
+        });
+    }
+    Ok(())
+}
+
+/// Number at the start of a value such as `50kHz` or `8000`
+fn leading_number(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let end = value.find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'))).unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+/// Time part of a header key, which is suffixed when several lines share a time
+fn timestamp(key: &str) -> &str {
+    key.split('#').next().unwrap_or(key)
+}
+
+pub(crate) fn is_time(text: &str) -> bool {
+    let parts: Vec<&str> = text.split(':').collect();
+    parts.len() == 3 && parts.iter().all(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::LiCor6800Fluorometer;
+
+    #[test]
+    fn test_fluorometer_settings() {
+        let settings = FluorometerSettings::parse_entries("MeasInt=1\tDarkMod=50Hz\tLightMod=250 Flash=MPF Target=8000\tRamp: 25\tOutRate=10").unwrap();
+        assert_eq!(settings.measuring_intensity, Some(1.0));
+        assert_eq!(settings.dark_modulation_rate, Some(50.0));
+        assert_eq!(settings.light_modulation_rate, Some(250.0));
+        assert_eq!(settings.flash_type, Some(FlashType::Multiphase));
+        assert_eq!(settings.flash_intensity, Some(8000.0));
+        assert_eq!(settings.mpf_ramp, Some(25.0));
+        assert_eq!(settings.other.get("OutRate").map(String::as_str), Some("10"));
+
+        // Settings at file open, sharing the stability definition's time, then
+        // a change to a rectangular flash after the second observation
+        let content = std::fs::read_to_string("../example_data/2025-05-30-1203_logdata_F2").unwrap();
+        let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let definition = lines.iter().position(|line| line.contains("Stability Definition:")).unwrap();
+        lines.insert(definition + 1, "12:04:20\tFlr Settings:\tMeasInt=1\tFlash=MPF\tTarget=8000".to_string());
+        let first = lines.iter().position(|line| line.starts_with("1\t")).unwrap();
+        lines.insert(first + 2, "12:07:00\tFlr Settings:\tMeasInt=1\tFlash=Rect\tTarget=8000".to_string());
+        let data = LiCor6800Fluorometer::new().parse_content(&lines.join("\n")).unwrap();
+
+        let settings = &data.metadata.fluorometer_settings;
+        assert_eq!(settings.len(), 2);
+        assert_eq!(settings[0].time.as_deref(), Some("12:04:20"));
+        assert_eq!((settings[1].after_row, settings[1].flash_type), (2, Some(FlashType::Rectangular)));
+        assert!(data.metadata.console_messages.is_empty());
+        let flash: Vec<_> = data.dataframe.column("flr_flash_type").unwrap().str().unwrap().into_iter().take(3).collect();
+        assert_eq!(flash, vec![Some("multiphase"), Some("multiphase"), Some("rectangular")]);
+        // Unchanged settings are not columns
+        assert!(data.dataframe.column("flr_flash_intensity").is_err());
+        assert!(data.dataframe.column("Stable").is_ok());
+    }
+}
@@ -0,0 +1,109 @@
+//! Built-in crosswalk between LI-6400 and LI-6800 variable names
+//!
+//! Only variables measured the same way and logged in the same units are
+//! paired, so harmonizing renames columns without converting values: LI-6400
+//! `Trmmol` (mmol m⁻² s⁻¹) becomes `Emm`, not `E` (mol m⁻² s⁻¹). Variables the
+//! instruments define differently, such as the LI-6400's two-sided `BLCond`,
+//! keep their own names.
+
+use crate::{ColumnRename, LiCorData, ParseError};
+use std::str::FromStr;
+
+/// Paired names: (LI-6400, LI-6800)
+pub const NAME_TABLE: &[(&str, &str)] = &[
+    ("Obs", "obs"),
+    ("HHMMSS", "hhmmss"),
+    ("FTime", "elapsed"),
+    ("Photo", "A"),
+    ("Trmmol", "Emm"),
+    ("Cond", "gsw"),
+    ("CndTotal", "gtw"),
+    ("Ci_Pa", "Pci"),
+    ("VpdL", "VPDleaf"),
+    ("Area", "S"),
+    ("CO2R", "CO2_r"),
+    ("CO2S", "CO2_s"),
+    ("H2OR", "H2O_r"),
+    ("H2OS", "H2O_s"),
+    ("RH_S", "RHcham"),
+    ("PARi", "Qin"),
+    ("PARo", "Qamb_out"),
+    ("Press", "Pa"),
+];
+
+/// Naming scheme to harmonize columns to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Harmonization {
+    /// LI-6800 names (`A`, `gsw`, `Emm`)
+    Names6800,
+    /// LI-6400 names (`Photo`, `Cond`, `Trmmol`)
+    Names6400,
+}
+
+impl FromStr for Harmonization {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "6800-names" | "6800" => Ok(Harmonization::Names6800),
+            "6400-names" | "6400" => Ok(Harmonization::Names6400),
+            _ => Err(format!("Unknown naming scheme '{}': use 6800-names or 6400-names", s)),
+        }
+    }
+}
+
+impl Harmonization {
+    /// Renames taking the other instrument's names to this scheme's
+    pub fn renames(&self) -> Vec<ColumnRename> {
+        NAME_TABLE.iter()
+            .map(|(legacy, current)| match self {
+                Harmonization::Names6800 => (legacy, current),
+                Harmonization::Names6400 => (current, legacy),
+            })
+            .map(|(from, to)| ColumnRename { from: from.to_string(), to: to.to_string() })
+            .collect()
+    }
+}
+
+impl LiCorData {
+    /// Rename the columns of the other instrument to the scheme's names
+    ///
+    /// Columns without a counterpart are kept as they are, as is a column
+    /// whose counterpart is already present.
+    pub fn harmonize(&self, harmonization: Harmonization) -> Result<LiCorData, ParseError> {
+        let renames: Vec<ColumnRename> = harmonization.renames()
+            .into_iter()
+            .filter(|rename| self.dataframe.column(&rename.from).is_ok() && self.dataframe.column(&rename.to).is_err())
+            .collect();
+        self.rename_columns(&renames)
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::LiCor6800Fluorometer;
+    use polars::prelude::*;
+
+    #[test]
+    fn test_harmonize() {
+        let data = LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        let legacy = data.harmonize(Harmonization::Names6400).unwrap();
+        let names = legacy.dataframe.get_column_names_str();
+        assert!(names.contains(&"Photo") && names.contains(&"Trmmol") && !names.contains(&"A"));
+        // E has no LI-6400 counterpart in the same units
+        assert!(names.contains(&"E"));
+        assert_eq!(legacy.dataframe.column("Photo").unwrap(), &data.dataframe.column("A").unwrap().clone().with_name("Photo".into()));
+
+        let round_trip = legacy.harmonize("6800-names".parse().unwrap()).unwrap();
+        assert_eq!(round_trip.dataframe.get_column_names(), data.dataframe.get_column_names());
+        assert!("6600-names".parse::<Harmonization>().is_err());
+
+        // A column already named for the scheme wins
+        let both = LiCorData { dataframe: df! { "A" => [1.0], "Photo" => [2.0] }.unwrap(), ..data };
+        let kept = both.harmonize(Harmonization::Names6800).unwrap();
+        assert_eq!(kept.dataframe.get_column_names_str(), vec!["A", "Photo"]);
+    }
+}
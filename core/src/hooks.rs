@@ -0,0 +1,122 @@
+//! User transforms run at fixed points of the parsing pipeline
+//!
+//! Hooks let a lab add its own cleaning steps (dropping a bad sensor's
+//! column, fixing a mislabelled header key, masking values) without forking
+//! the parser. Each stage runs its hooks in the order they were added, and
+//! an error from a hook fails the parse like any other.
+//!
+//! Hooks belong to the parse, so they see data as the parser returns it:
+//! `after_parse` runs before anything a caller does to the data afterwards
+//! (the CLI's output profiles, crosswalks, renames and unit styles). The
+//! `licor` CLI sets no hooks; they apply to library and Python callers.
+
+use crate::{LiCorData, ParseError, RawLiCorFile, VariableInfo};
+use polars::prelude::DataFrame;
+use std::fmt;
+use std::sync::Arc;
+
+/// Hook on the raw text table, before device and configuration validation
+pub type RawHook = Arc<dyn Fn(&mut RawLiCorFile) -> Result<(), ParseError> + Send + Sync>;
+/// Hook on the typed columns, before clock correction, row selection and
+/// derived columns
+pub type TypedHook = Arc<dyn Fn(&mut DataFrame, &mut Vec<VariableInfo>) -> Result<(), ParseError> + Send + Sync>;
+/// Hook on the parsed data, before the content hash is taken and the data is
+/// returned
+pub type ParsedHook = Arc<dyn Fn(&mut LiCorData) -> Result<(), ParseError> + Send + Sync>;
+
+/// Hooks for each stage, set as [`ParseOptions::hooks`](crate::ParseOptions::hooks)
+///
+/// ```
+/// use licor_core::{ParseError, ParseHooks};
+///
+/// let hooks = ParseHooks::default()
+///     .after_typing(|df, variable_info| {
+///         // The second leaf thermocouple was broken all season
+///         let _ = df.drop_in_place("TleafCnd2");
+///         variable_info.retain(|v| v.internal_name != "TleafCnd2");
+///         Ok::<(), ParseError>(())
+///     });
+/// ```
+#[derive(Clone, Default)]
+pub struct ParseHooks {
+    pub after_raw: Vec<RawHook>,
+    pub after_typing: Vec<TypedHook>,
+    pub after_parse: Vec<ParsedHook>,
+}
+
+impl ParseHooks {
+    /// Add a hook on the raw text table
+    pub fn after_raw(mut self, hook: impl Fn(&mut RawLiCorFile) -> Result<(), ParseError> + Send + Sync + 'static) -> Self {
+        self.after_raw.push(Arc::new(hook));
+        self
+    }
+
+    /// Add a hook on the typed columns
+    pub fn after_typing(mut self, hook: impl Fn(&mut DataFrame, &mut Vec<VariableInfo>) -> Result<(), ParseError> + Send + Sync + 'static) -> Self {
+        self.after_typing.push(Arc::new(hook));
+        self
+    }
+
+    /// Add a hook on the parsed data
+    pub fn after_parse(mut self, hook: impl Fn(&mut LiCorData) -> Result<(), ParseError> + Send + Sync + 'static) -> Self {
+        self.after_parse.push(Arc::new(hook));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.after_raw.is_empty() && self.after_typing.is_empty() && self.after_parse.is_empty()
+    }
+}
+
+impl fmt::Debug for ParseHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParseHooks")
+            .field("after_raw", &self.after_raw.len())
+            .field("after_typing", &self.after_typing.len())
+            .field("after_parse", &self.after_parse.len())
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::{LiCor6800Fluorometer, ParseOptions};
+
+    #[test]
+    fn test_parse_hooks() {
+        let hooks = ParseHooks::default()
+            .after_raw(|raw| {
+                raw.header.insert("Operator".to_string(), "RL".to_string());
+                Ok(())
+            })
+            .after_typing(|df, variable_info| {
+                df.drop_in_place("Fan").map_err(|e| ParseError::InvalidHeaderFormat { message: e.to_string() })?;
+                variable_info.retain(|v| v.internal_name != "Fan");
+                Ok(())
+            })
+            .after_parse(|data| {
+                data.metadata.chamber_type = Some("6800-01A".to_string());
+                Ok(())
+            });
+        let options = ParseOptions {
+            header_columns: vec!["Operator".to_string()],
+            hooks,
+            ..ParseOptions::default()
+        };
+        let path = "../example_data/2025-05-30-1203_logdata_F2";
+        let plain = LiCor6800Fluorometer::new().parse_file(path).unwrap();
+        let data = LiCor6800Fluorometer::with_options(options).parse_file(path).unwrap();
+        assert_eq!(data.dataframe.column("Operator").unwrap().str().unwrap().get(0), Some("RL"));
+        assert!(plain.dataframe.column("Fan").is_ok() && data.dataframe.column("Fan").is_err());
+        assert_eq!(data.variable_info.len(), data.dataframe.width());
+        assert_eq!(data.metadata.chamber_type.as_deref(), Some("6800-01A"));
+        assert_eq!(data.metadata.content_hash, Some(crate::content_hash(&data.dataframe).unwrap()));
+
+        let failing = ParseOptions {
+            hooks: ParseHooks::default().after_raw(|_| Err(ParseError::InvalidHeaderFormat { message: "rejected".to_string() })),
+            ..ParseOptions::default()
+        };
+        assert!(LiCor6800Fluorometer::with_options(failing).parse_file(path).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Translations of user-facing error and status messages
+//!
+//! English is the default. The CLI takes a `--lang` flag; the CLI and the
+//! language bindings also honour the `LICOR_LANG` environment variable
+//! (`en`, `es`, `pt`). Only errors and the CLI's convert summary are
+//! translated; warnings, reports and listings are English.
+
+use crate::ParseError;
+use std::str::FromStr;
+
+/// Environment variable selecting the message language
+pub const LANGUAGE_ENV: &str = "LICOR_LANG";
+
+/// Language used for user-facing messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+    Portuguese,
+}
+
+impl Language {
+    /// Language requested through `LICOR_LANG`, or English
+    pub fn from_env() -> Self {
+        std::env::var(LANGUAGE_ENV).ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    /// Accepts language codes and locales such as `es`, `pt_BR` or `pt-BR.UTF-8`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim().split(['_', '-', '.']).next().unwrap_or("").to_ascii_lowercase();
+        match code.as_str() {
+            "en" | "english" => Ok(Language::English),
+            "es" | "spanish" | "español" => Ok(Language::Spanish),
+            "pt" | "portuguese" | "português" => Ok(Language::Portuguese),
+            _ => Err(format!("Unsupported language '{}': use en, es or pt", s)),
+        }
+    }
+}
+
+impl ParseError {
+    /// Error message in the given language
+    pub fn localized(&self, language: Language) -> String {
+        use Language::*;
+        match (self, language) {
+            (_, English) => self.to_string(),
+            (ParseError::InvalidFileFormat { device }, Spanish) =>
+                format!("Formato de archivo no válido para el dispositivo {}", device),
+            (ParseError::InvalidFileFormat { device }, Portuguese) =>
+                format!("Formato de arquivo inválido para o dispositivo {}", device),
+            (ParseError::MissingRequiredHeader { field }, Spanish) =>
+                format!("Falta un campo obligatorio del encabezado: {}", field),
+            (ParseError::MissingRequiredHeader { field }, Portuguese) =>
+                format!("Campo obrigatório ausente no cabeçalho: {}", field),
+            (ParseError::UnknownVariable { variable }, Spanish) =>
+                format!("Variable desconocida: {}", variable),
+            (ParseError::UnknownVariable { variable }, Portuguese) =>
+                format!("Variável desconhecida: {}", variable),
+            (ParseError::MissingRequiredVariable { variable, config }, Spanish) =>
+                format!("Falta la variable obligatoria '{}' para la configuración '{}'", variable, config),
+            (ParseError::MissingRequiredVariable { variable, config }, Portuguese) =>
+                format!("Variável obrigatória '{}' ausente para a configuração '{}'", variable, config),
+            (ParseError::MalformedDataSection { expected, found }, Spanish) =>
+                format!("Sección de datos mal formada: se esperaban {} columnas y se encontraron {}", expected, found),
+            (ParseError::MalformedDataSection { expected, found }, Portuguese) =>
+                format!("Seção de dados malformada: esperadas {} colunas, encontradas {}", expected, found),
+            (ParseError::DataTypeError { value, expected_type, variable }, Spanish) =>
+                format!("Error de tipo de datos en la variable '{}': no se puede convertir '{}' a {}", variable, value, expected_type),
+            (ParseError::DataTypeError { value, expected_type, variable }, Portuguese) =>
+                format!("Erro de tipo de dados na variável '{}': não é possível converter '{}' para {}", variable, value, expected_type),
+            (ParseError::Io(e), Spanish) => format!("Error de entrada/salida: {}", e),
+            (ParseError::Io(e), Portuguese) => format!("Erro de entrada/saída: {}", e),
+            (ParseError::TomlParse(e), Spanish) => format!("Error al leer el TOML: {}", e),
+            (ParseError::TomlParse(e), Portuguese) => format!("Erro ao ler o TOML: {}", e),
+            (ParseError::InvalidHeaderFormat { message }, Spanish) =>
+                format!("Formato de encabezado no válido: {}", message),
+            (ParseError::InvalidHeaderFormat { message }, Portuguese) =>
+                format!("Formato de cabeçalho inválido: {}", message),
+            (ParseError::EmptyDataSection, Spanish) => "Sección de datos vacía o no válida".to_string(),
+            (ParseError::EmptyDataSection, Portuguese) => "Seção de dados vazia ou inválida".to_string(),
+            (ParseError::WriteError { format, message }, Spanish) =>
+                format!("No se pudo escribir la salida {}: {}", format, message),
+            (ParseError::WriteError { format, message }, Portuguese) =>
+                format!("Falha ao gravar a saída {}: {}", format, message),
+        }
+    }
+}
+
+/// Status and warning messages shown by the CLI and bindings
+#[derive(Debug, Clone, Copy)]
+pub enum Message<'a> {
+    FileNotFound { path: &'a str },
+    NoFilesFound { pattern: &'a str },
+    ConversionFailed { file: &'a str, error: &'a str },
+    SkippingSheet { sheet: &'a str, error: &'a str },
+    ConversionComplete,
+    SucceededCount(usize),
+    FailedCount(usize),
+    FailedConversions,
+    BatchAnomalies(usize),
+    SkippedCount(usize),
+    ReplacedOutputs(usize),
+    /// Total input and output sizes, already formatted, and the ratio of input
+    /// to output bytes
+    BatchSizes { input: &'a str, output: &'a str, ratio: f64, estimated: bool },
+    DryRun,
+}
+
+impl Message<'_> {
+    /// Message text in the given language
+    pub fn text(&self, language: Language) -> String {
+        use Language::*;
+        match (*self, language) {
+            (Message::FileNotFound { path }, English) => format!("File not found: {}", path),
+            (Message::FileNotFound { path }, Spanish) => format!("No se encontró el archivo: {}", path),
+            (Message::FileNotFound { path }, Portuguese) => format!("Arquivo não encontrado: {}", path),
+            (Message::NoFilesFound { pattern }, English) => format!("No files found matching pattern: {}", pattern),
+            (Message::NoFilesFound { pattern }, Spanish) => format!("Ningún archivo coincide con el patrón: {}", pattern),
+            (Message::NoFilesFound { pattern }, Portuguese) => format!("Nenhum arquivo corresponde ao padrão: {}", pattern),
+            (Message::ConversionFailed { file, error }, English) => format!("Error converting {}: {}", file, error),
+            (Message::ConversionFailed { file, error }, Spanish) => format!("Error al convertir {}: {}", file, error),
+            (Message::ConversionFailed { file, error }, Portuguese) => format!("Erro ao converter {}: {}", file, error),
+            (Message::SkippingSheet { sheet, error }, English) => format!("Skipping sheet '{}': {}", sheet, error),
+            (Message::SkippingSheet { sheet, error }, Spanish) => format!("Se omite la hoja '{}': {}", sheet, error),
+            (Message::SkippingSheet { sheet, error }, Portuguese) => format!("Ignorando a planilha '{}': {}", sheet, error),
+            (Message::BatchSizes { input, output, ratio, estimated }, English) =>
+                format!("{}: {} in, {} out, compression ratio {:.2}", if estimated { "Estimated size" } else { "Size" }, input, output, ratio),
+            (Message::BatchSizes { input, output, ratio, estimated }, Spanish) =>
+                format!("{}: {} de entrada, {} de salida, tasa de compresión {:.2}", if estimated { "Tamaño estimado" } else { "Tamaño" }, input, output, ratio),
+            (Message::BatchSizes { input, output, ratio, estimated }, Portuguese) =>
+                format!("{}: {} de entrada, {} de saída, taxa de compressão {:.2}", if estimated { "Tamanho estimado" } else { "Tamanho" }, input, output, ratio),
+            (Message::DryRun, English) => "Dry run: no output was written".to_string(),
+            (Message::DryRun, Spanish) => "Simulación: no se escribió ninguna salida".to_string(),
+            (Message::DryRun, Portuguese) => "Simulação: nenhuma saída foi gravada".to_string(),
+            (Message::ConversionComplete, English) => "Conversion complete:".to_string(),
+            (Message::ConversionComplete, Spanish) => "Conversión terminada:".to_string(),
+            (Message::ConversionComplete, Portuguese) => "Conversão concluída:".to_string(),
+            (Message::SucceededCount(n), English) => format!("Successfully converted: {}", n),
+            (Message::SucceededCount(n), Spanish) => format!("Convertidos correctamente: {}", n),
+            (Message::SucceededCount(n), Portuguese) => format!("Convertidos com sucesso: {}", n),
+            (Message::FailedCount(n), English) => format!("Failed: {}", n),
+            (Message::FailedCount(n), Spanish) => format!("Con errores: {}", n),
+            (Message::FailedCount(n), Portuguese) => format!("Com falha: {}", n),
+            (Message::FailedConversions, English) => "Failed conversions:".to_string(),
+            (Message::FailedConversions, Spanish) => "Conversiones con errores:".to_string(),
+            (Message::FailedConversions, Portuguese) => "Conversões com falha:".to_string(),
+            (Message::BatchAnomalies(n), English) => format!("Files deviating from the batch: {}", n),
+            (Message::BatchAnomalies(n), Spanish) => format!("Archivos que se apartan del lote: {}", n),
+            (Message::BatchAnomalies(n), Portuguese) => format!("Arquivos que destoam do lote: {}", n),
+            (Message::SkippedCount(n), English) => format!("Skipped (output exists): {}", n),
+            (Message::SkippedCount(n), Spanish) => format!("Omitidos (la salida ya existe): {}", n),
+            (Message::SkippedCount(n), Portuguese) => format!("Ignorados (a saída já existe): {}", n),
+            (Message::ReplacedOutputs(n), English) => format!("Replaced {} existing output file(s); use --skip-existing or --fail-if-exists to keep them", n),
+            (Message::ReplacedOutputs(n), Spanish) => format!("Se reemplazaron {} archivo(s) de salida existentes; use --skip-existing o --fail-if-exists para conservarlos", n),
+            (Message::ReplacedOutputs(n), Portuguese) => format!("{} arquivo(s) de saída existentes foram substituídos; use --skip-existing ou --fail-if-exists para mantê-los", n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_language() {
+        assert_eq!("es".parse(), Ok(Language::Spanish));
+        assert_eq!("pt_BR.UTF-8".parse(), Ok(Language::Portuguese));
+        assert_eq!("EN".parse(), Ok(Language::English));
+        assert!("de".parse::<Language>().is_err());
+    }
+
+    #[test]
+    fn test_localized_errors() {
+        let error = ParseError::MissingRequiredVariable {
+            variable: "A".to_string(),
+            config: "standard".to_string(),
+        };
+        assert_eq!(error.localized(Language::English), error.to_string());
+        assert_eq!(
+            error.localized(Language::Spanish),
+            "Falta la variable obligatoria 'A' para la configuración 'standard'"
+        );
+        assert_eq!(
+            Message::FailedCount(2).text(Language::Portuguese),
+            "Com falha: 2"
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! Catalog of LI-COR files for finding measurements across a directory tree
+//!
+//! Each file is scanned without building typed columns: the header and column
+//! names come from [`peek`](crate::peek), and text logs are then streamed to
+//! count observations and read the first and last timestamps.
+
+use crate::{ConfigAquatic, ConfigFluorometer, ConfigSoil, ConfigStandard, LiCorConfig, ParseError, RawLiCorFile};
+use polars::prelude::*;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// One row of the catalog
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexEntry {
+    pub path: String,
+    /// `LI-6800` or `LI-6400`, judged from the serial number field
+    pub device: String,
+    pub serial: String,
+    /// Configuration with the fewest missing variables
+    pub config: String,
+    /// First and last observation, as `YYYY-MM-DD HH:MM:SS`
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub n_obs: usize,
+}
+
+/// Scan one file for the catalog
+pub fn index_file(path: &str) -> Result<IndexEntry, ParseError> {
+    let is_workbook = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("xls") || e.eq_ignore_ascii_case("xlsx"));
+    let (raw_file, n_obs, start, end) = if is_workbook {
+        // Workbooks are read whole anyway
+        let raw_file = crate::peek(path, usize::MAX)?;
+        let dates = raw_file.column_names.iter()
+            .position(|name| name == "date")
+            .map(|index| raw_file.data_columns[index].clone())
+            .unwrap_or_default();
+        let n_obs = raw_file.num_rows();
+        (raw_file, n_obs, dates.first().cloned(), dates.last().cloned())
+    } else {
+        let raw_file = crate::peek(path, 0)?;
+        let (n_obs, start, end) = scan_observations(path, &raw_file)?;
+        (raw_file, n_obs, start, end)
+    };
+
+    let (device, serial) = match (raw_file.header.get("Console s/n"), raw_file.header.get("Unit")) {
+        (Some(serial), _) => ("LI-6800", serial.clone()),
+        (None, Some(serial)) => ("LI-6400", serial.clone()),
+        (None, None) => return Err(ParseError::MissingRequiredHeader { field: "Console s/n".to_string() }),
+    };
+
+    Ok(IndexEntry {
+        path: path.to_string(),
+        device: device.to_string(),
+        serial,
+        config: best_config(&raw_file).to_string(),
+        start: start.as_deref().and_then(timestamp),
+        end: end.as_deref().and_then(timestamp),
+        n_obs,
+    })
+}
+
+/// The catalog as a table, one row per file
+pub fn index_dataframe(entries: &[IndexEntry]) -> Result<DataFrame, ParseError> {
+    df! {
+        "path" => entries.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(),
+        "device" => entries.iter().map(|e| e.device.as_str()).collect::<Vec<_>>(),
+        "serial" => entries.iter().map(|e| e.serial.as_str()).collect::<Vec<_>>(),
+        "config" => entries.iter().map(|e| e.config.as_str()).collect::<Vec<_>>(),
+        "start" => entries.iter().map(|e| e.start.as_deref()).collect::<Vec<_>>(),
+        "end" => entries.iter().map(|e| e.end.as_deref()).collect::<Vec<_>>(),
+        "n_obs" => entries.iter().map(|e| e.n_obs as u64).collect::<Vec<_>>(),
+    }
+    .map_err(index_error)
+}
+
+/// Write the catalog to a Parquet file
+pub fn write_index(entries: &[IndexEntry], output_path: &str) -> Result<(), ParseError> {
+    let mut df = index_dataframe(entries)?;
+    let file = std::fs::File::create(output_path)?;
+    ParquetWriter::new(file).finish(&mut df).map_err(index_error)?;
+    Ok(())
+}
+
+/// Number of observations, and the `date` of the first and last, of a text log
+fn scan_observations(path: &str, raw_file: &RawLiCorFile) -> Result<(usize, Option<String>, Option<String>), ParseError> {
+    let date_index = raw_file.column_names.iter().position(|name| name == "date");
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let (mut in_data, mut table_rows, mut n_obs) = (false, 0, 0);
+    let (mut start, mut end) = (None, None);
+    for line in reader.lines() {
+        let line = line?;
+        if !in_data {
+            in_data = line.trim() == "[Data]";
+        } else if table_rows < 3 {
+            table_rows += 1;
+        } else if line.split('\t').next().is_some_and(|obs| obs.trim().parse::<f64>().is_ok()) {
+            n_obs += 1;
+            let date = date_index.and_then(|index| line.split('\t').nth(index)).map(str::to_string);
+            if start.is_none() {
+                start = date.clone();
+            }
+            end = date;
+        }
+    }
+    Ok((n_obs, start, end))
+}
+
+/// `YYYYMMDD HH:MM:SS` as `YYYY-MM-DD HH:MM:SS`
+fn timestamp(value: &str) -> Option<String> {
+    let (date, time) = value.trim().split_once(' ').unwrap_or((value.trim(), ""));
+    let digits: String = date.chars().filter(|c| *c != '-').collect();
+    if digits.len() != 8 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let date = format!("{}-{}-{}", &digits[..4], &digits[4..6], &digits[6..]);
+    Some(if time.is_empty() { date } else { format!("{} {}", date, time.trim()) })
+}
+
+/// Configuration with the fewest missing variables; the first listed wins
+/// ties, so fluorometer logs are not reported as standard
+fn best_config(raw_file: &RawLiCorFile) -> &'static str {
+    #[cfg(feature = "device-6400")]
+    let columns: Vec<String> = raw_file.column_names.iter()
+        .map(|name| <crate::Device6400 as crate::LiCorDevice>::canonical_column_name(name).to_string())
+        .collect();
+    #[cfg(not(feature = "device-6400"))]
+    let columns = raw_file.column_names.clone();
+    [
+        (ConfigFluorometer::CONFIG_NAME, ConfigFluorometer::missing_variables(&columns).len()),
+        (ConfigStandard::CONFIG_NAME, ConfigStandard::missing_variables(&columns).len()),
+        (ConfigAquatic::CONFIG_NAME, ConfigAquatic::missing_variables(&columns).len()),
+        (ConfigSoil::CONFIG_NAME, ConfigSoil::missing_variables(&columns).len()),
+    ]
+    .into_iter()
+    .min_by_key(|(_, missing)| *missing)
+    .map(|(name, _)| name)
+    .unwrap_or(ConfigStandard::CONFIG_NAME)
+}
+
+fn index_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to build the file index: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_file() {
+        let path = "../example_data/2025-05-30-1203_logdata_F2";
+        let full = RawLiCorFile::parse(&std::fs::read_to_string(path).unwrap()).unwrap();
+        let entry = index_file(path).unwrap();
+        assert_eq!(entry.device, "LI-6800");
+        assert_eq!(entry.serial, "68C-901292");
+        assert_eq!(entry.config, "fluorometer");
+        assert_eq!(entry.n_obs, full.num_rows());
+        assert_eq!(entry.start.as_deref(), Some("2025-05-29 12:09:24"));
+        assert!(entry.end > entry.start);
+
+        let df = index_dataframe(&[entry]).unwrap();
+        assert_eq!(df.get_column_names_str(), ["path", "device", "serial", "config", "start", "end", "n_obs"]);
+        assert!(index_file("../README.md").is_err());
+    }
+}
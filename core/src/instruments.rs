@@ -0,0 +1,267 @@
+//! Per-instrument defaults applied by console serial
+//!
+//! A lab with several consoles keeps one TOML file of `["<serial>"]` tables,
+//! and every file logged by a listed console is parsed with its defaults:
+//!
+//! ```toml
+//! ["68C-901292"]
+//! leaf_area = 2.0          # cm², replaces the logged S
+//! operator = "R. Leff"     # added as an `operator` column
+//! timezone = "-04:00"      # UTC offset the console clock is set to
+//! variables = { Oxygen = 2.0 }
+//! certificates = [
+//!     { uri = "https://lims.example.org/cal/2025-117.pdf", date = "2025-03-01", expires = "2026-03-01" },
+//! ]
+//!
+//! ["68H-581292"]           # a sensor head, wherever it is mounted
+//! certificates = [{ uri = "//labshare/cal/68H-581292.pdf", date = "2025-02-14" }]
+//! ```
+//!
+//! Certificates listed under the serial of the head, chamber or fluorometer
+//! logged in a file are recorded with those of its console.
+
+use crate::{DataType, ParseError, VariableDef, VariableInfo};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Defaults for one console
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InstrumentProfile {
+    /// Leaf area in the chamber (cm²), replacing the logged `S`
+    pub leaf_area: Option<f64>,
+    /// Person operating the console, added as an `operator` column
+    pub operator: Option<String>,
+    /// UTC offset the console clock is set to (`+02:00`, `UTC-5`, `Z`); the
+    /// `time` column is converted to UTC unless clock anchors are given
+    pub timezone: Option<String>,
+    /// Constant values replacing logged columns, or added when not logged
+    #[serde(default)]
+    pub variables: BTreeMap<String, f64>,
+    /// Calibration certificates of the instrument, recorded in the metadata
+    /// of every file it logged
+    #[serde(default)]
+    pub certificates: Vec<CalibrationCertificate>,
+}
+
+/// Reference to a calibration certificate kept outside the data, for
+/// traceability audits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CalibrationCertificate {
+    /// Serial of the certified instrument; the profile's serial when not given
+    #[serde(default)]
+    pub serial: Option<String>,
+    /// Path or URI of the certificate
+    pub uri: String,
+    /// Date of the calibration
+    #[serde(default)]
+    pub date: Option<String>,
+    /// Date the calibration is due again
+    #[serde(default)]
+    pub expires: Option<String>,
+}
+
+impl CalibrationCertificate {
+    /// One-line description, e.g. `68C-901292: cal/2025-117.pdf (2025-03-01, expires 2026-03-01)`
+    pub fn describe(&self) -> String {
+        let dates: Vec<String> = self.date.iter().cloned()
+            .chain(self.expires.iter().map(|expires| format!("expires {}", expires)))
+            .collect();
+        format!(
+            "{}{}{}",
+            self.serial.as_ref().map(|serial| format!("{}: ", serial)).unwrap_or_default(),
+            self.uri,
+            if dates.is_empty() { String::new() } else { format!(" ({})", dates.join(", ")) }
+        )
+    }
+}
+
+impl InstrumentProfile {
+    /// Offset of `timezone` east of UTC, in seconds
+    pub fn utc_offset(&self) -> Result<Option<i64>, ParseError> {
+        self.timezone.as_deref()
+            .map(|zone| parse_utc_offset(zone).ok_or_else(|| ParseError::InvalidHeaderFormat {
+                message: format!("Invalid timezone '{}': use a UTC offset such as +02:00 or UTC-5", zone)
+            }))
+            .transpose()
+    }
+}
+
+/// Instrument profiles by console serial, read from a TOML file
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct InstrumentProfiles {
+    pub instruments: HashMap<String, InstrumentProfile>,
+}
+
+impl InstrumentProfiles {
+    pub fn from_file(path: &str) -> Result<Self, ParseError> {
+        Self::from_toml_str(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self, ParseError> {
+        let profiles: Self = toml::from_str(content)?;
+        for profile in profiles.instruments.values() {
+            profile.utc_offset()?;
+        }
+        Ok(profiles)
+    }
+
+    /// Profile of a console serial, if listed
+    pub fn get(&self, serial: &str) -> Option<&InstrumentProfile> {
+        self.instruments.get(serial.trim())
+    }
+
+    /// Certificates of the instruments that logged a file: its console, then
+    /// its head, chamber and fluorometer, each with its serial set
+    pub fn certificates(&self, metadata: &crate::LiCorMetadata) -> Vec<CalibrationCertificate> {
+        let serials = [
+            Some(&metadata.device_serial),
+            metadata.head_serial.as_ref(),
+            metadata.chamber_serial.as_ref(),
+            metadata.fluorometer_serial.as_ref(),
+        ];
+        serials.into_iter().flatten()
+            .filter_map(|serial| Some((serial.trim(), self.get(serial)?)))
+            .flat_map(|(serial, profile)| profile.certificates.iter().map(move |certificate| CalibrationCertificate {
+                serial: certificate.serial.clone().or_else(|| Some(serial.to_string())),
+                ..certificate.clone()
+            }))
+            .collect()
+    }
+}
+
+/// Replace or add the profile's variables (the leaf area as `S`) and add its
+/// `operator` column
+pub(crate) fn apply_profile_columns(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    profile: &InstrumentProfile,
+) -> Result<(), ParseError> {
+    let height = df.height();
+    let mut variables = profile.variables.clone();
+    if let Some(area) = profile.leaf_area {
+        variables.entry("S".to_string()).or_insert(area);
+    }
+    for (name, value) in variables {
+        df.with_column(Column::new(name.as_str().into(), vec![value; height])).map_err(profile_error)?;
+        match variable_info.iter_mut().find(|v| v.internal_name == name) {
+            Some(info) => {
+                info.description = format!("{} (set by the instrument profile)", info.description);
+                info.data_type = DataType::Float;
+            }
+            None => {
+                let def = VariableDef::lookup(&name, "");
+                variable_info.push(VariableInfo {
+                    internal_name: name.clone(),
+                    display_label: def.map_or(name.as_str(), |def| def.display_label).to_string(),
+                    units: def.and_then(|def| def.units).map(str::to_string),
+                    units_pretty: def.and_then(|def| def.units).map(crate::pretty_units),
+                    units_ascii: def.and_then(|def| def.units).map(crate::ascii_units),
+                    description: format!("{} (set by the instrument profile)", def.map_or(name.as_str(), |def| def.description)),
+                    data_type: DataType::Float,
+                    column_category: "Profile".to_string(),
+                    derived_from: None,
+                });
+            }
+        }
+    }
+    if let Some(operator) = &profile.operator {
+        df.with_column(Column::new("operator".into(), vec![operator.as_str(); height])).map_err(profile_error)?;
+        variable_info.push(VariableInfo {
+            internal_name: "operator".to_string(),
+            display_label: "Operator".to_string(),
+            units: None,
+            units_pretty: None,
+            units_ascii: None,
+            description: "Console operator, from the instrument profile".to_string(),
+            data_type: DataType::String,
+            column_category: "Profile".to_string(),
+            derived_from: None,
+        });
+    }
+    Ok(())
+}
+
+/// Seconds east of UTC of `+HH:MM`, `-HH`, `UTC+2`, `GMT-05:30` or `Z`
+fn parse_utc_offset(zone: &str) -> Option<i64> {
+    let zone = zone.trim();
+    let offset = zone.strip_prefix("UTC").or_else(|| zone.strip_prefix("GMT")).unwrap_or(zone);
+    if offset.is_empty() || offset == "Z" {
+        return Some(0);
+    }
+    let sign = match offset.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+    let (hours, minutes) = (hours.parse::<i64>().ok()?, minutes.parse::<i64>().ok()?);
+    (hours <= 14 && minutes < 60).then_some(sign * (hours * 3600 + minutes * 60))
+}
+
+fn profile_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to apply instrument profile: {}", e)
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::{LiCor6800Fluorometer, ParseOptions};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_instrument_profiles() {
+        assert_eq!(parse_utc_offset("-04:00"), Some(-14_400));
+        assert_eq!(parse_utc_offset("UTC+5:30"), Some(19_800));
+        assert_eq!(parse_utc_offset("Z"), Some(0));
+        assert_eq!(parse_utc_offset("America/New_York"), None);
+        assert!(InstrumentProfiles::from_toml_str("[\"68C-1\"]\ntimezone = \"EST\"").is_err());
+        assert!(InstrumentProfiles::from_toml_str("[\"68C-1\"]\narea = 2.0").is_err());
+        assert!(InstrumentProfiles::from_toml_str("[\"68C-1\"]\ncertificates = [{ date = \"2025-03-01\" }]").is_err());
+
+        let profiles = InstrumentProfiles::from_toml_str(r#"
+            ["68C-901292"]
+            leaf_area = 2.0
+            operator = "R. Leff"
+            timezone = "-04:00"
+            variables = { Oxygen = 2.0 }
+            certificates = [{ uri = "cal/2025-117.pdf", date = "2025-03-01", expires = "2026-03-01" }]
+
+            ["68C-000000"]
+            leaf_area = 9.0
+            certificates = [{ uri = "cal/other.pdf" }]
+        "#).unwrap();
+        let parse = |profiles: Option<InstrumentProfiles>| {
+            let options = ParseOptions { instrument_profiles: profiles.map(Arc::new), ..ParseOptions::default() };
+            LiCor6800Fluorometer::with_options(options).parse_file("../example_data/2025-05-30-1203_logdata_F2").unwrap()
+        };
+        let plain = parse(None);
+        let data = parse(Some(profiles));
+        let df = &data.dataframe;
+        assert!(df.column("S").unwrap().f64().unwrap().into_no_null_iter().all(|s| s == 2.0));
+        assert_eq!(df.column("Oxygen").unwrap().f64().unwrap().get(0), Some(2.0));
+        assert_eq!(df.column("operator").unwrap().str().unwrap().get(0), Some("R. Leff"));
+        let time = |df: &DataFrame, name: &str| {
+            let column = df.column(name).unwrap().cast(&polars::datatypes::DataType::Float64).unwrap();
+            column.f64().unwrap().get(0).unwrap()
+        };
+        assert_eq!(time(df, "time") - time(&plain.dataframe, "time"), 14_400.0);
+        assert_eq!(time(df, "time_instrument"), time(&plain.dataframe, "time"));
+        assert_eq!(data.metadata.clock_correction.unwrap().offset, 14_400.0);
+        assert_eq!(data.metadata.calibration_certificates.len(), 1);
+        assert_eq!(
+            data.metadata.calibration_certificates[0].describe(),
+            "68C-901292: cal/2025-117.pdf (2025-03-01, expires 2026-03-01)"
+        );
+        assert!(plain.metadata.calibration_certificates.is_empty());
+
+        // Consoles without a profile parse as usual
+        let other = InstrumentProfiles::from_toml_str("[\"68C-000000\"]\noperator = \"A\"").unwrap();
+        assert!(parse(Some(other)).dataframe.equals_missing(&plain.dataframe));
+    }
+}
@@ -0,0 +1,178 @@
+//! Fluorometer flash traces joined onto gas-exchange observations
+//!
+//! The LI-6800 logs each flash (rectangular or multiphase) as a trace of
+//! fluorescence against time, separately from the observation it belongs to.
+//! [`align_flash_traces`] summarises each trace and attaches the summary to
+//! the observation logged closest to the flash.
+
+use crate::{DataType, ParseError, VariableInfo};
+use polars::prelude::*;
+
+/// Columns added by [`align_flash_traces`]: name, units, description
+const SUMMARY_COLUMNS: [(&str, Option<&str>, &str); 4] = [
+    ("flash_max", None, "Maximum fluorescence of the flash trace"),
+    ("flash_area", Some("s"), "Area under the flash trace (fluorescence x seconds)"),
+    ("flash_fit_rmse", None, "Root mean square error of the fit to the flash trace"),
+    ("flash_offset", Some("s"), "Start of the flash trace relative to the observation time"),
+];
+
+/// One flash trace
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlashTrace {
+    /// Start of the flash, in seconds since the epoch like the `time` column
+    pub time: f64,
+    /// Seconds since the start of the flash, one per sample
+    pub seconds: Vec<f64>,
+    /// Fluorescence, one per sample
+    pub signal: Vec<f64>,
+    /// Fitted fluorescence, one per sample, when the flash was fitted
+    pub fitted: Option<Vec<f64>>,
+}
+
+/// Summary statistics of a flash trace
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceSummary {
+    pub max: Option<f64>,
+    /// Trapezoidal area under the signal
+    pub area: Option<f64>,
+    pub fit_rmse: Option<f64>,
+}
+
+impl FlashTrace {
+    /// Summary of the finite samples; `fit_rmse` is `None` without a fit
+    pub fn summary(&self) -> TraceSummary {
+        let samples: Vec<(f64, f64)> = self.seconds.iter().zip(&self.signal)
+            .map(|(t, f)| (*t, *f))
+            .filter(|(t, f)| t.is_finite() && f.is_finite())
+            .collect();
+        let max = samples.iter().map(|(_, f)| *f).reduce(f64::max);
+        let area = (samples.len() > 1).then(|| samples.windows(2)
+            .map(|pair| (pair[1].0 - pair[0].0) * (pair[0].1 + pair[1].1) / 2.0)
+            .sum());
+
+        let fit_rmse = self.fitted.as_ref().and_then(|fitted| {
+            let residuals: Vec<f64> = self.signal.iter().zip(fitted)
+                .map(|(f, fit)| f - fit)
+                .filter(|r| r.is_finite())
+                .collect();
+            (!residuals.is_empty())
+                .then(|| (residuals.iter().map(|r| r * r).sum::<f64>() / residuals.len() as f64).sqrt())
+        });
+        TraceSummary { max, area, fit_rmse }
+    }
+}
+
+/// Append `flash_max`, `flash_area`, `flash_fit_rmse` and `flash_offset`
+/// columns summarising the flash trace of each observation
+///
+/// Each trace goes to the observation whose `time` is nearest its start, if
+/// within `tolerance` seconds; when several traces land on one observation
+/// the nearest is kept. Observations without a trace get nulls. Returns the
+/// number of traces that matched no observation.
+pub fn align_flash_traces(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    traces: &[FlashTrace],
+    tolerance: f64,
+) -> Result<usize, ParseError> {
+    let times: Vec<Option<f64>> = df.column("time")
+        .map_err(|_| ParseError::UnknownVariable { variable: "time".to_string() })?
+        .cast(&polars::datatypes::DataType::Float64)
+        .map_err(kinetics_error)?
+        .f64()
+        .map_err(kinetics_error)?
+        .into_iter()
+        .collect();
+
+    // Per observation: offset of the matched trace and its summary
+    let mut matched: Vec<Option<(f64, TraceSummary)>> = vec![None; times.len()];
+    let mut unmatched = 0;
+    for trace in traces {
+        let nearest = times.iter()
+            .enumerate()
+            .filter_map(|(row, time)| Some((row, trace.time - (*time)?)))
+            .filter(|(_, offset)| offset.abs() <= tolerance)
+            .min_by(|a, b| a.1.abs().total_cmp(&b.1.abs()));
+        let Some((row, offset)) = nearest else {
+            unmatched += 1;
+            continue;
+        };
+        if matched[row].is_none_or(|(current, _)| offset.abs() < current.abs()) {
+            matched[row] = Some((offset, trace.summary()));
+        }
+    }
+
+    let columns: [Vec<Option<f64>>; 4] = [
+        matched.iter().map(|m| m.and_then(|(_, s)| s.max)).collect(),
+        matched.iter().map(|m| m.and_then(|(_, s)| s.area)).collect(),
+        matched.iter().map(|m| m.and_then(|(_, s)| s.fit_rmse)).collect(),
+        matched.iter().map(|m| m.map(|(offset, _)| offset)).collect(),
+    ];
+    for ((name, units, description), values) in SUMMARY_COLUMNS.into_iter().zip(columns) {
+        df.with_column(Column::new(name.into(), values)).map_err(kinetics_error)?;
+        variable_info.retain(|v| v.internal_name != name);
+        variable_info.push(VariableInfo {
+            internal_name: name.to_string(),
+            display_label: name.to_string(),
+            units: units.map(str::to_string),
+            units_pretty: units.map(crate::pretty_units),
+            units_ascii: units.map(crate::ascii_units),
+            description: description.to_string(),
+            data_type: DataType::Float,
+            column_category: "Kinetics".to_string(),
+            derived_from: None,
+        });
+    }
+    Ok(unmatched)
+}
+
+fn kinetics_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to align flash traces: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace(time: f64, signal: &[f64], fitted: Option<Vec<f64>>) -> FlashTrace {
+        FlashTrace {
+            time,
+            seconds: (0..signal.len()).map(|i| i as f64 * 0.5).collect(),
+            signal: signal.to_vec(),
+            fitted,
+        }
+    }
+
+    #[test]
+    fn test_align_flash_traces() {
+        let summary = trace(0.0, &[1.0, 3.0, 2.0], Some(vec![1.0, 2.0, 2.0])).summary();
+        assert_eq!(summary.max, Some(3.0));
+        assert_eq!(summary.area, Some(2.25));
+        assert!((summary.fit_rmse.unwrap() - (1.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(trace(0.0, &[1.0], None).summary().area, None);
+
+        let mut df = df! { "time" => [100.0, 200.0, 300.0] }.unwrap();
+        let mut info = Vec::new();
+        let traces = [
+            trace(104.0, &[5.0, 6.0], None),
+            trace(101.0, &[7.0, 8.0], None),
+            trace(298.0, &[2.0, 1.0], Some(vec![2.0, 1.0])),
+            trace(250.0, &[9.0, 9.0], None),
+        ];
+        let unmatched = align_flash_traces(&mut df, &mut info, &traces, 10.0).unwrap();
+        assert_eq!(unmatched, 1);
+
+        let max: Vec<_> = df.column("flash_max").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(max, [Some(8.0), None, Some(2.0)]);
+        let offset: Vec<_> = df.column("flash_offset").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(offset, [Some(1.0), None, Some(-2.0)]);
+        let rmse: Vec<_> = df.column("flash_fit_rmse").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(rmse, [None, None, Some(0.0)]);
+        assert_eq!(info.len(), 4);
+
+        let mut no_time = df! { "obs" => [1] }.unwrap();
+        assert!(align_flash_traces(&mut no_time, &mut info, &traces, 10.0).is_err());
+    }
+}
@@ -1,19 +1,107 @@
 pub mod errors;
+pub mod anomaly;
+pub mod cache;
+pub mod catalog;
+pub mod clock;
+pub mod column_map;
+pub mod column_order;
+pub mod i18n;
+pub mod index;
+pub mod instruments;
+pub mod kinetics;
 pub mod macros;
 pub mod devices;
+pub mod derived;
+pub mod diff;
+pub mod doctor;
+pub mod fluorometer;
+pub mod repair;
+pub mod harmonize;
+pub mod hooks;
 pub mod configs;
+pub mod content_hash;
+pub mod crosswalk;
+pub mod curves;
+pub mod merge;
+pub mod migrate;
+pub mod parse_rules;
 pub mod parsing;
 pub mod parser;
+pub mod peek;
+pub mod plot;
+pub mod profiles;
+pub mod qc;
+pub mod recompute;
+pub mod remarks;
+pub mod reshape;
+pub mod row_order;
+pub mod schema;
+pub mod search;
+pub mod smoothing;
+pub mod stability;
+pub mod step_summary;
+pub mod summary;
+pub mod units;
+pub mod validate;
+pub mod writers;
+#[cfg(feature = "device-6400")]
+pub mod xls;
 
-pub use errors::ParseError;
+pub use errors::{ERROR_CODES, ParseError};
+pub use anomaly::{FileMeans, BatchAnomaly, ANOMALY_METRICS, DEFAULT_ANOMALY_THRESHOLD, batch_anomalies};
+pub use cache::{CacheKey, ParseCache};
+pub use catalog::{CatalogItem, catalog_item, catalog_json};
+pub use clock::{ClockAnchor, ClockCorrection, apply_clock_correction};
+pub use column_map::{ColumnCrosswalk, ColumnMapping, ColumnRename, unit_conversion};
+pub use column_order::ColumnOrder;
+pub use i18n::{Language, Message};
+pub use index::{IndexEntry, index_file, index_dataframe, write_index};
+pub use instruments::{CalibrationCertificate, InstrumentProfile, InstrumentProfiles};
+pub use kinetics::{FlashTrace, TraceSummary, align_flash_traces};
 pub use macros::{VariableDef, DataType, parse_licor_toml};
-pub use devices::{LiCorDevice, LiCorMetadata, Device6800, Device6400};
+pub use devices::{LiCorDevice, LiCorMetadata};
+#[cfg(feature = "device-6800")]
+pub use devices::Device6800;
+#[cfg(feature = "device-6400")]
+pub use devices::Device6400;
 pub use configs::{LiCorConfig, ConfigStandard, ConfigFluorometer, ConfigAquatic, ConfigSoil};
-pub use parsing::RawLiCorFile;
-pub use parser::{
-    LiCorParser, LiCorData, VariableInfo,
-    LiCor6800Standard, LiCor6800Fluorometer, LiCor6800Aquatic, LiCor6800Soil
-};
+pub use parse_rules::{ParseRule, BitDecoding, BitDef, DecodeTable};
+pub use parsing::{RawLiCorFile, ConsoleMessage};
+pub use crosswalk::{MetadataStandard, SiteInfo, write_metadata_crosswalk};
+pub use content_hash::content_hash;
+pub use curves::{Curve, CurveType, Group, split_curves, split_groups, group_file_stem, classify_curve};
+pub use derived::{DerivedVariable, USER_DEFINED_UNITS, add_derived_variables};
+pub use doctor::{Finding, Severity, diagnose};
+pub use fluorometer::{FlashType, FluorometerSettings};
+pub use repair::{Repair, repair};
+pub use diff::{FileDiff, HeaderDifference, UnitsDifference, diff_files, OutputDiff, TypeDifference, ValueDifference, diff_outputs};
+pub use harmonize::{Harmonization, NAME_TABLE};
+pub use hooks::{ParseHooks, RawHook, TypedHook, ParsedHook};
+pub use merge::merge_data;
+pub use migrate::{Migration, migrate_parquet, parquet_schema_version};
+pub use peek::peek;
+pub use plot::PlotSpec;
+pub use profiles::OutputProfile;
+pub use qc::{QcCheck, QcThresholds};
+pub use recompute::{RecomputeOptions, BoundaryLayerModel, DEFAULT_OXYGEN, DEFAULT_GBW_TOLERANCE, recompute_gas_exchange};
+pub use remarks::{RemarkParser, KeyValueRemarks, remark_text};
+pub use row_order::RowOrder;
+pub use schema::{SchemaColumn, output_schema, schema_ddl};
+pub use search::search_variables;
+pub use smoothing::{SmoothingSpec, SmoothingMethod, apply_smoothing};
+pub use stability::{StabilityCriterion, StabilityWindow, trim_to_stable};
+pub use step_summary::RobustStatistic;
+pub use summary::{VariableSummary, summarize};
+pub use units::{UnitStyle, pretty_units, ascii_units};
+pub use validate::{ValidationReport, UnitMismatch};
+pub use parser::{LiCorParser, LiCorData, VariableInfo, ParseOptions, NonFinite, DuplicateColumns, CoercionReport, SheetResult};
+#[cfg(feature = "device-6800")]
+pub use parser::{LiCor6800Standard, LiCor6800Fluorometer, LiCor6800Aquatic, LiCor6800Soil};
+#[cfg(feature = "device-6400")]
+pub use parser::{LiCor6400Standard, LiCor6400Fluorometer};
+pub use writers::{write_xlsx, write_netcdf, write_delta, write_hive_dataset, write_parquet, ParquetCodec, ParquetOptions, SCHEMA_VERSION};
+#[cfg(feature = "postgres")]
+pub use writers::write_postgres;
 
 // Test the macro system
 include_variable_definitions!("licor.toml");
@@ -36,6 +124,27 @@ mod tests {
         assert_eq!(aperture.units, Some("cm2"));
     }
 
+    #[test]
+    fn test_variable_units() {
+        let units = |name: &str| VARIABLE_DEFINITIONS.iter().find(|v| v.internal_name == name).unwrap().units;
+        // Flash times are logged in ms, as the console's units row says
+        assert_eq!(units("T@F1"), Some("ms"));
+        assert_eq!(units("T@F2"), Some("ms"));
+        assert_eq!(units("T@HIR"), Some("ms"));
+        assert_eq!(units("ETR"), Some("µmol m-2 s-1"));
+        assert_eq!(units("cf_co2_a"), Some("µmol mol-1"));
+    }
+
+    #[test]
+    fn test_variable_lookup() {
+        // `A` is defined in several tables; the units pick the gas-exchange one
+        let a = VariableDef::lookup("A", "µmol m⁻² s⁻¹").expect("Should find A");
+        assert_eq!(a.description, "Assimilation rate");
+        assert_eq!(a.category, "computed_results.gas_exchange_standard");
+        assert!(VariableDef::lookup("A", "").is_some());
+        assert!(VariableDef::lookup("not_a_variable", "").is_none());
+    }
+    
     #[test]
     fn test_variable_definitions_static() {
         let variables = &*VARIABLE_DEFINITIONS;
@@ -46,6 +155,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(all(feature = "device-6800", feature = "device-6400"))]
     fn test_device_trait() {
         use std::collections::HashMap;
         
@@ -61,6 +171,17 @@ mod tests {
         let metadata = Device6800::parse_metadata(&header).unwrap();
         assert_eq!(metadata.device_serial, "68C-901292");
         assert_eq!(metadata.console_version, "Bluestem v.2.1.13");
+        
+        // A 6800 header is not a 6400 header
+        assert!(Device6400::validate_header(&header).is_err());
+        
+        let mut legacy_header = HashMap::new();
+        legacy_header.insert("Console ver".to_string(), "OPEN 6.2.4".to_string());
+        legacy_header.insert("Unit".to_string(), "PSC-3572".to_string());
+        assert!(Device6400::validate_header(&legacy_header).is_ok());
+        assert_eq!(Device6400::parse_metadata(&legacy_header).unwrap().device_serial, "PSC-3572");
+        assert_eq!(Device6400::canonical_column_name("Photo"), "A");
+        assert_eq!(Device6800::canonical_column_name("Photo"), "Photo");
     }
 
     #[test]
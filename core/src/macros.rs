@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
@@ -8,9 +8,39 @@ pub struct VariableDef {
     pub units: Option<&'static str>,
     pub description: &'static str,
     pub data_type: DataType,
+    /// Section of `licor.toml` defining the variable, e.g. `computed_results.gas_exchange_standard`
+    pub category: &'static str,
+    /// How cells are read when units don't say (flags, bitmasks, categories)
+    pub parse_rule: Option<crate::ParseRule>,
 }
 
-#[derive(Debug, Clone)]
+impl VariableDef {
+    /// Definition of a column, using its units to choose between definitions
+    /// sharing a name (e.g. `A`); falls back to the first definition
+    pub fn lookup(name: &str, units: &str) -> Option<&'static VariableDef> {
+        let units = crate::validate::normalize_units(units);
+        let mut candidates = crate::VARIABLE_DEFINITIONS.iter()
+            .filter(|def| def.internal_name == name);
+        candidates.clone()
+            .find(|def| def.units.is_some_and(|u| crate::validate::normalize_units(u) == units))
+            .or_else(|| candidates.next())
+    }
+
+    /// Definition of the LI-6800 variable a device's column stands for, as
+    /// [`lookup`](Self::lookup) does, except that without units the definition
+    /// labelled with the name itself wins (`A` is the leaf assimilation rate,
+    /// not an aquatic or fluorometer flux)
+    pub fn lookup_canonical(name: &str, units: &str) -> Option<&'static VariableDef> {
+        if !units.is_empty() {
+            return Self::lookup(name, units);
+        }
+        crate::VARIABLE_DEFINITIONS.iter()
+            .find(|def| def.internal_name == name && def.display_label == name)
+            .or_else(|| Self::lookup(name, units))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DataType {
     Float,
     Integer,
@@ -19,6 +49,16 @@ pub enum DataType {
 }
 
 impl DataType {
+    /// Lowercase name, as shown in listings
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataType::Float => "float",
+            DataType::Integer => "integer",
+            DataType::String => "string",
+            DataType::Boolean => "boolean",
+        }
+    }
+    
     pub fn infer_from_units(units: &str) -> Self {
         match units {
             "" => DataType::String, // Default for empty units
@@ -48,6 +88,7 @@ struct TomlSection {
 }
 
 #[derive(Deserialize)]
+#[allow(dead_code)] // Section-level fields are kept for completeness of the schema
 struct TomlSubsection {
     source_table: Option<String>,
     section_title: Option<String>,
@@ -61,6 +102,8 @@ struct TomlVariable {
     units: String,
     description: String,
     internal_name: String,
+    #[serde(default)]
+    parse: Option<crate::ParseRule>,
 }
 
 pub fn parse_licor_toml() -> Result<Vec<VariableDef>, crate::ParseError> {
@@ -69,10 +112,13 @@ pub fn parse_licor_toml() -> Result<Vec<VariableDef>, crate::ParseError> {
     
     let mut variables = Vec::new();
     
-    for (_section_name, section) in config.sections {
-        for (_subsection_name, subsection) in section.subsections {
+    for (section_name, section) in config.sections {
+        for (subsection_name, subsection) in section.subsections {
+            let category: &'static str = Box::leak(format!("{}.{}", section_name, subsection_name).into_boxed_str());
             for var in subsection.variables {
-                let data_type = DataType::infer_from_units(&var.units);
+                let data_type = var.parse.as_ref()
+                    .and_then(|rule| rule.data_type())
+                    .unwrap_or_else(|| DataType::infer_from_units(&var.units));
                 
                 let variable_def = VariableDef {
                     internal_name: Box::leak(var.internal_name.into_boxed_str()),
@@ -84,6 +130,8 @@ pub fn parse_licor_toml() -> Result<Vec<VariableDef>, crate::ParseError> {
                     },
                     description: Box::leak(var.description.into_boxed_str()),
                     data_type,
+                    category,
+                    parse_rule: var.parse,
                 };
                 
                 variables.push(variable_def);
@@ -91,6 +139,8 @@ pub fn parse_licor_toml() -> Result<Vec<VariableDef>, crate::ParseError> {
         }
     }
     
+    // Table order is lost in the maps above, so fix one
+    variables.sort_by(|a, b| a.category.cmp(b.category).then_with(|| a.internal_name.cmp(b.internal_name)));
     Ok(variables)
 }
 
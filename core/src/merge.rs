@@ -0,0 +1,151 @@
+use crate::{ParseError, LiCorData, VariableInfo};
+use polars::prelude::*;
+
+type PolarsType = polars::datatypes::DataType;
+
+/// Combine parsed datasets into one, aligning their schemas
+///
+/// Columns appear in the order first seen, and a column a dataset lacks is
+/// filled with nulls. A column typed differently across datasets becomes
+/// `Float64` if every type is numeric and text otherwise. Rows are then sorted
+/// by `sort_by` (stable, nulls last). Metadata comes from the first dataset,
+/// with the content hash recomputed for the merged rows.
+pub fn merge_data(datasets: Vec<LiCorData>, sort_by: &[String]) -> Result<LiCorData, ParseError> {
+    let mut datasets = datasets.into_iter();
+    let Some(first) = datasets.next() else {
+        return Err(ParseError::EmptyDataSection);
+    };
+    let mut metadata = first.metadata.clone();
+    let mut variable_info = first.variable_info.clone();
+    let mut coercion_reports = first.coercion_reports.clone();
+    let mut frames = vec![first.dataframe];
+    for data in datasets {
+        for info in data.variable_info {
+            if !variable_info.iter().any(|v: &VariableInfo| v.internal_name == info.internal_name) {
+                variable_info.push(info);
+            }
+        }
+        coercion_reports.extend(data.coercion_reports);
+        frames.push(data.dataframe);
+    }
+
+    let schema = merged_schema(&frames);
+    let mut merged: Option<DataFrame> = None;
+    for frame in &frames {
+        let columns = schema.iter()
+            .map(|(name, dtype)| match frame.column(name) {
+                Ok(column) if column.dtype() == dtype => Ok(column.clone()),
+                Ok(column) => column.cast(dtype),
+                Err(_) => Ok(Column::full_null(name.as_str().into(), frame.height(), dtype)),
+            })
+            .collect::<PolarsResult<Vec<_>>>()
+            .map_err(merge_error)?;
+        let aligned = DataFrame::new(columns).map_err(merge_error)?;
+        match merged.as_mut() {
+            Some(merged) => {
+                merged.vstack_mut(&aligned).map_err(merge_error)?;
+            }
+            None => merged = Some(aligned),
+        }
+    }
+    let mut dataframe = merged.unwrap_or_default();
+    dataframe.align_chunks_par();
+
+    if !sort_by.is_empty() {
+        if let Some(missing) = sort_by.iter().find(|name| dataframe.column(name).is_err()) {
+            return Err(ParseError::UnknownVariable { variable: missing.clone() });
+        }
+        let sort_options = SortMultipleOptions::default()
+            .with_maintain_order(true)
+            .with_nulls_last(true);
+        dataframe = dataframe.sort(sort_by.to_vec(), sort_options).map_err(merge_error)?;
+    }
+
+    variable_info.retain(|v| dataframe.column(&v.internal_name).is_ok());
+    metadata.content_hash = Some(crate::content_hash(&dataframe)?);
+    Ok(LiCorData { metadata, dataframe, variable_info, coercion_reports })
+}
+
+/// Union of the frames' columns, in first-seen order, with their common type
+fn merged_schema(frames: &[DataFrame]) -> Vec<(String, PolarsType)> {
+    let mut schema: Vec<(String, Vec<PolarsType>)> = Vec::new();
+    for frame in frames {
+        for column in frame.get_columns() {
+            let name = column.name().as_str();
+            let index = match schema.iter().position(|(n, _)| n == name) {
+                Some(index) => index,
+                None => {
+                    schema.push((name.to_string(), Vec::new()));
+                    schema.len() - 1
+                }
+            };
+            // All-null columns take whatever type the other frames have
+            if !column.dtype().is_null() && !schema[index].1.contains(column.dtype()) {
+                schema[index].1.push(column.dtype().clone());
+            }
+        }
+    }
+
+    schema.into_iter()
+        .map(|(name, dtypes)| {
+            let dtype = match dtypes.as_slice() {
+                [] => PolarsType::Null,
+                [dtype] => dtype.clone(),
+                dtypes if dtypes.iter().all(|d| d.is_primitive_numeric()) => PolarsType::Float64,
+                _ => PolarsType::String,
+            };
+            (name, dtype)
+        })
+        .collect()
+}
+
+fn merge_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to merge data: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LiCorMetadata;
+
+    fn data(dataframe: DataFrame) -> LiCorData {
+        LiCorData {
+            metadata: LiCorMetadata {
+                device_serial: "68C-901292".to_string(),
+                console_version: "Bluestem v.2.1.13".to_string(),
+                ..Default::default()
+            },
+            dataframe,
+            variable_info: Vec::new(),
+            coercion_reports: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge_data() {
+        let a = data(df! {
+            "time" => [30.0, 10.0],
+            "A" => [12.5, 13.0],
+            "source_file" => ["a", "a"],
+        }.unwrap());
+        let b = data(df! {
+            "time" => [20.0],
+            "source_file" => ["b"],
+            "A" => ["#DIV/0!"],
+            "Fs" => [310i64],
+        }.unwrap());
+
+        let merged = merge_data(vec![a, b], &["time".to_string()]).unwrap();
+        let df = &merged.dataframe;
+        assert_eq!(df.get_column_names(), ["time", "A", "source_file", "Fs"]);
+        assert_eq!(df.column("A").unwrap().dtype(), &PolarsType::String);
+        let sources: Vec<_> = df.column("source_file").unwrap().str().unwrap().into_no_null_iter().collect();
+        assert_eq!(sources, ["a", "b", "a"]);
+        assert_eq!(df.column("Fs").unwrap().null_count(), 2);
+        assert!(merged.metadata.content_hash.is_some());
+
+        assert!(merge_data(Vec::new(), &[]).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Bring Parquet outputs of older versions up to the current layout
+//!
+//! Version 1 covers everything written before the layout was versioned:
+//! plain Parquet with no `licor.*` metadata, and files whose metadata or
+//! variable info lack fields added since. [`migrate_parquet`] fills what can
+//! be recovered (variable definitions from `licor.toml`, unit forms, the
+//! content hash) and rewrites the file with [`write_parquet`](crate::write_parquet).
+
+use crate::writers::parquet::{key_values, METADATA_KEY, SCHEMA_VERSION_KEY, VARIABLE_INFO_KEY};
+use crate::{content_hash, DataType, LiCorData, LiCorMetadata, ParquetOptions, ParseError, VariableDef, VariableInfo, SCHEMA_VERSION};
+use polars::prelude::*;
+use serde_json::{Map, Value};
+
+/// Changes made to bring a file to [`SCHEMA_VERSION`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// Schema version the file had
+    pub from: u32,
+    pub changes: Vec<String>,
+}
+
+/// Schema version of a Parquet file; 1 when it has none
+pub fn parquet_schema_version(path: &str) -> Result<u32, ParseError> {
+    let mut reader = ParquetReader::new(std::fs::File::open(path)?);
+    let key_values = key_values(&mut reader).map_err(|e| migrate_error(path, e))?;
+    Ok(key_values.iter()
+        .find(|(key, _)| key == SCHEMA_VERSION_KEY)
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(1))
+}
+
+/// Rewrite a Parquet file in schema `to_schema`, or with `dry_run` only work
+/// out what would change
+///
+/// Only the current schema, [`SCHEMA_VERSION`], can be written; any other
+/// version is an error. Returns `None` when the file is already in it. Data
+/// is kept as is; the file is rewritten with the default [`ParquetOptions`]
+/// through a temporary file next to it, so an interrupted migration leaves the
+/// original intact.
+pub fn migrate_parquet(path: &str, to_schema: u32, dry_run: bool) -> Result<Option<Migration>, ParseError> {
+    let invalid = |message: String| ParseError::InvalidHeaderFormat {
+        message: format!("{}: {}", path, message),
+    };
+    if to_schema != SCHEMA_VERSION {
+        return Err(invalid(format!("can only migrate to the current schema, v{}, not v{}", SCHEMA_VERSION, to_schema)));
+    }
+    let mut reader = ParquetReader::new(std::fs::File::open(path)?);
+    let key_values = key_values(&mut reader).map_err(|e| migrate_error(path, e))?;
+    let value = |key: &str| key_values.iter().find(|(k, _)| k == key).map(|(_, value)| value.as_str());
+    let from = value(SCHEMA_VERSION_KEY).and_then(|v| v.parse().ok()).unwrap_or(1);
+    if from > SCHEMA_VERSION {
+        return Err(invalid(format!("schema version {} is newer than this version of licor writes ({})", from, SCHEMA_VERSION)));
+    }
+    if from == SCHEMA_VERSION {
+        return Ok(None);
+    }
+    let dataframe = reader.finish().map_err(|e| migrate_error(path, e))?;
+    let mut changes = Vec::new();
+
+    let json = |key: &str| -> Result<Option<Value>, ParseError> {
+        value(key).map(serde_json::from_str).transpose().map_err(|e| invalid(format!("invalid {}: {}", key, e)))
+    };
+    let blank = LiCorMetadata::default();
+    let mut metadata: LiCorMetadata = match json(METADATA_KEY)? {
+        Some(stored) => {
+            let (merged, added) = fill_fields(serde_json::to_value(&blank).map_err(|e| invalid(e.to_string()))?, stored);
+            if !added.is_empty() {
+                changes.push(format!("Added metadata fields {}", added.join(", ")));
+            }
+            serde_json::from_value(merged).map_err(|e| invalid(format!("invalid {}: {}", METADATA_KEY, e)))?
+        }
+        None => {
+            changes.push("Added instrument metadata (unknown: the file had none)".to_string());
+            blank
+        }
+    };
+    if metadata.content_hash.is_none() {
+        metadata.content_hash = Some(content_hash(&dataframe)?);
+        changes.push("Added the content hash".to_string());
+    }
+
+    let stored: Vec<Value> = match json(VARIABLE_INFO_KEY)? {
+        Some(Value::Array(entries)) => entries,
+        Some(_) => return Err(invalid(format!("invalid {}: not a list", VARIABLE_INFO_KEY))),
+        None => Vec::new(),
+    };
+    let mut variable_info = Vec::new();
+    let (mut filled, mut described) = (0, 0);
+    for column in dataframe.get_columns() {
+        let name = column.name().as_str();
+        let fallback = column_info(name, column.dtype());
+        let info = match stored.iter().find(|entry| entry.get("internal_name").and_then(Value::as_str) == Some(name)) {
+            Some(entry) => {
+                let (merged, added) = fill_fields(serde_json::to_value(&fallback).map_err(|e| invalid(e.to_string()))?, entry.clone());
+                let mut info: VariableInfo = serde_json::from_value(merged)
+                    .map_err(|e| invalid(format!("invalid {}: {}", VARIABLE_INFO_KEY, e)))?;
+                // Unit forms are derived from the units as stored, not the definition's
+                info.units_pretty = info.units_pretty.or_else(|| info.units.as_deref().map(crate::pretty_units));
+                info.units_ascii = info.units_ascii.or_else(|| info.units.as_deref().map(crate::ascii_units));
+                filled += usize::from(!added.is_empty());
+                info
+            }
+            None => {
+                described += 1;
+                fallback
+            }
+        };
+        variable_info.push(info);
+    }
+    if filled > 0 {
+        changes.push(format!("Added missing fields to the variable info of {} columns", filled));
+    }
+    if described > 0 {
+        changes.push(format!("Added variable info for {} columns", described));
+    }
+    changes.push(format!("Set the schema version to {}", SCHEMA_VERSION));
+
+    if !dry_run {
+        let data = LiCorData { metadata, dataframe, variable_info, coercion_reports: Vec::new() };
+        let temporary = format!("{}.migrating", path);
+        crate::write_parquet(&data, &temporary, &ParquetOptions::default())
+            .and_then(|()| Ok(std::fs::rename(&temporary, path)?))
+            .inspect_err(|_| {
+                let _ = std::fs::remove_file(&temporary);
+            })?;
+    }
+    Ok(Some(Migration { from, changes }))
+}
+
+/// Variable info of a column from its definition in `licor.toml`, or its name
+/// and type when it has none
+fn column_info(name: &str, dtype: &polars::datatypes::DataType) -> VariableInfo {
+    let data_type = match dtype {
+        dtype if dtype.is_float() => DataType::Float,
+        dtype if dtype.is_integer() => DataType::Integer,
+        polars::datatypes::DataType::Boolean => DataType::Boolean,
+        _ => DataType::String,
+    };
+    let def = VariableDef::lookup(name, "");
+    VariableInfo {
+        internal_name: name.to_string(),
+        display_label: def.map_or(name, |def| def.display_label).to_string(),
+        units: def.and_then(|def| def.units).map(str::to_string),
+        units_pretty: def.and_then(|def| def.units).map(crate::pretty_units),
+        units_ascii: def.and_then(|def| def.units).map(crate::ascii_units),
+        description: def.map_or_else(|| format!("Unknown variable: {}", name), |def| def.description.to_string()),
+        data_type,
+        column_category: String::new(),
+        derived_from: None,
+    }
+}
+
+/// Fields of `stored` over `defaults`, with the names of the fields `stored`
+/// lacked
+fn fill_fields(defaults: Value, stored: Value) -> (Value, Vec<String>) {
+    let (Value::Object(defaults), Value::Object(stored)) = (defaults, stored) else {
+        return (Value::Null, Vec::new());
+    };
+    let added = defaults.keys().filter(|key| !stored.contains_key(*key)).cloned().collect();
+    let mut merged: Map<String, Value> = defaults;
+    merged.extend(stored);
+    (Value::Object(merged), added)
+}
+
+fn migrate_error(path: &str, e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("{}: {}", path, e)
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_parquet() {
+        let data = crate::LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("licor_test_migrate_{}.parquet", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        // Plain Parquet, as convert wrote before it stored metadata
+        let file = std::fs::File::create(path).unwrap();
+        crate::writers::parquet::write_parquet_frame(&mut data.dataframe.clone(), file, &ParquetOptions::default(), Vec::new()).unwrap();
+        assert_eq!(parquet_schema_version(path).unwrap(), 1);
+        assert!(LiCorData::from_parquet(path).is_err());
+
+        assert!(migrate_parquet(path, 1, true).is_err());
+        let planned = migrate_parquet(path, SCHEMA_VERSION, true).unwrap().unwrap();
+        assert_eq!(parquet_schema_version(path).unwrap(), 1);
+        let migration = migrate_parquet(path, SCHEMA_VERSION, false).unwrap().unwrap();
+        assert_eq!(migration, planned);
+        assert_eq!(migration.from, 1);
+        assert_eq!(migration.changes[0], "Added instrument metadata (unknown: the file had none)");
+
+        assert_eq!(parquet_schema_version(path).unwrap(), SCHEMA_VERSION);
+        let read = LiCorData::from_parquet(path).unwrap();
+        assert!(read.dataframe.equals_missing(&data.dataframe));
+        assert_eq!(read.metadata.content_hash, data.metadata.content_hash);
+        let flow = read.variable_info.iter().find(|v| v.internal_name == "Flow_s").unwrap();
+        assert_eq!(flow.units_ascii.as_deref(), Some("umol s-1"));
+        assert_eq!(migrate_parquet(path, SCHEMA_VERSION, false).unwrap(), None);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,229 @@
+//! Per-variable parse rules declared in `licor.toml`
+//!
+//! Status and flag columns don't fit the unit-based type inference. A variable
+//! can declare how its cells are read with a `parse` table:
+//!
+//! ```toml
+//! parse = { kind = "boolean", true = ["on", "OK"], false = ["off"] }
+//! parse = { kind = "bitmask", bits = ["GPIO_1", "GPIO_2"], radix = 2 }
+//! parse = { kind = "category" }
+//! ```
+//!
+//! A [`DecodeTable`] file names the bits of status columns in the same way,
+//! overriding the built-in rules.
+
+use crate::{DataType, ParseError, VariableInfo};
+use polars::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// How the cells of a variable are read
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ParseRule {
+    /// A boolean column; the listed values (case-insensitive) map to true or
+    /// false, and any other value keeps the column as text
+    Boolean {
+        #[serde(rename = "true")]
+        true_values: Vec<String>,
+        #[serde(rename = "false")]
+        false_values: Vec<String>,
+    },
+    /// An integer whose bits become boolean columns; the original column is kept
+    Bitmask(BitDecoding),
+    /// Text labels, kept as they are without numeric conversion
+    Category,
+    /// Whole numbers (counters, observation numbers); values logged as
+    /// floats such as `15.0` are read as integers, and fractional values are
+    /// rounded and reported
+    Integer,
+}
+
+/// Bits of a status column to decode into boolean columns
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BitDecoding {
+    /// Bits in order from the least significant, unless they give their `bit`
+    pub bits: Vec<BitDef>,
+    /// Base the values are written in (2 for strings like `11111111`)
+    #[serde(default = "default_radix")]
+    pub radix: u32,
+}
+
+/// One decoded bit: a column name, or a table with its position and meaning
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum BitDef {
+    Name(String),
+    Named {
+        name: String,
+        bit: Option<u32>,
+        description: Option<String>,
+    },
+}
+
+fn default_radix() -> u32 {
+    10
+}
+
+impl BitDef {
+    pub fn name(&self) -> &str {
+        match self {
+            BitDef::Name(name) | BitDef::Named { name, .. } => name,
+        }
+    }
+}
+
+/// Bit names for status columns, read from a TOML file of `[COLUMN]` tables:
+///
+/// ```toml
+/// [GPIO]
+/// radix = 2
+/// bits = [
+///     { bit = 0, name = "door_open", description = "Growth chamber door switch on GPIO 1" },
+/// ]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct DecodeTable {
+    pub columns: HashMap<String, BitDecoding>,
+}
+
+impl DecodeTable {
+    pub fn from_file(path: &str) -> Result<Self, ParseError> {
+        Self::from_toml_str(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self, ParseError> {
+        Ok(toml::from_str(content)?)
+    }
+}
+
+impl ParseRule {
+    /// Value of a cell under a `Boolean` rule
+    pub fn boolean(&self, value: &str) -> Option<bool> {
+        let ParseRule::Boolean { true_values, false_values } = self else {
+            return None;
+        };
+        let value = value.trim();
+        if true_values.iter().any(|v| v.eq_ignore_ascii_case(value)) {
+            Some(true)
+        } else if false_values.iter().any(|v| v.eq_ignore_ascii_case(value)) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Declared type of the variable, overriding the one inferred from units
+    pub(crate) fn data_type(&self) -> Option<DataType> {
+        match self {
+            ParseRule::Boolean { .. } => Some(DataType::Boolean),
+            ParseRule::Category => Some(DataType::String),
+            ParseRule::Integer => Some(DataType::Integer),
+            ParseRule::Bitmask(_) => None,
+        }
+    }
+}
+
+/// One boolean column per declared bit, null where a cell is missing or not
+/// an integer in the decoding's radix
+pub(crate) fn bitmask_columns(
+    source: &VariableInfo,
+    column_data: &[String],
+    decoding: &BitDecoding,
+) -> Vec<(Column, VariableInfo)> {
+    let radix = decoding.radix;
+    let masks: Vec<Option<u64>> = column_data.iter()
+        .map(|s| {
+            let s = s.trim();
+            // Integers logged as floats, e.g. "18.0"
+            let s = if radix == 10 { s.strip_suffix(".0").unwrap_or(s) } else { s };
+            u64::from_str_radix(s, radix).ok()
+        })
+        .collect();
+
+    decoding.bits.iter()
+        .enumerate()
+        .map(|(index, def)| match def {
+            BitDef::Named { bit: Some(bit), .. } => (*bit as usize, def),
+            _ => (index, def),
+        })
+        .filter(|(bit, _)| *bit < 64)
+        .map(|(bit, def)| {
+            let name = def.name().to_string();
+            let values: Vec<Option<bool>> = masks.iter()
+                .map(|mask| mask.map(|mask| mask & (1 << bit) != 0))
+                .collect();
+            let description = match def {
+                BitDef::Named { description: Some(description), .. } => description.clone(),
+                _ => format!("Bit {} of {}", bit, source.internal_name),
+            };
+            let info = VariableInfo {
+                internal_name: name.clone(),
+                display_label: name.clone(),
+                units: None,
+                units_pretty: None,
+                units_ascii: None,
+                description,
+                data_type: DataType::Boolean,
+                column_category: source.column_category.clone(),
+                derived_from: Some(source.internal_name.clone()),
+            };
+            (Column::new(name.as_str().into(), values), info)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        #[derive(Deserialize)]
+        struct Variable {
+            parse: ParseRule,
+        }
+        let rule: Variable = toml::from_str(r#"parse = { kind = "boolean", true = ["on", "OK"], false = ["off"] }"#).unwrap();
+        assert_eq!(rule.parse.boolean("ok"), Some(true));
+        assert_eq!(rule.parse.boolean("OFF"), Some(false));
+        assert_eq!(rule.parse.boolean("1"), None);
+
+        let rule: Variable = toml::from_str(r#"parse = { kind = "bitmask", bits = ["ch1", "ch2", "ch3"], radix = 2 }"#).unwrap();
+        let ParseRule::Bitmask(decoding) = rule.parse else { panic!("not a bitmask") };
+        let source = VariableInfo {
+            internal_name: "GPIO".to_string(),
+            display_label: "GPIO".to_string(),
+            units: None,
+            units_pretty: None,
+            units_ascii: None,
+            description: String::new(),
+            data_type: DataType::String,
+            column_category: "Aux".to_string(),
+            derived_from: None,
+        };
+        let data: Vec<String> = ["101", "010", "oops"].iter().map(|s| s.to_string()).collect();
+        let columns = bitmask_columns(&source, &data, &decoding);
+        assert_eq!(columns.len(), 3);
+        let ch1: Vec<Option<bool>> = columns[0].0.bool().unwrap().into_iter().collect();
+        assert_eq!(ch1, [Some(true), Some(false), None]);
+        assert_eq!(columns[1].1.derived_from.as_deref(), Some("GPIO"));
+
+        // Sparse, described bits from a decode table
+        let table = DecodeTable::from_toml_str(r#"
+            [GPIO]
+            radix = 2
+            bits = [{ bit = 2, name = "door_open", description = "Door switch" }]
+        "#).unwrap();
+        let columns = bitmask_columns(&source, &data, &table.columns["GPIO"]);
+        assert_eq!(columns[0].1.internal_name, "door_open");
+        assert_eq!(columns[0].1.description, "Door switch");
+        let door: Vec<Option<bool>> = columns[0].0.bool().unwrap().into_iter().collect();
+        assert_eq!(door, [Some(true), Some(false), None]);
+
+        let rule: Variable = toml::from_str(r#"parse = { kind = "category" }"#).unwrap();
+        assert_eq!(rule.parse, ParseRule::Category);
+        let rule: Variable = toml::from_str(r#"parse = { kind = "integer" }"#).unwrap();
+        assert!(matches!(rule.parse.data_type(), Some(DataType::Integer)));
+    }
+}
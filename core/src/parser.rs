@@ -1,15 +1,117 @@
 use crate::{
     ParseError, RawLiCorFile, LiCorDevice, LiCorConfig, LiCorMetadata, 
-    DataType, VARIABLE_DEFINITIONS
+    DataType, VariableDef, ParseRule
 };
+use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use polars::prelude::*;
 
 /// Type-safe LI-COR parser parameterized by device and configuration
 pub struct LiCorParser<D: LiCorDevice, C: LiCorConfig> {
     _device: PhantomData<D>,
     _config: PhantomData<C>,
+    options: ParseOptions,
+}
+
+/// Optional behaviour for the parsing pipeline
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Collect a `CoercionReport` for every column that could not be converted
+    /// to its expected type
+    pub coercion_report: bool,
+    /// Keep only the steady-state end of each measurement step
+    pub trim_to_stable: Option<crate::StabilityWindow>,
+    /// Append a `source_file` column holding the path each file was read from
+    pub include_source: bool,
+    /// Smoothed copies of selected variables, added as `<variable>_smooth` columns
+    pub smoothing: Vec<crate::SmoothingSpec>,
+    /// Header values to repeat on every row as constant columns, named either by
+    /// metadata field (`device_serial`, `chamber_type`, ...) or by raw header key
+    /// (`ChambConst:Aperture`, `Remark`, ...)
+    pub header_columns: Vec<String>,
+    /// Reference times used to correct instrument clock drift in the `time` column
+    pub clock_anchors: Vec<crate::ClockAnchor>,
+    /// Derived variables (WUE, iWUE) to compute from the gas-exchange results
+    pub derived: Vec<crate::DerivedVariable>,
+    /// Order of the instrument's columns (as logged by default)
+    pub column_order: crate::ColumnOrder,
+    /// Order of the rows (as logged by default)
+    pub row_order: crate::RowOrder,
+    /// Bit names for status columns, taking precedence over `licor.toml` rules
+    pub decode_table: Option<crate::DecodeTable>,
+    /// Recompute the gas-exchange results from the logged measurements
+    pub recompute: Option<crate::RecomputeOptions>,
+    /// Add a `stable_<variable>` column for each criterion of the header's
+    /// stability definition
+    pub stability_columns: bool,
+    /// Grammars for structured remarks, tried in order; each recognized
+    /// remark's fields become columns (see [`crate::remarks`])
+    pub remark_parsers: Vec<std::sync::Arc<dyn crate::RemarkParser>>,
+    /// Defaults applied to files from the listed console serials (see
+    /// [`crate::instruments`])
+    pub instrument_profiles: Option<std::sync::Arc<crate::InstrumentProfiles>>,
+    /// User transforms run after the raw parse, after typing and before the
+    /// data is returned for writing
+    pub hooks: crate::ParseHooks,
+    /// What non-finite cells in float columns become
+    pub non_finite: NonFinite,
+    /// What a column logged under the same name as an earlier one becomes
+    pub duplicate_columns: DuplicateColumns,
+}
+
+/// Handling of a column whose name was already used, such as the `TIME` and
+/// `hhmmss` both the system and measurement groups log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateColumns {
+    /// Keep it, numbered after the name (`TIME_1`)
+    #[default]
+    Number,
+    /// Drop it when every value equals the first column of the name, and
+    /// number it otherwise
+    Merge,
+    /// Fail the parse
+    Fail,
+}
+
+impl std::str::FromStr for DuplicateColumns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "number" => Ok(DuplicateColumns::Number),
+            "merge" => Ok(DuplicateColumns::Merge),
+            "fail" => Ok(DuplicateColumns::Fail),
+            _ => Err(format!("Unknown duplicate column handling '{}': use number, merge or fail", s)),
+        }
+    }
+}
+
+/// What non-finite cells (`inf`, `1e+309`, and MSVC's `1.#INF` and `-1.#IND`
+/// from some exports) become, keeping their column numeric
+///
+/// Integer columns cannot hold infinities, so any non-finite cell in them is
+/// null.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonFinite {
+    /// Infinite and overflowing cells become ±inf, indeterminate ones
+    /// (`1.#IND`, `1.#QNAN`, `nan`) null
+    #[default]
+    Infinity,
+    /// Every non-finite cell becomes null
+    Null,
+}
+
+impl std::str::FromStr for NonFinite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "infinity" | "inf" => Ok(NonFinite::Infinity),
+            "null" => Ok(NonFinite::Null),
+            _ => Err(format!("Unknown non-finite policy '{}': use infinity or null", s)),
+        }
+    }
 }
 
 /// Parsed LI-COR data with rich metadata
@@ -18,59 +120,237 @@ pub struct LiCorData {
     pub metadata: LiCorMetadata,
     pub dataframe: DataFrame,
     pub variable_info: Vec<VariableInfo>,
+    /// Per-column coercion failures (empty unless `ParseOptions::coercion_report`
+    /// is set), and integer columns whose fractional values were rounded
+    pub coercion_reports: Vec<CoercionReport>,
 }
 
-/// Information about a variable in the dataset
+/// Values that prevented a column from being converted to its expected type
 #[derive(Debug, Clone)]
+pub struct CoercionReport {
+    pub column: String,
+    pub expected_type: DataType,
+    /// Distinct offending values with their counts, most frequent first
+    pub offending_values: Vec<(String, usize)>,
+    /// The offending values were fractional numbers rounded into an integer
+    /// column, rather than values that kept the column as text
+    pub rounded: bool,
+}
+
+impl CoercionReport {
+    fn new(var_info: &VariableInfo, column_data: &[String], parses: impl Fn(&str) -> bool) -> Self {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for value in column_data {
+            if !is_missing(value) && !parses(value) {
+                *counts.entry(value.as_str()).or_insert(0) += 1;
+            }
+        }
+        
+        let mut offending_values: Vec<(String, usize)> = counts.into_iter()
+            .map(|(value, count)| (value.to_string(), count))
+            .collect();
+        offending_values.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        
+        Self {
+            column: var_info.internal_name.clone(),
+            expected_type: var_info.data_type.clone(),
+            offending_values,
+            rounded: false,
+        }
+    }
+    
+    /// Report of the fractional values of an integer column
+    fn rounded(var_info: &VariableInfo, column_data: &[String]) -> Self {
+        Self {
+            rounded: true,
+            ..Self::new(var_info, column_data, |s| parse_integer(s).is_none_or(|(_, whole)| whole))
+        }
+    }
+}
+
+/// Outcome of parsing one data table of a workbook, labelled by sheet
+pub type SheetResult = (String, Result<LiCorData, ParseError>);
+
+/// Information about a variable in the dataset
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VariableInfo {
     pub internal_name: String,
     pub display_label: String,
+    /// Units as logged or defined in `licor.toml`
     pub units: Option<String>,
+    /// Units with Unicode superscripts, e.g. `µmol m⁻² s⁻¹`
+    pub units_pretty: Option<String>,
+    /// Units in ASCII only, e.g. `umol m-2 s-1`
+    pub units_ascii: Option<String>,
     pub description: String,
     pub data_type: DataType,
     pub column_category: String,
+    /// Source variable of a derived column, such as a smoothed trace
+    pub derived_from: Option<String>,
 }
 
 impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
     /// Create a new parser instance
     pub fn new() -> Self {
+        Self::with_options(ParseOptions::default())
+    }
+    
+    /// Create a parser with non-default options
+    pub fn with_options(options: ParseOptions) -> Self {
         Self {
             _device: PhantomData,
             _config: PhantomData,
+            options,
         }
     }
     
     /// Parse a LI-COR file from file path
     pub fn parse_file(&self, path: &str) -> Result<LiCorData, ParseError> {
         let content = std::fs::read_to_string(path)?;
-        self.parse_content(&content)
+        let data = self.parse_content(&content)?;
+        self.with_source(data, path)
     }
     
     /// Parse a LI-COR file from string content
     pub fn parse_content(&self, content: &str) -> Result<LiCorData, ParseError> {
         // Stage 1: Raw parsing
         let raw_file = RawLiCorFile::parse(content)?;
+        self.parse_raw(raw_file)
+    }
+    
+    /// Parse every data table in a legacy Excel workbook (.xls)
+    ///
+    /// Returns one result per table, labelled by sheet name, so a sheet that does
+    /// not match the configuration does not prevent the others from converting.
+    #[cfg(feature = "device-6400")]
+    pub fn parse_xls(&self, path: &str) -> Result<Vec<SheetResult>, ParseError> {
+        let tables = crate::xls::read_xls_sheets(path)?;
+        Ok(tables.into_iter()
+            .map(|(label, raw_file)| {
+                let result = self.parse_raw(raw_file)
+                    .and_then(|data| self.with_source(data, &format!("{}#{}", path, label)));
+                (label, result)
+            })
+            .collect())
+    }
+    
+    /// Append the `source_file` column when `ParseOptions::include_source` is set
+    fn with_source(&self, mut data: LiCorData, source: &str) -> Result<LiCorData, ParseError> {
+        if !self.options.include_source {
+            return Ok(data);
+        }
+        
+        let column = Column::new("source_file".into(), vec![source; data.dataframe.height()]);
+        data.dataframe.with_column(column)
+            .map_err(|e| ParseError::InvalidHeaderFormat {
+                message: format!("Failed to add source column: {}", e)
+            })?;
+        data.variable_info.push(VariableInfo {
+            internal_name: "source_file".to_string(),
+            display_label: "Source file".to_string(),
+            units: None,
+            units_pretty: None,
+            units_ascii: None,
+            description: "Path of the file the observation was read from".to_string(),
+            data_type: DataType::String,
+            column_category: "Source".to_string(),
+            derived_from: None,
+        });
+        Ok(data)
+    }
+    
+    /// Run validation and type conversion on an already-extracted raw file
+    pub fn parse_raw(&self, mut raw_file: RawLiCorFile) -> Result<LiCorData, ParseError> {
+        for hook in &self.options.hooks.after_raw {
+            hook(&mut raw_file)?;
+        }
         
         // Stage 2: Device validation
         D::validate_header(&raw_file.header)?;
-        let metadata = D::parse_metadata(&raw_file.header)?;
+        let mut metadata = D::parse_metadata(&raw_file.header)?;
+        metadata.console_messages = std::mem::take(&mut raw_file.console_messages);
+        metadata.fluorometer_settings = crate::FluorometerSettings::from_log(&raw_file.header, &mut metadata.console_messages);
+        let profile = self.options.instrument_profiles.as_ref().and_then(|profiles| profiles.get(&metadata.device_serial));
+        if let Some(profiles) = &self.options.instrument_profiles {
+            metadata.calibration_certificates = profiles.certificates(&metadata);
+        }
+        
+        // Stage 3: Configuration validation (against LI-6800 names)
+        let canonical_columns: Vec<String> = raw_file.column_names.iter()
+            .map(|name| D::canonical_column_name(name).to_string())
+            .collect();
+        C::validate_columns(&canonical_columns)?;
         
-        // Stage 3: Configuration validation
-        C::validate_columns(&raw_file.column_names)?;
+        // Recompute settings completed from the header, before it is consumed
+        let recompute = self.options.recompute.as_ref().map(|options| crate::RecomputeOptions {
+            oxygen: Some(options.oxygen_or(raw_file.header.get("SysConst:Oxygen").map(String::as_str))),
+            ..options.clone()
+        });
+        
+        let stability_criteria = if self.options.stability_columns {
+            crate::StabilityCriterion::from_header(&raw_file.header)
+        } else {
+            Vec::new()
+        };
+        
+        // Header values to broadcast, resolved before the raw file is consumed
+        let header_values: Vec<(String, Option<String>)> = self.options.header_columns.iter()
+            .map(|key| {
+                let value = metadata.field(key).or_else(|| raw_file.header.get(key).map(String::as_str));
+                (key.clone(), value.map(str::to_string))
+            })
+            .collect();
         
         // Stage 4: Type conversion
-        let (dataframe, variable_info) = self.build_typed_dataframe(raw_file)?;
+        let (mut dataframe, mut variable_info, coercion_reports) = self.build_typed_dataframe(raw_file)?;
+        crate::column_order::apply_column_order(&mut dataframe, &mut variable_info, self.options.column_order)?;
+        crate::fluorometer::add_settings_columns(&mut dataframe, &mut variable_info, &metadata.fluorometer_settings)?;
+        crate::remarks::add_remark_columns(&mut dataframe, &mut variable_info, &metadata.console_messages, &self.options.remark_parsers)?;
+        if let Some(profile) = profile {
+            crate::instruments::apply_profile_columns(&mut dataframe, &mut variable_info, profile)?;
+        }
+        for hook in &self.options.hooks.after_typing {
+            hook(&mut dataframe, &mut variable_info)?;
+        }
+        
+        // Stage 5: Clock correction and optional row selection
+        metadata.clock_correction = crate::apply_clock_correction(&mut dataframe, &mut variable_info, &self.options.clock_anchors)?;
+        // Anchors give true time already; otherwise the console's UTC offset applies
+        if let Some(utc_offset) = profile.filter(|_| metadata.clock_correction.is_none()).map(|p| p.utc_offset()).transpose()?.flatten() {
+            metadata.clock_correction = Some(crate::clock::apply_utc_offset(&mut dataframe, &mut variable_info, utc_offset)?);
+        }
+        crate::row_order::apply_row_order(&mut dataframe, self.options.row_order)?;
+        
+        // Stage 6: Derived columns
+        if let Some(recompute) = &recompute {
+            crate::recompute_gas_exchange(&mut dataframe, &mut variable_info, recompute)?;
+        }
+        crate::add_derived_variables(&mut dataframe, &mut variable_info, &self.options.derived)?;
+        crate::stability::add_stability_columns(&mut dataframe, &mut variable_info, &stability_criteria)?;
+        // Smooth over every observation of a step, then keep its steady-state end
+        crate::apply_smoothing(&mut dataframe, &mut variable_info, &self.options.smoothing)?;
+        if let Some(window) = self.options.trim_to_stable {
+            dataframe = crate::trim_to_stable(&dataframe, window)?;
+        }
+        add_header_columns(&mut dataframe, &mut variable_info, header_values)?;
         
-        Ok(LiCorData {
+        let mut data = LiCorData {
             metadata,
             dataframe,
             variable_info,
-        })
+            coercion_reports,
+        };
+        for hook in &self.options.hooks.after_parse {
+            hook(&mut data)?;
+        }
+        data.metadata.content_hash = Some(crate::content_hash(&data.dataframe)?);
+        Ok(data)
     }
     
-    fn build_typed_dataframe(&self, raw_file: RawLiCorFile) -> Result<(DataFrame, Vec<VariableInfo>), ParseError> {
+    pub(crate) fn build_typed_dataframe(&self, raw_file: RawLiCorFile) -> Result<(DataFrame, Vec<VariableInfo>, Vec<CoercionReport>), ParseError> {
         let mut columns = Vec::new();
         let mut variable_info = Vec::new();
+        let mut coercion_reports = Vec::new();
         let mut used_names = HashSet::new();
         
         for (col_idx, column_name) in raw_file.column_names.iter().enumerate() {
@@ -80,6 +360,20 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
             
             // Make column name unique if there are duplicates
             let unique_name = if used_names.contains(column_name) {
+                match self.options.duplicate_columns {
+                    DuplicateColumns::Number => {}
+                    DuplicateColumns::Merge => {
+                        let first = raw_file.column_names.iter().position(|name| name == column_name);
+                        if first.is_some_and(|first| raw_file.data_columns[first] == raw_file.data_columns[col_idx]) {
+                            continue;
+                        }
+                    }
+                    DuplicateColumns::Fail => {
+                        return Err(ParseError::InvalidHeaderFormat {
+                            message: format!("Column '{}' is logged more than once", column_name)
+                        });
+                    }
+                }
                 let mut counter = 1;
                 loop {
                     let candidate = format!("{}_{}", column_name, counter);
@@ -93,15 +387,19 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
             };
             used_names.insert(unique_name.clone());
             
-            // Find variable definition
-            let var_def = VARIABLE_DEFINITIONS.iter()
-                .find(|def| def.internal_name == column_name);
+            // Find variable definition; a device's own name for an LI-6800
+            // variable (the LI-6400's `Photo` for `A`) takes that variable's
+            // units and type, keeping its name as the label
+            let units = raw_file.units.get(col_idx).map_or("", String::as_str);
+            let (var_def, aliased) = match VariableDef::lookup(column_name, units) {
+                Some(def) => (Some(def), false),
+                None => (VariableDef::lookup_canonical(D::canonical_column_name(column_name), units), true),
+            };
+            let parse_rule = var_def.and_then(|def| def.parse_rule.as_ref());
             
             // Get column data
-            let column_data: Vec<String> = raw_file.data_rows.iter()
-                .map(|row| row.get(col_idx).unwrap_or(&String::new()).clone())
-                .collect();
-                
+            let column_data = &raw_file.data_columns[col_idx];
+            
             if column_data.is_empty() {
                 continue;
             }
@@ -110,12 +408,15 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
             let var_info = if let Some(def) = var_def {
                 VariableInfo {
                     internal_name: unique_name.clone(),
-                    display_label: def.display_label.to_string(),
+                    display_label: if aliased { column_name.clone() } else { def.display_label.to_string() },
                     units: def.units.map(|s| s.to_string()),
+                    units_pretty: def.units.map(crate::pretty_units),
+                    units_ascii: def.units.map(crate::ascii_units),
                     description: def.description.to_string(),
                     data_type: def.data_type.clone(),
                     column_category: raw_file.column_categories.get(col_idx)
                         .unwrap_or(&String::new()).clone(),
+                    derived_from: None,
                 }
             } else {
                 // Unknown variable - infer type from units
@@ -127,10 +428,13 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
                     internal_name: unique_name.clone(),
                     display_label: column_name.clone(),
                     units: if units.is_empty() { None } else { Some(units.clone()) },
+                    units_pretty: (!units.is_empty()).then(|| crate::pretty_units(units)),
+                    units_ascii: (!units.is_empty()).then(|| crate::ascii_units(units)),
                     description: format!("Unknown variable: {}", column_name),
                     data_type,
                     column_category: raw_file.column_categories.get(col_idx)
                         .unwrap_or(&String::new()).clone(),
+                    derived_from: None,
                 }
             };
             
@@ -138,13 +442,14 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
             let series = match &var_info.data_type {
                 DataType::Float => {
                     // Try to parse as float, but fall back to string if any value fails
+                    let non_finite = self.options.non_finite;
                     let mut can_parse_all = true;
                     let values: Vec<Option<f64>> = column_data.iter()
                         .map(|s| {
-                            if s.is_empty() || s == "-" || s.to_lowercase() == "none" {
+                            if is_missing(s) {
                                 None
-                            } else if let Ok(val) = s.parse::<f64>() {
-                                Some(val)
+                            } else if let Some(val) = parse_float(s, non_finite) {
+                                val
                             } else {
                                 can_parse_all = false;
                                 None
@@ -155,6 +460,9 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
                     if can_parse_all {
                         Series::new((&var_info.internal_name).into(), values)
                     } else {
+                        if self.options.coercion_report {
+                            coercion_reports.push(CoercionReport::new(&var_info, column_data, |s| parse_float(s, non_finite).is_some()));
+                        }
                         // Fall back to string type
                         let values: Vec<Option<String>> = column_data.iter()
                             .map(|s| if s.is_empty() { None } else { Some(s.clone()) })
@@ -163,14 +471,19 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
                     }
                 }
                 DataType::Integer => {
-                    // Try to parse as integer, but fall back to string if any value fails
+                    // Try to parse as integer (rounding fractional numbers, which
+                    // are always reported), but fall back to string if any value fails
                     let mut can_parse_all = true;
+                    let mut fractional = false;
                     let values: Vec<Option<i64>> = column_data.iter()
                         .map(|s| {
-                            if s.is_empty() || s == "-" || s.to_lowercase() == "none" {
+                            if is_missing(s) {
                                 None
-                            } else if let Ok(val) = s.parse::<i64>() {
+                            } else if let Some((val, whole)) = parse_integer(s) {
+                                fractional |= !whole;
                                 Some(val)
+                            } else if is_non_finite(s) {
+                                None
                             } else {
                                 can_parse_all = false;
                                 None
@@ -179,8 +492,14 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
                         .collect();
                     
                     if can_parse_all {
+                        if fractional {
+                            coercion_reports.push(CoercionReport::rounded(&var_info, column_data));
+                        }
                         Series::new((&var_info.internal_name).into(), values)
                     } else {
+                        if self.options.coercion_report {
+                            coercion_reports.push(CoercionReport::new(&var_info, column_data, |s| parse_integer(s).is_some() || is_non_finite(s)));
+                        }
                         // Fall back to string type
                         let values: Vec<Option<String>> = column_data.iter()
                             .map(|s| if s.is_empty() { None } else { Some(s.clone()) })
@@ -190,20 +509,21 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
                 }
                 DataType::Boolean => {
                     // Try to parse as boolean, but fall back to string if any value fails
+                    let to_bool = |s: &str| match parse_rule {
+                        Some(rule @ ParseRule::Boolean { .. }) => rule.boolean(s),
+                        _ => parse_bool(s),
+                    };
                     let mut can_parse_all = true;
                     let values: Vec<Option<bool>> = column_data.iter()
                         .map(|s| {
-                            if s.is_empty() || s == "-" || s.to_lowercase() == "none" {
+                            if is_missing(s) {
                                 None
                             } else {
-                                match s.to_lowercase().as_str() {
-                                    "true" | "1" | "on" | "yes" => Some(true),
-                                    "false" | "0" | "off" | "no" => Some(false),
-                                    _ => {
-                                        can_parse_all = false;
-                                        None
-                                    }
+                                let parsed = to_bool(s);
+                                if parsed.is_none() {
+                                    can_parse_all = false;
                                 }
+                                parsed
                             }
                         })
                         .collect();
@@ -211,6 +531,9 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
                     if can_parse_all {
                         Series::new((&var_info.internal_name).into(), values)
                     } else {
+                        if self.options.coercion_report {
+                            coercion_reports.push(CoercionReport::new(&var_info, column_data, |s| to_bool(s).is_some()));
+                        }
                         // Fall back to string type
                         let values: Vec<Option<String>> = column_data.iter()
                             .map(|s| if s.is_empty() { None } else { Some(s.clone()) })
@@ -227,8 +550,23 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
                 }
             };
             
+            let decoding = self.options.decode_table.as_ref()
+                .and_then(|table| table.columns.get(column_name))
+                .or(match parse_rule {
+                    Some(ParseRule::Bitmask(decoding)) => Some(decoding),
+                    _ => None,
+                });
+            let bit_columns = decoding
+                .map(|decoding| crate::parse_rules::bitmask_columns(&var_info, column_data, decoding))
+                .unwrap_or_default();
             columns.push(series.into());
             variable_info.push(var_info);
+            for (column, info) in bit_columns {
+                if used_names.insert(info.internal_name.clone()) {
+                    columns.push(column);
+                    variable_info.push(info);
+                }
+            }
         }
         
         if columns.is_empty() {
@@ -240,7 +578,99 @@ impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
                 message: format!("Failed to create DataFrame: {}", e) 
             })?;
             
-        Ok((dataframe, variable_info))
+        Ok((dataframe, variable_info, coercion_reports))
+    }
+}
+
+/// Append each header value as a constant column (numeric when it parses as a number)
+fn add_header_columns(
+    dataframe: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    header_values: Vec<(String, Option<String>)>,
+) -> Result<(), ParseError> {
+    let height = dataframe.height();
+    for (key, value) in header_values {
+        let number = value.as_deref().and_then(|v| fast_float2::parse::<f64, _>(v.trim()).ok());
+        let (column, data_type) = match number {
+            Some(number) => (Column::new(key.as_str().into(), vec![number; height]), DataType::Float),
+            None => (Column::new(key.as_str().into(), vec![value.clone(); height]), DataType::String),
+        };
+        dataframe.with_column(column)
+            .map_err(|e| ParseError::InvalidHeaderFormat {
+                message: format!("Failed to add header column '{}': {}", key, e)
+            })?;
+        variable_info.push(VariableInfo {
+            internal_name: key.clone(),
+            display_label: key.clone(),
+            units: None,
+            units_pretty: None,
+            units_ascii: None,
+            description: format!("Header value '{}'", key),
+            data_type,
+            column_category: "Header".to_string(),
+            derived_from: None,
+        });
+    }
+    Ok(())
+}
+
+/// Sentinel values LI-COR writes for missing cells (checked without allocating)
+fn is_missing(s: &str) -> bool {
+    s.is_empty() || s == "-" || s.eq_ignore_ascii_case("none")
+}
+
+/// An integer cell, and whether it was whole: `15` and `15.0` are, `15.4` is
+/// rounded to 15
+fn parse_integer(s: &str) -> Option<(i64, bool)> {
+    if let Ok(value) = s.parse::<i64>() {
+        return Some((value, true));
+    }
+    let value = fast_float2::parse::<f64, _>(s).ok()
+        .filter(|value: &f64| value.is_finite() && value.abs() < i64::MAX as f64)?;
+    Some((value.round() as i64, value.fract() == 0.0))
+}
+
+/// A float cell, with a non-finite value as `policy` says; None when the cell
+/// is not a number
+fn parse_float(s: &str, policy: NonFinite) -> Option<Option<f64>> {
+    let value = fast_float2::parse::<f64, _>(s).ok().or_else(|| msvc_special_value(s))?;
+    Some(match policy {
+        _ if value.is_finite() => Some(value),
+        NonFinite::Infinity if value.is_infinite() => Some(value),
+        _ => None,
+    })
+}
+
+fn is_non_finite(s: &str) -> bool {
+    matches!(parse_float(s, NonFinite::Null), Some(None))
+}
+
+/// MSVC's spellings of special values: `1.#INF`, `-1.#IND`, `1.#QNAN` and
+/// `1.#SNAN`, with any trailing zeros of the requested precision (`1.#INF00`)
+fn msvc_special_value(s: &str) -> Option<f64> {
+    let (sign, rest) = match s.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let special = rest.strip_prefix("1.#")?.trim_end_matches('0');
+    let value = match special.to_ascii_uppercase().as_str() {
+        "INF" => f64::INFINITY,
+        "IND" | "QNAN" | "SNAN" => f64::NAN,
+        _ => return None,
+    };
+    Some(sign * value)
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    const TRUE_VALUES: [&str; 4] = ["true", "1", "on", "yes"];
+    const FALSE_VALUES: [&str; 4] = ["false", "0", "off", "no"];
+    
+    if TRUE_VALUES.iter().any(|v| s.eq_ignore_ascii_case(v)) {
+        Some(true)
+    } else if FALSE_VALUES.iter().any(|v| s.eq_ignore_ascii_case(v)) {
+        Some(false)
+    } else {
+        None
     }
 }
 
@@ -251,16 +681,25 @@ impl<D: LiCorDevice, C: LiCorConfig> Default for LiCorParser<D, C> {
 }
 
 // Type aliases for common parser combinations
+#[cfg(feature = "device-6800")]
 pub type LiCor6800Standard = LiCorParser<crate::Device6800, crate::ConfigStandard>;
+#[cfg(feature = "device-6800")]
 pub type LiCor6800Fluorometer = LiCorParser<crate::Device6800, crate::ConfigFluorometer>;
+#[cfg(feature = "device-6800")]
 pub type LiCor6800Aquatic = LiCorParser<crate::Device6800, crate::ConfigAquatic>;
+#[cfg(feature = "device-6800")]
 pub type LiCor6800Soil = LiCorParser<crate::Device6800, crate::ConfigSoil>;
+#[cfg(feature = "device-6400")]
+pub type LiCor6400Standard = LiCorParser<crate::Device6400, crate::ConfigStandard>;
+#[cfg(feature = "device-6400")]
+pub type LiCor6400Fluorometer = LiCorParser<crate::Device6400, crate::ConfigFluorometer>;
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "device-6800")]
     fn test_typed_parser_sample_file() {
         let parser = LiCor6800Fluorometer::new();
         let content = std::fs::read_to_string("../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1")
@@ -293,6 +732,208 @@ mod tests {
     }
     
     #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_integer_columns() {
+        // Counters are Int64 in every file, so files join on obs
+        for path in ["../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1", "../example_data/2025-05-30-1203_logdata_F2"] {
+            let data = LiCor6800Fluorometer::new().parse_file(path).unwrap();
+            for name in ["obs", "averaging", "count", "Stable", "Total"] {
+                let Ok(column) = data.dataframe.column(name) else { continue };
+                assert_eq!(column.dtype(), &polars::datatypes::DataType::Int64, "{} in {}", name, path);
+            }
+            assert!(data.coercion_reports.is_empty());
+        }
+        assert_eq!(parse_integer("15.0"), Some((15, true)));
+        assert_eq!(parse_integer("15.6"), Some((16, false)));
+        assert_eq!(parse_integer("oops"), None);
+        
+        // Fractional values are rounded, with a report even when reports are off
+        let content = std::fs::read_to_string("../example_data/2025-05-30-1203_logdata_F2").unwrap();
+        let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let names = lines.iter().position(|line| line.starts_with("obs\t")).unwrap();
+        let column = lines[names].split('\t').position(|name| name == "averaging").unwrap();
+        let mut row: Vec<String> = lines[names + 2].split('\t').map(str::to_string).collect();
+        row[column] = "14.6".to_string();
+        lines[names + 2] = row.join("\t");
+        let data = LiCor6800Fluorometer::new().parse_content(&lines.join("\n")).unwrap();
+        assert_eq!(data.dataframe.column("averaging").unwrap().i64().unwrap().get(0), Some(15));
+        assert_eq!(data.coercion_reports.len(), 1);
+        assert!(data.coercion_reports[0].rounded);
+        assert_eq!(data.coercion_reports[0].offending_values, vec![("14.6".to_string(), 1)]);
+    }
+    
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_non_finite_values() {
+        assert_eq!(parse_float("1.#INF", NonFinite::Infinity), Some(Some(f64::INFINITY)));
+        assert_eq!(parse_float("-1.#INF00", NonFinite::Infinity), Some(Some(f64::NEG_INFINITY)));
+        assert_eq!(parse_float("1e+309", NonFinite::Infinity), Some(Some(f64::INFINITY)));
+        assert_eq!(parse_float("-1.#IND", NonFinite::Infinity), Some(None));
+        assert_eq!(parse_float("nan", NonFinite::Infinity), Some(None));
+        assert_eq!(parse_float("1.#INF", NonFinite::Null), Some(None));
+        assert_eq!(parse_float("2.5", NonFinite::Null), Some(Some(2.5)));
+        assert_eq!(parse_float("1.#XYZ", NonFinite::Infinity), None);
+        assert_eq!("null".parse::<NonFinite>(), Ok(NonFinite::Null));
+        
+        let content = std::fs::read_to_string("../example_data/2025-05-30-1203_logdata_F2").unwrap();
+        let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let names = lines.iter().position(|line| line.starts_with("obs\t")).unwrap();
+        let position = |name: &str| lines[names].split('\t').position(|n| n == name).unwrap();
+        let (a, ci, averaging) = (position("A"), position("Ci"), position("averaging"));
+        for (offset, cells) in [(2, ["1.#INF", "-1.#IND", "1e+309"]), (3, ["-1e+309", "1.#QNAN", "2"])] {
+            let mut row: Vec<String> = lines[names + offset].split('\t').map(str::to_string).collect();
+            (row[a], row[ci], row[averaging]) = (cells[0].to_string(), cells[1].to_string(), cells[2].to_string());
+            lines[names + offset] = row.join("\t");
+        }
+        let content = lines.join("\n");
+        let parse = |non_finite| {
+            let options = ParseOptions { non_finite, coercion_report: true, ..ParseOptions::default() };
+            LiCor6800Fluorometer::with_options(options).parse_content(&content).unwrap()
+        };
+        
+        let data = parse(NonFinite::Infinity);
+        let column = |data: &LiCorData, name: &str| data.dataframe.column(name).unwrap().clone();
+        let values = column(&data, "A");
+        assert_eq!(values.f64().unwrap().get(0), Some(f64::INFINITY));
+        assert_eq!(values.f64().unwrap().get(1), Some(f64::NEG_INFINITY));
+        assert_eq!(column(&data, "Ci").f64().unwrap().null_count(), 2);
+        let averaging = column(&data, "averaging");
+        assert_eq!((averaging.i64().unwrap().get(0), averaging.i64().unwrap().get(1)), (None, Some(2)));
+        assert!(data.coercion_reports.is_empty());
+        
+        let data = parse(NonFinite::Null);
+        assert_eq!(column(&data, "A").f64().unwrap().null_count(), 2);
+    }
+    
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_include_source() {
+        let path = "../example_data/2025-05-30-1203_logdata_F2";
+        let parser = LiCor6800Fluorometer::with_options(ParseOptions {
+            include_source: true,
+            ..ParseOptions::default()
+        });
+        let data = parser.parse_file(path).expect("Should parse sample file");
+        
+        let source = data.dataframe.column("source_file").unwrap().str().unwrap();
+        assert_eq!(source.len(), data.dataframe.height());
+        assert!(source.into_iter().all(|s| s == Some(path)));
+        assert!(data.variable_info.iter().any(|v| v.internal_name == "source_file"));
+        
+        // Off by default
+        let data = LiCor6800Fluorometer::new().parse_file(path).unwrap();
+        assert!(data.dataframe.column("source_file").is_err());
+    }
+    
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_header_columns() {
+        let parser = LiCor6800Fluorometer::with_options(ParseOptions {
+            header_columns: vec![
+                "device_serial".to_string(),
+                "ChambConst:blc_a".to_string(),
+                "Not a header key".to_string(),
+            ],
+            ..ParseOptions::default()
+        });
+        let data = parser.parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .expect("Should parse sample file");
+        let df = &data.dataframe;
+        
+        let serial = df.column("device_serial").unwrap().str().unwrap();
+        assert!(serial.into_iter().all(|s| s == Some("68C-901292")));
+        let blc_a = df.column("ChambConst:blc_a").unwrap().f64().unwrap();
+        assert!(blc_a.into_iter().all(|v| v == Some(0.25)));
+        assert_eq!(df.column("Not a header key").unwrap().null_count(), df.height());
+    }
+    
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_original_column_order() {
+        let content = std::fs::read_to_string("../example_data/2025-05-30-1203_logdata_F2").unwrap();
+        let mut raw_file = RawLiCorFile::parse(&content).unwrap();
+        raw_file.column_names[1] = "obs".to_string();
+        let raw_names: Vec<String> = raw_file.column_names.iter().filter(|n| !n.is_empty()).cloned().collect();
+        
+        // Repeated names are renamed in place rather than moved to the end
+        let data = LiCor6800Fluorometer::new().parse_raw(raw_file).unwrap();
+        assert!(data.variable_info.iter().zip(data.dataframe.get_column_names()).all(|(v, n)| v.internal_name == n.as_str()));
+        // Decoded bitmask columns sit next to their source
+        let names: Vec<&str> = data.variable_info.iter()
+            .filter(|v| v.derived_from.is_none())
+            .map(|v| v.internal_name.as_str())
+            .collect();
+        assert_eq!(names.len(), raw_names.len());
+        assert_eq!(names[1], "obs_1");
+        assert!(names.iter().zip(&raw_names).all(|(n, raw)| n == raw || n.starts_with(&format!("{}_", raw))));
+    }
+    
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_duplicate_columns() {
+        let path = "../example_data/2025-05-30-1203_logdata_F2";
+        let parse = |duplicate_columns| {
+            let options = ParseOptions { duplicate_columns, ..ParseOptions::default() };
+            LiCor6800Fluorometer::with_options(options).parse_file(path)
+        };
+        let numbered = parse(DuplicateColumns::Number).unwrap();
+        let merged = parse(DuplicateColumns::Merge).unwrap();
+        let df = &numbered.dataframe;
+        assert!(df.column("TIME_1").is_ok() && df.column("hhmmss_1").is_ok());
+        // Each numbered copy is dropped exactly when it repeats the first column
+        for name in ["TIME", "time", "hhmmss", "ID", "Fo"] {
+            let copy = format!("{}_1", name);
+            let same = df.column(name).unwrap().equals_missing(df.column(&copy).unwrap());
+            assert_eq!(merged.dataframe.column(&copy).is_err(), same, "{}", copy);
+        }
+        // The system and measurement TIME agree; their clock readings do not
+        assert!(merged.dataframe.column("TIME_1").is_err() && merged.dataframe.column("hhmmss_1").is_ok());
+        assert_eq!(merged.variable_info.len(), merged.dataframe.width());
+        
+        let error = parse(DuplicateColumns::Fail).unwrap_err();
+        assert!(error.to_string().contains("logged more than once"));
+        assert_eq!("merge".parse::<DuplicateColumns>(), Ok(DuplicateColumns::Merge));
+    }
+    
+    #[test]
+    fn test_coercion_report() {
+        let var_info = VariableInfo {
+            internal_name: "A".to_string(),
+            display_label: "A".to_string(),
+            units: None,
+            units_pretty: None,
+            units_ascii: None,
+            description: String::new(),
+            data_type: DataType::Float,
+            column_category: String::new(),
+            derived_from: None,
+        };
+        let column_data: Vec<String> = ["1.5", "#DIV/0!", "", "#DIV/0!", "oops"]
+            .iter().map(|s| s.to_string()).collect();
+        
+        let report = CoercionReport::new(&var_info, &column_data, |s| s.parse::<f64>().is_ok());
+        assert_eq!(report.column, "A");
+        assert_eq!(report.offending_values, vec![
+            ("#DIV/0!".to_string(), 2),
+            ("oops".to_string(), 1),
+        ]);
+    }
+    
+    #[test]
+    fn test_cell_sentinels() {
+        assert!(is_missing(""));
+        assert!(is_missing("-"));
+        assert!(is_missing("None"));
+        assert!(is_missing("NONE"));
+        assert!(!is_missing("0"));
+        
+        assert_eq!(parse_bool("True"), Some(true));
+        assert_eq!(parse_bool("off"), Some(false));
+        assert_eq!(parse_bool("maybe"), None);
+    }
+    
+    #[test]
+    #[cfg(feature = "device-6800")]
     fn test_type_safety() {
         // This should compile - correct device/config combination
         let _parser = LiCor6800Fluorometer::new();
@@ -1,4 +1,5 @@
 use crate::ParseError;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Raw LI-COR file structure extracted from string content
@@ -8,22 +9,38 @@ pub struct RawLiCorFile {
     pub column_categories: Vec<String>,
     pub column_names: Vec<String>,
     pub units: Vec<String>,
-    pub data_rows: Vec<Vec<String>>,
+    /// Data cells stored column-major: one vector per column, all of equal length
+    pub data_columns: Vec<Vec<String>>,
+    /// Data rows (1-based) whose field count differed from the column count,
+    /// with the number of fields found; such rows are padded or truncated
+    pub malformed_rows: Vec<(usize, usize)>,
+    /// Console warnings and errors written inside the data section
+    pub console_messages: Vec<ConsoleMessage>,
+}
+
+/// A console message found among the data rows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsoleMessage {
+    /// Number of data rows read before the message
+    pub after_row: usize,
+    pub text: String,
 }
 
 impl RawLiCorFile {
     /// Parse a LI-COR file from string content
     pub fn parse(content: &str) -> Result<Self, ParseError> {
-        let mut lines = content.lines().map(|s| s.trim()).collect::<Vec<_>>();
+        // Lines are not trimmed: the units row starts with a tab for `obs`, which
+        // has no units, and trailing tabs mark empty cells
+        let lines = content.lines().collect::<Vec<_>>();
         
         // Find [Header] section
-        let header_start = lines.iter().position(|line| *line == "[Header]")
+        let header_start = lines.iter().position(|line| line.trim() == "[Header]")
             .ok_or_else(|| ParseError::InvalidHeaderFormat { 
                 message: "Missing [Header] section".to_string() 
             })?;
             
         // Find [Data] section
-        let data_start = lines.iter().position(|line| *line == "[Data]")
+        let data_start = lines.iter().position(|line| line.trim() == "[Data]")
             .ok_or_else(|| ParseError::InvalidHeaderFormat { 
                 message: "Missing [Data] section".to_string() 
             })?;
@@ -61,26 +78,38 @@ impl RawLiCorFile {
         
         let num_cols = max_cols;
         
-        // Parse data rows (skip first 3 lines which are headers)
-        let mut data_rows = Vec::new();
-        for (_line_num, line) in data_lines.iter().skip(3).enumerate() {
+        // Parse data rows (skip first 3 lines which are headers) straight into columns
+        let mut data_columns: Vec<Vec<String>> = vec![Vec::new(); num_cols];
+        let mut malformed_rows = Vec::new();
+        let mut console_messages = Vec::new();
+        for line in data_lines.iter().skip(3) {
             if line.trim().is_empty() {
                 continue; // Skip empty lines
             }
             
-            let row = Self::parse_tab_separated_line(line)?;
-            if row.len() != num_cols {
-                // For now, pad short rows with empty strings or truncate long rows
-                // This is more lenient than failing immediately
-                let mut adjusted_row = row;
-                adjusted_row.resize(num_cols, String::new());
-                data_rows.push(adjusted_row);
+            if Self::is_console_message(line, num_cols) {
+                console_messages.push(ConsoleMessage {
+                    after_row: data_columns[0].len(),
+                    text: line.split('\t').map(str::trim).filter(|s| !s.is_empty()).collect::<Vec<_>>().join(" "),
+                });
                 continue;
             }
-            data_rows.push(row);
+            
+            // Short rows are padded with empty strings and long rows truncated;
+            // this is more lenient than failing immediately
+            let row = Self::parse_tab_separated_line(line)?;
+            // Trailing tabs past the last column are empty cells, not extra fields
+            let fields = line.split('\t').count().min(row.len().max(num_cols));
+            if fields < num_cols || row.len() > num_cols {
+                malformed_rows.push((data_columns[0].len() + 1, fields));
+            }
+            let mut row = row.into_iter();
+            for column in data_columns.iter_mut() {
+                column.push(row.next().unwrap_or_default());
+            }
         }
         
-        if data_rows.is_empty() {
+        if data_columns.first().is_none_or(|column| column.is_empty()) {
             return Err(ParseError::EmptyDataSection);
         }
         
@@ -89,20 +118,39 @@ impl RawLiCorFile {
             column_categories,
             column_names,
             units,
-            data_rows,
+            data_columns,
+            malformed_rows,
+            console_messages,
         })
     }
     
+    /// Whether a data-section line is a console message rather than an observation
+    ///
+    /// Observations start with the observation number and fill most columns;
+    /// a message such as "Warning: CO2 r out of range" (or a timestamped one)
+    /// does neither.
+    pub(crate) fn is_console_message(line: &str, num_cols: usize) -> bool {
+        let mut fields = line.split('\t').map(str::trim).filter(|s| !s.is_empty());
+        let first = fields.next().unwrap_or_default();
+        first.parse::<f64>().is_err() && fields.count() < num_cols / 2
+    }
+    
+    /// Number of data rows (observations)
+    pub fn num_rows(&self) -> usize {
+        self.data_columns.first().map_or(0, |column| column.len())
+    }
+    
     fn parse_header(lines: &[&str]) -> Result<HashMap<String, String>, ParseError> {
         let mut header = HashMap::new();
         
         for line in lines {
-            if line.is_empty() {
+            if line.trim().is_empty() {
                 continue;
             }
             
             // Handle various header formats
             if let Some((key, value)) = Self::parse_header_line(line) {
+                let key = Self::header_key(&header, key, &value);
                 header.insert(key, value);
             }
         }
@@ -110,6 +158,18 @@ impl RawLiCorFile {
         Ok(header)
     }
     
+    /// Key a header line is stored under
+    ///
+    /// Timestamped lines (`12:04:20\tStability Definition:\t...`) written
+    /// together share a time, so later ones are keyed `HH:MM:SS#2`, `#3`, ...
+    /// rather than replacing the first. Other repeated keys keep the last value.
+    fn header_key(header: &HashMap<String, String>, key: String, value: &str) -> String {
+        if !header.contains_key(&key) || !value.contains(':') || key.split(':').count() != 3 {
+            return key;
+        }
+        (2..).map(|n| format!("{}#{}", key, n)).find(|k| !header.contains_key(k)).unwrap_or(key)
+    }
+    
     fn parse_header_line(line: &str) -> Option<(String, String)> {
         // Handle different separator patterns in header
         if let Some(tab_pos) = line.find('\t') {
@@ -166,6 +226,21 @@ impl RawLiCorFile {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_units_row_alignment() {
+        let content = std::fs::read_to_string("../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1").unwrap();
+        let raw_file = RawLiCorFile::parse(&content).unwrap();
+        let units = |name: &str| {
+            let index = raw_file.column_names.iter().position(|n| n == name).unwrap();
+            raw_file.units[index].as_str()
+        };
+        // `obs` has no units, so the row starts with an empty cell
+        assert_eq!(units("obs"), "");
+        assert_eq!(units("time"), "s");
+        assert_eq!(units("date"), "");
+        assert_eq!(units("Ci"), "µmol mol⁻¹");
+    }
+
     #[test]
     fn test_raw_parsing_sample_file() {
         let content = std::fs::read_to_string("../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1")
@@ -179,7 +254,9 @@ mod tests {
         
         // Test data structure
         assert!(!raw_file.column_names.is_empty());
-        assert!(!raw_file.data_rows.is_empty());
+        assert!(raw_file.num_rows() > 0);
+        assert_eq!(raw_file.data_columns.len(), raw_file.column_names.len());
+        assert!(raw_file.data_columns.iter().all(|c| c.len() == raw_file.num_rows()));
         assert_eq!(raw_file.column_names.len(), raw_file.units.len());
         assert_eq!(raw_file.column_names.len(), raw_file.column_categories.len());
         
@@ -187,5 +264,44 @@ mod tests {
         assert!(raw_file.column_names.contains(&"obs".to_string()));
         assert!(raw_file.column_names.contains(&"A".to_string()));
         assert!(raw_file.column_names.contains(&"E".to_string()));
+        assert!(raw_file.console_messages.is_empty());
+    }
+
+    #[test]
+    fn test_console_messages_kept_out_of_data() {
+        let content = std::fs::read_to_string("../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1")
+            .expect("Should be able to read sample file");
+        let rows = RawLiCorFile::parse(&content).unwrap().num_rows();
+        
+        // Messages after the second observation, bare and timestamped
+        let mut lines: Vec<&str> = content.lines().collect();
+        let data = lines.iter().position(|line| line.trim() == "[Data]").unwrap();
+        lines.insert(data + 6, "12:10:54\tWarning: CO2 r out of range");
+        lines.insert(data + 6, "ERROR: Flow control failure");
+        
+        let raw_file = RawLiCorFile::parse(&lines.join("\n")).unwrap();
+        assert_eq!(raw_file.num_rows(), rows);
+        assert!(raw_file.malformed_rows.is_empty());
+        assert_eq!(raw_file.console_messages, vec![
+            ConsoleMessage { after_row: 2, text: "ERROR: Flow control failure".to_string() },
+            ConsoleMessage { after_row: 2, text: "12:10:54 Warning: CO2 r out of range".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_header_lines_sharing_a_time() {
+        let header = RawLiCorFile::parse_header(&[
+            "12:04:20\tStability Definition:\tA (GasEx): Slp<1 Per=15",
+            "12:04:20\tFlr Settings:\tMeasInt=1",
+            "12:04:20\tFlr Settings:\tMeasInt=2",
+            "Chamber type\t6800-01A",
+            "Chamber type\t6800-13",
+        ]).unwrap();
+        assert_eq!(header.get("12:04:20").map(String::as_str), Some("Stability Definition:\tA (GasEx): Slp<1 Per=15"));
+        assert_eq!(header.get("12:04:20#2").map(String::as_str), Some("Flr Settings:\tMeasInt=1"));
+        assert_eq!(header.get("12:04:20#3").map(String::as_str), Some("Flr Settings:\tMeasInt=2"));
+        // Keys that are not times keep the last value
+        assert_eq!(header.get("Chamber type").map(String::as_str), Some("6800-13"));
+        assert_eq!(header.len(), 4);
     }
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use crate::{ParseError, RawLiCorFile};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Header, column names and units, and the first `rows` observations of a
+/// file, without reading the rest
+///
+/// Text logs are read only as far as the requested observations, so this is
+/// cheap enough for file pickers and indexes over large logs. Workbooks
+/// (`.xls`/`.xlsx`) are read whole and give their first data table. Values are
+/// the unconverted cells; pass the result to `LiCorParser::parse_raw` for
+/// typed columns.
+pub fn peek(path: &str, rows: usize) -> Result<RawLiCorFile, ParseError> {
+    let is_workbook = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("xls") || e.eq_ignore_ascii_case("xlsx"));
+    let mut raw_file = if is_workbook {
+        peek_workbook(path)?
+    } else {
+        peek_text(path, rows)?
+    };
+
+    for column in raw_file.data_columns.iter_mut() {
+        column.truncate(rows);
+    }
+    raw_file.malformed_rows.retain(|(row, _)| *row <= rows);
+    raw_file.console_messages.retain(|message| message.after_row < rows.max(1));
+    Ok(raw_file)
+}
+
+/// Read a text log up to its `rows`-th observation (at least one, so the
+/// data section parses)
+fn peek_text(path: &str, rows: usize) -> Result<RawLiCorFile, ParseError> {
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut content = String::new();
+    let mut in_data = false;
+    let mut table_rows = 0;
+    let mut observations = 0;
+    for line in reader.lines() {
+        let line = line?;
+        content.push_str(&line);
+        content.push('\n');
+
+        if !in_data {
+            in_data = line.trim() == "[Data]";
+        } else if table_rows < 3 {
+            table_rows += 1;
+        } else if line.split('\t').next().is_some_and(|obs| obs.trim().parse::<f64>().is_ok()) {
+            observations += 1;
+            if observations >= rows.max(1) {
+                break;
+            }
+        }
+    }
+    RawLiCorFile::parse(&content)
+}
+
+#[cfg(feature = "device-6400")]
+fn peek_workbook(path: &str) -> Result<RawLiCorFile, ParseError> {
+    crate::xls::read_xls_sheets(path)?
+        .into_iter()
+        .next()
+        .map(|(_, raw_file)| raw_file)
+        .ok_or(ParseError::EmptyDataSection)
+}
+
+#[cfg(not(feature = "device-6400"))]
+fn peek_workbook(path: &str) -> Result<RawLiCorFile, ParseError> {
+    Err(ParseError::InvalidHeaderFormat {
+        message: format!("'{}' is a workbook; reading workbooks needs the `device-6400` feature", path)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peek() {
+        let path = "../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1";
+        let full = RawLiCorFile::parse(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        let peeked = peek(path, 3).unwrap();
+        assert_eq!(peeked.num_rows(), 3);
+        assert_eq!(peeked.column_names, full.column_names);
+        assert_eq!(peeked.units, full.units);
+        assert_eq!(peeked.header, full.header);
+        assert_eq!(peeked.data_columns[0], full.data_columns[0][..3]);
+
+        // Names and units only
+        assert_eq!(peek(path, 0).unwrap().num_rows(), 0);
+        assert_eq!(peek(path, 1000).unwrap().num_rows(), full.num_rows());
+    }
+}
@@ -0,0 +1,205 @@
+//! Quick-look SVG charts for field QC
+//!
+//! Time series of selected variables against elapsed time, or scatter plots
+//! such as A against Ci, drawn straight from parsed data. The charts are
+//! plain SVG, so they open in any browser without a plotting library.
+
+use crate::{LiCorData, ParseError};
+use polars::prelude::*;
+use std::fmt::Write;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 500.0;
+const LEFT: f64 = 80.0;
+const RIGHT: f64 = 20.0;
+const TOP: f64 = 40.0;
+const BOTTOM: f64 = 60.0;
+const COLORS: &[&str] = &["#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b", "#e377c2", "#17becf"];
+
+/// Columns to plot and how
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotSpec {
+    /// Column on the x axis
+    pub x: String,
+    /// Columns on the y axis, one series each
+    pub y: Vec<String>,
+    /// Join the points of each series in row order
+    pub lines: bool,
+}
+
+impl PlotSpec {
+    /// Time series of `y` against elapsed time (`elapsed` on the LI-6800,
+    /// `FTime` on the LI-6400, otherwise the observation number)
+    pub fn time_series(data: &LiCorData, y: Vec<String>) -> Self {
+        let x = ["elapsed", "FTime", "obs", "Obs"].iter()
+            .find(|name| data.dataframe.column(name).is_ok())
+            .unwrap_or(&"elapsed")
+            .to_string();
+        PlotSpec { x, y, lines: true }
+    }
+
+    /// A against Ci scatter (`Photo` for A on the LI-6400)
+    pub fn aci(data: &LiCorData) -> Self {
+        let a = if data.dataframe.column("A").is_err() && data.dataframe.column("Photo").is_ok() { "Photo" } else { "A" };
+        PlotSpec { x: "Ci".to_string(), y: vec![a.to_string()], lines: false }
+    }
+}
+
+impl LiCorData {
+    /// Draw the columns of `spec` as an SVG chart with units on the axes
+    ///
+    /// Rows where a value is missing or not finite are left out.
+    pub fn plot_svg(&self, spec: &PlotSpec, title: &str) -> Result<String, ParseError> {
+        let x = self.plot_values(&spec.x)?;
+        let series = spec.y.iter()
+            .map(|name| Ok((name.as_str(), self.plot_values(name)?)))
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        let finite = |values: &[Option<f64>]| values.iter().flatten().copied().filter(|v| v.is_finite()).collect::<Vec<_>>();
+        let x_ticks = nice_ticks(&finite(&x));
+        let y_ticks = nice_ticks(&series.iter().flat_map(|(_, values)| finite(values)).collect::<Vec<_>>());
+        let (x_min, x_max) = (x_ticks[0], x_ticks[x_ticks.len() - 1]);
+        let (y_min, y_max) = (y_ticks[0], y_ticks[y_ticks.len() - 1]);
+        let to_x = |v: f64| LEFT + (v - x_min) / (x_max - x_min) * (WIDTH - LEFT - RIGHT);
+        let to_y = |v: f64| HEIGHT - BOTTOM - (v - y_min) / (y_max - y_min) * (HEIGHT - TOP - BOTTOM);
+
+        let mut svg = String::new();
+        let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif" font-size="12">"#);
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        let _ = writeln!(svg, r#"<text x="{}" y="24" text-anchor="middle" font-size="14">{}</text>"#, WIDTH / 2.0, escape(title));
+
+        // Axes, gridlines and tick labels
+        let (x_step, y_step) = (x_ticks[1] - x_ticks[0], y_ticks[1] - y_ticks[0]);
+        for &tick in &x_ticks {
+            let px = to_x(tick);
+            let _ = writeln!(svg, r##"<line x1="{px:.1}" y1="{TOP}" x2="{px:.1}" y2="{}" stroke="#e0e0e0"/>"##, HEIGHT - BOTTOM);
+            let _ = writeln!(svg, r#"<text x="{px:.1}" y="{}" text-anchor="middle">{}</text>"#, HEIGHT - BOTTOM + 18.0, tick_label(tick, x_step));
+        }
+        for &tick in &y_ticks {
+            let py = to_y(tick);
+            let _ = writeln!(svg, r##"<line x1="{LEFT}" y1="{py:.1}" x2="{}" y2="{py:.1}" stroke="#e0e0e0"/>"##, WIDTH - RIGHT);
+            let _ = writeln!(svg, r#"<text x="{}" y="{:.1}" text-anchor="end">{}</text>"#, LEFT - 6.0, py + 4.0, tick_label(tick, y_step));
+        }
+        let _ = writeln!(svg, r#"<rect x="{LEFT}" y="{TOP}" width="{}" height="{}" fill="none" stroke="black"/>"#, WIDTH - LEFT - RIGHT, HEIGHT - TOP - BOTTOM);
+        let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#, (LEFT + WIDTH - RIGHT) / 2.0, HEIGHT - 16.0, escape(&self.axis_label(&spec.x)));
+        if let [name] = spec.y.as_slice() {
+            let middle = (TOP + HEIGHT - BOTTOM) / 2.0;
+            let _ = writeln!(svg, r#"<text x="18" y="{middle}" text-anchor="middle" transform="rotate(-90 18 {middle})">{}</text>"#, escape(&self.axis_label(name)));
+        }
+
+        for (index, (name, values)) in series.iter().enumerate() {
+            let color = COLORS[index % COLORS.len()];
+            let points: Vec<Option<(f64, f64)>> = x.iter().zip(values)
+                .map(|(x, y)| match (x, y) {
+                    (Some(x), Some(y)) if x.is_finite() && y.is_finite() => Some((to_x(*x), to_y(*y))),
+                    _ => None,
+                })
+                .collect();
+            if spec.lines {
+                // A missing value breaks the line
+                for run in points.split(Option::is_none).filter(|run| run.len() > 1) {
+                    let coordinates: Vec<String> = run.iter().flatten().map(|(x, y)| format!("{x:.1},{y:.1}")).collect();
+                    let _ = writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="1.5"/>"#, coordinates.join(" "));
+                }
+            }
+            for (px, py) in points.iter().flatten() {
+                let _ = writeln!(svg, r#"<circle cx="{px:.1}" cy="{py:.1}" r="2.5" fill="{color}"/>"#);
+            }
+            if series.len() > 1 {
+                let ly = TOP + 16.0 + 16.0 * index as f64;
+                let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="10" height="10" fill="{color}"/>"#, WIDTH - RIGHT - 150.0, ly - 9.0);
+                let _ = writeln!(svg, r#"<text x="{}" y="{ly}">{}</text>"#, WIDTH - RIGHT - 134.0, escape(&self.axis_label(name)));
+            }
+        }
+        svg.push_str("</svg>\n");
+        Ok(svg)
+    }
+
+    /// Values of a column as floats, nulls where it has none
+    fn plot_values(&self, name: &str) -> Result<Vec<Option<f64>>, ParseError> {
+        let column = self.dataframe.column(name)
+            .map_err(|_| ParseError::UnknownVariable { variable: name.to_string() })?;
+        let values = column.cast(&polars::datatypes::DataType::Float64).map_err(plot_error)?;
+        Ok(values.f64().map_err(plot_error)?.into_iter().collect())
+    }
+
+    /// Column name with its units, as `A (µmol m⁻² s⁻¹)`
+    fn axis_label(&self, name: &str) -> String {
+        let units = self.variable_info.iter()
+            .find(|v| v.internal_name == name)
+            .and_then(|v| v.units_pretty.clone().or_else(|| v.units.clone()))
+            .filter(|units| !units.is_empty());
+        match units {
+            Some(units) => format!("{} ({})", name, units),
+            None => name.to_string(),
+        }
+    }
+}
+
+/// Evenly spaced round tick values covering `values`, at least two
+fn nice_ticks(values: &[f64]) -> Vec<f64> {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let (min, max) = match (min.is_finite(), max.is_finite()) {
+        (false, _) | (_, false) => (0.0, 1.0),
+        // A constant series gets a band around its value
+        _ if min == max => (min - min.abs().max(1.0) * 0.5, max + max.abs().max(1.0) * 0.5),
+        _ => (min, max),
+    };
+    let rough = (max - min) / 5.0;
+    let magnitude = 10f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0].iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * magnitude);
+    let first = (min / step).floor() as i64;
+    let last = (max / step).ceil() as i64;
+    (first..=last.max(first + 1)).map(|i| i as f64 * step).collect()
+}
+
+/// Tick value with as many decimals as the step needs
+fn tick_label(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    let label = format!("{:.*}", decimals, value);
+    if label.trim_start_matches('-').chars().all(|c| c == '0' || c == '.') { "0".to_string() } else { label }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn plot_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to plot: {}", e)
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::LiCor6800Fluorometer;
+
+    #[test]
+    fn test_plot_svg() {
+        assert_eq!(nice_ticks(&[0.3, 9.7]), vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(nice_ticks(&[5.0]), vec![2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+        assert_eq!(tick_label(0.25, 0.05), "0.25");
+        assert_eq!(tick_label(-0.0, 1.0), "0");
+
+        let data = LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        let spec = PlotSpec::time_series(&data, vec!["A".to_string(), "gsw".to_string()]);
+        assert_eq!(spec.x, "elapsed");
+        let svg = data.plot_svg(&spec, "F2").unwrap();
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert_eq!(svg.matches("<circle").count(), 2 * data.dataframe.height());
+
+        let aci = data.plot_svg(&PlotSpec::aci(&data), "F2").unwrap();
+        assert!(!aci.contains("<polyline") && aci.contains("A (µmol m⁻² s⁻¹)"));
+
+        let missing = data.plot_svg(&PlotSpec::time_series(&data, vec!["Photo".to_string()]), "F2");
+        assert!(matches!(missing, Err(ParseError::UnknownVariable { .. })));
+    }
+}
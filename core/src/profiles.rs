@@ -0,0 +1,279 @@
+use crate::{ParseError, LiCorData, VariableInfo, DataType};
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Header value holding the O₂ concentration (percent) on the LI-6800
+pub const OXYGEN_HEADER: &str = "SysConst:Oxygen";
+
+/// Columns passed through unchanged by every profile, when present
+const PASSTHROUGH: [&str; 4] = ["obs", "date", "curve_id", "source_file"];
+
+/// Column layout expected by a downstream analysis tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputProfile {
+    /// PhotoGEA (R): LI-6800 names with PhotoGEA unit strings, plus the
+    /// `total_pressure` and `oxygen` columns its fitting functions need
+    PhotoGea,
+    /// msuRACiFit-style A/Ci fitting: `A`, `Ci`, `Tleaf`, `Pressure`
+    MsuRaciFit,
+    /// plantecophys (R) `fitaci` defaults: `Photo`, `Ci`, `Tleaf`, `PARi`, `Patm`
+    Plantecophys,
+}
+
+impl FromStr for OutputProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "photogea" => Ok(OutputProfile::PhotoGea),
+            "msuracifit" => Ok(OutputProfile::MsuRaciFit),
+            "plantecophys" => Ok(OutputProfile::Plantecophys),
+            _ => Err(format!("Unknown profile '{}': use photogea, msuracifit or plantecophys", s)),
+        }
+    }
+}
+
+/// Where a profile column comes from
+enum Source {
+    /// First column present, multiplied by its factor
+    Column(&'static [(&'static str, f64)]),
+    /// Chamber pressure (`Pa` + `ΔPcham`, in kPa) multiplied by the factor
+    TotalPressure(f64),
+    /// O₂ percent from the broadcast header value, else ambient 21 %
+    Oxygen,
+}
+
+struct ProfileColumn {
+    name: &'static str,
+    source: Source,
+    units: &'static str,
+    required: bool,
+}
+
+const ASSIMILATION: &[(&str, f64)] = &[("A", 1.0), ("Photo", 1.0)];
+const INTERCELLULAR_CO2: &[(&str, f64)] = &[("Ci", 1.0)];
+const LEAF_TEMPERATURE: &[(&str, f64)] = &[("TleafCnd", 1.0), ("Tleaf", 1.0)];
+const INCIDENT_LIGHT: &[(&str, f64)] = &[("Qin", 1.0), ("PARi", 1.0)];
+
+const PHOTOGEA: &[ProfileColumn] = &[
+    ProfileColumn { name: "A", source: Source::Column(ASSIMILATION), units: "micromol m^(-2) s^(-1)", required: true },
+    ProfileColumn { name: "Ci", source: Source::Column(INTERCELLULAR_CO2), units: "micromol mol^(-1)", required: true },
+    ProfileColumn { name: "Ca", source: Source::Column(&[("Ca", 1.0), ("CO2R", 1.0)]), units: "micromol mol^(-1)", required: false },
+    ProfileColumn { name: "gsw", source: Source::Column(&[("gsw", 1.0), ("Cond", 1.0)]), units: "mol m^(-2) s^(-1)", required: false },
+    ProfileColumn { name: "E", source: Source::Column(&[("E", 1.0), ("Trmmol", 1e-3)]), units: "mol m^(-2) s^(-1)", required: false },
+    ProfileColumn { name: "Qin", source: Source::Column(INCIDENT_LIGHT), units: "micromol m^(-2) s^(-1)", required: false },
+    ProfileColumn { name: "TleafCnd", source: Source::Column(LEAF_TEMPERATURE), units: "degrees C", required: true },
+    ProfileColumn { name: "total_pressure", source: Source::TotalPressure(0.01), units: "bar", required: true },
+    ProfileColumn { name: "oxygen", source: Source::Oxygen, units: "percent", required: true },
+    ProfileColumn { name: "PhiPS2", source: Source::Column(&[("PhiPS2", 1.0)]), units: "dimensionless", required: false },
+    ProfileColumn { name: "ETR", source: Source::Column(&[("ETR", 1.0)]), units: "micromol m^(-2) s^(-1)", required: false },
+];
+
+const MSURACIFIT: &[ProfileColumn] = &[
+    ProfileColumn { name: "A", source: Source::Column(ASSIMILATION), units: "µmol m-2 s-1", required: true },
+    ProfileColumn { name: "Ci", source: Source::Column(INTERCELLULAR_CO2), units: "µmol mol-1", required: true },
+    ProfileColumn { name: "Tleaf", source: Source::Column(LEAF_TEMPERATURE), units: "°C", required: true },
+    ProfileColumn { name: "Pressure", source: Source::TotalPressure(1.0), units: "kPa", required: true },
+];
+
+const PLANTECOPHYS: &[ProfileColumn] = &[
+    ProfileColumn { name: "Photo", source: Source::Column(ASSIMILATION), units: "µmol m-2 s-1", required: true },
+    ProfileColumn { name: "Ci", source: Source::Column(INTERCELLULAR_CO2), units: "µmol mol-1", required: true },
+    ProfileColumn { name: "Tleaf", source: Source::Column(LEAF_TEMPERATURE), units: "°C", required: true },
+    ProfileColumn { name: "PARi", source: Source::Column(INCIDENT_LIGHT), units: "µmol m-2 s-1", required: false },
+    ProfileColumn { name: "Patm", source: Source::TotalPressure(1.0), units: "kPa", required: true },
+];
+
+impl OutputProfile {
+    /// Name used on the command line and in error messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputProfile::PhotoGea => "photogea",
+            OutputProfile::MsuRaciFit => "msuracifit",
+            OutputProfile::Plantecophys => "plantecophys",
+        }
+    }
+
+    /// Header values the profile reads; pass them in `ParseOptions::header_columns`
+    pub fn header_keys(&self) -> &'static [&'static str] {
+        match self {
+            OutputProfile::PhotoGea => &[OXYGEN_HEADER],
+            OutputProfile::MsuRaciFit | OutputProfile::Plantecophys => &[],
+        }
+    }
+
+    fn columns(&self) -> &'static [ProfileColumn] {
+        match self {
+            OutputProfile::PhotoGea => PHOTOGEA,
+            OutputProfile::MsuRaciFit => MSURACIFIT,
+            OutputProfile::Plantecophys => PLANTECOPHYS,
+        }
+    }
+}
+
+impl LiCorData {
+    /// Rename, convert and select columns for an analysis tool
+    ///
+    /// LI-6400 names (`Photo`, `PARi`, `Cond`, ...) are accepted as inputs.
+    /// Optional columns missing from the data are left out; a missing required
+    /// column is an error. `obs`, `date`, `curve_id` and `source_file` are kept
+    /// when present so curves can still be told apart.
+    pub fn to_profile(&self, profile: OutputProfile) -> Result<LiCorData, ParseError> {
+        let df = &self.dataframe;
+        let mut columns = Vec::new();
+        let mut variable_info = Vec::new();
+
+        for name in PASSTHROUGH {
+            if let Ok(column) = df.column(name) {
+                columns.push(column.clone());
+                if let Some(info) = self.variable_info.iter().find(|v| v.internal_name == name) {
+                    variable_info.push(info.clone());
+                }
+            }
+        }
+
+        for spec in profile.columns() {
+            let (values, source) = match &spec.source {
+                Source::Column(candidates) => match first_column(df, candidates)? {
+                    Some(found) => found,
+                    None if spec.required => return Err(ParseError::MissingRequiredVariable {
+                        variable: candidates[0].0.to_string(),
+                        config: profile.name().to_string(),
+                    }),
+                    None => continue,
+                },
+                Source::TotalPressure(scale) => {
+                    let (pressure, _) = first_column(df, &[("Pa", 1.0), ("Press", 1.0)])?
+                        .ok_or_else(|| ParseError::MissingRequiredVariable {
+                            variable: "Pa".to_string(),
+                            config: profile.name().to_string(),
+                        })?;
+                    // The chamber overpressure is only logged by the LI-6800
+                    let overpressure = first_column(df, &[("ΔPcham", 1.0)])?.map(|(values, _)| values);
+                    let values = pressure.iter().enumerate()
+                        .map(|(row, p)| {
+                            let delta = overpressure.as_ref().map_or(Some(0.0), |d| d[row]);
+                            Some((p.as_ref()? + delta?) * scale)
+                        })
+                        .collect();
+                    (values, "Pa".to_string())
+                }
+                Source::Oxygen => {
+                    let header = first_column(df, &[(OXYGEN_HEADER, 1.0)])?.map(|(values, _)| values);
+                    let values = (0..df.height())
+                        .map(|row| Some(header.as_ref().and_then(|h| h[row]).unwrap_or(21.0)))
+                        .collect();
+                    (values, OXYGEN_HEADER.to_string())
+                }
+            };
+
+            let description = self.variable_info.iter()
+                .find(|v| v.internal_name == source)
+                .map(|v| v.description.clone())
+                .unwrap_or_default();
+            columns.push(Column::new(spec.name.into(), values));
+            variable_info.push(VariableInfo {
+                internal_name: spec.name.to_string(),
+                display_label: spec.name.to_string(),
+                units: Some(spec.units.to_string()),
+                units_pretty: Some(crate::pretty_units(spec.units)),
+                units_ascii: Some(crate::ascii_units(spec.units)),
+                description,
+                data_type: DataType::Float,
+                column_category: profile.name().to_string(),
+                derived_from: (source != spec.name).then_some(source),
+            });
+        }
+
+        let dataframe = DataFrame::new(columns).map_err(|e| ParseError::InvalidHeaderFormat {
+            message: format!("Failed to apply {} profile: {}", profile.name(), e)
+        })?;
+        Ok(LiCorData {
+            metadata: self.metadata.clone(),
+            dataframe,
+            variable_info,
+            coercion_reports: self.coercion_reports.clone(),
+        })
+    }
+}
+
+/// Scaled values of a source column and the column's name
+type SourceValues = (Vec<Option<f64>>, String);
+
+/// Values of the first candidate column present, scaled, with its name
+fn first_column(df: &DataFrame, candidates: &[(&str, f64)]) -> Result<Option<SourceValues>, ParseError> {
+    let Some((name, scale)) = candidates.iter().find(|(name, _)| df.column(name).is_ok()) else {
+        return Ok(None);
+    };
+    let values = df.column(name)
+        .and_then(|c| c.cast(&polars::datatypes::DataType::Float64))
+        .map_err(|e| ParseError::DataTypeError {
+            value: e.to_string(),
+            expected_type: "number".to_string(),
+            variable: name.to_string(),
+        })?;
+    let values = values.f64()
+        .map_err(|e| ParseError::InvalidHeaderFormat { message: e.to_string() })?
+        .into_iter()
+        .map(|v| v.map(|v| v * scale))
+        .collect();
+    Ok(Some((values, name.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LiCorMetadata;
+
+    fn sample(dataframe: DataFrame) -> LiCorData {
+        LiCorData {
+            metadata: LiCorMetadata {
+                device_serial: "PSC-1234".to_string(),
+                console_version: "6.3.4".to_string(),
+                ..Default::default()
+            },
+            dataframe,
+            variable_info: Vec::new(),
+            coercion_reports: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_profiles() {
+        let data = sample(df! {
+            "obs" => [1i64, 2],
+            "A" => [12.0, 20.0],
+            "Ci" => [150.0, 400.0],
+            "TleafCnd" => [25.0, 25.1],
+            "Pa" => [97.0, 97.0],
+            "ΔPcham" => [0.1, 0.2],
+            "Qin" => [1500.0, 1500.0],
+        }.unwrap());
+
+        let photogea = data.to_profile(OutputProfile::PhotoGea).unwrap();
+        assert_eq!(
+            photogea.dataframe.get_column_names_str(),
+            vec!["obs", "A", "Ci", "Qin", "TleafCnd", "total_pressure", "oxygen"]
+        );
+        let pressure = photogea.dataframe.column("total_pressure").unwrap().f64().unwrap().get(1).unwrap();
+        assert!((pressure - 0.972).abs() < 1e-12);
+        assert_eq!(photogea.dataframe.column("oxygen").unwrap().f64().unwrap().get(0), Some(21.0));
+        let pressure_info = photogea.variable_info.iter().find(|v| v.internal_name == "total_pressure").unwrap();
+        assert_eq!(pressure_info.units.as_deref(), Some("bar"));
+
+        // LI-6400 names map onto the plantecophys defaults
+        let licor6400 = sample(df! {
+            "Photo" => [8.0],
+            "Ci" => [250.0],
+            "Tleaf" => [24.0],
+            "PARi" => [1200.0],
+            "Press" => [98.5],
+        }.unwrap());
+        let plantecophys = licor6400.to_profile(OutputProfile::Plantecophys).unwrap();
+        assert_eq!(plantecophys.dataframe.get_column_names_str(), vec!["Photo", "Ci", "Tleaf", "PARi", "Patm"]);
+        assert_eq!(plantecophys.dataframe.column("Patm").unwrap().f64().unwrap().get(0), Some(98.5));
+
+        let missing = sample(df! { "A" => [1.0] }.unwrap()).to_profile(OutputProfile::MsuRaciFit);
+        assert!(matches!(missing, Err(ParseError::MissingRequiredVariable { .. })));
+    }
+}
@@ -0,0 +1,231 @@
+//! Quality checks on the gas analyzers and the air supply
+//!
+//! Each check compares logged measurements with what the flow and reference
+//! control should produce, flagging observations that point to a chamber
+//! leak, an exhausted desiccant or a dry humidifier. Checks whose columns are
+//! not logged are skipped.
+
+use crate::{DataType, LiCorData, ParseError, VariableInfo};
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Limits the checks flag observations against
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QcThresholds {
+    /// Largest fraction of the chamber inflow (`Flow`) that may be missing from
+    /// the sample flow (`Flow_s`)
+    pub flow_loss: f64,
+    /// Largest amount the sample may be drier than the reference, mmol mol⁻¹
+    pub sample_drier: f64,
+    /// Largest distance of `H2O_r` from its setpoint, mmol mol⁻¹
+    pub h2o_tolerance: f64,
+    /// Largest distance of `CO2_r` from its setpoint, µmol mol⁻¹
+    pub co2_tolerance: f64,
+    /// Desiccant or humidifier valve position (%) treated as fully open
+    pub valve_open: f64,
+}
+
+impl Default for QcThresholds {
+    fn default() -> Self {
+        QcThresholds {
+            flow_loss: 0.15,
+            sample_drier: 0.05,
+            h2o_tolerance: 0.5,
+            co2_tolerance: 5.0,
+            valve_open: 95.0,
+        }
+    }
+}
+
+impl QcThresholds {
+    /// Change one threshold from `NAME=VALUE`
+    pub fn set(&mut self, setting: &str) -> Result<(), String> {
+        let (name, value) = setting.split_once('=')
+            .ok_or_else(|| format!("Invalid QC threshold '{}': use NAME=VALUE", setting))?;
+        let value = value.trim().parse::<f64>().ok()
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .ok_or_else(|| format!("Invalid QC threshold value '{}'", value))?;
+        match name.trim() {
+            "flow_loss" => self.flow_loss = value,
+            "sample_drier" => self.sample_drier = value,
+            "h2o_tolerance" => self.h2o_tolerance = value,
+            "co2_tolerance" => self.co2_tolerance = value,
+            "valve_open" => self.valve_open = value,
+            name => return Err(format!(
+                "Unknown QC threshold '{}': use flow_loss, sample_drier, h2o_tolerance, co2_tolerance or valve_open", name
+            )),
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for QcThresholds {
+    type Err = String;
+
+    /// Defaults changed by comma-separated `NAME=VALUE` settings
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut thresholds = QcThresholds::default();
+        for setting in s.split(',').filter(|s| !s.trim().is_empty()) {
+            thresholds.set(setting)?;
+        }
+        Ok(thresholds)
+    }
+}
+
+/// Result of one check over a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct QcCheck {
+    /// Name of the check; its column is `qc_<name>`
+    pub name: &'static str,
+    /// What a flag means
+    pub description: &'static str,
+    /// Per observation: flagged, passed, or `None` where an input is missing
+    pub flags: Vec<Option<bool>>,
+}
+
+impl QcCheck {
+    /// Observations flagged
+    pub fn flagged(&self) -> usize {
+        self.flags.iter().filter(|flag| **flag == Some(true)).count()
+    }
+
+    /// Observations the check could be applied to
+    pub fn checked(&self) -> usize {
+        self.flags.iter().filter(|flag| flag.is_some()).count()
+    }
+}
+
+impl LiCorData {
+    /// Run the analyzer and air supply checks
+    ///
+    /// * `leak_flow`: more than `flow_loss` of `Flow` is missing from
+    ///   `Flow_s`, so air escapes the chamber
+    /// * `sample_drier`: `H2O_s` is below `H2O_r` by more than `sample_drier`,
+    ///   which a transpiring leaf cannot cause; a leak or a match offset can
+    /// * `desiccant_exhausted`: `H2O_r` is above its setpoint with the
+    ///   desiccant valve fully open
+    /// * `humidifier_dry`: `H2O_r` is below its setpoint with the humidifier
+    ///   valve fully open
+    /// * `co2_control`: `CO2_r` is off its setpoint, as with spent soda lime
+    ///   or an empty CO₂ cartridge
+    pub fn analyzer_qc(&self, thresholds: &QcThresholds) -> Result<Vec<QcCheck>, ParseError> {
+        let column = |name: &str| -> Result<Option<Vec<Option<f64>>>, ParseError> {
+            match self.dataframe.column(name) {
+                Ok(column) => {
+                    let values = column.cast(&polars::datatypes::DataType::Float64).map_err(qc_error)?;
+                    Ok(Some(values.f64().map_err(qc_error)?.into_iter().collect()))
+                }
+                Err(_) => Ok(None),
+            }
+        };
+        let height = self.dataframe.height();
+        let pairs = |a: &[Option<f64>], b: &[Option<f64>], test: &dyn Fn(f64, f64) -> bool| -> Vec<Option<bool>> {
+            a.iter().zip(b).map(|(a, b)| Some(test((*a)?, (*b)?))).collect()
+        };
+        // Without the valve position, the setpoint alone decides
+        let valve = |name: &str| -> Result<Vec<Option<f64>>, ParseError> {
+            Ok(column(name)?.unwrap_or_else(|| vec![Some(100.0); height]))
+        };
+
+        let mut checks = Vec::new();
+        if let (Some(flow), Some(flow_s)) = (column("Flow")?, column("Flow_s")?) {
+            checks.push(QcCheck {
+                name: "leak_flow",
+                description: "Air escaping the chamber: the sample flow is well below the inflow",
+                flags: pairs(&flow, &flow_s, &|flow, flow_s| flow > 0.0 && (flow - flow_s) / flow > thresholds.flow_loss),
+            });
+        }
+        if let (Some(h2o_s), Some(h2o_r)) = (column("H2O_s")?, column("H2O_r")?) {
+            checks.push(QcCheck {
+                name: "sample_drier",
+                description: "Sample air drier than reference air: probable leak or match offset",
+                flags: pairs(&h2o_s, &h2o_r, &|h2o_s, h2o_r| h2o_r - h2o_s > thresholds.sample_drier),
+            });
+            if let Some(setpoint) = column("H2O_r_sp")? {
+                let above = pairs(&h2o_r, &setpoint, &|h2o_r, setpoint| h2o_r - setpoint > thresholds.h2o_tolerance);
+                let below = pairs(&h2o_r, &setpoint, &|h2o_r, setpoint| setpoint - h2o_r > thresholds.h2o_tolerance);
+                let open = |valve: Vec<Option<f64>>| -> Vec<Option<bool>> {
+                    valve.into_iter().map(|v| v.map(|v| v >= thresholds.valve_open)).collect()
+                };
+                let both = |a: Vec<Option<bool>>, b: Vec<Option<bool>>| -> Vec<Option<bool>> {
+                    a.into_iter().zip(b).map(|(a, b)| Some(a? && b?)).collect()
+                };
+                checks.push(QcCheck {
+                    name: "desiccant_exhausted",
+                    description: "Reference air wetter than its setpoint with the desiccant fully used",
+                    flags: both(above, open(valve("Desiccant_%")?)),
+                });
+                checks.push(QcCheck {
+                    name: "humidifier_dry",
+                    description: "Reference air drier than its setpoint with the humidifier fully used",
+                    flags: both(below, open(valve("Humidifier_%")?)),
+                });
+            }
+        }
+        if let (Some(co2_r), Some(setpoint)) = (column("CO2_r")?, column("CO2_r_sp")?) {
+            checks.push(QcCheck {
+                name: "co2_control",
+                description: "Reference CO2 off its setpoint: check the soda lime and CO2 cartridge",
+                flags: pairs(&co2_r, &setpoint, &|co2_r, setpoint| (co2_r - setpoint).abs() > thresholds.co2_tolerance),
+            });
+        }
+        Ok(checks)
+    }
+
+    /// Add a boolean `qc_<name>` column per check
+    pub fn with_qc_columns(mut self, checks: &[QcCheck]) -> Result<LiCorData, ParseError> {
+        for check in checks {
+            let name = format!("qc_{}", check.name);
+            self.dataframe.with_column(Column::new(name.as_str().into(), &check.flags)).map_err(qc_error)?;
+            self.variable_info.push(VariableInfo {
+                display_label: name.clone(),
+                internal_name: name,
+                units: None,
+                units_pretty: None,
+                units_ascii: None,
+                description: check.description.to_string(),
+                data_type: DataType::Boolean,
+                column_category: "QC".to_string(),
+                derived_from: None,
+            });
+        }
+        Ok(self)
+    }
+}
+
+fn qc_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to run QC checks: {}", e)
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::LiCor6800Fluorometer;
+
+    #[test]
+    fn test_analyzer_qc() {
+        let parser = LiCor6800Fluorometer::new();
+        // Flow_s is about 70% of Flow in this log, as with a leaking gasket
+        let leaking = parser.parse_file("../example_data/2025-05-30-1203_logdata_F2").unwrap();
+        let sealed = parser.parse_file("../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1").unwrap();
+        let thresholds = QcThresholds::default();
+
+        let checks = leaking.analyzer_qc(&thresholds).unwrap();
+        let names: Vec<&str> = checks.iter().map(|c| c.name).collect();
+        assert_eq!(names, ["leak_flow", "sample_drier", "desiccant_exhausted", "humidifier_dry", "co2_control"]);
+        let height = leaking.dataframe.height();
+        assert_eq!((checks[0].flagged(), checks[0].checked()), (height, height));
+        assert!(checks[1..].iter().all(|check| check.flagged() == 0));
+        assert_eq!(sealed.analyzer_qc(&thresholds).unwrap()[0].flagged(), 0);
+
+        let strict: QcThresholds = "co2_tolerance=0.01, h2o_tolerance=0".parse().unwrap();
+        let checks = sealed.analyzer_qc(&strict).unwrap();
+        assert!(checks[4].flagged() > 0);
+        assert!("flow=1".parse::<QcThresholds>().is_err());
+
+        let data = leaking.with_qc_columns(&checks).unwrap();
+        assert_eq!(data.dataframe.column("qc_leak_flow").unwrap().dtype(), &polars::datatypes::DataType::Boolean);
+    }
+}
@@ -0,0 +1,443 @@
+//! Gas-exchange results recomputed from the logged measurements
+//!
+//! Uses the LI-6800 steady-state equations (no leak or dynamic correction) on
+//! `Flow`, `CO2_r`, `CO2_s`, `H2O_r`, `H2O_s`, `S`, `K`, `Pa`, `ΔPcham`,
+//! `TleafCnd` and `gbw`, so results can be redone after changing a constant.
+//! Each result is added as `<variable>_rc`, leaving the logged one in place.
+
+use crate::{DataType, ParseError, VariableInfo};
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Oxygen percentage assumed when neither the options nor the header give one
+pub const DEFAULT_OXYGEN: f64 = 21.0;
+
+/// Relative difference between modeled and logged gbw flagged by default
+pub const DEFAULT_GBW_TOLERANCE: f64 = 0.2;
+
+/// Fan speed (rpm) at which a convection model's air speed is given
+const REFERENCE_FAN_SPEED: f64 = 10_000.0;
+
+/// Settings for [`recompute_gas_exchange`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecomputeOptions {
+    /// Oxygen in the air stream (%), overriding the header's `SysConst:Oxygen`
+    pub oxygen: Option<f64>,
+    /// Source of the boundary layer conductance
+    pub boundary_layer: BoundaryLayerModel,
+    /// Relative difference from the logged gbw above which an observation is
+    /// flagged (`DEFAULT_GBW_TOLERANCE` when `None`)
+    pub gbw_tolerance: Option<f64>,
+    /// Cuticular conductance to water vapor (mol m⁻² s⁻¹) to remove from gsw
+    pub cuticular_conductance: Option<f64>,
+}
+
+/// How the boundary layer conductance to water vapor (one side) is obtained
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BoundaryLayerModel {
+    /// The console's value in the `gbw` column
+    #[default]
+    Logged,
+    /// A measured value (mol m⁻² s⁻¹), e.g. from a wet filter paper replica
+    Constant(f64),
+    /// Forced convection over a flat plate, gbw = 0.147 √(u/d) (Campbell &
+    /// Norman 1998), with the air speed scaled by `Fan_speed`
+    Convection {
+        /// Air speed over the leaf at 10,000 rpm (m s⁻¹)
+        speed: f64,
+        /// Characteristic leaf dimension (m)
+        dimension: f64,
+    },
+}
+
+impl BoundaryLayerModel {
+    /// Conductance for one observation
+    fn gbw(&self, logged: Option<f64>, fan_speed: Option<f64>) -> Option<f64> {
+        match *self {
+            BoundaryLayerModel::Logged => logged,
+            BoundaryLayerModel::Constant(gbw) => Some(gbw),
+            BoundaryLayerModel::Convection { speed, dimension } => {
+                let speed = fan_speed.map_or(speed, |fan| speed * fan / REFERENCE_FAN_SPEED);
+                Some(0.147 * (speed.max(0.0) / dimension).sqrt())
+            }
+        }
+    }
+}
+
+impl FromStr for BoundaryLayerModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let usage = || format!(
+            "Invalid boundary layer model '{}': use logged, constant:GBW or convection:SPEED:DIMENSION", s
+        );
+        let parts: Vec<&str> = s.trim().split(':').map(str::trim).collect();
+        let number = |part: &str| part.parse::<f64>().ok().filter(|v| v.is_finite() && *v > 0.0).ok_or_else(usage);
+        match parts.as_slice() {
+            [name] if name.eq_ignore_ascii_case("logged") => Ok(BoundaryLayerModel::Logged),
+            [name, gbw] if name.eq_ignore_ascii_case("constant") => Ok(BoundaryLayerModel::Constant(number(gbw)?)),
+            [name, speed, dimension] if name.eq_ignore_ascii_case("convection") => Ok(BoundaryLayerModel::Convection {
+                speed: number(speed)?,
+                dimension: number(dimension)?,
+            }),
+            _ => Err(usage()),
+        }
+    }
+}
+
+impl RecomputeOptions {
+    /// Oxygen percentage to use: the override, else the header's, else 21%
+    pub fn oxygen_or(&self, header: Option<&str>) -> f64 {
+        self.oxygen
+            .or_else(|| header.and_then(|value| value.trim().parse().ok()))
+            .unwrap_or(DEFAULT_OXYGEN)
+    }
+}
+
+/// Results for one observation
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct GasExchange {
+    e: Option<f64>,
+    a: Option<f64>,
+    gtw: Option<f64>,
+    gsw: Option<f64>,
+    gtc: Option<f64>,
+    ci: Option<f64>,
+    gamma_star: Option<f64>,
+}
+
+/// Output columns: name, units, description
+const RESULTS: [(&str, &str, &str); 7] = [
+    ("E_rc", "mol m-2 s-1", "Transpiration rate, recomputed"),
+    ("A_rc", "µmol m-2 s-1", "Assimilation rate, recomputed"),
+    ("gtw_rc", "mol m-2 s-1", "Total conductance to water vapor, recomputed"),
+    ("gsw_rc", "mol m-2 s-1", "Stomatal conductance to water vapor, recomputed"),
+    ("gtc_rc", "mol m-2 s-1", "Total conductance to CO2, recomputed"),
+    ("Ci_rc", "µmol mol-1", "Intercellular CO2, recomputed"),
+    ("GammaStar", "µmol mol-1", "CO2 compensation point in the absence of day respiration"),
+];
+
+/// Append the recomputed gas-exchange results (`E_rc`, `A_rc`, `gtw_rc`,
+/// `gsw_rc`, `gtc_rc`, `Ci_rc`) and `GammaStar`
+///
+/// With a boundary layer model other than `Logged`, the modeled conductance is
+/// used and added as `gbw_rc`, and `gbw_flag` marks observations where it
+/// differs from the logged `gbw` by more than the tolerance.
+///
+/// With a cuticular conductance gcw, `gsw_rc` is the stomatal part gsw − gcw,
+/// and `Ci_rc` uses only the transpiration through the stomata, E (gsw − gcw)
+/// / gsw, since both paths share the leaf-to-air gradient. CO₂ crossing the
+/// cuticle is neglected (its conductance is a small fraction of gcw). Where
+/// gcw is not below gsw, `gsw_rc`, `gtc_rc` and `Ci_rc` are null.
+///
+/// `options.oxygen` should already hold the file's setting (see
+/// [`RecomputeOptions::oxygen_or`]); it scales Γ*, which is proportional to
+/// the O₂ partial pressure. The logged CO₂ and H₂O already carry the console's
+/// oxygen correction, so the flux equations use them as they are. Rows with a
+/// missing input get nulls.
+pub fn recompute_gas_exchange(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    options: &RecomputeOptions,
+) -> Result<(), ParseError> {
+    let oxygen = options.oxygen.unwrap_or(DEFAULT_OXYGEN);
+    let flow = values(df, "Flow")?;
+    let co2_r = values(df, "CO2_r")?;
+    let co2_s = values(df, "CO2_s")?;
+    let h2o_r = values(df, "H2O_r")?;
+    let h2o_s = values(df, "H2O_s")?;
+    let area = values(df, "S")?;
+    let ratio = values(df, "K")?;
+    let pa = values(df, "Pa")?;
+    let logged_gbw = match options.boundary_layer {
+        BoundaryLayerModel::Logged => values(df, "gbw")?,
+        _ => values(df, "gbw").unwrap_or_else(|_| vec![None; df.height()]),
+    };
+    let fan_speed = values(df, "Fan_speed").ok();
+    let gbw: Vec<Option<f64>> = logged_gbw.iter().enumerate()
+        .map(|(row, logged)| options.boundary_layer.gbw(*logged, fan_speed.as_ref().and_then(|fan| fan[row])))
+        .collect();
+    let tleaf = values(df, "TleafCnd").or_else(|_| values(df, "Tleaf"))?;
+    // Chamber overpressure is small; treat it as zero when not logged
+    let delta_p = values(df, "ΔPcham").unwrap_or_else(|_| vec![Some(0.0); df.height()]);
+
+    let results: Vec<GasExchange> = (0..df.height())
+        .map(|row| {
+            let (Some(flow), Some(cr), Some(cs), Some(wr), Some(ws), Some(s)) =
+                (flow[row], co2_r[row], co2_s[row], h2o_r[row], h2o_s[row], area[row]) else {
+                return GasExchange::default();
+            };
+            let e = flow * (ws - wr) / (100.0 * s * (1000.0 - ws));
+            let a = flow * (cr - cs * (1000.0 - wr) / (1000.0 - ws)) / (100.0 * s);
+            let gamma_star = tleaf[row].map(|t| gamma_star(t, oxygen));
+
+            let conductances = (|| {
+                let (t, p, gbw, k) = (tleaf[row]?, pa[row]? + delta_p[row].unwrap_or(0.0), gbw[row]?, ratio[row]?);
+                let w_leaf = saturation_vapor_pressure(t) / p * 1000.0;
+                let gtw = e * (1000.0 - (w_leaf + ws) / 2.0) / (w_leaf - ws);
+                let gsw = stomatal_conductance(gtw, gbw, k);
+                let (gsw, e) = match options.cuticular_conductance {
+                    Some(gcw) if gsw - gcw > 0.0 => (gsw - gcw, e * (gsw - gcw) / gsw),
+                    // Nulled by the finiteness check below
+                    Some(_) => (f64::NAN, f64::NAN),
+                    None => (gsw, e),
+                };
+                let gtc = 1.0 / ((k + 1.0) / (gsw / 1.6) + 1.0 / (gbw / 1.37))
+                    + k / ((k + 1.0) / (gsw / 1.6) + k / (gbw / 1.37));
+                let ci = ((gtc - e / 2.0) * cs - a) / (gtc + e / 2.0);
+                Some((gtw, gsw, gtc, ci))
+            })();
+            let (gtw, gsw, gtc, ci) = match conductances {
+                Some((gtw, gsw, gtc, ci)) => (Some(gtw), Some(gsw), Some(gtc), Some(ci)),
+                None => (None, None, None, None),
+            };
+            GasExchange { e: Some(e), a: Some(a), gtw, gsw, gtc, ci, gamma_star }
+        })
+        .collect();
+
+    let columns: [Vec<Option<f64>>; 7] = [
+        results.iter().map(|r| r.e).collect(),
+        results.iter().map(|r| r.a).collect(),
+        results.iter().map(|r| r.gtw).collect(),
+        results.iter().map(|r| r.gsw).collect(),
+        results.iter().map(|r| r.gtc).collect(),
+        results.iter().map(|r| r.ci).collect(),
+        results.iter().map(|r| r.gamma_star).collect(),
+    ];
+    for ((name, units, description), values) in RESULTS.into_iter().zip(columns) {
+        let values: Vec<Option<f64>> = values.into_iter().map(|v| v.filter(|v| v.is_finite())).collect();
+        df.with_column(Column::new(name.into(), values)).map_err(recompute_error)?;
+        let (description, source) = match (name, options.cuticular_conductance) {
+            ("GammaStar", _) => (format!("{} at {}% O2 (Bernacchi et al. 2001)", description, oxygen), "TleafCnd"),
+            ("gsw_rc" | "gtc_rc" | "Ci_rc", Some(gcw)) => {
+                (format!("{} without cuticular conductance gcw = {} mol m-2 s-1", description, gcw), name.trim_end_matches("_rc"))
+            }
+            _ => (description.to_string(), name.trim_end_matches("_rc")),
+        };
+        variable_info.push(VariableInfo {
+            internal_name: name.to_string(),
+            display_label: name.to_string(),
+            units: Some(units.to_string()),
+            units_pretty: Some(crate::pretty_units(units)),
+            units_ascii: Some(crate::ascii_units(units)),
+            description,
+            data_type: DataType::Float,
+            column_category: "Recomputed".to_string(),
+            derived_from: Some(source.to_string()),
+        });
+    }
+
+    if options.boundary_layer != BoundaryLayerModel::Logged {
+        let tolerance = options.gbw_tolerance.unwrap_or(DEFAULT_GBW_TOLERANCE);
+        let flags: Vec<Option<bool>> = gbw.iter().zip(&logged_gbw)
+            .map(|(modeled, logged)| {
+                let (modeled, logged) = ((*modeled)?, (*logged)?);
+                Some((modeled - logged).abs() > tolerance * logged.abs())
+            })
+            .collect();
+        let units = "mol m-2 s-1";
+        for (column, info) in [
+            (Column::new("gbw_rc".into(), gbw), VariableInfo {
+                internal_name: "gbw_rc".to_string(),
+                display_label: "gbw_rc".to_string(),
+                units: Some(units.to_string()),
+                units_pretty: Some(crate::pretty_units(units)),
+                units_ascii: Some(crate::ascii_units(units)),
+                description: format!("Boundary layer conductance to water vapor, {}", options.boundary_layer.describe()),
+                data_type: DataType::Float,
+                column_category: "Recomputed".to_string(),
+                derived_from: Some("gbw".to_string()),
+            }),
+            (Column::new("gbw_flag".into(), flags), VariableInfo {
+                internal_name: "gbw_flag".to_string(),
+                display_label: "gbw_flag".to_string(),
+                units: None,
+                units_pretty: None,
+                units_ascii: None,
+                description: format!("Modeled gbw differs from the logged gbw by more than {}%", tolerance * 100.0),
+                data_type: DataType::Boolean,
+                column_category: "Recomputed".to_string(),
+                derived_from: Some("gbw".to_string()),
+            }),
+        ] {
+            df.with_column(column).map_err(recompute_error)?;
+            variable_info.push(info);
+        }
+    }
+    Ok(())
+}
+
+impl BoundaryLayerModel {
+    /// Model and parameters, for the `gbw_rc` description
+    fn describe(&self) -> String {
+        match self {
+            BoundaryLayerModel::Logged => "as logged".to_string(),
+            BoundaryLayerModel::Constant(gbw) => format!("constant {}", gbw),
+            BoundaryLayerModel::Convection { speed, dimension } => format!(
+                "forced convection at {} m s-1 per 10000 rpm over {} m (Campbell & Norman 1998)", speed, dimension
+            ),
+        }
+    }
+}
+
+/// Saturation vapor pressure (kPa) at `t` °C, as used by the LI-6800
+fn saturation_vapor_pressure(t: f64) -> f64 {
+    0.61365 * (17.502 * t / (240.97 + t)).exp()
+}
+
+/// Stomatal conductance to water vapor from the total conductance, with `k`
+/// the stomatal ratio (0 for leaves with stomata on one side)
+fn stomatal_conductance(gtw: f64, gbw: f64, k: f64) -> f64 {
+    let kf = (k * k + 1.0) / ((k + 1.0) * (k + 1.0));
+    let x = 1.0 / gtw - kf / gbw;
+    let root = (x * x + 4.0 * k / ((k + 1.0) * (k + 1.0)) * (2.0 / (gtw * gbw) - kf / (gbw * gbw))).sqrt();
+    2.0 / (x + gtw.signum() * root)
+}
+
+/// Γ* (µmol mol⁻¹) at leaf temperature `t` °C, from its 25 °C value at 21% O₂
+/// scaled to `oxygen` %
+fn gamma_star(t: f64, oxygen: f64) -> f64 {
+    const GAMMA_STAR_25: f64 = 42.75;
+    const ACTIVATION_ENERGY: f64 = 37_830.0;
+    const GAS_CONSTANT: f64 = 8.314;
+    let kelvin = t + 273.15;
+    GAMMA_STAR_25 * oxygen / 21.0
+        * (ACTIVATION_ENERGY * (kelvin - 298.15) / (298.15 * GAS_CONSTANT * kelvin)).exp()
+}
+
+/// Values of a column as `f64`
+fn values(df: &DataFrame, name: &str) -> Result<Vec<Option<f64>>, ParseError> {
+    let column = df.column(name)
+        .map_err(|_| ParseError::UnknownVariable { variable: name.to_string() })?
+        .cast(&polars::datatypes::DataType::Float64)
+        .map_err(recompute_error)?;
+    Ok(column.f64().map_err(recompute_error)?.into_iter().collect())
+}
+
+fn recompute_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to recompute gas exchange: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recompute_gas_exchange() {
+        // First observation of the F2 example
+        let mut df = df! {
+            "Flow" => [Some(500.0692), None],
+            "CO2_r" => [1200.0027, 1200.0027],
+            "CO2_s" => [1201.4183, 1201.4183],
+            "H2O_r" => [22.079673, 22.079673],
+            "H2O_s" => [22.607067, 22.607067],
+            "S" => [6.0, 6.0],
+            "K" => [0.0, 0.0],
+            "Pa" => [100.10873, 100.10873],
+            "ΔPcham" => [0.03664, 0.03664],
+            "TleafCnd" => [25.011187, 25.011187],
+            "gbw" => [2.2605017, 2.2605017],
+        }.unwrap();
+        let mut info = Vec::new();
+        let options = RecomputeOptions { oxygen: Some(2.0), ..RecomputeOptions::default() };
+        recompute_gas_exchange(&mut df, &mut info, &options).unwrap();
+
+        let get = |name: &str| df.column(name).unwrap().f64().unwrap().get(0);
+        let (e, a) = (get("E_rc").unwrap(), get("A_rc").unwrap());
+        // The logged E and A (0.000458, -1.744) include the leak correction
+        assert!((e - 0.00044972).abs() < 1e-8);
+        assert!((a + 1.72019).abs() < 1e-4);
+        // gsw and gtc reduce to the one-sided formulas with K = 0
+        let (gtw, gsw, gtc) = (get("gtw_rc").unwrap(), get("gsw_rc").unwrap(), get("gtc_rc").unwrap());
+        assert!((1.0 / gsw - (1.0 / gtw - 1.0 / 2.2605017)).abs() < 1e-9);
+        assert!((1.0 / gtc - (1.6 / gsw + 1.37 / 2.2605017)).abs() < 1e-9);
+        assert!(get("Ci_rc").unwrap() > 1201.4183);
+        assert_eq!(df.column("A_rc").unwrap().null_count(), 1);
+
+        // Γ* scales with oxygen: 42.75 at 25 °C and 21%
+        assert!((gamma_star(25.0, 21.0) - 42.75).abs() < 1e-9);
+        let gamma = get("GammaStar").unwrap();
+        assert!((gamma - gamma_star(25.011187, 21.0) * 2.0 / 21.0).abs() < 1e-9);
+        assert!(info.last().unwrap().description.contains("2% O2"));
+
+        assert_eq!(RecomputeOptions::default().oxygen_or(Some("2.0")), 2.0);
+        assert_eq!(RecomputeOptions { oxygen: Some(10.0), ..RecomputeOptions::default() }.oxygen_or(Some("21.0")), 10.0);
+        assert_eq!(RecomputeOptions::default().oxygen_or(None), 21.0);
+
+        let mut missing = df! { "Flow" => [500.0] }.unwrap();
+        assert!(recompute_gas_exchange(&mut missing, &mut info, &RecomputeOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_boundary_layer_models() {
+        assert_eq!("logged".parse(), Ok(BoundaryLayerModel::Logged));
+        assert_eq!("constant:2.5".parse(), Ok(BoundaryLayerModel::Constant(2.5)));
+        let model: BoundaryLayerModel = "convection:1.0:0.01".parse().unwrap();
+        assert!("convection:1.0".parse::<BoundaryLayerModel>().is_err());
+        assert!("constant:-1".parse::<BoundaryLayerModel>().is_err());
+        // 0.147 √(1 / 0.01), and a quarter of the fan speed halves √u
+        assert!((model.gbw(None, None).unwrap() - 1.47).abs() < 1e-12);
+        assert!((model.gbw(None, Some(2500.0)).unwrap() - 0.735).abs() < 1e-12);
+
+        let mut df = df! {
+            "Flow" => [500.0, 500.0],
+            "CO2_r" => [400.0, 400.0],
+            "CO2_s" => [390.0, 390.0],
+            "H2O_r" => [20.0, 20.0],
+            "H2O_s" => [22.0, 22.0],
+            "S" => [6.0, 6.0],
+            "K" => [0.0, 0.0],
+            "Pa" => [100.0, 100.0],
+            "TleafCnd" => [25.0, 25.0],
+            "gbw" => [2.6, 3.5],
+        }.unwrap();
+        let mut info = Vec::new();
+        let options = RecomputeOptions { boundary_layer: BoundaryLayerModel::Constant(2.5), ..RecomputeOptions::default() };
+        recompute_gas_exchange(&mut df, &mut info, &options).unwrap();
+        let flags: Vec<_> = df.column("gbw_flag").unwrap().bool().unwrap().into_iter().collect();
+        assert_eq!(flags, [Some(false), Some(true)]);
+        // Both rows use the constant, whatever was logged
+        let gsw: Vec<_> = df.column("gsw_rc").unwrap().f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(gsw[0], gsw[1]);
+        assert!(matches!(info.last().unwrap().data_type, DataType::Boolean));
+    }
+
+    #[test]
+    fn test_cuticular_conductance() {
+        let frame = df! {
+            "Flow" => [500.0, 500.0],
+            "CO2_r" => [400.0, 400.0],
+            "CO2_s" => [390.0, 390.0],
+            "H2O_r" => [20.0, 20.0],
+            "H2O_s" => [22.0, 21.0],
+            "S" => [6.0, 6.0],
+            "K" => [0.0, 0.0],
+            "Pa" => [100.0, 100.0],
+            "TleafCnd" => [25.0, 25.0],
+            "gbw" => [2.5, 2.5],
+        }.unwrap();
+        let column = |df: &DataFrame, name: &str| -> Vec<Option<f64>> {
+            df.column(name).unwrap().f64().unwrap().into_iter().collect()
+        };
+        let mut plain = frame.clone();
+        recompute_gas_exchange(&mut plain, &mut Vec::new(), &RecomputeOptions::default()).unwrap();
+        let gsw = column(&plain, "gsw_rc");
+
+        let gcw = gsw[1].unwrap() + 0.001;
+        let mut corrected = frame;
+        let mut info = Vec::new();
+        let options = RecomputeOptions { cuticular_conductance: Some(gcw), ..RecomputeOptions::default() };
+        recompute_gas_exchange(&mut corrected, &mut info, &options).unwrap();
+        let stomatal = column(&corrected, "gsw_rc");
+        assert!((stomatal[0].unwrap() - (gsw[0].unwrap() - gcw)).abs() < 1e-12);
+        // Less water through the stomata means a lower Ci for the same A
+        assert!(column(&corrected, "Ci_rc")[0].unwrap() < column(&plain, "Ci_rc")[0].unwrap());
+        // gcw above gsw leaves nothing to attribute to the stomata
+        assert_eq!(stomatal[1], None);
+        assert_eq!(column(&corrected, "Ci_rc")[1], None);
+        assert_eq!(column(&corrected, "gtw_rc"), column(&plain, "gtw_rc"));
+        assert!(info.iter().any(|v| v.internal_name == "Ci_rc" && v.description.contains("cuticular")));
+    }
+}
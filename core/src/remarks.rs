@@ -0,0 +1,168 @@
+//! Structured remarks mapped into columns
+//!
+//! Remarks typed on the console are logged among the data rows as
+//! `HH:MM:SS Remark=...` lines. A [`RemarkParser`] turns a remark written in
+//! some grammar (barcode scans, GPS fixes, a lab's sample codes) into named
+//! fields, each of which becomes a column holding the value in effect on every
+//! row logged after the remark.
+
+use crate::{ConsoleMessage, DataType, ParseError, VariableInfo};
+use polars::prelude::*;
+use std::fmt::Debug;
+
+/// A grammar for structured remarks
+///
+/// Parsers are tried in the order given in
+/// [`ParseOptions::remark_parsers`](crate::ParseOptions::remark_parsers); the
+/// first to recognize a remark maps it.
+pub trait RemarkParser: Debug + Send + Sync {
+    /// Fields of a remark as `(column, value)` pairs, or `None` when the
+    /// remark is not in this grammar
+    fn parse(&self, remark: &str) -> Option<Vec<(String, String)>>;
+}
+
+/// Built-in grammar: `NAME=VALUE` or `NAME: VALUE` pairs separated by `;` or
+/// `,` become `remark_<NAME>` columns, and any other remark the `remark` column
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyValueRemarks;
+
+impl RemarkParser for KeyValueRemarks {
+    fn parse(&self, remark: &str) -> Option<Vec<(String, String)>> {
+        let remark = remark.trim();
+        if remark.is_empty() {
+            return None;
+        }
+        let pairs: Option<Vec<(String, String)>> = remark.split([';', ','])
+            .map(|part| {
+                let (name, value) = part.split_once('=').or_else(|| part.split_once(':'))?;
+                let name = name.split_whitespace().collect::<Vec<_>>().join("_");
+                (!name.is_empty()).then(|| (format!("remark_{}", name), value.trim().to_string()))
+            })
+            .collect();
+        Some(pairs.unwrap_or_else(|| vec![("remark".to_string(), remark.to_string())]))
+    }
+}
+
+/// Text of a remark message, without its time and `Remark=` label
+pub fn remark_text(message: &str) -> Option<&str> {
+    let text = match message.split_once(' ') {
+        Some((time, text)) if crate::fluorometer::is_time(time) => text,
+        _ => message,
+    };
+    text.trim_start().strip_prefix("Remark=").or_else(|| text.trim_start().strip_prefix("Remark:"))
+}
+
+/// Add a column for each field of the remarks the parsers recognize, holding
+/// the latest value logged before each row
+///
+/// Columns whose values are all numbers are numeric, the rest text. The rows
+/// must be as read (before any trimming or sorting).
+pub(crate) fn add_remark_columns(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    messages: &[ConsoleMessage],
+    parsers: &[std::sync::Arc<dyn RemarkParser>],
+) -> Result<(), ParseError> {
+    if parsers.is_empty() {
+        return Ok(());
+    }
+    let height = df.height();
+    let mut columns: Vec<(String, Vec<Option<String>>)> = Vec::new();
+    for message in messages {
+        let Some(fields) = remark_text(&message.text).and_then(|text| parsers.iter().find_map(|parser| parser.parse(text))) else {
+            continue;
+        };
+        for (name, value) in fields {
+            let index = match columns.iter().position(|(column, _)| *column == name) {
+                Some(index) => index,
+                None => {
+                    columns.push((name, vec![None; height]));
+                    columns.len() - 1
+                }
+            };
+            columns[index].1[message.after_row.min(height)..].fill(Some(value));
+        }
+    }
+
+    for (name, values) in columns {
+        if df.column(&name).is_ok() {
+            return Err(ParseError::InvalidHeaderFormat {
+                message: format!("Remark field '{}' has the name of a logged column", name)
+            });
+        }
+        let numbers: Option<Vec<Option<f64>>> = values.iter()
+            .map(|value| value.as_deref().map_or(Some(None), |v| v.parse::<f64>().ok().map(Some)))
+            .collect();
+        let is_number = numbers.is_some();
+        let column = match numbers {
+            Some(numbers) => Column::new(name.as_str().into(), numbers),
+            None => Column::new(name.as_str().into(), values),
+        };
+        df.with_column(column).map_err(|e| ParseError::InvalidHeaderFormat {
+            message: format!("Failed to add remark column: {}", e)
+        })?;
+        variable_info.push(VariableInfo {
+            internal_name: name.clone(),
+            display_label: name.clone(),
+            units: None,
+            units_pretty: None,
+            units_ascii: None,
+            description: format!("Remark field in effect: {}", name),
+            data_type: if is_number { DataType::Float } else { DataType::String },
+            column_category: "Remarks".to_string(),
+            derived_from: Some("Remark".to_string()),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::{LiCor6800Fluorometer, ParseOptions};
+    use std::sync::Arc;
+
+    /// Barcode scans such as `BC#A1234`
+    #[derive(Debug)]
+    struct Barcodes;
+
+    impl RemarkParser for Barcodes {
+        fn parse(&self, remark: &str) -> Option<Vec<(String, String)>> {
+            let code = remark.trim().strip_prefix("BC#")?;
+            Some(vec![("barcode".to_string(), code.to_string())])
+        }
+    }
+
+    #[test]
+    fn test_remark_columns() {
+        assert_eq!(KeyValueRemarks.parse("plant = 3; leaf: 2").unwrap(), vec![
+            ("remark_plant".to_string(), "3".to_string()),
+            ("remark_leaf".to_string(), "2".to_string()),
+        ]);
+        assert_eq!(KeyValueRemarks.parse("sunny, windy").unwrap(), vec![("remark".to_string(), "sunny, windy".to_string())]);
+        assert_eq!(remark_text("12:07:00 Remark=leaf 2"), Some("leaf 2"));
+        assert_eq!(remark_text("12:07:00 Launched AutoProg /User/Prog"), None);
+
+        let content = std::fs::read_to_string("../example_data/2025-05-30-1203_logdata_F2").unwrap();
+        let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let first = lines.iter().position(|line| line.starts_with("1\t")).unwrap();
+        lines.insert(first + 2, "12:07:00\tRemark=plant=3;leaf=2".to_string());
+        lines.insert(first, "12:04:30\tRemark=BC#A1234".to_string());
+        let content = lines.join("\n");
+
+        let options = ParseOptions {
+            remark_parsers: vec![Arc::new(Barcodes), Arc::new(KeyValueRemarks)],
+            ..ParseOptions::default()
+        };
+        let data = LiCor6800Fluorometer::with_options(options).parse_content(&content).unwrap();
+        let df = &data.dataframe;
+        assert_eq!(df.column("barcode").unwrap().str().unwrap().get(0), Some("A1234"));
+        let plant: Vec<_> = df.column("remark_plant").unwrap().f64().unwrap().into_iter().take(3).collect();
+        assert_eq!(plant, vec![None, None, Some(3.0)]);
+        assert_eq!(data.metadata.console_messages.len(), 2);
+
+        // Without parsers, remarks stay console messages only
+        let plain = LiCor6800Fluorometer::new().parse_content(&content).unwrap();
+        assert_eq!(plain.dataframe.width() + 3, df.width());
+    }
+}
@@ -0,0 +1,178 @@
+//! Best-effort repair of damaged text logs
+//!
+//! [`repair`] fixes the damage [`diagnose`](crate::diagnose) most often finds in
+//! copied files and returns the cleaned text with a list of every change, so
+//! the original can be kept and the repair checked.
+
+use crate::RawLiCorFile;
+
+/// A change made to a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    /// Line of the file (1-based) the change is on, if it is on one
+    pub line: Option<usize>,
+    pub change: String,
+}
+
+impl Repair {
+    fn new(line: Option<usize>, change: impl Into<String>) -> Self {
+        Repair { line, change: change.into() }
+    }
+}
+
+/// Clean a file's encoding, line endings, repeated header blocks and cut-off
+/// last observation
+///
+/// UTF-16 and Latin-1 text is converted to UTF-8, NUL bytes are removed, mixed
+/// line endings become `\n`, a `[Header]` block repeated inside the data (as
+/// when the console appends to a log after a restart) is dropped when its
+/// columns match, and a last observation cut off without a line ending is
+/// deleted. Lines are numbered as in the file after its line endings are fixed.
+pub fn repair(bytes: &[u8]) -> (String, Vec<Repair>) {
+    let mut repairs = Vec::new();
+    let content = if let Some(text) = decode_utf16(bytes) {
+        repairs.push(Repair::new(None, "Converted UTF-16 text to UTF-8"));
+        text
+    } else {
+        let nuls = bytes.iter().filter(|&&b| b == 0).count();
+        let bytes: Vec<u8> = if nuls > 0 {
+            let first = bytes.iter().position(|&b| b == 0).unwrap_or_default();
+            let line = bytes[..first].iter().filter(|&&b| b == b'\n').count() + 1;
+            repairs.push(Repair::new(Some(line), format!("Removed {} NUL bytes", nuls)));
+            bytes.iter().copied().filter(|&b| b != 0).collect()
+        } else {
+            bytes.to_vec()
+        };
+        match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => {
+                let line = e.as_bytes()[..e.utf8_error().valid_up_to()].iter().filter(|&&b| b == b'\n').count() + 1;
+                repairs.push(Repair::new(Some(line), "Converted Latin-1 text to UTF-8"));
+                e.as_bytes().iter().map(|&b| b as char).collect()
+            }
+        }
+    };
+    let content = match content.strip_prefix('\u{feff}') {
+        Some(rest) => {
+            repairs.push(Repair::new(Some(1), "Removed the byte order mark"));
+            rest.to_string()
+        }
+        None => content,
+    };
+
+    // CRLF alone is read fine; a mix, or old Mac CR endings, is not
+    let crlf = content.matches("\r\n").count();
+    let cr = content.matches('\r').count() - crlf;
+    let lf = content.matches('\n').count() - crlf;
+    let content = if cr > 0 || (crlf > 0 && lf > 0) {
+        let endings: Vec<String> = [(crlf, "CRLF"), (cr, "CR")].iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, name)| format!("{} {}", count, name))
+            .collect();
+        repairs.push(Repair::new(None, format!("Converted {} line endings to LF", endings.join(" and "))));
+        content.replace("\r\n", "\n").replace('\r', "\n")
+    } else {
+        content
+    };
+
+    let ends_with_newline = content.ends_with('\n');
+    let mut lines: Vec<&str> = content.lines().collect();
+    let Some(data_start) = lines.iter().position(|line| line.trim() == "[Data]") else {
+        return (finish(&lines), repairs);
+    };
+    let names = lines.get(data_start + 2).copied().unwrap_or_default();
+    let columns = lines[data_start + 1..].iter().take(3).map(|line| line.split('\t').count()).max().unwrap_or_default();
+
+    let mut removed = vec![false; lines.len()];
+    let mut index = data_start + 1;
+    while index < lines.len() {
+        if lines[index].trim() == "[Header]" {
+            let table_end = lines[index..].iter()
+                .position(|line| line.trim() == "[Data]")
+                .map(|offset| index + offset + 4)
+                .filter(|&end| end <= lines.len() && lines[end - 2] == names);
+            if let Some(end) = table_end {
+                repairs.push(Repair::new(Some(index + 1), format!("Removed the header block repeated on lines {}-{}", index + 1, end)));
+                removed[index..end].fill(true);
+                index = end;
+                continue;
+            }
+        }
+        index += 1;
+    }
+
+    let last = lines.iter().enumerate()
+        .rposition(|(index, line)| !removed[index] && !line.trim().is_empty());
+    if let Some(last) = last.filter(|&last| last > data_start + 3 && !ends_with_newline) {
+        let fields = lines[last].split('\t').count();
+        if fields < columns && !RawLiCorFile::is_console_message(lines[last], columns) {
+            repairs.push(Repair::new(Some(last + 1), format!("Removed the last observation, cut off after {} of {} fields", fields, columns)));
+            removed[last] = true;
+        }
+    }
+
+    let mut index = 0;
+    lines.retain(|_| {
+        index += 1;
+        !removed[index - 1]
+    });
+    (finish(&lines), repairs)
+}
+
+/// Text of a file with a UTF-16 byte order mark
+fn decode_utf16(bytes: &[u8]) -> Option<String> {
+    let unit: fn([u8; 2]) -> u16 = match bytes.get(..2)? {
+        [0xFF, 0xFE] => u16::from_le_bytes,
+        [0xFE, 0xFF] => u16::from_be_bytes,
+        _ => return None,
+    };
+    let units: Vec<u16> = bytes[2..].chunks_exact(2).map(|pair| unit([pair[0], pair[1]])).collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+fn finish(lines: &[&str]) -> String {
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::{diagnose, LiCor6800Fluorometer};
+
+    #[test]
+    fn test_repair() {
+        let content = std::fs::read_to_string("../example_data/2025-05-30-1203_logdata_F2").unwrap();
+        assert_eq!(repair(content.as_bytes()), (content.clone(), Vec::new()));
+
+        // Logging resumed after a restart, some lines with CRLF, a NUL and a cut-off copy
+        let lines: Vec<&str> = content.lines().collect();
+        let data_start = lines.iter().position(|line| *line == "[Data]").unwrap();
+        let mut damaged: Vec<String> = lines[..data_start + 6].iter().map(|line| line.to_string()).collect();
+        damaged.extend(lines[..data_start + 4].iter().map(|line| line.to_string()));
+        damaged.extend(lines[data_start + 6..].iter().map(|line| line.to_string()));
+        damaged[data_start + 4].push('\r');
+        damaged[data_start + 5].insert(0, '\0');
+        let mut damaged = damaged.join("\n");
+        damaged.truncate(damaged.len() - 100);
+
+        let (repaired, repairs) = repair(damaged.as_bytes());
+        let changes: Vec<&str> = repairs.iter().map(|r| r.change.as_str()).collect();
+        assert_eq!(changes, [
+            "Removed 1 NUL bytes",
+            "Converted 1 CRLF line endings to LF",
+            &format!("Removed the header block repeated on lines {}-{}", data_start + 7, 2 * data_start + 10),
+            &format!("Removed the last observation, cut off after {} of {} fields",
+                lines.last().unwrap()[..lines.last().unwrap().len() - 100].split('\t').count(),
+                lines[data_start + 2].split('\t').count()),
+        ]);
+        assert_eq!(repairs[0].line, Some(data_start + 6));
+        assert_eq!(repaired, lines[..lines.len() - 1].join("\n") + "\n");
+        assert_eq!(diagnose(repaired.as_bytes()), Vec::new());
+        LiCor6800Fluorometer::new().parse_content(&repaired).unwrap();
+
+        let utf16: Vec<u8> = [0xFF, 0xFE].into_iter().chain(content.encode_utf16().flat_map(u16::to_le_bytes)).collect();
+        assert_eq!(repair(&utf16).0, content);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{ParseError, LiCorData, VariableInfo, DataType};
+use polars::prelude::*;
+
+/// Columns of long-format data, with their type and description
+const LONG_COLUMNS: [(&str, DataType, &str); 6] = [
+    ("obs", DataType::Integer, "Observation number"),
+    ("timestamp", DataType::Integer, "Observation time (UTC, milliseconds since the Unix epoch)"),
+    ("variable", DataType::String, "Name of the variable"),
+    ("value", DataType::Float, "Value of the variable, in its units"),
+    ("units", DataType::String, "Units of the variable"),
+    ("category", DataType::String, "Category of the variable"),
+];
+
+impl LiCorData {
+    /// Reshape the data into long ("tidy") format, keeping its variable info
+    ///
+    /// The frame is [`to_long_format`](Self::to_long_format). Its variable info
+    /// describes the six long columns, followed by the info of every variable
+    /// that appears in the `variable` column, so units and descriptions still
+    /// travel with the data (e.g. to the XLSX variable sheet or Parquet
+    /// `licor.variable_info`).
+    pub fn to_long(&self) -> Result<LiCorData, ParseError> {
+        let dataframe = self.to_long_format()?;
+        let mut variable_info: Vec<VariableInfo> = LONG_COLUMNS.iter()
+            .map(|(name, data_type, description)| VariableInfo {
+                internal_name: name.to_string(),
+                display_label: name.to_string(),
+                units: None,
+                units_pretty: None,
+                units_ascii: None,
+                description: description.to_string(),
+                data_type: data_type.clone(),
+                column_category: "Long".to_string(),
+                derived_from: None,
+            })
+            .collect();
+        let variables = dataframe.column("variable").map_err(reshape_error)?
+            .unique_stable().map_err(reshape_error)?;
+        for name in variables.str().map_err(reshape_error)?.into_no_null_iter() {
+            variable_info.extend(self.variable_info.iter().find(|v| v.internal_name == name).cloned());
+        }
+        Ok(LiCorData {
+            metadata: self.metadata.clone(),
+            dataframe,
+            variable_info,
+            coercion_reports: self.coercion_reports.clone(),
+        })
+    }
+
+    /// Reshape the observations into long ("tidy") format
+    ///
+    /// Returns one row per observation and variable with the columns `obs`,
+    /// `timestamp` (from the epoch `time` column, null when absent), `variable`,
+    /// `value`, `units` and `category`. Only numeric and boolean variables are
+    /// included; booleans become 0/1. Text columns such as dates and remarks are
+    /// left out because they have no numeric value.
+    pub fn to_long_format(&self) -> Result<DataFrame, ParseError> {
+        let df = &self.dataframe;
+        let obs = optional_column(df, "obs", &polars::datatypes::DataType::Int64)?;
+        let time = optional_column(df, "time", &polars::datatypes::DataType::Float64)?;
+        let obs = obs.as_ref().map(|c| c.i64()).transpose().map_err(reshape_error)?;
+        let time = time.as_ref().map(|c| c.f64()).transpose().map_err(reshape_error)?;
+
+        let mut obs_values: Vec<Option<i64>> = Vec::new();
+        let mut timestamps: Vec<Option<i64>> = Vec::new();
+        let mut variables: Vec<&str> = Vec::new();
+        let mut values: Vec<Option<f64>> = Vec::new();
+        let mut units: Vec<Option<&str>> = Vec::new();
+        let mut categories: Vec<Option<&str>> = Vec::new();
+
+        for column in df.get_columns() {
+            let name = column.name().as_str();
+            if name == "obs" || name == "time" || !is_numeric(column.dtype()) {
+                continue;
+            }
+            let info = self.variable_info.iter().find(|v| v.internal_name == name);
+            let column_values = column.cast(&polars::datatypes::DataType::Float64).map_err(reshape_error)?;
+            let column_values = column_values.f64().map_err(reshape_error)?;
+
+            for (row, value) in column_values.into_iter().enumerate() {
+                obs_values.push(obs.and_then(|o| o.get(row)));
+                // Epoch seconds to milliseconds
+                timestamps.push(time.and_then(|t| t.get(row)).map(|t| (t * 1000.0).round() as i64));
+                variables.push(name);
+                values.push(value);
+                units.push(info.and_then(|i| i.units.as_deref()));
+                categories.push(info.map(|i| i.column_category.as_str()));
+            }
+        }
+
+        let timestamp = Int64Chunked::from_iter_options("timestamp".into(), timestamps.into_iter())
+            .into_datetime(TimeUnit::Milliseconds, None);
+        DataFrame::new(vec![
+            Column::new("obs".into(), obs_values),
+            timestamp.into_column(),
+            Column::new("variable".into(), variables),
+            Column::new("value".into(), values),
+            Column::new("units".into(), units),
+            Column::new("category".into(), categories),
+        ]).map_err(reshape_error)
+    }
+}
+
+fn optional_column(df: &DataFrame, name: &str, dtype: &polars::datatypes::DataType) -> Result<Option<Column>, ParseError> {
+    df.column(name).ok()
+        .map(|column| column.cast(dtype))
+        .transpose()
+        .map_err(reshape_error)
+}
+
+fn is_numeric(dtype: &polars::datatypes::DataType) -> bool {
+    dtype.is_primitive_numeric() || matches!(dtype, polars::datatypes::DataType::Boolean)
+}
+
+fn reshape_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to reshape data: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LiCorMetadata, VariableInfo, DataType};
+
+    #[test]
+    fn test_to_long_format() {
+        let data = LiCorData {
+            metadata: LiCorMetadata {
+                device_serial: "68C-901292".to_string(),
+                console_version: "Bluestem v.2.1.13".to_string(),
+                ..Default::default()
+            },
+            dataframe: df! {
+                "obs" => [1.0, 2.0],
+                "time" => [1748526912.1, 1748526972.1],
+                "A" => [Some(12.5), None],
+                "date" => ["20250529 09:55:12", "20250529 09:56:12"],
+                "Fan" => [true, false],
+            }.unwrap(),
+            variable_info: vec![VariableInfo {
+                internal_name: "A".to_string(),
+                display_label: "A".to_string(),
+                units: Some("µmol m⁻² s⁻¹".to_string()),
+                units_pretty: Some("µmol m⁻² s⁻¹".to_string()),
+                units_ascii: Some("umol m-2 s-1".to_string()),
+                description: String::new(),
+                data_type: DataType::Float,
+                column_category: "GasEx".to_string(),
+                derived_from: None,
+            }],
+            coercion_reports: Vec::new(),
+        };
+
+        let long = data.to_long_format().unwrap();
+        assert_eq!(long.height(), 4);
+        assert_eq!(
+            long.get_column_names_str(),
+            vec!["obs", "timestamp", "variable", "value", "units", "category"]
+        );
+
+        let variables: Vec<_> = long.column("variable").unwrap().str().unwrap().into_no_null_iter().collect();
+        assert_eq!(variables, vec!["A", "A", "Fan", "Fan"]);
+        let values: Vec<_> = long.column("value").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(values, vec![Some(12.5), None, Some(1.0), Some(0.0)]);
+        assert_eq!(long.column("units").unwrap().str().unwrap().get(0), Some("µmol m⁻² s⁻¹"));
+        assert_eq!(long.column("obs").unwrap().i64().unwrap().get(3), Some(2));
+        assert_eq!(
+            long.column("timestamp").unwrap().datetime().unwrap().phys.get(0),
+            Some(1748526912100)
+        );
+
+        // The long columns are described, then the variables they hold
+        let long = data.to_long().unwrap();
+        let names: Vec<_> = long.variable_info.iter().map(|v| v.internal_name.as_str()).collect();
+        assert_eq!(names, vec!["obs", "timestamp", "variable", "value", "units", "category", "A"]);
+        assert_eq!(long.variable_info[6].units.as_deref(), Some("µmol m⁻² s⁻¹"));
+        assert_eq!(long.dataframe.height(), 4);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::ParseError;
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Order of the observations in the parsed data
+///
+/// By default rows keep the order they were logged in. Short rows padded with
+/// nulls and long rows truncated stay where they were, and console messages are
+/// taken out without moving the rows around them. Sorting is stable, so rows
+/// with equal keys also keep their logged order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RowOrder {
+    /// As logged
+    #[default]
+    Input,
+    /// By the epoch `time` column (`FTime` for the LI-6400), nulls last
+    Time,
+    /// By observation number (`obs`, or `Obs` for the LI-6400), nulls last
+    Obs,
+}
+
+impl FromStr for RowOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "input" | "original" => Ok(RowOrder::Input),
+            "time" => Ok(RowOrder::Time),
+            "obs" => Ok(RowOrder::Obs),
+            _ => Err(format!("Unknown row order '{}': use input, time or obs", s)),
+        }
+    }
+}
+
+/// Sort the frame's rows into `order`
+pub(crate) fn apply_row_order(dataframe: &mut DataFrame, order: RowOrder) -> Result<(), ParseError> {
+    let candidates: &[&str] = match order {
+        RowOrder::Input => return Ok(()),
+        RowOrder::Time => &["time", "FTime"],
+        RowOrder::Obs => &["obs", "Obs"],
+    };
+    let key = candidates.iter()
+        .find(|name| dataframe.column(name).is_ok())
+        .ok_or_else(|| ParseError::UnknownVariable { variable: candidates[0].to_string() })?;
+    let sort_error = |e: PolarsError| ParseError::InvalidHeaderFormat {
+        message: format!("Failed to sort rows by {}: {}", key, e)
+    };
+    // Sorted as numbers, whether or not the column converted to a number type
+    let values = dataframe.column(key).map_err(sort_error)?
+        .cast(&polars::datatypes::DataType::Float64)
+        .map_err(sort_error)?;
+    let sort_options = SortOptions::default()
+        .with_maintain_order(true)
+        .with_nulls_last(true);
+    let indices = values.as_materialized_series().arg_sort(sort_options);
+    *dataframe = dataframe.take(&indices).map_err(sort_error)?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::{LiCor6800Fluorometer, ParseOptions};
+
+    #[test]
+    fn test_row_order() {
+        // Swap the first two observations and cut the third one short
+        let content = std::fs::read_to_string("../example_data/2025-05-30-1203_logdata_F2").unwrap();
+        let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let first = lines.iter().position(|line| line.starts_with("1\t")).unwrap();
+        lines.swap(first, first + 1);
+        let short: Vec<&str> = lines[first + 2].split('\t').take(5).collect();
+        lines[first + 2] = short.join("\t");
+        let content = lines.join("\n");
+
+        let obs = |order: RowOrder| -> Vec<Option<i64>> {
+            let options = ParseOptions { row_order: order, ..ParseOptions::default() };
+            let data = LiCor6800Fluorometer::with_options(options).parse_content(&content).unwrap();
+            data.dataframe.column("obs").unwrap().cast(&DataType::Int64).unwrap().i64().unwrap().into_iter().collect()
+        };
+        assert_eq!(obs(RowOrder::Input)[..4], [Some(2), Some(1), Some(3), Some(4)]);
+        assert_eq!(obs(RowOrder::Obs)[..4], [Some(1), Some(2), Some(3), Some(4)]);
+
+        assert_eq!(obs(RowOrder::Time), obs(RowOrder::Obs));
+        assert!("elapsed".parse::<RowOrder>().is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{DataType, LiCorConfig, LiCorDevice, VariableDef, VARIABLE_DEFINITIONS};
+
+/// One column of the data a device and configuration produce
+#[derive(Debug, Clone)]
+pub struct SchemaColumn {
+    /// Column name as the device logs it
+    pub name: String,
+    pub data_type: DataType,
+    pub units: Option<String>,
+    /// Section of `licor.toml` defining the variable
+    pub category: String,
+    pub description: String,
+    /// Whether the configuration requires the column (others appear when logged)
+    pub required: bool,
+}
+
+impl DataType {
+    /// Polars/Arrow type of a column holding this type
+    pub fn arrow_name(&self) -> &'static str {
+        match self {
+            DataType::Float => "Float64",
+            DataType::Integer => "Int64",
+            DataType::String => "String",
+            DataType::Boolean => "Boolean",
+        }
+    }
+
+    /// SQL type of a column holding this type
+    pub fn sql_name(&self) -> &'static str {
+        match self {
+            DataType::Float => "DOUBLE PRECISION",
+            DataType::Integer => "BIGINT",
+            DataType::String => "TEXT",
+            DataType::Boolean => "BOOLEAN",
+        }
+    }
+}
+
+/// Columns a device and configuration produce: the configuration's required
+/// variables, then (with `all`) every other variable in the dictionary
+///
+/// Which optional columns a file has depends on what the console logged, so
+/// parsed data may hold a subset of them, plus columns the dictionary lacks.
+pub fn output_schema<D: LiCorDevice, C: LiCorConfig>(all: bool) -> Vec<SchemaColumn> {
+    let required = C::expected_variables();
+    let device_name = |canonical: &str| D::column_aliases().iter()
+        .find(|(_, c)| *c == canonical)
+        .map_or(canonical, |(alias, _)| *alias)
+        .to_string();
+    let column = |def: &VariableDef, name: String, required: bool| SchemaColumn {
+        name,
+        data_type: def.data_type.clone(),
+        units: def.units.map(str::to_string),
+        category: def.category.to_string(),
+        description: def.description.to_string(),
+        required,
+    };
+
+    let mut columns: Vec<SchemaColumn> = required.iter()
+        .map(|name| match definition(name, C::CONFIG_NAME) {
+            Some(def) => column(def, device_name(name), true),
+            // Typed from its units when parsed; text when it has none
+            None => SchemaColumn {
+                name: device_name(name),
+                data_type: DataType::String,
+                units: None,
+                category: String::new(),
+                description: "Not in the variable dictionary".to_string(),
+                required: true,
+            },
+        })
+        .collect();
+    if all {
+        for def in VARIABLE_DEFINITIONS.iter() {
+            let name = device_name(def.internal_name);
+            if !columns.iter().any(|c| c.name == name) {
+                let def = definition(def.internal_name, C::CONFIG_NAME).unwrap_or(def);
+                columns.push(column(def, name, false));
+            }
+        }
+    }
+    columns
+}
+
+/// Definition of a variable logged under a configuration, by name or else by
+/// label (`Pa` is defined as `Press`)
+///
+/// When several share the name (`A`, `Qin`, ...), one from the configuration's
+/// own section wins, then a gas-exchange result, and one from another
+/// chamber's section comes last.
+fn definition(name: &str, config: &str) -> Option<&'static VariableDef> {
+    let by_name = |def: &&VariableDef| def.internal_name == name;
+    let by_label = |def: &&VariableDef| def.display_label.replace('’', "'") == name;
+    let candidates: Vec<&VariableDef> = match VARIABLE_DEFINITIONS.iter().filter(by_name).collect::<Vec<_>>() {
+        found if found.is_empty() => VARIABLE_DEFINITIONS.iter().filter(by_label).collect(),
+        found => found,
+    };
+    candidates.into_iter()
+        .min_by_key(|def| {
+            if def.category.contains(config) {
+                0
+            } else if ["aquatic", "soil"].iter().any(|chamber| def.category.contains(chamber)) {
+                3
+            } else if def.category.contains("gas_exchange") {
+                1
+            } else {
+                2
+            }
+        })
+}
+
+/// `CREATE TABLE` statement for the columns, with units and descriptions as
+/// column comments
+pub fn schema_ddl(table: &str, columns: &[SchemaColumn]) -> String {
+    let definitions: Vec<String> = columns.iter()
+        .map(|c| format!("    {} {}", quote_identifier(&c.name), c.data_type.sql_name()))
+        .collect();
+    let mut ddl = format!(
+        "CREATE TABLE IF NOT EXISTS {} (\n{}\n);\n",
+        quote_identifier(table),
+        definitions.join(",\n")
+    );
+    for c in columns {
+        let comment = match &c.units {
+            Some(units) if !units.is_empty() => format!("{} ({})", c.description, units),
+            _ => c.description.clone(),
+        };
+        ddl.push_str(&format!(
+            "COMMENT ON COLUMN {}.{} IS '{}';\n",
+            quote_identifier(table),
+            quote_identifier(&c.name),
+            comment.replace('\'', "''")
+        ));
+    }
+    ddl
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(all(test, feature = "device-6800", feature = "device-6400"))]
+mod tests {
+    use super::*;
+    use crate::{ConfigFluorometer, ConfigStandard, Device6400, Device6800};
+
+    #[test]
+    fn test_output_schema() {
+        let required = output_schema::<Device6800, ConfigStandard>(false);
+        assert_eq!(required.len(), ConfigStandard::expected_variables().len());
+        assert!(required.iter().all(|c| c.required));
+        let a = required.iter().find(|c| c.name == "A").unwrap();
+        assert_eq!(a.data_type.arrow_name(), "Float64");
+        assert_eq!(a.units.as_deref(), Some("µmol m-2 s-1"));
+
+        // LI-6400 logs use their own names
+        let legacy = output_schema::<Device6400, ConfigStandard>(false);
+        assert!(legacy.iter().any(|c| c.name == "Photo"));
+        assert!(!legacy.iter().any(|c| c.name == "A"));
+
+        let all = output_schema::<Device6800, ConfigFluorometer>(true);
+        assert!(all.len() > 400);
+        assert!(!all.iter().find(|c| c.name == "CO2_s").unwrap().required);
+        let qin = output_schema::<Device6800, crate::ConfigAquatic>(false);
+        assert_eq!(qin.iter().find(|c| c.name == "Qin").unwrap().description, "Flux incident on aquatic sample");
+
+        let ddl = schema_ddl("leaf \"gas\"", &required);
+        assert!(ddl.starts_with("CREATE TABLE IF NOT EXISTS \"leaf \"\"gas\"\"\" (\n    \"obs\""));
+        assert!(ddl.contains("\"A\" DOUBLE PRECISION"));
+        assert!(ddl.contains("IS 'Assimilation rate (µmol m-2 s-1)';"));
+        // Defined under another name
+        let pa = required.iter().find(|c| c.name == "Pa").unwrap();
+        assert_eq!(pa.units.as_deref(), Some("kPa"));
+    }
+}
@@ -0,0 +1,124 @@
+//! Fuzzy search over the variable definitions
+//!
+//! Every word of the query is matched against the words of a definition's
+//! name, label, description and category, tolerating prefixes and small typos
+//! (edit distance with transpositions), so "quantm flow" still finds
+//! "Absorbed quantum flow".
+
+use crate::{VariableDef, VARIABLE_DEFINITIONS};
+
+/// Lowest word similarity, from 0 to 1, that counts as a match
+const MIN_SIMILARITY: f64 = 0.75;
+
+/// Variable definitions matching `query`, best match first
+///
+/// A definition matches when every word of the query is similar to one of its
+/// words. An exact name match ranks first, then whole-phrase matches, then
+/// definitions by their mean word similarity.
+pub fn search_variables(query: &str) -> Vec<&'static VariableDef> {
+    let query_words = words(query);
+    if query_words.is_empty() {
+        return Vec::new();
+    }
+    let phrase = query_words.join(" ");
+
+    let mut matches: Vec<(f64, &'static VariableDef)> = VARIABLE_DEFINITIONS.iter()
+        .filter_map(|def| {
+            let score = score(def, query, &phrase, &query_words)?;
+            Some((score, def))
+        })
+        .collect();
+    // Stable, so equal scores keep definition order
+    matches.sort_by(|a, b| b.0.total_cmp(&a.0));
+    matches.into_iter().map(|(_, def)| def).collect()
+}
+
+fn score(def: &VariableDef, query: &str, phrase: &str, query_words: &[String]) -> Option<f64> {
+    if def.internal_name.eq_ignore_ascii_case(query.trim()) {
+        return Some(3.0);
+    }
+    let fields = [def.internal_name, def.display_label, def.description, def.category];
+    if fields.iter().any(|field| words(field).join(" ").contains(phrase)) {
+        return Some(2.0);
+    }
+
+    let def_words: Vec<String> = fields.iter().flat_map(|field| words(field)).collect();
+    let mut total = 0.0;
+    for query_word in query_words {
+        let best = def_words.iter()
+            .map(|word| similarity(query_word, word))
+            .fold(0.0, f64::max);
+        if best < MIN_SIMILARITY {
+            return None;
+        }
+        total += best;
+    }
+    Some(total / query_words.len() as f64)
+}
+
+/// Lowercase alphanumeric words, also splitting `snake_case` names
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// 1 for equal words, 0.9 when the query word starts the word, otherwise one
+/// minus the edit distance relative to the longer word
+fn similarity(query_word: &str, word: &str) -> f64 {
+    if query_word == word {
+        return 1.0;
+    }
+    if query_word.chars().count() >= 2 && word.starts_with(query_word) {
+        return 0.9;
+    }
+    let a: Vec<char> = query_word.chars().collect();
+    let b: Vec<char> = word.chars().collect();
+    1.0 - edit_distance(&a, &b) as f64 / a.len().max(b.len()) as f64
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and adjacent transpositions
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    rows[0] = (0..=b.len()).collect();
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(query: &str) -> Vec<&'static str> {
+        search_variables(query).iter().map(|def| def.internal_name).collect()
+    }
+
+    #[test]
+    fn test_search_variables() {
+        assert_eq!(names("Aperture")[0], "Aperture");
+        assert!(names("boundary layer coefficient").contains(&"blc_a"));
+        // Typos and prefixes
+        assert!(names("quantm flow").contains(&"Qabs*"));
+        assert!(names("aper").contains(&"Aperture"));
+        assert!(names("xyzzy plugh").is_empty());
+        assert!(names("  ").is_empty());
+
+        assert_eq!(edit_distance(&['y', 'e', 'i', 'l', 'd'], &['y', 'i', 'e', 'l', 'd']), 1);
+    }
+}
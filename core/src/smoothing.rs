@@ -0,0 +1,275 @@
+use crate::{ParseError, VariableInfo, DataType};
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Filter applied by a [`SmoothingSpec`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmoothingMethod {
+    RollingMean,
+    RollingMedian,
+    /// Savitzky–Golay filter with the given polynomial order
+    SavitzkyGolay { order: usize },
+}
+
+/// Smoothing of one variable over a centered window of observations
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothingSpec {
+    pub variable: String,
+    pub method: SmoothingMethod,
+    /// Window length in observations (odd)
+    pub window: usize,
+}
+
+impl SmoothingSpec {
+    /// Name of the column holding the smoothed values
+    pub fn output_name(&self) -> String {
+        format!("{}_smooth", self.variable)
+    }
+
+    fn describe(&self) -> String {
+        let method = match self.method {
+            SmoothingMethod::RollingMean => "Rolling mean".to_string(),
+            SmoothingMethod::RollingMedian => "Rolling median".to_string(),
+            SmoothingMethod::SavitzkyGolay { order } => format!("Savitzky-Golay (order {})", order),
+        };
+        format!("{} of {} over {} observations", method, self.variable, self.window)
+    }
+}
+
+impl FromStr for SmoothingSpec {
+    type Err = String;
+
+    /// Parse `VARIABLE:METHOD:WINDOW[:ORDER]`, e.g. `A:mean:5` or `F:savgol:7:2`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').map(str::trim).collect();
+        let invalid = |reason: &str| format!(
+            "Invalid smoothing '{}': {} (expected VARIABLE:METHOD:WINDOW[:ORDER] with METHOD mean, median or savgol)",
+            s, reason
+        );
+
+        let (variable, method, window) = match parts.as_slice() {
+            [variable, method, window] | [variable, method, window, _] => (*variable, *method, *window),
+            _ => return Err(invalid("wrong number of fields")),
+        };
+        let window: usize = window.parse().map_err(|_| invalid("window is not a number"))?;
+        if window.is_multiple_of(2) {
+            return Err(invalid("window must be odd"));
+        }
+
+        let method = match (method, parts.get(3)) {
+            ("mean", None) => SmoothingMethod::RollingMean,
+            ("median", None) => SmoothingMethod::RollingMedian,
+            ("savgol", order) => {
+                let order = order.map_or(Ok(2), |o| o.parse()).map_err(|_| invalid("order is not a number"))?;
+                if order >= window {
+                    return Err(invalid("order must be smaller than the window"));
+                }
+                SmoothingMethod::SavitzkyGolay { order }
+            }
+            _ => return Err(invalid("unknown method or unexpected order")),
+        };
+
+        Ok(SmoothingSpec { variable: variable.to_string(), method, window })
+    }
+}
+
+/// Append a `<variable>_smooth` column for each spec
+///
+/// Each measurement step (as for [`trim_to_stable`](crate::trim_to_stable)) is
+/// smoothed on its own, so windows never mix observations from different
+/// setpoints or curves. Windows are centered on each observation and shrink
+/// (rolling filters) or shift (Savitzky–Golay) at the ends of a step; missing
+/// values are ignored. The new variables inherit units and category from their source and
+/// record it in `derived_from`.
+pub fn apply_smoothing(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    specs: &[SmoothingSpec],
+) -> Result<(), ParseError> {
+    let steps = crate::stability::step_ranges(df)?;
+    for spec in specs {
+        let column = df.column(&spec.variable)
+            .map_err(|_| ParseError::UnknownVariable { variable: spec.variable.clone() })?;
+        let values: Vec<Option<f64>> = column.cast(&polars::datatypes::DataType::Float64)
+            .map_err(smoothing_error)?
+            .f64()
+            .map_err(smoothing_error)?
+            .into_iter()
+            .collect();
+
+        let smoothed: Vec<Option<f64>> = steps.iter()
+            .flat_map(|&(start, end)| {
+                let values = &values[start..end];
+                match spec.method {
+                    SmoothingMethod::RollingMean => rolling(values, spec.window, |w| w.iter().sum::<f64>() / w.len() as f64),
+                    SmoothingMethod::RollingMedian => rolling(values, spec.window, median),
+                    SmoothingMethod::SavitzkyGolay { order } => savitzky_golay(values, spec.window, order),
+                }
+            })
+            .collect();
+
+        let name = spec.output_name();
+        df.with_column(Column::new(name.as_str().into(), smoothed)).map_err(smoothing_error)?;
+
+        let source = variable_info.iter().find(|v| v.internal_name == spec.variable);
+        let info = VariableInfo {
+            internal_name: name.clone(),
+            display_label: name,
+            units: source.and_then(|v| v.units.clone()),
+            units_pretty: source.and_then(|v| v.units_pretty.clone()),
+            units_ascii: source.and_then(|v| v.units_ascii.clone()),
+            description: spec.describe(),
+            data_type: DataType::Float,
+            column_category: source.map(|v| v.column_category.clone()).unwrap_or_default(),
+            derived_from: Some(spec.variable.clone()),
+        };
+        variable_info.push(info);
+    }
+    Ok(())
+}
+
+/// Apply `stat` to the non-missing values of a centered, edge-truncated window
+fn rolling(values: &[Option<f64>], window: usize, stat: impl Fn(&mut [f64]) -> f64) -> Vec<Option<f64>> {
+    let half = window / 2;
+    let mut buffer = Vec::with_capacity(window);
+    (0..values.len())
+        .map(|i| {
+            buffer.clear();
+            let end = (i + half + 1).min(values.len());
+            buffer.extend(values[i.saturating_sub(half)..end].iter().flatten());
+            (!buffer.is_empty()).then(|| stat(&mut buffer))
+        })
+        .collect()
+}
+
+pub(crate) fn median(window: &mut [f64]) -> f64 {
+    window.sort_by(f64::total_cmp);
+    let mid = window.len() / 2;
+    if window.len().is_multiple_of(2) {
+        (window[mid - 1] + window[mid]) / 2.0
+    } else {
+        window[mid]
+    }
+}
+
+/// Least-squares polynomial fit over each window, evaluated at its observation
+///
+/// Near the ends the window is shifted to stay inside the series, like SciPy's
+/// `mode="interp"`.
+fn savitzky_golay(values: &[Option<f64>], window: usize, order: usize) -> Vec<Option<f64>> {
+    let n = values.len();
+    let window = window.min(n);
+    let half = window / 2;
+
+    (0..n)
+        .map(|i| {
+            let start = i.saturating_sub(half).min(n - window);
+            let points: Vec<(f64, f64)> = (start..start + window)
+                .filter_map(|j| values[j].map(|y| (j as f64 - i as f64, y)))
+                .collect();
+            // Lower the order when missing values leave too few points
+            let order = order.min(points.len().saturating_sub(1));
+            if points.is_empty() {
+                return None;
+            }
+            fit_polynomial(&points, order).map(|coefficients| coefficients[0])
+        })
+        .collect()
+}
+
+/// Coefficients of the least-squares polynomial through `points` (constant term first)
+fn fit_polynomial(points: &[(f64, f64)], order: usize) -> Option<Vec<f64>> {
+    let size = order + 1;
+    // Normal equations: (XᵀX) c = Xᵀy, solved by Gaussian elimination
+    let mut matrix = vec![vec![0.0; size + 1]; size];
+    for &(x, y) in points {
+        let powers: Vec<f64> = (0..size).map(|p| x.powi(p as i32)).collect();
+        for row in 0..size {
+            for col in 0..size {
+                matrix[row][col] += powers[row] * powers[col];
+            }
+            matrix[row][size] += powers[row] * y;
+        }
+    }
+
+    for pivot in 0..size {
+        let best = (pivot..size).max_by(|a, b| matrix[*a][pivot].abs().total_cmp(&matrix[*b][pivot].abs()))?;
+        if matrix[best][pivot].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(pivot, best);
+        let pivot_row = matrix[pivot].clone();
+        for (row, values) in matrix.iter_mut().enumerate() {
+            if row != pivot {
+                let factor = values[pivot] / pivot_row[pivot];
+                for (value, pivot_value) in values.iter_mut().zip(&pivot_row).skip(pivot) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+    }
+
+    Some((0..size).map(|row| matrix[row][size] / matrix[row][row]).collect())
+}
+
+fn smoothing_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to smooth data: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec() {
+        let spec: SmoothingSpec = "A:mean:5".parse().unwrap();
+        assert_eq!(spec.method, SmoothingMethod::RollingMean);
+        assert_eq!(spec.output_name(), "A_smooth");
+        assert_eq!("F:savgol:7".parse::<SmoothingSpec>().unwrap().method, SmoothingMethod::SavitzkyGolay { order: 2 });
+        assert!("A:mean:4".parse::<SmoothingSpec>().is_err());
+        assert!("A:savgol:5:5".parse::<SmoothingSpec>().is_err());
+        assert!("A:mode:5".parse::<SmoothingSpec>().is_err());
+    }
+
+    #[test]
+    fn test_filters() {
+        let values = vec![Some(1.0), Some(2.0), Some(9.0), None, Some(4.0)];
+        let mean = rolling(&values, 3, |w| w.iter().sum::<f64>() / w.len() as f64);
+        assert_eq!(mean, vec![Some(1.5), Some(4.0), Some(5.5), Some(6.5), Some(4.0)]);
+        let med = rolling(&values, 3, median);
+        assert_eq!(med[1], Some(2.0));
+
+        // A quadratic is reproduced exactly by an order-2 filter, including the ends
+        let quadratic: Vec<Option<f64>> = (0..7).map(|x| Some((x * x) as f64 - 3.0 * x as f64)).collect();
+        let smoothed = savitzky_golay(&quadratic, 5, 2);
+        for (expected, actual) in quadratic.iter().zip(&smoothed) {
+            assert!((expected.unwrap() - actual.unwrap()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_apply_smoothing() {
+        let mut df = df! { "A" => [1.0, 3.0, 5.0] }.unwrap();
+        let mut info = Vec::new();
+        apply_smoothing(&mut df, &mut info, &["A:mean:3".parse().unwrap()]).unwrap();
+
+        let smoothed: Vec<_> = df.column("A_smooth").unwrap().f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(smoothed, vec![2.0, 3.0, 4.0]);
+        assert_eq!(info[0].derived_from.as_deref(), Some("A"));
+
+        let missing = apply_smoothing(&mut df, &mut info, &["gsw:mean:3".parse().unwrap()]);
+        assert!(matches!(missing, Err(ParseError::UnknownVariable { .. })));
+
+        // Windows stop at step boundaries
+        let mut steps = df! {
+            "curve_id" => ["1", "1", "1", "1", "2"],
+            "program_step" => ["1", "1", "2", "2", "1"],
+            "A" => [1.0, 3.0, 10.0, 20.0, 100.0],
+        }.unwrap();
+        apply_smoothing(&mut steps, &mut Vec::new(), &["A:mean:3".parse().unwrap()]).unwrap();
+        let smoothed: Vec<_> = steps.column("A_smooth").unwrap().f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(smoothed, vec![2.0, 2.0, 15.0, 15.0, 100.0]);
+    }
+}
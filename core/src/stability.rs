@@ -0,0 +1,347 @@
+use crate::{DataType, ParseError, VariableInfo};
+use std::collections::HashMap;
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Columns holding seconds since the start of the log, in order of preference
+const TIME_COLUMNS: [&str; 3] = ["elapsed", "time", "FTime"];
+
+/// Observation window kept by [`trim_to_stable`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StabilityWindow {
+    /// Keep observations from the last N seconds of each step
+    LastSeconds(f64),
+    /// Keep the last M observations of each step that met every stability criterion
+    LastStable(usize),
+}
+
+impl FromStr for StabilityWindow {
+    type Err = String;
+
+    /// Parse `"<N>s"` (seconds) or `"<M>"` (stable observations)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || format!(
+            "Invalid stability window '{}': use e.g. '60s' for the last 60 seconds or '5' for the last 5 stable observations",
+            s
+        );
+        match s.strip_suffix('s') {
+            Some(seconds) => seconds.trim().parse::<f64>().ok()
+                .filter(|v| v.is_finite() && *v > 0.0)
+                .map(StabilityWindow::LastSeconds)
+                .ok_or_else(invalid),
+            None => s.parse::<usize>().ok()
+                .filter(|v| *v > 0)
+                .map(StabilityWindow::LastStable)
+                .ok_or_else(invalid),
+        }
+    }
+}
+
+/// Keep only the steady-state end of each measurement step
+///
+/// Steps are runs of consecutive rows sharing `curve_id` and `program_step`
+/// (AutoProgram data) or, in LI-6800 logs, the setpoints `CO2_r_sp` and `Q`,
+/// so each point of a response curve keeps its own stable end; without any of
+/// those columns the whole table is one step. For
+/// `LastSeconds`, time comes from `elapsed`, `time` or `FTime`. For
+/// `LastStable`, a row is stable when `Stable` equals `Total` (LI-6800) or
+/// `StableF` is 1 (LI-6400); without stability columns every row counts.
+pub fn trim_to_stable(df: &DataFrame, window: StabilityWindow) -> Result<DataFrame, ParseError> {
+    let steps = step_ranges(df)?;
+    let mut keep = vec![false; df.height()];
+
+    match window {
+        StabilityWindow::LastSeconds(seconds) => {
+            let times = TIME_COLUMNS.iter()
+                .find_map(|name| df.column(name).ok())
+                .ok_or_else(|| trim_error("no elapsed time column to measure the window".to_string()))
+                .and_then(float_values)?;
+            for (start, end) in steps {
+                let Some(last) = times[start..end].iter().rev().find_map(|t| *t) else {
+                    continue;
+                };
+                for row in start..end {
+                    keep[row] = times[row].is_some_and(|t| t >= last - seconds);
+                }
+            }
+        }
+        StabilityWindow::LastStable(count) => {
+            let stable = stable_rows(df)?;
+            for (start, end) in steps {
+                for row in (start..end).rev().filter(|row| stable[*row]).take(count) {
+                    keep[row] = true;
+                }
+            }
+        }
+    }
+
+    let mask = BooleanChunked::from_slice("keep".into(), &keep);
+    df.filter(&mask).map_err(|e| trim_error(e.to_string()))
+}
+
+/// One criterion of the LI-6800 stability definition, such as
+/// `ΔCO2 (Meas2): Slp<0.5 Per=20`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StabilityCriterion {
+    /// Signal the criterion watches, as in its `<variable>:SLP` and `:SD` columns
+    pub variable: String,
+    /// Column group the signal belongs to
+    pub group: Option<String>,
+    /// Largest slope magnitude, per minute
+    pub max_slope: Option<f64>,
+    /// Largest standard deviation
+    pub max_sd: Option<f64>,
+    /// Seconds the slope and standard deviation are computed over
+    pub period: Option<f64>,
+}
+
+impl StabilityCriterion {
+    /// Criteria of a definition, one per tab-separated entry; entries that are
+    /// not criteria are skipped
+    pub fn parse_definition(definition: &str) -> Vec<Self> {
+        let definition = definition.trim_start().strip_prefix("Stability Definition:").unwrap_or(definition);
+        definition.split('\t').filter_map(Self::parse).collect()
+    }
+
+    /// Criteria of the last stability definition in a header
+    pub fn from_header(header: &HashMap<String, String>) -> Vec<Self> {
+        header.iter()
+            .filter(|(_, value)| value.starts_with("Stability Definition:"))
+            .max_by_key(|(key, _)| key.as_str())
+            .map(|(_, value)| Self::parse_definition(value))
+            .unwrap_or_default()
+    }
+
+    fn parse(entry: &str) -> Option<Self> {
+        let (signal, limits) = entry.split_once(':')?;
+        let (variable, group) = match signal.split_once('(') {
+            Some((variable, group)) => (variable.trim(), Some(group.trim().trim_end_matches(')').to_string())),
+            None => (signal.trim(), None),
+        };
+        let mut criterion = StabilityCriterion { variable: variable.to_string(), group, max_slope: None, max_sd: None, period: None };
+        for limit in limits.split_whitespace() {
+            let value = |prefix: &str| limit.strip_prefix(prefix).and_then(|v| v.parse::<f64>().ok());
+            if let Some(slope) = value("Slp<") {
+                criterion.max_slope = Some(slope);
+            } else if let Some(sd) = value("Sd<").or_else(|| value("SD<")) {
+                criterion.max_sd = Some(sd);
+            } else if let Some(period) = value("Per=") {
+                criterion.period = Some(period);
+            }
+        }
+        (!criterion.variable.is_empty() && (criterion.max_slope.is_some() || criterion.max_sd.is_some())).then_some(criterion)
+    }
+
+    /// Name of the column [`add_stability_columns`] adds for this criterion
+    pub fn column_name(&self) -> String {
+        format!("stable_{}", self.variable)
+    }
+}
+
+/// Add a `stable_<variable>` column per criterion, true where the signal's
+/// logged slope magnitude and standard deviation are under their limits
+///
+/// Unlike the instrument's `Stable` count, these can be combined freely, e.g.
+/// to keep rows where CO₂ alone had settled.
+pub(crate) fn add_stability_columns(
+    df: &mut DataFrame,
+    variable_info: &mut Vec<VariableInfo>,
+    criteria: &[StabilityCriterion],
+) -> Result<(), ParseError> {
+    for criterion in criteria {
+        let limits = [("SLP", criterion.max_slope), ("SD", criterion.max_sd)];
+        let mut inputs = Vec::new();
+        let mut stable = vec![Some(true); df.height()];
+        for (statistic, limit) in limits.iter().filter_map(|(statistic, limit)| limit.map(|limit| (statistic, limit))) {
+            let name = format!("{}:{}", criterion.variable, statistic);
+            let values = float_values(df.column(&name)
+                .map_err(|_| ParseError::UnknownVariable { variable: name.clone() })?)?;
+            for (stable, value) in stable.iter_mut().zip(values) {
+                *stable = match (*stable, value) {
+                    (Some(ok), Some(value)) => Some(ok && value.abs() < limit),
+                    _ => None,
+                };
+            }
+            inputs.push(name);
+        }
+
+        let name = criterion.column_name();
+        df.with_column(Column::new(name.as_str().into(), stable))
+            .map_err(|e| ParseError::InvalidHeaderFormat {
+                message: format!("Failed to add stability column: {}", e)
+            })?;
+        let conditions: Vec<String> = limits.iter()
+            .filter_map(|(statistic, limit)| limit.map(|limit| format!("|{}| < {}", statistic, limit)))
+            .collect();
+        variable_info.push(VariableInfo {
+            display_label: name.clone(),
+            internal_name: name,
+            units: None,
+            units_pretty: None,
+            units_ascii: None,
+            description: format!("Whether {} met its stability criterion ({})", criterion.variable, conditions.join(", ")),
+            data_type: DataType::Boolean,
+            column_category: "Stability".to_string(),
+            derived_from: Some(inputs.join(", ")),
+        });
+    }
+    Ok(())
+}
+
+/// Half-open row ranges of consecutive rows belonging to the same step
+///
+/// AutoProgram data marks steps with `curve_id` and `program_step`. LI-6800
+/// logs record neither, so there a step is a run of rows sharing the setpoints
+/// that [`split_curves`](crate::split_curves) follows (`CO2_r_sp`, `Q`).
+/// Without any of these columns the whole table is one step.
+pub(crate) fn step_ranges(df: &DataFrame) -> Result<Vec<(usize, usize)>, ParseError> {
+    let height = df.height();
+    let key_columns: Vec<&Column> = match (df.column("curve_id"), df.column("program_step")) {
+        (Ok(curve), Ok(step)) => vec![curve, step],
+        _ => crate::curves::SETPOINT_COLUMNS.iter().filter_map(|name| df.column(name).ok()).collect(),
+    };
+    let keys = key_columns.into_iter().map(string_values).collect::<Result<Vec<_>, _>>()?;
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for row in 1..=height {
+        if row == height || keys.iter().any(|key| key[row] != key[start]) {
+            ranges.push((start, row));
+            start = row;
+        }
+    }
+    Ok(ranges)
+}
+
+/// Whether each row met all of its stability criteria
+fn stable_rows(df: &DataFrame) -> Result<Vec<bool>, ParseError> {
+    if let (Ok(stable), Ok(total)) = (df.column("Stable"), df.column("Total")) {
+        let stable = float_values(stable)?;
+        let total = float_values(total)?;
+        return Ok(stable.iter().zip(&total)
+            .map(|(s, t)| matches!((s, t), (Some(s), Some(t)) if *t > 0.0 && s >= t))
+            .collect());
+    }
+    if let Ok(flag) = df.column("StableF") {
+        return Ok(float_values(flag)?.iter()
+            .map(|v| v.is_some_and(|v| v >= 1.0))
+            .collect());
+    }
+    Ok(vec![true; df.height()])
+}
+
+fn float_values(column: &Column) -> Result<Vec<Option<f64>>, ParseError> {
+    let values = column.cast(&polars::datatypes::DataType::Float64)
+        .map_err(|e| trim_error(e.to_string()))?;
+    Ok(values.f64().map_err(|e| trim_error(e.to_string()))?.into_iter().collect())
+}
+
+fn string_values(column: &Column) -> Result<Vec<Option<String>>, ParseError> {
+    let values = column.cast(&polars::datatypes::DataType::String)
+        .map_err(|e| trim_error(e.to_string()))?;
+    Ok(values.str().map_err(|e| trim_error(e.to_string()))?
+        .into_iter()
+        .map(|v| v.map(str::to_string))
+        .collect())
+}
+
+fn trim_error(message: String) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to trim to stable window: {}", message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!("60s".parse(), Ok(StabilityWindow::LastSeconds(60.0)));
+        assert_eq!("5".parse(), Ok(StabilityWindow::LastStable(5)));
+        assert!("0".parse::<StabilityWindow>().is_err());
+        assert!("soon".parse::<StabilityWindow>().is_err());
+    }
+
+    #[test]
+    fn test_trim_per_step() {
+        let df = df! {
+            "obs" => [1i64, 2, 3, 4, 5, 6],
+            "elapsed" => [0.0, 30.0, 60.0, 90.0, 120.0, 150.0],
+            "curve_id" => ["1", "1", "1", "1", "1", "1"],
+            "program_step" => ["1", "1", "1", "2", "2", "2"],
+            "Stable" => [0.0, 3.0, 2.0, 3.0, 3.0, 1.0],
+            "Total" => [3.0, 3.0, 3.0, 3.0, 3.0, 3.0],
+        }.unwrap();
+
+        let obs = |df: DataFrame| -> Vec<i64> {
+            df.column("obs").unwrap().i64().unwrap().into_no_null_iter().collect()
+        };
+
+        let trimmed = trim_to_stable(&df, StabilityWindow::LastSeconds(30.0)).unwrap();
+        assert_eq!(obs(trimmed), vec![2, 3, 5, 6]);
+
+        let trimmed = trim_to_stable(&df, StabilityWindow::LastStable(1)).unwrap();
+        assert_eq!(obs(trimmed), vec![2, 5]);
+
+        // Without step columns the table is a single step
+        let flat = df.drop("program_step").unwrap();
+        let trimmed = trim_to_stable(&flat, StabilityWindow::LastStable(2)).unwrap();
+        assert_eq!(obs(trimmed), vec![4, 5]);
+    }
+
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_trim_setpoint_steps() {
+        // A light curve logged three times at each setpoint
+        let mut df = crate::LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap()
+            .dataframe
+            .head(Some(9));
+        let light = [2000.0, 2000.0, 2000.0, 1000.0, 1000.0, 1000.0, 0.0, 0.0, 0.0];
+        let stable = [1.0, 3.0, 3.0, 3.0, 3.0, 2.0, 3.0, 2.0, 1.0];
+        df.with_column(Series::new("Q".into(), light)).unwrap();
+        df.with_column(Series::new("Stable".into(), stable)).unwrap();
+        df.with_column(Series::new("Total".into(), [3.0; 9])).unwrap();
+        df.with_column(Series::new("obs".into(), (1..=9i64).collect::<Vec<_>>())).unwrap();
+
+        // Every setpoint keeps its last stable observation
+        let trimmed = trim_to_stable(&df, StabilityWindow::LastStable(1)).unwrap();
+        let obs: Vec<i64> = trimmed.column("obs").unwrap().i64().unwrap().into_no_null_iter().collect();
+        assert_eq!(obs, vec![3, 5, 7]);
+    }
+
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_stability_columns() {
+        let criteria = StabilityCriterion::parse_definition("Stability Definition:\tF (FlrLS): Slp<1 Per=20\tΔCO2 (Meas2): Slp<0.5 Sd<0.1 Per=20\tnone");
+        assert_eq!(criteria.len(), 2);
+        assert_eq!(criteria[1], StabilityCriterion {
+            variable: "ΔCO2".to_string(),
+            group: Some("Meas2".to_string()),
+            max_slope: Some(0.5),
+            max_sd: Some(0.1),
+            period: Some(20.0),
+        });
+
+        // The instrument's own flags agree with the recomputed ones
+        let data = crate::LiCor6800Fluorometer::with_options(crate::ParseOptions { stability_columns: true, ..Default::default() })
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        for variable in ["F", "ΔH2O", "ΔCO2"] {
+            let ours: Vec<Option<f64>> = float_values(data.dataframe.column(&format!("stable_{}", variable)).unwrap()).unwrap();
+            let logged = float_values(data.dataframe.column(&format!("{}:OK", variable)).unwrap()).unwrap();
+            assert_eq!(ours, logged, "{}", variable);
+        }
+
+        let mut df = df! { "ΔCO2:SLP" => [0.1, -0.6, 0.2], "ΔCO2:SD" => [Some(0.05), Some(0.05), None] }.unwrap();
+        let mut info = Vec::new();
+        add_stability_columns(&mut df, &mut info, &criteria[1..]).unwrap();
+        let stable: Vec<_> = df.column("stable_ΔCO2").unwrap().bool().unwrap().into_iter().collect();
+        assert_eq!(stable, vec![Some(true), Some(false), None]);
+        assert_eq!(info[0].derived_from.as_deref(), Some("ΔCO2:SLP, ΔCO2:SD"));
+        assert!(matches!(add_stability_columns(&mut df, &mut info, &criteria[..1]), Err(ParseError::UnknownVariable { .. })));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{DataType, LiCorData, ParseError, VariableInfo};
+use polars::prelude::*;
+use std::str::FromStr;
+
+/// Scale taking the median absolute deviation to a standard deviation for
+/// normally distributed values
+const MAD_SCALE: f64 = 1.4826;
+
+/// Outlier-robust central value of a step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RobustStatistic {
+    Median,
+    /// Mean after dropping this fraction of the values from each end
+    TrimmedMean(f64),
+}
+
+impl FromStr for RobustStatistic {
+    type Err = String;
+
+    /// Parse `median`, `trimmed` (10% from each end) or `trimmed:<FRACTION>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        let invalid = || format!("Invalid statistic '{}': use median, trimmed or trimmed:<FRACTION> below 0.5", s);
+        match s.split_once(':') {
+            None if s == "median" => Ok(RobustStatistic::Median),
+            None if s == "trimmed" => Ok(RobustStatistic::TrimmedMean(0.1)),
+            Some(("trimmed", fraction)) => fraction.trim().parse::<f64>().ok()
+                .filter(|f| (0.0..0.5).contains(f))
+                .map(RobustStatistic::TrimmedMean)
+                .ok_or_else(invalid),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl RobustStatistic {
+    fn name(&self) -> String {
+        match self {
+            RobustStatistic::Median => "median".to_string(),
+            RobustStatistic::TrimmedMean(fraction) => format!("{}% trimmed mean", fraction * 100.0),
+        }
+    }
+
+    /// Central value of sorted values
+    fn center(&self, sorted: &[f64]) -> f64 {
+        match self {
+            RobustStatistic::Median => median(sorted),
+            RobustStatistic::TrimmedMean(fraction) => {
+                let cut = (sorted.len() as f64 * fraction).floor() as usize;
+                let kept = &sorted[cut..sorted.len() - cut];
+                kept.iter().sum::<f64>() / kept.len() as f64
+            }
+        }
+    }
+}
+
+impl LiCorData {
+    /// One row per measurement step with robust steady-state values
+    ///
+    /// Steps are found as for `trim_to_stable` (runs of `curve_id` and
+    /// `program_step`, or of the LI-6800 setpoints `CO2_r_sp` and `Q`, else the
+    /// whole log), so trimming first summarizes only
+    /// the stable end of each step. Numeric columns become the step's central
+    /// value plus `<name>_mad`, the median absolute deviation scaled by 1.4826
+    /// to match a standard deviation; other columns keep the step's last value.
+    /// `step` numbers the steps and `n` counts their observations.
+    pub fn step_summary(&self, statistic: RobustStatistic) -> Result<LiCorData, ParseError> {
+        let df = &self.dataframe;
+        let steps = crate::stability::step_ranges(df)?;
+        let mut columns = vec![
+            Column::new("step".into(), (1..=steps.len() as u32).collect::<Vec<_>>()),
+            Column::new("n".into(), steps.iter().map(|(start, end)| (end - start) as u32).collect::<Vec<_>>()),
+        ];
+        let mut variable_info = vec![
+            step_info("step", "Measurement step, numbered from 1", DataType::Integer, None),
+            step_info("n", "Observations in the step", DataType::Integer, None),
+        ];
+
+        for column in df.get_columns() {
+            let name = column.name().as_str();
+            let info = self.variable_info.iter().find(|v| v.internal_name == name);
+            let grouping = name == "curve_id" || name == "program_step";
+            if !column.dtype().is_primitive_numeric() || grouping {
+                let last: Vec<usize> = steps.iter().map(|(_, end)| end - 1).collect();
+                let indices = IdxCa::from_vec("".into(), last.iter().map(|&i| i as IdxSize).collect());
+                columns.push(column.take(&indices).map_err(summary_error)?);
+                variable_info.extend(info.cloned());
+                continue;
+            }
+
+            let values = column.cast(&polars::datatypes::DataType::Float64).map_err(summary_error)?;
+            let values: Vec<Option<f64>> = values.f64().map_err(summary_error)?.into_iter().collect();
+            let (centers, spreads): (Vec<Option<f64>>, Vec<Option<f64>>) = steps.iter()
+                .map(|(start, end)| {
+                    let mut sorted: Vec<f64> = values[*start..*end].iter().flatten().copied().filter(|v| v.is_finite()).collect();
+                    if sorted.is_empty() {
+                        return (None, None);
+                    }
+                    sorted.sort_by(f64::total_cmp);
+                    let mid = median(&sorted);
+                    let mut deviations: Vec<f64> = sorted.iter().map(|v| (v - mid).abs()).collect();
+                    deviations.sort_by(f64::total_cmp);
+                    (Some(statistic.center(&sorted)), Some(MAD_SCALE * median(&deviations)))
+                })
+                .unzip();
+            columns.push(Column::new(name.into(), centers));
+            let mad_name = format!("{}_mad", name);
+            columns.push(Column::new(mad_name.as_str().into(), spreads));
+
+            let mut center_info = info.cloned().unwrap_or_else(|| step_info(name, "", DataType::Float, None));
+            center_info.data_type = DataType::Float;
+            center_info.description = format!("{} (step {})", center_info.description, statistic.name()).trim_start().to_string();
+            let mut mad_info = center_info.clone();
+            mad_info.internal_name = mad_name.clone();
+            mad_info.display_label = mad_name;
+            mad_info.description = format!("Scaled median absolute deviation of {} within the step", name);
+            mad_info.column_category = "Derived".to_string();
+            mad_info.derived_from = Some(name.to_string());
+            variable_info.push(center_info);
+            variable_info.push(mad_info);
+        }
+
+        Ok(LiCorData {
+            dataframe: DataFrame::new(columns).map_err(summary_error)?,
+            variable_info,
+            ..self.clone()
+        })
+    }
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) { (sorted[mid - 1] + sorted[mid]) / 2.0 } else { sorted[mid] }
+}
+
+fn step_info(name: &str, description: &str, data_type: DataType, units: Option<String>) -> VariableInfo {
+    VariableInfo {
+        internal_name: name.to_string(),
+        display_label: name.to_string(),
+        units_pretty: units.as_deref().map(crate::pretty_units),
+        units_ascii: units.as_deref().map(crate::ascii_units),
+        units,
+        description: description.to_string(),
+        data_type,
+        column_category: "Derived".to_string(),
+        derived_from: None,
+    }
+}
+
+fn summary_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to summarize steps: {}", e)
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::LiCor6800Fluorometer;
+
+    #[test]
+    fn test_step_summary() {
+        let data = LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        let steps = LiCorData {
+            dataframe: df! {
+                "curve_id" => ["1", "1", "1", "1", "1", "1"],
+                "program_step" => ["1", "1", "1", "1", "2", "2"],
+                "A" => [10.0, 11.0, 12.0, 90.0, 5.0, 7.0],
+                "date" => ["a", "b", "c", "d", "e", "f"],
+            }.unwrap(),
+            ..data.clone()
+        };
+
+        let summary = steps.step_summary(RobustStatistic::Median).unwrap();
+        let df = &summary.dataframe;
+        assert_eq!(df.get_column_names_str(), ["step", "n", "curve_id", "program_step", "A", "A_mad", "date"]);
+        let a: Vec<_> = df.column("A").unwrap().f64().unwrap().into_iter().collect();
+        // The outlier barely moves the median
+        assert_eq!(a, vec![Some(11.5), Some(6.0)]);
+        let mad = df.column("A_mad").unwrap().f64().unwrap().get(0).unwrap();
+        assert!((mad - MAD_SCALE * 1.0).abs() < 1e-12);
+        assert_eq!(df.column("date").unwrap().str().unwrap().get(1), Some("f"));
+        let a_info = summary.variable_info.iter().find(|v| v.internal_name == "A_mad").unwrap();
+        assert_eq!(a_info.units.as_deref(), Some("µmol m-2 s-1"));
+
+        let trimmed = steps.step_summary("trimmed:0.25".parse().unwrap()).unwrap();
+        assert_eq!(trimmed.dataframe.column("A").unwrap().f64().unwrap().get(0), Some(11.5));
+        assert!("trimmed:0.5".parse::<RobustStatistic>().is_err());
+
+    }
+
+    #[test]
+    fn test_step_summary_setpoints() {
+        // An A–Ci curve logged twice at each CO₂ setpoint
+        let mut data = LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        assert_eq!(data.dataframe.height(), 10);
+        let setpoints = [400.0, 400.0, 300.0, 300.0, 200.0, 200.0, 100.0, 100.0, 50.0, 50.0];
+        let a = [20.0, 22.0, 16.0, 17.0, 11.0, 12.0, 5.0, 6.0, 1.0, 2.0];
+        data.dataframe.with_column(Series::new("CO2_r_sp".into(), setpoints)).unwrap();
+        data.dataframe.with_column(Series::new("A".into(), a)).unwrap();
+
+        let summary = data.step_summary(RobustStatistic::Median).unwrap();
+        let df = &summary.dataframe;
+        assert_eq!(df.height(), 5);
+        let n: Vec<_> = df.column("n").unwrap().u32().unwrap().into_no_null_iter().collect();
+        assert_eq!(n, vec![2; 5]);
+        let co2: Vec<_> = df.column("CO2_r_sp").unwrap().f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(co2, vec![400.0, 300.0, 200.0, 100.0, 50.0]);
+        let a: Vec<_> = df.column("A").unwrap().f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(a, vec![21.0, 16.5, 11.5, 5.5, 1.5]);
+
+        // Setpoints held throughout the log make one step
+        data.dataframe.with_column(Series::new("CO2_r_sp".into(), [400.0; 10])).unwrap();
+        let whole = data.step_summary(RobustStatistic::Median).unwrap();
+        assert_eq!(whole.dataframe.height(), 1);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{LiCorData, ParseError};
+use polars::prelude::*;
+
+/// Quality-control statistics of one column
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableSummary {
+    pub name: String,
+    pub units: Option<String>,
+    /// Observations with a value
+    pub count: usize,
+    /// Observations without a value
+    pub missing: usize,
+    /// Minimum, maximum and mean of the finite values (numeric columns only)
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+}
+
+/// Statistics of every column, or of `columns` in that order when given
+pub fn summarize(data: &LiCorData, columns: &[String]) -> Result<Vec<VariableSummary>, ParseError> {
+    let df = &data.dataframe;
+    let names: Vec<String> = if columns.is_empty() {
+        df.get_column_names().iter().map(|name| name.to_string()).collect()
+    } else {
+        columns.to_vec()
+    };
+
+    names.into_iter()
+        .map(|name| {
+            let column = df.column(&name)
+                .map_err(|_| ParseError::UnknownVariable { variable: name.clone() })?;
+            let missing = column.null_count();
+            let (min, max, mean) = if column.dtype().is_primitive_numeric() {
+                let values = column.cast(&polars::datatypes::DataType::Float64).map_err(summary_error)?;
+                let finite: Vec<f64> = values.f64().map_err(summary_error)?
+                    .into_no_null_iter()
+                    .filter(|v| v.is_finite())
+                    .collect();
+                (
+                    finite.iter().copied().reduce(f64::min),
+                    finite.iter().copied().reduce(f64::max),
+                    (!finite.is_empty()).then(|| finite.iter().sum::<f64>() / finite.len() as f64),
+                )
+            } else {
+                (None, None, None)
+            };
+            let units = data.variable_info.iter()
+                .find(|v| v.internal_name == name)
+                .and_then(|v| v.units.clone());
+            Ok(VariableSummary { name, units, count: column.len() - missing, missing, min, max, mean })
+        })
+        .collect()
+}
+
+fn summary_error(e: PolarsError) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Failed to summarize data: {}", e)
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::LiCor6800Fluorometer;
+
+    #[test]
+    fn test_summarize() {
+        let data = LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        let summary = summarize(&data, &[]).unwrap();
+        assert_eq!(summary.len(), data.dataframe.width());
+
+        let summary = summarize(&data, &["A".to_string(), "hhmmss".to_string()]).unwrap();
+        let a = &summary[0];
+        assert_eq!(a.count + a.missing, data.dataframe.height());
+        assert!(a.min.unwrap() <= a.mean.unwrap() && a.mean.unwrap() <= a.max.unwrap());
+        assert_eq!(a.units.as_deref(), Some("µmol m-2 s-1"));
+        // Text columns are only counted
+        assert_eq!(summary[1].mean, None);
+
+        assert!(summarize(&data, &["nope".to_string()]).is_err());
+    }
+}
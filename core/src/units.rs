@@ -0,0 +1,128 @@
+//! Unit strings in a Unicode display form and an ASCII-safe form
+//!
+//! LI-COR logs and `licor.toml` mix spellings (`m-2`, `m⁻²`, `J/µmol`). Both
+//! forms start from one spelling with negative powers instead of a divisor:
+//! `µmol m⁻² s⁻¹` for display, and `umol m-2 s-1` for tools that only read
+//! ASCII.
+
+use crate::validate::normalize_units;
+use crate::{LiCorData, VariableInfo};
+use std::str::FromStr;
+
+/// Which spelling of units to write
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnitStyle {
+    /// As logged by the instrument or defined in `licor.toml`
+    #[default]
+    Original,
+    /// Unicode superscripts and `µ`, e.g. `µmol m⁻² s⁻¹`
+    Pretty,
+    /// ASCII only, e.g. `umol m-2 s-1`
+    Ascii,
+}
+
+impl FromStr for UnitStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "original" => Ok(UnitStyle::Original),
+            "pretty" | "unicode" => Ok(UnitStyle::Pretty),
+            "ascii" => Ok(UnitStyle::Ascii),
+            _ => Err(format!("Unknown unit style '{}': use original, pretty or ascii", s)),
+        }
+    }
+}
+
+/// Unicode form of a unit string: `umol m-2 s-1` becomes `µmol m⁻² s⁻¹` and
+/// `J/µmol` becomes `J µmol⁻¹`
+pub fn pretty_units(units: &str) -> String {
+    normalize_units(units)
+        .split(' ')
+        .map(|token| {
+            let split = token.trim_end_matches(|c: char| c.is_ascii_digit() || c == '-').len();
+            let (unit, power) = token.split_at(split);
+            // A bare number, or a hyphen inside a unit
+            if unit.is_empty() || power.is_empty() || power == "-" || power[1..].contains('-') {
+                return pretty_unit(token);
+            }
+            let power: String = power.chars()
+                .map(|c| match c {
+                    '-' => '⁻',
+                    digit => ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'][digit as usize - '0' as usize],
+                })
+                .collect();
+            format!("{}{}", pretty_unit(unit), power)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `µ` for an ASCII micro prefix and `°C` for `degC`
+fn pretty_unit(unit: &str) -> String {
+    match unit {
+        "degC" => "°C".to_string(),
+        "umol" | "ug" | "um" | "us" | "ul" | "uL" | "uV" => unit.replacen('u', "µ", 1),
+        _ => unit.to_string(),
+    }
+}
+
+/// ASCII-safe form of a unit string: `µmol m⁻² s⁻¹` becomes `umol m-2 s-1`,
+/// `°C` becomes `degC` and `Ω` becomes `ohm`
+pub fn ascii_units(units: &str) -> String {
+    let mut ascii = String::new();
+    for c in normalize_units(units).chars() {
+        match c {
+            'µ' | 'μ' => ascii.push('u'),
+            '°' => ascii.push_str("deg"),
+            'Ω' => ascii.push_str("ohm"),
+            c => ascii.push(c),
+        }
+    }
+    ascii
+}
+
+impl VariableInfo {
+    /// Units in the given style
+    pub fn units_in(&self, style: UnitStyle) -> Option<&str> {
+        match style {
+            UnitStyle::Original => self.units.as_deref(),
+            UnitStyle::Pretty => self.units_pretty.as_deref(),
+            UnitStyle::Ascii => self.units_ascii.as_deref(),
+        }
+    }
+}
+
+impl LiCorData {
+    /// Use the given unit style for `VariableInfo::units`, which writers output
+    pub fn with_unit_style(mut self, style: UnitStyle) -> Self {
+        for info in &mut self.variable_info {
+            info.units = info.units_in(style).map(str::to_string);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_forms() {
+        assert_eq!(pretty_units("µmol m-2 s-1"), "µmol m⁻² s⁻¹");
+        assert_eq!(pretty_units("umol m-2 s-1"), "µmol m⁻² s⁻¹");
+        assert_eq!(pretty_units("J/µmol"), "J µmol⁻¹");
+        assert_eq!(pretty_units("m3 m-3"), "m³ m⁻³");
+        assert_eq!(pretty_units("degC"), "°C");
+        assert_eq!(pretty_units("1-qL"), "1-qL");
+        assert_eq!(pretty_units("%"), "%");
+
+        assert_eq!(ascii_units("µmol m⁻² s⁻¹"), "umol m-2 s-1");
+        assert_eq!(ascii_units("µmol m-2 s-1"), "umol m-2 s-1");
+        assert_eq!(ascii_units("°C"), "degC");
+        assert_eq!(ascii_units("K/kPa"), "K kPa-1");
+        assert!(ascii_units("μg ml⁻¹").is_ascii());
+
+        assert_eq!(pretty_units(&ascii_units("µmol mol⁻¹ s⁻¹")), "µmol mol⁻¹ s⁻¹");
+    }
+}
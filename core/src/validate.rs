@@ -0,0 +1,162 @@
+use crate::{
+    ParseError, RawLiCorFile, LiCorDevice, LiCorConfig, LiCorParser, ParseOptions,
+    CoercionReport, VARIABLE_DEFINITIONS
+};
+
+/// Problems found in one data table, without stopping at the first
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    /// Variables the configuration expects that the file does not log
+    pub missing_variables: Vec<String>,
+    /// Number of columns the data rows should have
+    pub expected_fields: usize,
+    /// Data rows (1-based) with the wrong number of fields, with the count found
+    pub malformed_rows: Vec<(usize, usize)>,
+    /// Columns whose units differ from the variable definitions
+    pub unit_mismatches: Vec<UnitMismatch>,
+    /// Columns kept as text because some values did not convert
+    pub type_fallbacks: Vec<CoercionReport>,
+    /// Integer columns with fractional values, which were rounded
+    pub rounded_columns: Vec<CoercionReport>,
+}
+
+/// Units in the file that differ from the definition of a known variable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitMismatch {
+    pub column: String,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ValidationReport {
+    /// Whether no problems were found (rounded columns are only warnings)
+    pub fn is_valid(&self) -> bool {
+        self.missing_variables.is_empty()
+            && self.malformed_rows.is_empty()
+            && self.unit_mismatches.is_empty()
+            && self.type_fallbacks.is_empty()
+    }
+}
+
+impl<D: LiCorDevice, C: LiCorConfig> LiCorParser<D, C> {
+    /// Check a LI-COR file's content against the device and configuration
+    pub fn validate_content(&self, content: &str) -> Result<ValidationReport, ParseError> {
+        self.validate_raw(RawLiCorFile::parse(content)?)
+    }
+
+    /// Check an already-extracted raw file against the device and configuration
+    ///
+    /// Header errors are returned as `Err`, since nothing else can be checked
+    /// without a recognisable header; everything else is collected in the report.
+    pub fn validate_raw(&self, raw_file: RawLiCorFile) -> Result<ValidationReport, ParseError> {
+        D::validate_header(&raw_file.header)?;
+
+        let canonical_columns: Vec<String> = raw_file.column_names.iter()
+            .map(|name| D::canonical_column_name(name).to_string())
+            .collect();
+        let missing_variables = C::missing_variables(&canonical_columns).into_iter()
+            .map(str::to_string)
+            .collect();
+
+        // Files without a units row (workbooks) have nothing to compare
+        let unit_mismatches = raw_file.column_names.iter()
+            .zip(&raw_file.units)
+            .filter(|(_, found)| !found.is_empty())
+            .filter_map(|(column, found)| {
+                // Some names are defined more than once (e.g. `A`); any match will do
+                let mut defined = VARIABLE_DEFINITIONS.iter()
+                    .filter(|def| def.internal_name == column)
+                    .filter_map(|def| def.units)
+                    .peekable();
+                let expected = *defined.peek()?;
+                let found_units = normalize_units(found);
+                (!defined.any(|units| normalize_units(units) == found_units)).then(|| UnitMismatch {
+                    column: column.clone(),
+                    expected: expected.to_string(),
+                    found: found.clone(),
+                })
+            })
+            .collect();
+
+        let expected_fields = raw_file.column_names.len();
+        let malformed_rows = raw_file.malformed_rows.clone();
+        let parser = Self::with_options(ParseOptions { coercion_report: true, ..ParseOptions::default() });
+        let (_, _, reports) = parser.build_typed_dataframe(raw_file)?;
+        let (rounded_columns, type_fallbacks) = reports.into_iter().partition(|report| report.rounded);
+
+        Ok(ValidationReport {
+            missing_variables,
+            expected_fields,
+            malformed_rows,
+            unit_mismatches,
+            type_fallbacks,
+            rounded_columns,
+        })
+    }
+}
+
+/// Units in one spelling: superscripts as plain text (`m⁻²` → `m-2`) and a
+/// single divisor as a negative power (`µmol/mol` → `µmol mol-1`)
+pub(crate) fn normalize_units(units: &str) -> String {
+    let units: String = units.trim()
+        .chars()
+        .map(|c| match c {
+            '⁻' => '-',
+            '⁰' => '0',
+            '¹' => '1',
+            '²' => '2',
+            '³' => '3',
+            '⁴'..='⁹' => char::from(b'4' + (c as u32 - '⁴' as u32) as u8),
+            c => c,
+        })
+        .collect();
+    match units.split_once('/') {
+        Some((numerator, denominator)) if !denominator.contains([' ', '/']) => {
+            format!("{} {}-1", numerator.trim(), denominator.trim())
+        }
+        _ if units == "secs" => "s".to_string(),
+        _ => units,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_validate_content() {
+        let content = std::fs::read_to_string("../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1")
+            .expect("Should be able to read sample file");
+        let report = crate::LiCor6800Fluorometer::new().validate_content(&content).unwrap();
+        assert!(report.is_valid(), "sample file should validate: {:?}", report);
+
+        // Damage the file: cut the first data row short, relabel the units of A
+        // and put text in the E column
+        let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let data = lines.iter().position(|line| line.trim() == "[Data]").unwrap();
+        let names: Vec<String> = lines[data + 2].split('\t').map(str::to_string).collect();
+        let a = names.iter().position(|name| name == "A").unwrap();
+        let e = names.iter().position(|name| name == "E").unwrap();
+        let mut units: Vec<String> = lines[data + 3].split('\t').map(str::to_string).collect();
+        units[a] = "mmol m-2 s-1".to_string();
+        lines[data + 3] = units.join("\t");
+        let mut row: Vec<String> = lines[data + 5].split('\t').map(str::to_string).collect();
+        row[e] = "#DIV/0!".to_string();
+        row[names.iter().position(|name| name == "obs").unwrap()] = "1.5".to_string();
+        lines[data + 5] = row.join("\t");
+        let row: Vec<String> = lines[data + 4].split('\t').take(100).map(str::to_string).collect();
+        lines[data + 4] = row.join("\t");
+
+        let report = crate::LiCor6800Fluorometer::new().validate_content(&lines.join("\n")).unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.malformed_rows, vec![(1, 100)]);
+        assert_eq!(report.unit_mismatches[0].column, "A");
+        assert_eq!(report.unit_mismatches[0].found, "mmol m-2 s-1");
+        assert!(report.type_fallbacks.iter().any(|r| r.column == "E"));
+        assert_eq!(report.rounded_columns[0].column, "obs");
+        assert!(report.missing_variables.is_empty());
+
+        // The soil configuration expects variables this log does not have
+        let soil = crate::LiCor6800Soil::new().validate_content(&content).unwrap();
+        assert_eq!(soil.missing_variables, vec!["Tsoil", "VWC"]);
+    }
+}
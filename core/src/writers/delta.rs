@@ -0,0 +1,419 @@
+use crate::{ParseError, LiCorData};
+use super::parquet::{licor_key_values, write_parquet_frame, ParquetOptions};
+use polars::prelude::*;
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LOG_DIR: &str = "_delta_log";
+/// Versions tried past the first before giving up on a commit that other
+/// writers keep claiming
+const MAX_COMMIT_ATTEMPTS: u64 = 100;
+/// Longest string kept in min/max statistics, Delta's default prefix length
+const STATS_STRING_LENGTH: usize = 32;
+
+/// Append parsed data to a Delta Lake table
+///
+/// The observations are written as a Parquet data file inside `table_path` and
+/// registered with a new commit in `_delta_log`, so the table can be read by
+/// delta-rs, Spark, DuckDB and other Delta readers. The first commit creates the
+/// table from the data's schema; later appends must have the same columns.
+/// Each commit records the source file and instrument metadata in its
+/// `commitInfo` so every row can be traced back to the file it came from, and
+/// each data file carries the same key-value metadata as
+/// [`write_parquet`](crate::write_parquet). Tables checkpointed by other
+/// writers are read from their `_last_checkpoint`, so appends still work after
+/// old commits have been cleaned up. Each data file is registered with its
+/// row count and per-column null counts and min/max values, so readers can
+/// skip files by predicate.
+///
+/// The log is written here rather than through the `deltalake` crate, which
+/// brings arrow-rs and DataFusion in alongside Polars' own Arrow
+/// implementation: a second columnar stack for what amounts to writing one
+/// Parquet file and one JSON commit. Only that much of the protocol is
+/// implemented: blind appends to unpartitioned tables at reader/writer
+/// version 1/2, without deletion vectors, column mapping or other table
+/// features.
+pub fn write_delta(
+    data: &LiCorData,
+    table_path: &str,
+    source_file: &str,
+    parquet: &ParquetOptions,
+) -> Result<(), ParseError> {
+    let table_dir = Path::new(table_path);
+    let log_dir = table_dir.join(LOG_DIR);
+    fs::create_dir_all(&log_dir)?;
+
+    let mut dataframe = delta_compatible(&data.dataframe)?;
+    let schema = schema_string(&dataframe);
+    let existing = read_table_state(&log_dir)?;
+    if let Some(state) = &existing {
+        if state.schema != schema {
+            return Err(delta_error(format!(
+                "Schema of '{}' does not match the existing table at {}",
+                source_file, table_path
+            )));
+        }
+    }
+
+    let now = timestamp_millis();
+    let version = existing.as_ref().map_or(0, |state| state.next_version);
+    let data_file = format!("part-{:05}-{}.parquet", version, uuid::Uuid::new_v4());
+    let data_path = table_dir.join(&data_file);
+    let file = fs::File::create(&data_path)?;
+    write_parquet_frame(&mut dataframe, file, parquet, licor_key_values(data)?)?;
+    let size = fs::metadata(&data_path)?.len();
+
+    let mut actions = Vec::new();
+    if existing.is_none() {
+        actions.push(json!({ "protocol": { "minReaderVersion": 1, "minWriterVersion": 2 } }));
+        actions.push(json!({
+            "metaData": {
+                "id": uuid::Uuid::new_v4().to_string(),
+                "format": { "provider": "parquet", "options": {} },
+                "schemaString": schema,
+                "partitionColumns": [],
+                "configuration": {},
+                "createdTime": now,
+            }
+        }));
+    }
+    actions.push(json!({
+        "add": {
+            "path": data_file,
+            "partitionValues": {},
+            "size": size,
+            "modificationTime": now,
+            "dataChange": true,
+            "stats": file_stats(&dataframe).to_string(),
+        }
+    }));
+    actions.push(json!({ "commitInfo": commit_info(data, source_file, existing.is_none(), now) }));
+
+    let mut commit = String::new();
+    for action in &actions {
+        commit.push_str(&action.to_string());
+        commit.push('\n');
+    }
+
+    // Commits must never overwrite each other: if a concurrent writer claimed
+    // this version, retry with the next one
+    for version in version..=version + MAX_COMMIT_ATTEMPTS {
+        let commit_path = log_dir.join(format!("{:020}.json", version));
+        match OpenOptions::new().write(true).create_new(true).open(&commit_path) {
+            Ok(mut log_file) => {
+                log_file.write_all(commit.as_bytes())?;
+                return Ok(());
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && existing.is_some() => {}
+            Err(e) => {
+                fs::remove_file(&data_path).ok();
+                return Err(e.into());
+            }
+        }
+    }
+    fs::remove_file(&data_path).ok();
+    Err(delta_error(format!(
+        "Gave up committing '{}' to {} after {} versions were taken by other writers",
+        source_file, table_path, MAX_COMMIT_ATTEMPTS + 1
+    )))
+}
+
+/// The `stats` of an add action: row count, and null counts and min/max
+/// values per column
+///
+/// Min/max are left out where they cannot be stated exactly: for booleans,
+/// for float columns holding NaN or infinities (which JSON cannot carry), and
+/// for strings longer than [`STATS_STRING_LENGTH`].
+fn file_stats(df: &DataFrame) -> Value {
+    let mut min_values = serde_json::Map::new();
+    let mut max_values = serde_json::Map::new();
+    let mut null_count = serde_json::Map::new();
+    for column in df.get_columns() {
+        let name = column.name().to_string();
+        null_count.insert(name.clone(), json!(column.null_count()));
+        let bounds = match column.dtype() {
+            polars::datatypes::DataType::Float64 => column.f64().ok().and_then(|values| {
+                let values: Vec<f64> = values.into_no_null_iter().collect();
+                if values.iter().any(|v| !v.is_finite()) {
+                    return None;
+                }
+                let min = values.iter().copied().reduce(f64::min)?;
+                let max = values.iter().copied().reduce(f64::max)?;
+                Some((json!(min), json!(max)))
+            }),
+            polars::datatypes::DataType::Int64 => column.i64().ok().and_then(|values| {
+                Some((json!(values.min()?), json!(values.max()?)))
+            }),
+            polars::datatypes::DataType::String => column.str().ok().and_then(|values| {
+                if values.into_no_null_iter().any(|v| v.chars().count() > STATS_STRING_LENGTH) {
+                    return None;
+                }
+                let min = values.into_no_null_iter().min()?;
+                let max = values.into_no_null_iter().max()?;
+                Some((json!(min), json!(max)))
+            }),
+            _ => None,
+        };
+        if let Some((min, max)) = bounds {
+            min_values.insert(name.clone(), min);
+            max_values.insert(name, max);
+        }
+    }
+    json!({
+        "numRecords": df.height(),
+        "minValues": min_values,
+        "maxValues": max_values,
+        "nullCount": null_count,
+    })
+}
+
+/// Schema and next commit version of an existing table
+struct TableState {
+    schema: String,
+    next_version: u64,
+}
+
+fn read_table_state(log_dir: &Path) -> Result<Option<TableState>, ParseError> {
+    let mut versions: Vec<u64> = fs::read_dir(log_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_suffix(".json")?.parse().ok()
+        })
+        .collect();
+    versions.sort_unstable();
+    let checkpoint = last_checkpoint(log_dir)?;
+    let Some(latest) = versions.last().copied().max(checkpoint) else {
+        return Ok(None);
+    };
+
+    // The most recent metaData action defines the table schema. Commits up to
+    // a checkpoint may have been cleaned up, so the checkpoint stands in for them.
+    let mut schema = None;
+    for version in versions.iter().rev().filter(|version| checkpoint.is_none_or(|c| **version > c)) {
+        schema = commit_schema(log_dir, *version)?;
+        if schema.is_some() {
+            break;
+        }
+    }
+    if schema.is_none() {
+        if let Some(checkpoint) = checkpoint {
+            schema = checkpoint_schema(log_dir, checkpoint)?;
+        }
+    }
+
+    let schema = schema.ok_or_else(|| delta_error(format!(
+        "No table metadata found in {}", log_dir.display()
+    )))?;
+    Ok(Some(TableState {
+        schema,
+        next_version: latest + 1,
+    }))
+}
+
+/// Version of the latest checkpoint, from `_last_checkpoint`
+fn last_checkpoint(log_dir: &Path) -> Result<Option<u64>, ParseError> {
+    let path = log_dir.join("_last_checkpoint");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents: Value = serde_json::from_str(&fs::read_to_string(&path)?)
+        .map_err(|e| delta_error(format!("Invalid {}: {}", path.display(), e)))?;
+    contents["version"].as_u64()
+        .map(Some)
+        .ok_or_else(|| delta_error(format!("No version in {}", path.display())))
+}
+
+/// Schema from the metaData action of a JSON commit, if it has one
+fn commit_schema(log_dir: &Path, version: u64) -> Result<Option<String>, ParseError> {
+    let contents = fs::read_to_string(log_dir.join(format!("{:020}.json", version)))?;
+    Ok(contents.lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|action| action["metaData"]["schemaString"].as_str().map(str::to_string))
+        .next())
+}
+
+/// Schema from the metaData rows of a (single- or multi-part) Parquet checkpoint
+fn checkpoint_schema(log_dir: &Path, version: u64) -> Result<Option<String>, ParseError> {
+    let prefix = format!("{:020}.checkpoint.", version);
+    let mut parts: Vec<PathBuf> = fs::read_dir(log_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name()
+            .map(|name| name.to_string_lossy())
+            .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".parquet")))
+        .collect();
+    if parts.is_empty() {
+        return Err(delta_error(format!("Checkpoint {} is missing from {}", version, log_dir.display())));
+    }
+    parts.sort();
+
+    let checkpoint_error = |path: &Path, e: PolarsError| delta_error(format!("Failed to read checkpoint {}: {}", path.display(), e));
+    for part in parts {
+        let actions = ParquetReader::new(fs::File::open(&part)?)
+            .with_columns(Some(vec!["metaData".to_string()]))
+            .finish()
+            .map_err(|e| checkpoint_error(&part, e))?;
+        let schemas = actions.column("metaData")
+            .and_then(|column| column.struct_()?.field_by_name("schemaString"))
+            .map_err(|e| checkpoint_error(&part, e))?;
+        let schema = schemas.str().map_err(|e| checkpoint_error(&part, e))?
+            .into_iter()
+            .flatten()
+            .next()
+            .map(str::to_string);
+        if schema.is_some() {
+            return Ok(schema);
+        }
+    }
+    Ok(None)
+}
+
+/// Cast columns without a Delta primitive type to strings
+fn delta_compatible(df: &DataFrame) -> Result<DataFrame, ParseError> {
+    let columns = df.get_columns().iter()
+        .map(|column| match delta_type(column.dtype()) {
+            Some(_) => Ok(column.clone()),
+            None => column.cast(&polars::datatypes::DataType::String),
+        })
+        .collect::<PolarsResult<Vec<_>>>()
+        .map_err(|e| delta_error(e.to_string()))?;
+    DataFrame::new(columns).map_err(|e| delta_error(e.to_string()))
+}
+
+fn delta_type(dtype: &polars::datatypes::DataType) -> Option<&'static str> {
+    match dtype {
+        polars::datatypes::DataType::Float64 => Some("double"),
+        polars::datatypes::DataType::Int64 => Some("long"),
+        polars::datatypes::DataType::Boolean => Some("boolean"),
+        polars::datatypes::DataType::String => Some("string"),
+        _ => None,
+    }
+}
+
+fn schema_string(df: &DataFrame) -> String {
+    let fields: Vec<Value> = df.get_columns().iter()
+        .map(|column| json!({
+            "name": column.name().as_str(),
+            "type": delta_type(column.dtype()).unwrap_or("string"),
+            "nullable": true,
+            "metadata": {},
+        }))
+        .collect();
+    json!({ "type": "struct", "fields": fields }).to_string()
+}
+
+fn commit_info(data: &LiCorData, source_file: &str, create: bool, timestamp: u128) -> Value {
+    let metadata = &data.metadata;
+    json!({
+        "timestamp": timestamp,
+        "operation": if create { "CREATE TABLE" } else { "WRITE" },
+        "operationParameters": { "mode": "Append" },
+        "isBlindAppend": true,
+        "clientVersion": format!("licor-{}", env!("CARGO_PKG_VERSION")),
+        "licor": {
+            "source_file": source_file,
+            "rows": data.dataframe.height(),
+            "device_serial": metadata.device_serial,
+            "console_version": metadata.console_version,
+            "head_serial": metadata.head_serial,
+            "head_version": metadata.head_version,
+            "chamber_type": metadata.chamber_type,
+            "chamber_serial": metadata.chamber_serial,
+            "fluorometer_serial": metadata.fluorometer_serial,
+            "calibration_date": metadata.calibration_date,
+            "clock_correction": metadata.clock_correction.as_ref().map(|c| json!({
+                "offset": c.offset,
+                "drift": c.drift,
+                "origin": c.origin,
+                "anchors": c.anchors,
+            })),
+            "content_hash": metadata.content_hash,
+            "calibration_certificates": metadata.calibration_certificates,
+        },
+    })
+}
+
+fn timestamp_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default()
+}
+
+fn delta_error(message: String) -> ParseError {
+    ParseError::WriteError {
+        format: "delta".to_string(),
+        message,
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::LiCor6800Fluorometer;
+
+    #[test]
+    fn test_write_delta_appends_commits() {
+        let parser = LiCor6800Fluorometer::new();
+        let data = parser.parse_file("../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1")
+            .expect("Should parse sample file");
+
+        let table = std::env::temp_dir().join(format!("licor_test_delta_{}", std::process::id()));
+        fs::remove_dir_all(&table).ok();
+        let table_path = table.to_str().unwrap();
+
+        let parquet = ParquetOptions::default();
+        write_delta(&data, table_path, "first.txt", &parquet).expect("Should create table");
+        write_delta(&data, table_path, "second.txt", &parquet).expect("Should append to table");
+
+        let log_dir = table.join(LOG_DIR);
+        let first = fs::read_to_string(log_dir.join(format!("{:020}.json", 0))).unwrap();
+        let second = fs::read_to_string(log_dir.join(format!("{:020}.json", 1))).unwrap();
+        let add: Value = first.lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .find(|action| action.get("add").is_some())
+            .unwrap();
+        let stats: Value = serde_json::from_str(add["add"]["stats"].as_str().unwrap()).unwrap();
+        assert_eq!(stats["numRecords"], data.dataframe.height());
+        let obs = data.dataframe.column("obs").unwrap().cast(&polars::datatypes::DataType::Int64).unwrap();
+        assert_eq!(stats["minValues"]["obs"], obs.i64().unwrap().min().unwrap());
+        assert_eq!(stats["maxValues"]["obs"], obs.i64().unwrap().max().unwrap());
+        assert_eq!(stats["nullCount"]["A"], data.dataframe.column("A").unwrap().null_count());
+        assert!(first.contains("\"metaData\""));
+        assert!(!second.contains("\"metaData\""));
+        assert!(second.contains("\"source_file\":\"second.txt\""));
+
+        let state = read_table_state(&log_dir).unwrap().unwrap();
+        assert_eq!(state.next_version, 2);
+        assert_eq!(state.schema, schema_string(&delta_compatible(&data.dataframe).unwrap()));
+
+        let parquet_files: Vec<_> = fs::read_dir(&table).unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "parquet"))
+            .collect();
+        assert_eq!(parquet_files.len(), 2);
+        let part = LiCorData::from_parquet(parquet_files[0].to_str().unwrap()).expect("Should keep licor metadata");
+        assert_eq!(part.metadata.device_serial, data.metadata.device_serial);
+        assert_eq!(part.variable_info.len(), data.variable_info.len());
+
+        // Another writer checkpoints version 1 and cleans up the commits
+        let schemas = Series::new("schemaString".into(), [Some(state.schema.clone()), None]);
+        let metadata = StructChunked::from_series("metaData".into(), 2, [schemas].iter()).unwrap();
+        let mut checkpoint = DataFrame::new(vec![metadata.into_series().into()]).unwrap();
+        let file = fs::File::create(log_dir.join(format!("{:020}.checkpoint.parquet", 1))).unwrap();
+        write_parquet_frame(&mut checkpoint, file, &parquet, Vec::new()).unwrap();
+        fs::write(log_dir.join("_last_checkpoint"), r#"{"version":1,"size":2}"#).unwrap();
+        fs::remove_file(log_dir.join(format!("{:020}.json", 0))).unwrap();
+        fs::remove_file(log_dir.join(format!("{:020}.json", 1))).unwrap();
+
+        write_delta(&data, table_path, "third.txt", &parquet).expect("Should append after a checkpoint");
+        assert!(log_dir.join(format!("{:020}.json", 2)).exists());
+        assert_eq!(read_table_state(&log_dir).unwrap().unwrap().next_version, 3);
+        fs::remove_dir_all(&table).ok();
+    }
+}
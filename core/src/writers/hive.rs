@@ -0,0 +1,255 @@
+use crate::{ParseError, LiCorData};
+use super::parquet::{licor_key_values, write_parquet_frame, ParquetOptions};
+use polars::prelude::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Partition value used by Hive for rows whose key is unknown
+const DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Write parsed data into a Hive-partitioned Parquet dataset
+///
+/// Rows are split by measurement date into `date=YYYY-MM-DD/<file_stem>.parquet`
+/// under `root`, and additionally by console serial
+/// (`date=.../device=<serial>/...`) when `partition_by_device` is set.
+/// The date comes from the `date` column, falling back to the epoch `time`
+/// column (or `timestamp` for long-format data). The `date` column itself is
+/// left out of the files, since Hive readers add the `date` partition key as a
+/// column; the time of day stays in `hhmmss` and `time`. Re-writing the same
+/// `file_stem` first removes its files from every partition, so rows that moved
+/// to another date leave nothing behind. Each file carries the same key-value
+/// metadata as [`write_parquet`](crate::write_parquet), so it can be read back
+/// with [`LiCorData::from_parquet`]. Returns the paths of the files written.
+pub fn write_hive_dataset(
+    data: &LiCorData,
+    root: &str,
+    file_stem: &str,
+    partition_by_device: bool,
+    parquet: &ParquetOptions,
+) -> Result<Vec<String>, ParseError> {
+    let dates = row_dates(&data.dataframe)?;
+
+    let mut partitions: BTreeMap<String, Vec<bool>> = BTreeMap::new();
+    for (row, date) in dates.iter().enumerate() {
+        let date = date.as_deref().unwrap_or(DEFAULT_PARTITION);
+        let mask = partitions.entry(date.to_string())
+            .or_insert_with(|| vec![false; dates.len()]);
+        mask[row] = true;
+    }
+
+    let mut data = data.clone();
+    if data.dataframe.column("date").is_ok() {
+        data.dataframe = data.dataframe.drop("date").map_err(hive_error)?;
+        data.variable_info.retain(|info| info.internal_name != "date");
+    }
+    remove_stem(Path::new(root), file_stem)?;
+
+    let key_values = licor_key_values(&data)?;
+    let mut paths = Vec::new();
+    for (date, mask) in partitions {
+        let mut dir = Path::new(root).join(format!("date={}", date));
+        if partition_by_device {
+            dir = dir.join(format!("device={}", escape_partition_value(&data.metadata.device_serial)));
+        }
+        std::fs::create_dir_all(&dir)?;
+
+        let mask = BooleanChunked::from_slice("mask".into(), &mask);
+        let mut partition = data.dataframe.filter(&mask).map_err(hive_error)?;
+
+        let path = dir.join(format!("{}.parquet", file_stem));
+        let file = std::fs::File::create(&path)?;
+        write_parquet_frame(&mut partition, file, parquet, key_values.clone())?;
+        paths.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(paths)
+}
+
+/// Remove `<file_stem>.parquet` from every `date=`/`device=` partition under `root`
+fn remove_stem(root: &Path, file_stem: &str) -> Result<(), ParseError> {
+    let entries = match std::fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    let file_name = format!("{}.parquet", file_stem);
+    for entry in entries {
+        let date_dir = entry?.path();
+        if !is_partition(&date_dir, "date") {
+            continue;
+        }
+        let mut dirs = vec![date_dir.clone()];
+        for entry in std::fs::read_dir(&date_dir)? {
+            let device_dir = entry?.path();
+            if is_partition(&device_dir, "device") {
+                dirs.push(device_dir);
+            }
+        }
+        // Deepest first, so a date directory emptied of its devices goes too
+        for dir in dirs.iter().rev() {
+            match std::fs::remove_file(dir.join(&file_name)) {
+                Ok(()) => {
+                    // Only succeeds once no other file is left in the partition
+                    std::fs::remove_dir(dir).ok();
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        std::fs::remove_dir(&date_dir).ok();
+    }
+    Ok(())
+}
+
+fn is_partition(path: &Path, key: &str) -> bool {
+    path.is_dir() && path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(&format!("{}=", key)))
+}
+
+/// Measurement date (`YYYY-MM-DD`) of each row, if it can be determined
+pub(crate) fn row_dates(df: &DataFrame) -> Result<Vec<Option<String>>, ParseError> {
+    // LI-6800 logs record local dates as "YYYYMMDD HH:MM:SS"
+    if let Ok(column) = df.column("date") {
+        if let Ok(values) = column.str() {
+            return Ok(values.into_iter()
+                .map(|value| value.and_then(parse_date))
+                .collect());
+        }
+    }
+
+    // Otherwise fall back to the epoch timestamp (UTC)
+    if let Ok(column) = df.column("time") {
+        let seconds = column.cast(&polars::datatypes::DataType::Float64).map_err(hive_error)?;
+        return Ok(seconds.f64().map_err(hive_error)?.into_iter()
+            .map(|value| value.filter(|v| v.is_finite()).map(epoch_date))
+            .collect());
+    }
+
+    // Long-format data carries a millisecond `timestamp` instead
+    if let Ok(column) = df.column("timestamp") {
+        if let polars::datatypes::DataType::Datetime(TimeUnit::Milliseconds, _) = column.dtype() {
+            let millis = column.cast(&polars::datatypes::DataType::Int64).map_err(hive_error)?;
+            return Ok(millis.i64().map_err(hive_error)?.into_iter()
+                .map(|value| value.map(|ms| epoch_date(ms as f64 / 1000.0)))
+                .collect());
+        }
+    }
+
+    Ok(vec![None; df.height()])
+}
+
+/// Extract the date from "YYYYMMDD ..." or "YYYY-MM-DD ..." values
+fn parse_date(value: &str) -> Option<String> {
+    let date = value.split_whitespace().next()?;
+    let digits: String = date.chars().filter(|c| *c != '-').collect();
+    if digits.len() != 8 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("{}-{}-{}", &digits[..4], &digits[4..6], &digits[6..]))
+}
+
+/// Convert seconds since the Unix epoch to a UTC calendar date
+fn epoch_date(seconds: f64) -> String {
+    // Days-to-civil conversion from Howard Hinnant's date algorithms
+    let days = (seconds / 86_400.0).floor() as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Percent-encode characters that are not safe in a partition directory name
+fn escape_partition_value(value: &str) -> String {
+    if value.is_empty() {
+        return DEFAULT_PARTITION.to_string();
+    }
+    let mut escaped = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.') {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    escaped
+}
+
+fn hive_error(e: PolarsError) -> ParseError {
+    ParseError::WriteError {
+        format: "hive".to_string(),
+        message: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_dates() {
+        assert_eq!(parse_date("20250529 09:55:12").as_deref(), Some("2025-05-29"));
+        assert_eq!(parse_date("2025-05-29").as_deref(), Some("2025-05-29"));
+        assert_eq!(parse_date("09:55:12"), None);
+        assert_eq!(epoch_date(1748526912.1), "2025-05-29");
+        assert_eq!(epoch_date(0.0), "1970-01-01");
+        assert_eq!(escape_partition_value("68C-901292"), "68C-901292");
+        assert_eq!(escape_partition_value("a/b c"), "a%2Fb%20c");
+    }
+
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_write_hive_dataset_sample_file() {
+        let parser = crate::LiCor6800Fluorometer::new();
+        let data = parser.parse_file("../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1")
+            .expect("Should parse sample file");
+
+        let root = std::env::temp_dir().join(format!("licor_test_hive_{}", std::process::id()));
+        std::fs::remove_dir_all(&root).ok();
+
+        let paths = write_hive_dataset(&data, root.to_str().unwrap(), "sample", true, &ParquetOptions::default())
+            .expect("Should write dataset");
+        assert!(!paths.is_empty());
+        let path = root.join("date=2025-05-29/device=68C-901292/sample.parquet");
+        let partition = LiCorData::from_parquet(path.to_str().unwrap()).expect("Should keep licor metadata");
+        assert_eq!(partition.metadata.device_serial, data.metadata.device_serial);
+        // The date is the partition key, not a column of the files
+        assert!(partition.dataframe.column("date").is_err());
+        assert_eq!(partition.dataframe.width(), data.dataframe.width() - 1);
+        assert_eq!(partition.variable_info.len(), data.variable_info.len() - 1);
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_rewrite_removes_stale_partitions() {
+        let parser = crate::LiCor6800Fluorometer::new();
+        let data = parser.parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .expect("Should parse sample file");
+        let mut moved = data.clone();
+        let height = moved.dataframe.height();
+        moved.dataframe.replace("date", Series::new("date".into(), vec!["20240101 12:00:00"; height]))
+            .expect("Should replace dates");
+
+        let root = std::env::temp_dir().join(format!("licor_test_hive_rewrite_{}", std::process::id()));
+        std::fs::remove_dir_all(&root).ok();
+        let root_str = root.to_str().unwrap();
+        std::fs::create_dir_all(root.join("date=2024-01-01")).unwrap();
+        std::fs::write(root.join("date=2024-01-01/other.parquet"), b"").unwrap();
+
+        write_hive_dataset(&moved, root_str, "sample", false, &ParquetOptions::default()).expect("Should write dataset");
+        assert!(root.join("date=2024-01-01/sample.parquet").exists());
+
+        let paths = write_hive_dataset(&data, root_str, "sample", false, &ParquetOptions::default()).expect("Should rewrite dataset");
+        assert_eq!(paths, vec![root.join("date=2025-05-29/sample.parquet").to_string_lossy().to_string()]);
+        assert!(!root.join("date=2024-01-01/sample.parquet").exists());
+        // Other stems in the partition are left alone
+        assert!(root.join("date=2024-01-01/other.parquet").exists());
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,17 @@
+//! Output writers for parsed LI-COR data beyond the default Parquet path
+
+pub mod delta;
+pub mod hive;
+pub mod netcdf;
+pub mod parquet;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod xlsx;
+
+pub use delta::write_delta;
+pub use hive::write_hive_dataset;
+pub use netcdf::write_netcdf;
+pub use parquet::{write_parquet, ParquetCodec, ParquetOptions, SCHEMA_VERSION};
+#[cfg(feature = "postgres")]
+pub use postgres::write_postgres;
+pub use xlsx::write_xlsx;
@@ -0,0 +1,551 @@
+use crate::{ParseError, LiCorData};
+use polars::prelude::*;
+use std::collections::HashSet;
+use std::io::Write;
+
+// NetCDF 64-bit offset (CDF-2) format tags and type codes
+const NC_DIMENSION: u32 = 0x0A;
+const NC_VARIABLE: u32 = 0x0B;
+const NC_ATTRIBUTE: u32 = 0x0C;
+const NC_BYTE: u32 = 1;
+const NC_CHAR: u32 = 2;
+const NC_INT: u32 = 4;
+const NC_DOUBLE: u32 = 6;
+
+// Default fill values from the NetCDF specification
+const FILL_BYTE: i8 = -127;
+const FILL_INT: i32 = -2147483647;
+
+/// Write parsed data to a NetCDF (64-bit offset format) file with CF-style attributes
+///
+/// Each column becomes a variable along the `obs` dimension with `units` and
+/// `long_name` attributes taken from the variable definitions in licor.toml.
+/// Instrument metadata is stored in global attributes. The 64-bit offset
+/// format lifts the classic format's 2 GiB limit on data offsets; a single
+/// variable must still fit in 4 GiB.
+pub fn write_netcdf(data: &LiCorData, path: &str) -> Result<(), ParseError> {
+    let bytes = encode_netcdf(data)?;
+    let mut file = std::fs::File::create(path)?;
+    file.write_all(&bytes)?;
+    Ok(())
+}
+
+enum Values {
+    Byte(Vec<i8>),
+    Int(Vec<i32>),
+    Double(Vec<f64>),
+    Char { strings: Vec<String>, width: usize },
+}
+
+struct NcVariable {
+    name: String,
+    dim_ids: Vec<u32>,
+    attributes: Vec<(String, AttrValue)>,
+    values: Values,
+}
+
+enum AttrValue {
+    Text(String),
+    Byte(i8),
+    Int(i32),
+    Double(f64),
+}
+
+fn encode_netcdf(data: &LiCorData) -> Result<Vec<u8>, ParseError> {
+    let n_obs = data.dataframe.height();
+    let mut dimensions: Vec<(String, usize)> = vec![("obs".to_string(), n_obs)];
+    let mut variables = Vec::new();
+    let mut used_names = HashSet::new();
+
+    for column in data.dataframe.get_columns() {
+        let original_name = column.name().to_string();
+        let name = unique_name(netcdf_name(&original_name), &mut used_names);
+        let info = data.variable_info.iter().find(|v| v.internal_name == original_name);
+
+        let series = column.as_materialized_series();
+        let (values, fill) = match series.dtype() {
+            polars::datatypes::DataType::Float64 => {
+                let values = series.f64().map_err(polars_error)?
+                    .into_iter()
+                    .map(|v| v.unwrap_or(f64::NAN))
+                    .collect();
+                (Values::Double(values), Some(AttrValue::Double(f64::NAN)))
+            }
+            polars::datatypes::DataType::Int64 => {
+                let ints = series.i64().map_err(polars_error)?;
+                // Classic NetCDF has no 64-bit integers; fall back to doubles if needed
+                let fits = ints.into_iter().flatten().all(|v| i32::try_from(v).is_ok() && v as i32 != FILL_INT);
+                if fits {
+                    let values = ints.into_iter()
+                        .map(|v| v.map(|v| v as i32).unwrap_or(FILL_INT))
+                        .collect();
+                    (Values::Int(values), Some(AttrValue::Int(FILL_INT)))
+                } else {
+                    let values = ints.into_iter()
+                        .map(|v| v.map(|v| v as f64).unwrap_or(f64::NAN))
+                        .collect();
+                    (Values::Double(values), Some(AttrValue::Double(f64::NAN)))
+                }
+            }
+            polars::datatypes::DataType::Boolean => {
+                let values = series.bool().map_err(polars_error)?
+                    .into_iter()
+                    .map(|v| v.map(|v| v as i8).unwrap_or(FILL_BYTE))
+                    .collect();
+                (Values::Byte(values), Some(AttrValue::Byte(FILL_BYTE)))
+            }
+            _ => {
+                let strings = series.cast(&polars::datatypes::DataType::String).map_err(polars_error)?;
+                let strings: Vec<String> = strings.str().map_err(polars_error)?
+                    .into_iter()
+                    .map(|v| v.unwrap_or("").to_string())
+                    .collect();
+                let width = strings.iter().map(|s| s.len()).max().unwrap_or(0).max(1);
+                (Values::Char { strings, width }, None)
+            }
+        };
+
+        let mut dim_ids = vec![0];
+        if let Values::Char { width, .. } = &values {
+            dimensions.push((format!("{}_strlen", name), *width));
+            dim_ids.push((dimensions.len() - 1) as u32);
+        }
+
+        let mut attributes = Vec::new();
+        if let Some(info) = info {
+            let long_name = if info.description.is_empty() {
+                info.display_label.clone()
+            } else {
+                info.description.clone()
+            };
+            attributes.push(("long_name".to_string(), AttrValue::Text(long_name)));
+            if let Some(units) = &info.units {
+                attributes.push(("units".to_string(), AttrValue::Text(cf_units(units))));
+            }
+            if !info.column_category.is_empty() {
+                attributes.push(("licor_category".to_string(), AttrValue::Text(info.column_category.clone())));
+            }
+        }
+        if name != original_name {
+            attributes.push(("licor_name".to_string(), AttrValue::Text(original_name.clone())));
+        }
+        if let Some(fill) = fill {
+            attributes.push(("_FillValue".to_string(), fill));
+        }
+
+        variables.push(NcVariable { name, dim_ids, attributes, values });
+    }
+
+    let metadata = &data.metadata;
+    let mut global_attributes = vec![
+        ("Conventions".to_string(), AttrValue::Text("CF-1.8".to_string())),
+        ("source".to_string(), AttrValue::Text(format!("LI-COR {}", metadata.console_version))),
+        ("instrument_serial".to_string(), AttrValue::Text(metadata.device_serial.clone())),
+    ];
+    let optional_fields = [
+        ("head_serial", &metadata.head_serial),
+        ("head_version", &metadata.head_version),
+        ("chamber_type", &metadata.chamber_type),
+        ("chamber_serial", &metadata.chamber_serial),
+        ("fluorometer_serial", &metadata.fluorometer_serial),
+        ("calibration_date", &metadata.calibration_date),
+    ];
+    for (key, value) in optional_fields {
+        if let Some(value) = value {
+            global_attributes.push((key.to_string(), AttrValue::Text(value.clone())));
+        }
+    }
+    if let Some(correction) = &metadata.clock_correction {
+        global_attributes.push(("clock_correction".to_string(), AttrValue::Text(correction.describe())));
+    }
+    if let Some(hash) = &metadata.content_hash {
+        global_attributes.push(("content_hash".to_string(), AttrValue::Text(hash.clone())));
+    }
+    if !metadata.calibration_certificates.is_empty() {
+        let certificates: Vec<String> = metadata.calibration_certificates.iter().map(|c| c.describe()).collect();
+        global_attributes.push(("calibration_certificates".to_string(), AttrValue::Text(certificates.join("; "))));
+    }
+
+    encode_file(&dimensions, &global_attributes, &variables)
+}
+
+fn encode_file(
+    dimensions: &[(String, usize)],
+    global_attributes: &[(String, AttrValue)],
+    variables: &[NcVariable],
+) -> Result<Vec<u8>, ParseError> {
+    // Lengths and variable sizes are 32-bit fields even with 64-bit offsets
+    if let Some((name, _)) = dimensions.iter().find(|(_, len)| i32::try_from(*len).is_err()) {
+        return Err(size_error(format!("dimension '{}' has more than {} entries", name, i32::MAX)));
+    }
+    if let Some(variable) = variables.iter().find(|v| padded_len(values_len(&v.values)) > u32::MAX as usize - 3) {
+        return Err(size_error(format!("variable '{}' is larger than 4 GiB", variable.name)));
+    }
+
+    // Header size is needed up front to compute each variable's data offset,
+    // so encode once with placeholder offsets to measure it
+    let header_len = encode_header(dimensions, global_attributes, variables, &vec![0; variables.len()]).len();
+
+    let mut offsets = Vec::with_capacity(variables.len());
+    let mut offset = header_len;
+    for variable in variables {
+        offsets.push(offset as u64);
+        offset += padded_len(values_len(&variable.values));
+    }
+
+    let mut buf = encode_header(dimensions, global_attributes, variables, &offsets);
+    for variable in variables {
+        encode_values(&mut buf, &variable.values);
+    }
+    Ok(buf)
+}
+
+fn encode_header(
+    dimensions: &[(String, usize)],
+    global_attributes: &[(String, AttrValue)],
+    variables: &[NcVariable],
+    offsets: &[u64],
+) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"CDF\x02");
+    put_u32(&mut buf, 0); // numrecs: no record dimension
+
+    put_u32(&mut buf, NC_DIMENSION);
+    put_u32(&mut buf, dimensions.len() as u32);
+    for (name, len) in dimensions {
+        put_name(&mut buf, name);
+        put_u32(&mut buf, *len as u32);
+    }
+
+    encode_attributes(&mut buf, global_attributes);
+
+    if variables.is_empty() {
+        put_u32(&mut buf, 0);
+        put_u32(&mut buf, 0);
+    } else {
+        put_u32(&mut buf, NC_VARIABLE);
+        put_u32(&mut buf, variables.len() as u32);
+    }
+    for (variable, offset) in variables.iter().zip(offsets) {
+        put_name(&mut buf, &variable.name);
+        put_u32(&mut buf, variable.dim_ids.len() as u32);
+        for dim_id in &variable.dim_ids {
+            put_u32(&mut buf, *dim_id);
+        }
+        encode_attributes(&mut buf, &variable.attributes);
+        let nc_type = match variable.values {
+            Values::Byte(_) => NC_BYTE,
+            Values::Int(_) => NC_INT,
+            Values::Double(_) => NC_DOUBLE,
+            Values::Char { .. } => NC_CHAR,
+        };
+        put_u32(&mut buf, nc_type);
+        put_u32(&mut buf, padded_len(values_len(&variable.values)) as u32);
+        buf.extend_from_slice(&offset.to_be_bytes());
+    }
+
+    buf
+}
+
+fn encode_attributes(buf: &mut Vec<u8>, attributes: &[(String, AttrValue)]) {
+    if attributes.is_empty() {
+        put_u32(buf, 0);
+        put_u32(buf, 0);
+        return;
+    }
+
+    put_u32(buf, NC_ATTRIBUTE);
+    put_u32(buf, attributes.len() as u32);
+    for (name, value) in attributes {
+        put_name(buf, name);
+        match value {
+            AttrValue::Text(text) => {
+                put_u32(buf, NC_CHAR);
+                put_u32(buf, text.len() as u32);
+                buf.extend_from_slice(text.as_bytes());
+                pad(buf);
+            }
+            AttrValue::Byte(v) => {
+                put_u32(buf, NC_BYTE);
+                put_u32(buf, 1);
+                buf.push(*v as u8);
+                pad(buf);
+            }
+            AttrValue::Int(v) => {
+                put_u32(buf, NC_INT);
+                put_u32(buf, 1);
+                buf.extend_from_slice(&v.to_be_bytes());
+            }
+            AttrValue::Double(v) => {
+                put_u32(buf, NC_DOUBLE);
+                put_u32(buf, 1);
+                buf.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+    }
+}
+
+fn encode_values(buf: &mut Vec<u8>, values: &Values) {
+    match values {
+        Values::Byte(values) => buf.extend(values.iter().map(|v| *v as u8)),
+        Values::Int(values) => {
+            for v in values {
+                buf.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+        Values::Double(values) => {
+            for v in values {
+                buf.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+        Values::Char { strings, width } => {
+            for s in strings {
+                buf.extend_from_slice(s.as_bytes());
+                buf.extend(std::iter::repeat_n(0u8, width - s.len()));
+            }
+        }
+    }
+    pad(buf);
+}
+
+fn values_len(values: &Values) -> usize {
+    match values {
+        Values::Byte(values) => values.len(),
+        Values::Int(values) => values.len() * 4,
+        Values::Double(values) => values.len() * 8,
+        Values::Char { strings, width } => strings.len() * width,
+    }
+}
+
+fn padded_len(len: usize) -> usize {
+    len.div_ceil(4) * 4
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_name(buf: &mut Vec<u8>, name: &str) {
+    put_u32(buf, name.len() as u32);
+    buf.extend_from_slice(name.as_bytes());
+    pad(buf);
+}
+
+fn pad(buf: &mut Vec<u8>) {
+    while !buf.len().is_multiple_of(4) {
+        buf.push(0);
+    }
+}
+
+/// Make a LI-COR variable name safe for NetCDF/CF (letters, digits, underscores)
+fn netcdf_name(name: &str) -> String {
+    let mut cleaned: String = name.chars()
+        .map(|c| match c {
+            'Δ' => "delta_".to_string(),
+            'α' => "alpha_".to_string(),
+            '\'' => "_prime".to_string(),
+            '@' => "_at_".to_string(),
+            '%' => "_pct".to_string(),
+            c if c.is_ascii_alphanumeric() || c == '_' => c.to_string(),
+            _ => "_".to_string(),
+        })
+        .collect();
+
+    if !cleaned.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        cleaned.insert(0, 'v');
+    }
+    cleaned
+}
+
+fn unique_name(name: String, used_names: &mut HashSet<String>) -> String {
+    let mut candidate = name.clone();
+    let mut counter = 1;
+    while used_names.contains(&candidate) {
+        candidate = format!("{}_{}", name, counter);
+        counter += 1;
+    }
+    used_names.insert(candidate.clone());
+    candidate
+}
+
+/// Convert LI-COR unit strings to UDUNITS-compatible spellings
+fn cf_units(units: &str) -> String {
+    match units {
+        "°C" | "C" => return "degC".to_string(),
+        "%" => return "percent".to_string(),
+        "sec" => return "s".to_string(),
+        "hrs" => return "h".to_string(),
+        _ => {}
+    }
+
+    crate::ascii_units(units)
+}
+
+fn polars_error(e: PolarsError) -> ParseError {
+    ParseError::WriteError {
+        format: "netcdf".to_string(),
+        message: e.to_string(),
+    }
+}
+
+fn size_error(message: String) -> ParseError {
+    ParseError::WriteError {
+        format: "netcdf".to_string(),
+        message: format!("too large for NetCDF: {}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "device-6800")]
+    use crate::LiCor6800Fluorometer;
+
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_encode_netcdf_sample_file() {
+        let parser = LiCor6800Fluorometer::new();
+        let data = parser.parse_file("../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1")
+            .expect("Should parse sample file");
+
+        let bytes = encode_netcdf(&data).expect("Should encode NetCDF");
+        assert_eq!(&bytes[..4], b"CDF\x02");
+        assert!(bytes.len().is_multiple_of(4));
+
+        let file = read_netcdf(&bytes);
+        let rows = data.dataframe.height();
+        assert_eq!(file.dimensions[0], ("obs".to_string(), rows));
+        assert_eq!(file.variables.len(), data.dataframe.width());
+        assert_eq!(file.attribute(&file.attributes, "instrument_serial"), data.metadata.device_serial.as_bytes());
+
+        // Values read back from each variable's offset match the data
+        let a = file.variable("A");
+        assert_eq!((a.nc_type, a.dim_ids.as_slice()), (NC_DOUBLE, &[0][..]));
+        assert_eq!(file.attribute(&a.attributes, "units"), b"umol m-2 s-1");
+        let expected: Vec<f64> = data.dataframe.column("A").unwrap().f64().unwrap().into_iter().map(|v| v.unwrap_or(f64::NAN)).collect();
+        let values: Vec<f64> = bytes[a.begin..a.begin + rows * 8].chunks(8).map(|b| f64::from_be_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(values.iter().map(|v| v.to_bits()).collect::<Vec<_>>(), expected.iter().map(|v| v.to_bits()).collect::<Vec<_>>());
+
+        // Text columns are padded to a per-variable string length dimension
+        let date = file.variable("date");
+        assert_eq!(date.nc_type, NC_CHAR);
+        let (strlen_name, width) = &file.dimensions[date.dim_ids[1]];
+        assert_eq!(strlen_name, "date_strlen");
+        let first = data.dataframe.column("date").unwrap().str().unwrap().get(0).unwrap();
+        assert_eq!(&bytes[date.begin..date.begin + first.len()], first.as_bytes());
+        assert_eq!(*width, first.len());
+
+        // Every variable's data lies inside the file
+        for variable in &file.variables {
+            assert!(variable.begin + variable.vsize <= bytes.len(), "{}", variable.name);
+        }
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let variables = [NcVariable {
+            name: "x".to_string(),
+            dim_ids: vec![0],
+            attributes: Vec::new(),
+            values: Values::Byte(Vec::new()),
+        }];
+        let too_long = [("obs".to_string(), i32::MAX as usize + 1)];
+        assert!(matches!(encode_file(&too_long, &[], &variables), Err(ParseError::WriteError { .. })));
+        assert!(encode_file(&[("obs".to_string(), 0)], &[], &variables).is_ok());
+    }
+
+    #[cfg(feature = "device-6800")]
+    struct ReadVariable {
+        name: String,
+        dim_ids: Vec<usize>,
+        attributes: Vec<(String, Vec<u8>)>,
+        nc_type: u32,
+        vsize: usize,
+        begin: usize,
+    }
+
+    #[cfg(feature = "device-6800")]
+    struct ReadFile {
+        dimensions: Vec<(String, usize)>,
+        attributes: Vec<(String, Vec<u8>)>,
+        variables: Vec<ReadVariable>,
+    }
+
+    #[cfg(feature = "device-6800")]
+    impl ReadFile {
+        fn variable(&self, name: &str) -> &ReadVariable {
+            self.variables.iter().find(|v| v.name == name).unwrap()
+        }
+
+        fn attribute<'a>(&self, attributes: &'a [(String, Vec<u8>)], name: &str) -> &'a [u8] {
+            &attributes.iter().find(|(n, _)| n == name).unwrap().1
+        }
+    }
+
+    /// Minimal CDF-2 header reader, following the NetCDF classic format spec
+    #[cfg(feature = "device-6800")]
+    fn read_netcdf(bytes: &[u8]) -> ReadFile {
+        let mut pos = 4;
+        let mut u32_at = |pos: &mut usize| {
+            let value = u32::from_be_bytes(bytes[*pos..*pos + 4].try_into().unwrap());
+            *pos += 4;
+            value as usize
+        };
+        let name = |pos: &mut usize, u32_at: &mut dyn FnMut(&mut usize) -> usize| {
+            let len = u32_at(pos);
+            let name = String::from_utf8(bytes[*pos..*pos + len].to_vec()).unwrap();
+            *pos += padded_len(len);
+            name
+        };
+        let type_size = |nc_type: u32| match nc_type {
+            NC_BYTE | NC_CHAR => 1,
+            NC_INT => 4,
+            NC_DOUBLE => 8,
+            other => panic!("unexpected type {}", other),
+        };
+        let attributes = |pos: &mut usize, u32_at: &mut dyn FnMut(&mut usize) -> usize| {
+            let tag = u32_at(pos);
+            let count = u32_at(pos);
+            assert!(tag == NC_ATTRIBUTE as usize || (tag == 0 && count == 0));
+            (0..count)
+                .map(|_| {
+                    let name = name(pos, u32_at);
+                    let size = type_size(u32_at(pos) as u32) * u32_at(pos);
+                    let value = bytes[*pos..*pos + size].to_vec();
+                    *pos += padded_len(size);
+                    (name, value)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(u32_at(&mut pos), 0, "numrecs");
+        assert_eq!(u32_at(&mut pos), NC_DIMENSION as usize);
+        let dimensions = (0..u32_at(&mut pos))
+            .map(|_| (name(&mut pos, &mut u32_at), u32_at(&mut pos)))
+            .collect();
+        let global = attributes(&mut pos, &mut u32_at);
+        assert_eq!(u32_at(&mut pos), NC_VARIABLE as usize);
+        let variables = (0..u32_at(&mut pos))
+            .map(|_| {
+                let name = name(&mut pos, &mut u32_at);
+                let dim_ids = (0..u32_at(&mut pos)).map(|_| u32_at(&mut pos)).collect();
+                let attributes = attributes(&mut pos, &mut u32_at);
+                let nc_type = u32_at(&mut pos) as u32;
+                let vsize = u32_at(&mut pos);
+                let begin = u64::from_be_bytes(bytes[pos..pos + 8].try_into().unwrap()) as usize;
+                pos += 8;
+                ReadVariable { name, dim_ids, attributes, nc_type, vsize, begin }
+            })
+            .collect();
+        ReadFile { dimensions, attributes: global, variables }
+    }
+
+    #[test]
+    fn test_cf_names_and_units() {
+        assert_eq!(netcdf_name("Fv/Fm"), "Fv_Fm");
+        assert_eq!(netcdf_name("Fm'"), "Fm_prime");
+        assert_eq!(netcdf_name("1-qL"), "v1_qL");
+        assert_eq!(cf_units("µmol m⁻² s⁻¹"), "umol m-2 s-1");
+        assert_eq!(cf_units("°C"), "degC");
+    }
+}
@@ -0,0 +1,349 @@
+use crate::{ParseError, LiCorData};
+use polars::prelude::*;
+use polars_parquet::write::{
+    ChildWriteOptions, ColumnWriteOptions, CompressionOptions, Encoding, FileWriter, KeyValue,
+    RowGroupIterator, StatisticsOptions, Version, WriteOptions,
+};
+use std::io::Write;
+use std::str::FromStr;
+
+/// Compression codec for Parquet output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParquetCodec {
+    Uncompressed,
+    Snappy,
+    #[default]
+    Zstd,
+}
+
+impl FromStr for ParquetCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "uncompressed" | "none" => Ok(ParquetCodec::Uncompressed),
+            "snappy" => Ok(ParquetCodec::Snappy),
+            "zstd" => Ok(ParquetCodec::Zstd),
+            _ => Err(format!("Unsupported compression '{}': use zstd, snappy or uncompressed", s)),
+        }
+    }
+}
+
+/// Compression and encoding settings for Parquet output
+///
+/// The default (zstd at its default level, dictionary encoding on) matches
+/// what Polars writes on its own. Archives can raise the zstd level up to 22;
+/// `snappy` or `uncompressed` trade file size for read and write speed.
+///
+/// For large merged datasets, sorting by the columns queries filter on (e.g.
+/// `time`) and using smaller row groups keeps each group's min/max statistics
+/// narrow, so readers can skip most groups with predicate pushdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParquetOptions {
+    pub codec: ParquetCodec,
+    /// Compression level; only zstd takes one (1-22)
+    pub level: Option<i32>,
+    /// Dictionary-encode integer and string columns
+    pub dictionary: bool,
+    /// Rows per row group (Polars picks when unset)
+    pub row_group_size: Option<usize>,
+    /// Columns to sort the rows by before writing, in order (nulls last)
+    pub sort_by: Vec<String>,
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        ParquetOptions {
+            codec: ParquetCodec::default(),
+            level: None,
+            dictionary: true,
+            row_group_size: None,
+            sort_by: Vec::new(),
+        }
+    }
+}
+
+impl ParquetOptions {
+    fn compression(&self) -> Result<ParquetCompression, ParseError> {
+        match (self.codec, self.level) {
+            (ParquetCodec::Zstd, level) => {
+                let level = level.map(ZstdLevel::try_new).transpose().map_err(parquet_error)?;
+                Ok(ParquetCompression::Zstd(level))
+            }
+            (codec, Some(_)) => Err(ParseError::WriteError {
+                format: "parquet".to_string(),
+                message: format!("{:?} compression does not take a level", codec),
+            }),
+            (ParquetCodec::Snappy, None) => Ok(ParquetCompression::Snappy),
+            (ParquetCodec::Uncompressed, None) => Ok(ParquetCompression::Uncompressed),
+        }
+    }
+}
+
+/// Metadata key holding the content hash of the parsed data
+pub const CONTENT_HASH_KEY: &str = "licor.content_hash";
+/// Metadata key holding the instrument metadata as JSON
+pub const METADATA_KEY: &str = "licor.metadata";
+/// Metadata key holding the variable info of the columns as JSON
+pub const VARIABLE_INFO_KEY: &str = "licor.variable_info";
+/// Metadata key holding the layout version of the file
+pub const SCHEMA_VERSION_KEY: &str = "licor.schema_version";
+/// Layout version [`write_parquet`] writes; files without
+/// `licor.schema_version` are version 1 (see [`migrate_parquet`](crate::migrate_parquet))
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Write parsed data to a Parquet file
+///
+/// The file's key-value metadata holds the instrument metadata and variable
+/// info as JSON (`licor.metadata`, `licor.variable_info`), so
+/// [`LiCorData::from_parquet`] can read it back, the layout version under
+/// `licor.schema_version`, and the content hash, when known, under
+/// `licor.content_hash`.
+pub fn write_parquet(data: &LiCorData, path: &str, options: &ParquetOptions) -> Result<(), ParseError> {
+    let mut file = std::fs::File::create(path)?;
+    write_parquet_frame(&mut data.dataframe.clone(), &mut file, options, licor_key_values(data)?)
+}
+
+/// Key-value metadata [`write_parquet`] stores with the data
+pub(crate) fn licor_key_values(data: &LiCorData) -> Result<Vec<(String, String)>, ParseError> {
+    let json = |e: serde_json::Error| ParseError::WriteError { format: "parquet".to_string(), message: e.to_string() };
+    let mut key_values = vec![
+        (METADATA_KEY.to_string(), serde_json::to_string(&data.metadata).map_err(json)?),
+        (VARIABLE_INFO_KEY.to_string(), serde_json::to_string(&data.variable_info).map_err(json)?),
+        (SCHEMA_VERSION_KEY.to_string(), SCHEMA_VERSION.to_string()),
+    ];
+    key_values.extend(data.metadata.content_hash.iter().map(|hash| (CONTENT_HASH_KEY.to_string(), hash.clone())));
+    Ok(key_values)
+}
+
+impl LiCorData {
+    /// Read a Parquet file written by [`write_parquet`], restoring its metadata
+    /// and variable info
+    ///
+    /// Coercion reports are not stored, so they come back empty.
+    pub fn from_parquet(path: &str) -> Result<LiCorData, ParseError> {
+        let invalid = |message: String| ParseError::InvalidHeaderFormat {
+            message: format!("{}: {}", path, message),
+        };
+        let mut reader = ParquetReader::new(std::fs::File::open(path)?);
+        let key_values = key_values(&mut reader).map_err(|e| invalid(e.to_string()))?;
+        let value = |key: &str| key_values.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| invalid(format!("no {} metadata; was it written by licor convert?", key)));
+        let version = value(SCHEMA_VERSION_KEY).ok().and_then(|v| v.parse::<u32>().ok()).unwrap_or(1);
+        if version > SCHEMA_VERSION {
+            return Err(invalid(format!("schema version {} is newer than this version of licor reads ({})", version, SCHEMA_VERSION)));
+        }
+
+        let metadata = serde_json::from_str(value(METADATA_KEY)?)
+            .map_err(|e| invalid(format!("invalid {}: {}", METADATA_KEY, e)))?;
+        let variable_info = serde_json::from_str(value(VARIABLE_INFO_KEY)?)
+            .map_err(|e| invalid(format!("invalid {}: {}", VARIABLE_INFO_KEY, e)))?;
+        let dataframe = reader.finish().map_err(|e| invalid(e.to_string()))?;
+        Ok(LiCorData { metadata, dataframe, variable_info, coercion_reports: Vec::new() })
+    }
+}
+
+/// Key-value metadata of a Parquet file
+pub(crate) fn key_values(reader: &mut ParquetReader<std::fs::File>) -> PolarsResult<Vec<(String, String)>> {
+    Ok(reader.get_metadata()?
+        .key_value_metadata()
+        .iter()
+        .flatten()
+        .filter_map(|kv| Some((kv.key.clone(), kv.value.clone()?)))
+        .collect())
+}
+
+/// Write a DataFrame as Parquet with the given settings and key-value metadata
+/// (shared by the dataset writers)
+pub(crate) fn write_parquet_frame<W: Write>(
+    df: &mut DataFrame,
+    writer: W,
+    options: &ParquetOptions,
+    key_values: Vec<(String, String)>,
+) -> Result<(), ParseError> {
+    let compression = options.compression()?;
+    if options.row_group_size == Some(0) {
+        return Err(ParseError::WriteError {
+            format: "parquet".to_string(),
+            message: "row group size must be at least 1".to_string(),
+        });
+    }
+    if !options.sort_by.is_empty() {
+        if let Some(missing) = options.sort_by.iter().find(|name| df.column(name).is_err()) {
+            return Err(ParseError::UnknownVariable { variable: missing.clone() });
+        }
+        let sort_options = SortMultipleOptions::default()
+            .with_maintain_order(true)
+            .with_nulls_last(true);
+        *df = df.sort(options.sort_by.clone(), sort_options).map_err(parquet_error)?;
+    }
+
+    // Each chunk becomes a row group
+    match options.row_group_size {
+        Some(size) => *df = split_row_groups(df, size).map_err(parquet_error)?,
+        None => {
+            df.align_chunks_par();
+        }
+    }
+
+    if options.dictionary {
+        ParquetWriter::new(writer)
+            .with_compression(compression)
+            .with_row_group_size(options.row_group_size)
+            .with_key_value_metadata((!key_values.is_empty()).then(|| KeyValueMetadata::from_static(key_values.clone())))
+            .finish(df)
+            .map_err(parquet_error)?;
+        return Ok(());
+    }
+
+    // Polars always dictionary-encodes integers and strings, so plain encoding
+    // goes through the lower-level writer
+    let schema = df.schema().to_arrow(CompatLevel::newest());
+    let mut column_options = get_column_write_options(&schema, &[]);
+    column_options.iter_mut().for_each(plain_encoding);
+    let write_options = WriteOptions {
+        statistics: StatisticsOptions::default(),
+        version: Version::V1,
+        compression: CompressionOptions::from(compression),
+        data_page_size: None,
+    };
+
+    let batches = df.iter_chunks(CompatLevel::newest(), false).map(Ok);
+    let row_groups = RowGroupIterator::try_new(batches, &schema, write_options, column_options.clone())
+        .map_err(parquet_error)?;
+    let mut file_writer = FileWriter::try_new(writer, schema, write_options, &column_options)
+        .map_err(parquet_error)?;
+    for group in row_groups {
+        file_writer.write(group.map_err(parquet_error)?).map_err(parquet_error)?;
+    }
+    let key_values = key_values.into_iter()
+        .map(|(key, value)| KeyValue { key, value: Some(value) })
+        .collect::<Vec<_>>();
+    file_writer.end((!key_values.is_empty()).then_some(key_values), &column_options).map_err(parquet_error)?;
+    Ok(())
+}
+
+/// Re-chunk the rows into consecutive chunks of `size` rows
+fn split_row_groups(df: &DataFrame, size: usize) -> PolarsResult<DataFrame> {
+    let mut groups = (0..df.height()).step_by(size).map(|offset| {
+        let mut group = df.slice(offset as i64, size);
+        group.as_single_chunk_par();
+        group
+    });
+    let Some(mut result) = groups.next() else {
+        return Ok(df.clone());
+    };
+    for group in groups {
+        result.vstack_mut(&group)?;
+    }
+    Ok(result)
+}
+
+fn plain_encoding(options: &mut ColumnWriteOptions) {
+    match &mut options.children {
+        ChildWriteOptions::Leaf(field) => field.encoding = Encoding::Plain,
+        ChildWriteOptions::ListLike(list) => plain_encoding(&mut list.child),
+        ChildWriteOptions::Struct(fields) => fields.children.iter_mut().for_each(plain_encoding),
+    }
+}
+
+fn parquet_error(e: PolarsError) -> ParseError {
+    ParseError::WriteError {
+        format: "parquet".to_string(),
+        message: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_codec() {
+        assert_eq!("ZSTD".parse(), Ok(ParquetCodec::Zstd));
+        assert_eq!("none".parse(), Ok(ParquetCodec::Uncompressed));
+        assert!("lzma".parse::<ParquetCodec>().is_err());
+    }
+
+    #[test]
+    fn test_write_parquet_options() {
+        let df = df! {
+            "obs" => [1i64, 2, 3, 4],
+            "A" => [12.5, 13.0, 12.8, 12.9],
+            "hhmmss" => ["09:55:12", "09:56:12", "09:57:12", "09:58:12"],
+        }.unwrap();
+
+        let settings = [
+            ParquetOptions::default(),
+            ParquetOptions { level: Some(19), dictionary: false, ..ParquetOptions::default() },
+            ParquetOptions { codec: ParquetCodec::Snappy, dictionary: false, ..ParquetOptions::default() },
+            ParquetOptions { codec: ParquetCodec::Uncompressed, ..ParquetOptions::default() },
+        ];
+        for options in settings {
+            let mut buffer = Vec::new();
+            write_parquet_frame(&mut df.clone(), &mut buffer, &options, Vec::new()).unwrap();
+            let read = ParquetReader::new(std::io::Cursor::new(buffer)).finish().unwrap();
+            assert!(read.equals(&df), "round trip failed for {:?}", options);
+        }
+
+        let invalid = ParquetOptions { codec: ParquetCodec::Snappy, level: Some(3), ..ParquetOptions::default() };
+        assert!(write_parquet_frame(&mut df.clone(), Vec::new(), &invalid, Vec::new()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "device-6800")]
+    fn test_parquet_round_trip() {
+        let data = crate::LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("licor_test_round_trip_{}.parquet", std::process::id()));
+        let path = path.to_str().unwrap();
+        write_parquet(&data, path, &ParquetOptions::default()).unwrap();
+
+        let read = LiCorData::from_parquet(path).unwrap();
+        assert!(read.dataframe.equals_missing(&data.dataframe));
+        assert_eq!(read.metadata.device_serial, "68C-901292");
+        assert_eq!(read.metadata.content_hash, data.metadata.content_hash);
+        assert_eq!(read.variable_info.len(), data.variable_info.len());
+        let a = read.variable_info.iter().find(|v| v.internal_name == "A").unwrap();
+        assert_eq!(a.units.as_deref(), Some("µmol m-2 s-1"));
+
+        // Plain Parquet files lack the metadata
+        write_parquet_frame(&mut data.dataframe.clone(), std::fs::File::create(path).unwrap(), &ParquetOptions::default(), Vec::new()).unwrap();
+        assert!(LiCorData::from_parquet(path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_row_groups_and_sorting() {
+        let df = df! {
+            "obs" => [3i64, 1, 4, 2, 5],
+            "time" => [30.0, 10.0, 40.0, 20.0, 50.0],
+        }.unwrap();
+
+        for dictionary in [true, false] {
+            let options = ParquetOptions {
+                dictionary,
+                row_group_size: Some(2),
+                sort_by: vec!["time".to_string()],
+                ..ParquetOptions::default()
+            };
+            let mut buffer = Vec::new();
+            write_parquet_frame(&mut df.clone(), &mut buffer, &options, Vec::new()).unwrap();
+
+            let mut reader = ParquetReader::new(std::io::Cursor::new(buffer));
+            assert_eq!(reader.get_metadata().unwrap().row_groups.len(), 3);
+            let read = reader.finish().unwrap();
+            let obs: Vec<i64> = read.column("obs").unwrap().i64().unwrap().into_no_null_iter().collect();
+            assert_eq!(obs, vec![1, 2, 3, 4, 5]);
+        }
+
+        let unknown = ParquetOptions { sort_by: vec!["elapsed".to_string()], ..ParquetOptions::default() };
+        assert!(matches!(
+            write_parquet_frame(&mut df.clone(), Vec::new(), &unknown, Vec::new()),
+            Err(ParseError::UnknownVariable { .. })
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{ParseError, LiCorData};
+use polars::prelude::*;
+use postgres::{Client, NoTls};
+use std::io::Write;
+
+const CREATE_TABLES: &str = "
+    CREATE TABLE IF NOT EXISTS licor_files (
+        source_file TEXT PRIMARY KEY,
+        device_serial TEXT NOT NULL,
+        console_version TEXT NOT NULL,
+        head_serial TEXT,
+        head_version TEXT,
+        chamber_type TEXT,
+        chamber_serial TEXT,
+        fluorometer_serial TEXT,
+        calibration_date TEXT,
+        n_observations BIGINT NOT NULL,
+        imported_at TIMESTAMPTZ NOT NULL DEFAULT now()
+    );
+    ALTER TABLE licor_files ADD COLUMN IF NOT EXISTS calibration_certificates JSONB;
+    CREATE TABLE IF NOT EXISTS licor_observations (
+        source_file TEXT NOT NULL REFERENCES licor_files (source_file) ON DELETE CASCADE,
+        row_index BIGINT NOT NULL,
+        data JSONB NOT NULL,
+        PRIMARY KEY (source_file, row_index)
+    );
+";
+
+const UPSERT_FILE: &str = "
+    INSERT INTO licor_files (
+        source_file, device_serial, console_version, head_serial, head_version,
+        chamber_type, chamber_serial, fluorometer_serial, calibration_date, n_observations,
+        calibration_certificates
+    )
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11::TEXT::JSONB)
+    ON CONFLICT (source_file) DO UPDATE SET
+        device_serial = EXCLUDED.device_serial,
+        console_version = EXCLUDED.console_version,
+        head_serial = EXCLUDED.head_serial,
+        head_version = EXCLUDED.head_version,
+        chamber_type = EXCLUDED.chamber_type,
+        chamber_serial = EXCLUDED.chamber_serial,
+        fluorometer_serial = EXCLUDED.fluorometer_serial,
+        calibration_date = EXCLUDED.calibration_date,
+        n_observations = EXCLUDED.n_observations,
+        calibration_certificates = EXCLUDED.calibration_certificates,
+        imported_at = now()
+";
+
+/// Load parsed data into a PostgreSQL database
+///
+/// File metadata is upserted into `licor_files` keyed by `source_file` (with
+/// the instruments' calibration certificates as JSONB), and
+/// observations are bulk-loaded with `COPY` into `licor_observations` as one
+/// JSONB object per row. Re-importing a file replaces its observations, all
+/// within a single transaction. Tables are created if they do not exist.
+pub fn write_postgres(data: &LiCorData, dsn: &str, source_file: &str) -> Result<(), ParseError> {
+    let mut client = Client::connect(dsn, NoTls).map_err(postgres_error)?;
+    client.batch_execute(CREATE_TABLES).map_err(postgres_error)?;
+
+    let mut transaction = client.transaction().map_err(postgres_error)?;
+
+    let metadata = &data.metadata;
+    let n_observations = data.dataframe.height() as i64;
+    let certificates = (!metadata.calibration_certificates.is_empty())
+        .then(|| serde_json::to_string(&metadata.calibration_certificates))
+        .transpose()
+        .map_err(|e| ParseError::WriteError { format: "postgres".to_string(), message: e.to_string() })?;
+    transaction.execute(UPSERT_FILE, &[
+        &source_file,
+        &metadata.device_serial,
+        &metadata.console_version,
+        &metadata.head_serial,
+        &metadata.head_version,
+        &metadata.chamber_type,
+        &metadata.chamber_serial,
+        &metadata.fluorometer_serial,
+        &metadata.calibration_date,
+        &n_observations,
+        &certificates,
+    ]).map_err(postgres_error)?;
+
+    transaction.execute("DELETE FROM licor_observations WHERE source_file = $1", &[&source_file])
+        .map_err(postgres_error)?;
+
+    let mut writer = transaction
+        .copy_in("COPY licor_observations (source_file, row_index, data) FROM STDIN")
+        .map_err(postgres_error)?;
+    for (row_index, row) in row_objects(&data.dataframe)?.into_iter().enumerate() {
+        let line = format!(
+            "{}\t{}\t{}\n",
+            escape_copy_text(source_file),
+            row_index,
+            escape_copy_text(&serde_json::Value::Object(row).to_string()),
+        );
+        writer.write_all(line.as_bytes())?;
+    }
+    writer.finish().map_err(postgres_error)?;
+
+    transaction.commit().map_err(postgres_error)?;
+    Ok(())
+}
+
+/// Convert each DataFrame row to a JSON object keyed by column name
+fn row_objects(df: &DataFrame) -> Result<Vec<serde_json::Map<String, serde_json::Value>>, ParseError> {
+    let mut rows = vec![serde_json::Map::new(); df.height()];
+
+    for column in df.get_columns() {
+        let name = column.name().to_string();
+        for (row, object) in rows.iter_mut().enumerate() {
+            let value = column.get(row).map_err(|e| ParseError::WriteError {
+                format: "postgres".to_string(),
+                message: e.to_string(),
+            })?;
+            let json = match value {
+                AnyValue::Null => serde_json::Value::Null,
+                AnyValue::Boolean(v) => serde_json::Value::Bool(v),
+                AnyValue::Int64(v) => serde_json::Value::from(v),
+                // NaN and infinities are not representable in JSON
+                AnyValue::Float64(v) => serde_json::Number::from_f64(v)
+                    .map(serde_json::Value::Number)
+                    .unwrap_or(serde_json::Value::Null),
+                AnyValue::String(v) => serde_json::Value::String(v.to_string()),
+                other => serde_json::Value::String(other.to_string()),
+            };
+            object.insert(name.clone(), json);
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Escape a value for PostgreSQL's COPY text format
+fn escape_copy_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn postgres_error(e: postgres::Error) -> ParseError {
+    ParseError::WriteError {
+        format: "postgres".to_string(),
+        message: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_copy_text() {
+        assert_eq!(escape_copy_text(r#"{"a":"x\ty"}"#), r#"{"a":"x\\ty"}"#);
+        assert_eq!(escape_copy_text("line\nbreak"), "line\\nbreak");
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{ParseError, LiCorData};
+use polars::prelude::*;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+
+/// Write parsed data to an Excel workbook
+///
+/// The workbook contains three sheets:
+/// - `Header`: instrument metadata
+/// - `Data`: one row of variable labels, one row of units, then the observations
+/// - `Variables`: the variable dictionary (labels, units, descriptions, types)
+pub fn write_xlsx(data: &LiCorData, path: &str) -> Result<(), ParseError> {
+    let mut workbook = Workbook::new();
+    let bold = Format::new().set_bold();
+    
+    let header_sheet = workbook.add_worksheet();
+    write_header_sheet(header_sheet, data, &bold).map_err(xlsx_error)?;
+    
+    let data_sheet = workbook.add_worksheet();
+    write_data_sheet(data_sheet, data, &bold)?;
+    
+    let variables_sheet = workbook.add_worksheet();
+    write_variables_sheet(variables_sheet, data, &bold).map_err(xlsx_error)?;
+    
+    workbook.save(path).map_err(xlsx_error)
+}
+
+fn write_header_sheet(sheet: &mut Worksheet, data: &LiCorData, bold: &Format) -> Result<(), XlsxError> {
+    sheet.set_name("Header")?;
+    
+    let metadata = &data.metadata;
+    let fields: [(&str, Option<&String>); 8] = [
+        ("Console s/n", Some(&metadata.device_serial)),
+        ("Console ver", Some(&metadata.console_version)),
+        ("Head s/n", metadata.head_serial.as_ref()),
+        ("Head ver", metadata.head_version.as_ref()),
+        ("Chamber type", metadata.chamber_type.as_ref()),
+        ("Chamber s/n", metadata.chamber_serial.as_ref()),
+        ("Fluorometer", metadata.fluorometer_serial.as_ref()),
+        ("Factory cal date", metadata.calibration_date.as_ref()),
+    ];
+    
+    for (row, (key, value)) in fields.iter().enumerate() {
+        sheet.write_string_with_format(row as u32, 0, *key, bold)?;
+        if let Some(value) = value {
+            sheet.write_string(row as u32, 1, value.as_str())?;
+        }
+    }
+    let certificates = metadata.calibration_certificates.iter().map(|c| ("Calibration cert", Some(c.describe())));
+    let extra_fields = [
+        ("Clock correction", metadata.clock_correction.as_ref().map(|c| c.describe())),
+        ("Content hash", metadata.content_hash.clone()),
+    ];
+    let mut row = fields.len() as u32;
+    for (key, value) in extra_fields.into_iter().chain(certificates) {
+        if let Some(value) = value {
+            sheet.write_string_with_format(row, 0, key, bold)?;
+            sheet.write_string(row, 1, value)?;
+            row += 1;
+        }
+    }
+    sheet.set_column_width(0, 20)?;
+    
+    Ok(())
+}
+
+fn write_data_sheet(sheet: &mut Worksheet, data: &LiCorData, bold: &Format) -> Result<(), ParseError> {
+    sheet.set_name("Data").map_err(xlsx_error)?;
+    
+    // Rows 0 and 1 hold labels and units; observations start at row 2
+    const FIRST_DATA_ROW: u32 = 2;
+    
+    for (col_idx, column) in data.dataframe.get_columns().iter().enumerate() {
+        let col = u16::try_from(col_idx).map_err(|_| ParseError::WriteError {
+            format: "xlsx".to_string(),
+            message: format!("Too many columns for a worksheet: {}", data.dataframe.width()),
+        })?;
+        
+        // Internal names stay on the Variables sheet
+        let info = data.variable_info.iter()
+            .find(|v| v.internal_name == column.name().as_str());
+        let label = info.map_or(column.name().as_str(), |v| v.display_label.as_str());
+        let units = info.and_then(|v| v.units.as_deref()).unwrap_or_default();
+        
+        sheet.write_string_with_format(0, col, label, bold).map_err(xlsx_error)?;
+        sheet.write_string(1, col, units).map_err(xlsx_error)?;
+        
+        let series = column.as_materialized_series();
+        match series.dtype() {
+            polars::datatypes::DataType::Float64 => {
+                for (row, value) in series.f64().map_err(polars_error)?.into_iter().enumerate() {
+                    if let Some(value) = value {
+                        sheet.write_number(FIRST_DATA_ROW + row as u32, col, value).map_err(xlsx_error)?;
+                    }
+                }
+            }
+            polars::datatypes::DataType::Int64 => {
+                for (row, value) in series.i64().map_err(polars_error)?.into_iter().enumerate() {
+                    if let Some(value) = value {
+                        sheet.write_number(FIRST_DATA_ROW + row as u32, col, value as f64).map_err(xlsx_error)?;
+                    }
+                }
+            }
+            polars::datatypes::DataType::Boolean => {
+                for (row, value) in series.bool().map_err(polars_error)?.into_iter().enumerate() {
+                    if let Some(value) = value {
+                        sheet.write_boolean(FIRST_DATA_ROW + row as u32, col, value).map_err(xlsx_error)?;
+                    }
+                }
+            }
+            _ => {
+                let strings = series.cast(&polars::datatypes::DataType::String).map_err(polars_error)?;
+                for (row, value) in strings.str().map_err(polars_error)?.into_iter().enumerate() {
+                    if let Some(value) = value {
+                        sheet.write_string(FIRST_DATA_ROW + row as u32, col, value).map_err(xlsx_error)?;
+                    }
+                }
+            }
+        }
+    }
+    
+    sheet.set_freeze_panes(FIRST_DATA_ROW, 0).map_err(xlsx_error)?;
+    
+    Ok(())
+}
+
+fn write_variables_sheet(sheet: &mut Worksheet, data: &LiCorData, bold: &Format) -> Result<(), XlsxError> {
+    sheet.set_name("Variables")?;
+    
+    let headings = ["Name", "Label", "Units", "Description", "Data type", "Category"];
+    for (col, heading) in headings.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *heading, bold)?;
+    }
+    
+    for (idx, info) in data.variable_info.iter().enumerate() {
+        let row = idx as u32 + 1;
+        sheet.write_string(row, 0, info.internal_name.as_str())?;
+        sheet.write_string(row, 1, info.display_label.as_str())?;
+        sheet.write_string(row, 2, info.units.clone().unwrap_or_default())?;
+        sheet.write_string(row, 3, info.description.as_str())?;
+        sheet.write_string(row, 4, format!("{:?}", info.data_type))?;
+        sheet.write_string(row, 5, info.column_category.as_str())?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    
+    Ok(())
+}
+
+fn xlsx_error(e: XlsxError) -> ParseError {
+    ParseError::WriteError {
+        format: "xlsx".to_string(),
+        message: e.to_string(),
+    }
+}
+
+fn polars_error(e: PolarsError) -> ParseError {
+    ParseError::WriteError {
+        format: "xlsx".to_string(),
+        message: e.to_string(),
+    }
+}
+
+#[cfg(all(test, feature = "device-6800"))]
+mod tests {
+    use super::*;
+    use crate::LiCor6800Fluorometer;
+
+    #[test]
+    fn test_write_xlsx_sample_file() {
+        let parser = LiCor6800Fluorometer::new();
+        let data = parser.parse_file("../example_data/2025-05-30-0948_logdata_flr_kinetics_and_gas_ex1")
+            .expect("Should parse sample file");
+        
+        let path = std::env::temp_dir().join("licor_test_write_xlsx.xlsx");
+        write_xlsx(&data, path.to_str().unwrap()).expect("Should write workbook");
+        
+        let bytes = std::fs::read(&path).expect("Workbook should exist");
+        // XLSX files are zip archives
+        assert_eq!(&bytes[..2], b"PK");
+        std::fs::remove_file(&path).ok();
+    }
+    
+    #[test]
+    #[cfg(feature = "device-6400")]
+    fn test_data_sheet_headers() {
+        use calamine::{open_workbook_auto, Reader};
+        
+        let data = LiCor6800Fluorometer::new()
+            .parse_file("../example_data/2025-05-30-1203_logdata_F2")
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("licor_test_xlsx_headers_{}.xlsx", std::process::id()));
+        write_xlsx(&data, path.to_str().unwrap()).unwrap();
+        
+        let mut workbook = open_workbook_auto(&path).unwrap();
+        let sheet = workbook.worksheet_range("Data").unwrap();
+        let header = |name: &str| {
+            let col = data.dataframe.get_column_index(name).unwrap();
+            (sheet.get((0, col)).unwrap().to_string(), sheet.get((1, col)).unwrap().to_string())
+        };
+        let info = data.variable_info.iter().find(|v| v.display_label != v.internal_name).unwrap();
+        assert_eq!(header(&info.internal_name), (info.display_label.clone(), info.units.clone().unwrap_or_default()));
+        
+        // The Variables sheet maps labels back to internal names
+        let variables = workbook.worksheet_range("Variables").unwrap();
+        assert!(variables.rows().any(|row| row[0].to_string() == info.internal_name && row[1].to_string() == info.display_label));
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,307 @@
+use crate::{ParseError, RawLiCorFile};
+use calamine::{open_workbook_auto, Data, Reader};
+use std::collections::HashMap;
+
+/// Read legacy LI-6400 Excel workbooks (.xls) into raw file structures
+///
+/// Each sheet holding a data table (a row starting with `Obs`, followed by
+/// observation rows) becomes one `RawLiCorFile`. Sheets with several tables,
+/// such as AutoProgram runs that were restarted, yield one entry per table,
+/// labelled `<sheet>`, `<sheet>_2`, ... Sheets without a data table are skipped.
+///
+/// Tables containing AutoProgram runs gain `curve_id` (1-based count of
+/// AutoProgram launches) and `program_step` (observation number within the run)
+/// columns; observations logged outside a run leave both empty.
+pub fn read_xls_sheets(path: &str) -> Result<Vec<(String, RawLiCorFile)>, ParseError> {
+    let mut workbook = open_workbook_auto(path)
+        .map_err(|e| ParseError::InvalidHeaderFormat {
+            message: format!("Failed to open workbook: {}", e)
+        })?;
+
+    let mut tables = Vec::new();
+    for sheet_name in workbook.sheet_names() {
+        let range = workbook.worksheet_range(&sheet_name)
+            .map_err(|e| ParseError::InvalidHeaderFormat {
+                message: format!("Failed to read sheet '{}': {}", sheet_name, e)
+            })?;
+
+        let rows: Vec<Vec<String>> = range.rows()
+            .map(|row| row.iter().map(cell_to_string).collect())
+            .collect();
+
+        for (idx, table) in split_sheet(&rows).into_iter().enumerate() {
+            let label = if idx == 0 {
+                sheet_name.clone()
+            } else {
+                format!("{}_{}", sheet_name, idx + 1)
+            };
+            tables.push((label, table));
+        }
+    }
+
+    Ok(tables)
+}
+
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(s) => s.trim().to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Split a sheet into its data tables, sharing header lines seen so far
+fn split_sheet(rows: &[Vec<String>]) -> Vec<RawLiCorFile> {
+    let mut tables = Vec::new();
+    let mut header = HashMap::new();
+    let mut categories: Vec<String> = Vec::new();
+    let mut current: Option<TableBuilder> = None;
+    let mut pending_curve = false;
+
+    for row in rows {
+        let first = row.first().map(|s| s.as_str()).unwrap_or("");
+
+        if first.eq_ignore_ascii_case("obs") {
+            // A new column-name row starts a new table
+            if let Some(table) = current.take().and_then(TableBuilder::finish) {
+                tables.push(table);
+            }
+            let column_names = trim_trailing_empty(row);
+            let num_cols = column_names.len();
+            let mut column_categories = std::mem::take(&mut categories);
+            column_categories.resize(num_cols, String::new());
+            let mut table = TableBuilder::new(RawLiCorFile {
+                header: header.clone(),
+                column_categories,
+                column_names,
+                units: vec![String::new(); num_cols],
+                data_columns: vec![Vec::new(); num_cols],
+                malformed_rows: Vec::new(),
+                console_messages: Vec::new(),
+            });
+            // An AutoProgram launched before the column header applies to this table
+            if std::mem::take(&mut pending_curve) {
+                table.start_curve();
+            }
+            current = Some(table);
+            continue;
+        }
+
+        if is_autoprogram_marker(first) {
+            match current.as_mut() {
+                Some(table) => table.start_curve(),
+                None => pending_curve = true,
+            }
+            continue;
+        }
+
+        if let Some(table) = current.as_mut() {
+            // Observation rows start with the observation number; anything else
+            // (remarks, blank rows, a new header block) is not data
+            if first.parse::<f64>().is_ok() {
+                table.push_observation(row);
+                continue;
+            }
+        }
+
+        parse_header_row(row, &mut header, &mut categories);
+    }
+
+    if let Some(table) = current.and_then(TableBuilder::finish) {
+        tables.push(table);
+    }
+
+    tables
+}
+
+/// AutoProgram launches are logged as remark rows such as
+/// "10:42:30 Launched AutoProg /User/Prog/A-CiCurve"
+fn is_autoprogram_marker(cell: &str) -> bool {
+    cell.to_ascii_lowercase().contains("autoprog")
+}
+
+/// Accumulates one data table, tracking AutoProgram curves and steps
+struct TableBuilder {
+    raw: RawLiCorFile,
+    curve_ids: Vec<String>,
+    program_steps: Vec<String>,
+    curve: Option<usize>,
+    curves_started: usize,
+    step: usize,
+}
+
+impl TableBuilder {
+    fn new(raw: RawLiCorFile) -> Self {
+        Self {
+            raw,
+            curve_ids: Vec::new(),
+            program_steps: Vec::new(),
+            curve: None,
+            curves_started: 0,
+            step: 0,
+        }
+    }
+
+    fn start_curve(&mut self) {
+        self.curves_started += 1;
+        self.curve = Some(self.curves_started);
+        self.step = 0;
+    }
+
+    fn push_observation(&mut self, row: &[String]) {
+        // Cells beyond the column names are dropped
+        let fields = trim_trailing_empty(row).len();
+        if fields > self.raw.column_names.len() {
+            self.raw.malformed_rows.push((self.raw.num_rows() + 1, fields));
+        }
+        for (col_idx, column) in self.raw.data_columns.iter_mut().enumerate() {
+            column.push(row.get(col_idx).cloned().unwrap_or_default());
+        }
+
+        // Observations logged outside an AutoProgram have no curve or step
+        match self.curve {
+            Some(curve) => {
+                self.step += 1;
+                self.curve_ids.push(curve.to_string());
+                self.program_steps.push(self.step.to_string());
+            }
+            None => {
+                self.curve_ids.push(String::new());
+                self.program_steps.push(String::new());
+            }
+        }
+    }
+
+    /// Finish the table, appending `curve_id`/`program_step` columns when the
+    /// table contains AutoProgram runs; empty tables are dropped
+    fn finish(self) -> Option<RawLiCorFile> {
+        let mut raw = self.raw;
+        if raw.num_rows() == 0 {
+            return None;
+        }
+
+        if self.curves_started > 0 {
+            for (name, values) in [("curve_id", self.curve_ids), ("program_step", self.program_steps)] {
+                raw.column_categories.push("AutoProgram".to_string());
+                raw.column_names.push(name.to_string());
+                raw.units.push(String::new());
+                raw.data_columns.push(values);
+            }
+        }
+
+        Some(raw)
+    }
+}
+
+fn parse_header_row(row: &[String], header: &mut HashMap<String, String>, categories: &mut Vec<String>) {
+    let cells = trim_trailing_empty(row);
+    let Some(first) = cells.first() else {
+        return;
+    };
+
+    if first.starts_with("OPEN ") {
+        // Console software version line, e.g. "OPEN 6.2.4"
+        header.insert("Console ver".to_string(), first.clone());
+    } else if let Some(key) = first.strip_suffix('=') {
+        // Constant lines such as "Unit=" | "PSC-3572"
+        let value = cells.get(1).cloned().unwrap_or_default();
+        header.insert(key.trim().to_string(), value);
+    } else if cells.iter().all(|c| c == "in" || c == "out" || c.is_empty()) {
+        // Row marking each column as an input or computed output
+        *categories = cells;
+    } else if cells.len() == 1 && !first.starts_with('$') && !header.contains_key("File opened") {
+        // The line after the version is the date the file was opened
+        header.insert("File opened".to_string(), first.clone());
+    }
+}
+
+fn trim_trailing_empty(row: &[String]) -> Vec<String> {
+    let mut cells = row.to_vec();
+    while cells.last().is_some_and(|c| c.is_empty()) {
+        cells.pop();
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(lines: &[&str]) -> Vec<Vec<String>> {
+        lines.iter()
+            .map(|line| line.split('|').map(|s| s.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_sheet_with_restarted_program() {
+        let sheet = rows(&[
+            "OPEN 6.2.4",
+            "Thr Aug 11 2016 10:40:12",
+            "Unit=|PSC-3572",
+            "$STARTOFDATA$",
+            "in|in|out",
+            "Obs|HHMMSS|Photo",
+            "1|10:41:02|12.5",
+            "2|10:42:10|13.1",
+            "10:42:30 Remark=leaf 2",
+            "Obs|HHMMSS|Photo",
+            "1|10:50:00|9.8",
+        ]);
+
+        let tables = split_sheet(&sheet);
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].header.get("Console ver").unwrap(), "OPEN 6.2.4");
+        assert_eq!(tables[0].header.get("Unit").unwrap(), "PSC-3572");
+        assert_eq!(tables[0].column_categories, vec!["in", "in", "out"]);
+        assert_eq!(tables[0].num_rows(), 2);
+        assert_eq!(tables[0].data_columns[2], vec!["12.5", "13.1"]);
+        assert_eq!(tables[1].num_rows(), 1);
+        assert!(!tables[0].column_names.contains(&"curve_id".to_string()));
+    }
+
+    #[test]
+    fn test_autoprogram_curves() {
+        let sheet = rows(&[
+            "OPEN 6.2.4",
+            "Unit=|PSC-3572",
+            "Obs|HHMMSS|Photo",
+            "1|10:40:00|11.0",
+            "10:41:00 Launched AutoProg /User/Prog/LightCurve",
+            "2|10:42:10|13.1",
+            "3|10:44:10|10.2",
+            "10:45:00 Launched AutoProg /User/Prog/LightCurve",
+            "4|10:46:10|8.4",
+        ]);
+
+        let tables = split_sheet(&sheet);
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        let curve_idx = table.column_names.iter().position(|c| c == "curve_id").unwrap();
+        let step_idx = table.column_names.iter().position(|c| c == "program_step").unwrap();
+        assert_eq!(table.data_columns[curve_idx], vec!["", "1", "1", "2"]);
+        assert_eq!(table.data_columns[step_idx], vec!["", "1", "2", "1"]);
+    }
+
+    #[test]
+    fn test_aliased_variable_types() {
+        let sheet = rows(&[
+            "OPEN 6.2.4",
+            "Unit=|PSC-3572",
+            "Obs|HHMMSS|Photo|Cond|Ci|Trmmol|CO2R|BLCond|Tair|Tleaf|Flow|Press",
+            "1|10:41:02|12.5|0.21|280|3.1|400|2.8|25.1|25.4|500|98.1",
+            "2|10:42:10|13.1|0.25|275|3.3|400|2.8|25.1|25.5|500|98.1",
+        ]);
+        let table = split_sheet(&sheet).remove(0);
+        let data = crate::LiCor6400Standard::new().parse_raw(table).unwrap();
+
+        // Photo is the LI-6800's A, so it takes A's units and type
+        let photo = data.variable_info.iter().find(|v| v.internal_name == "Photo").unwrap();
+        assert_eq!(photo.display_label, "Photo");
+        assert_eq!(photo.units.as_deref(), Some("µmol m-2 s-1"));
+        assert_eq!(data.dataframe.column("Photo").unwrap().dtype(), &polars::prelude::DataType::Float64);
+        let obs = data.variable_info.iter().find(|v| v.internal_name == "Obs").unwrap();
+        assert!(matches!(obs.data_type, crate::DataType::Integer));
+        assert_eq!(data.dataframe.column("Obs").unwrap().dtype(), &polars::prelude::DataType::Int64);
+    }
+}
@@ -0,0 +1,433 @@
+//! Desktop front-end: drop LI-COR files on the window to check, plot and convert them
+
+use eframe::egui;
+use egui_plot::{Plot, PlotPoints, Points};
+use licor_core::{
+    LiCorData, ParseError, ParquetOptions, RawLiCorFile, ValidationReport,
+    LiCor6800Standard, LiCor6800Fluorometer, LiCor6800Aquatic, LiCor6800Soil,
+    LiCor6400Standard, LiCor6400Fluorometer, write_netcdf, write_parquet, write_xlsx,
+};
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+
+fn main() -> eframe::Result {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([1000.0, 680.0])
+            .with_drag_and_drop(true),
+        ..Default::default()
+    };
+    eframe::run_native("LI-COR converter", options, Box::new(|_cc| Ok(Box::<LicorApp>::default())))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Device {
+    Li6800,
+    Li6400,
+}
+
+impl Device {
+    /// LI-6400 data comes in workbooks, LI-6800 data in text logs
+    fn detect(path: &Path) -> Device {
+        let workbook = path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("xls") || e.eq_ignore_ascii_case("xlsx"));
+        if workbook { Device::Li6400 } else { Device::Li6800 }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Device::Li6800 => "LI-6800",
+            Device::Li6400 => "LI-6400",
+        }
+    }
+
+    fn configs(&self) -> &'static [Config] {
+        match self {
+            Device::Li6800 => &[Config::Fluorometer, Config::Standard, Config::Aquatic, Config::Soil],
+            Device::Li6400 => &[Config::Fluorometer, Config::Standard],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Config {
+    Standard,
+    Fluorometer,
+    Aquatic,
+    Soil,
+}
+
+impl Config {
+    fn label(&self) -> &'static str {
+        match self {
+            Config::Standard => "standard",
+            Config::Fluorometer => "fluorometer",
+            Config::Aquatic => "aquatic",
+            Config::Soil => "soil",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Parquet,
+    Csv,
+    Xlsx,
+    Netcdf,
+}
+
+impl OutputFormat {
+    const ALL: [OutputFormat; 4] = [OutputFormat::Parquet, OutputFormat::Csv, OutputFormat::Xlsx, OutputFormat::Netcdf];
+
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Parquet => "parquet",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Xlsx => "xlsx",
+            OutputFormat::Netcdf => "nc",
+        }
+    }
+}
+
+/// A data table of a dropped file (a text log, or one sheet of a workbook) and
+/// what has been done with it
+struct Entry {
+    path: PathBuf,
+    /// Workbook sheet the table came from
+    sheet: Option<String>,
+    device: Device,
+    config: Option<Config>,
+    data: Option<LiCorData>,
+    report: Option<ValidationReport>,
+    status: String,
+    failed: bool,
+}
+
+impl Entry {
+    /// Read a file, one entry per data table (every sheet of a workbook), each
+    /// parsed with the configuration that leaves the fewest variables missing
+    fn load(path: PathBuf) -> Vec<Entry> {
+        let device = Device::detect(&path);
+        let entry = |sheet: Option<String>| Entry {
+            path: path.clone(),
+            sheet,
+            device,
+            config: None,
+            data: None,
+            report: None,
+            status: String::new(),
+            failed: false,
+        };
+        let tables = match read_tables(&path, device) {
+            Ok(tables) => tables,
+            Err(e) => {
+                let mut failed = entry(None);
+                failed.status = e.to_string();
+                failed.failed = true;
+                return vec![failed];
+            }
+        };
+        tables.into_iter()
+            .map(|(sheet, raw)| {
+                let mut entry = entry(sheet);
+                match entry.parse(raw) {
+                    Ok(()) => entry.status = "Ready".to_string(),
+                    Err(e) => {
+                        entry.status = e.to_string();
+                        entry.failed = true;
+                    }
+                }
+                entry
+            })
+            .collect()
+    }
+
+    /// File name, with the sheet for a workbook table
+    fn name(&self) -> String {
+        let name = self.path.file_name().map_or_else(String::new, |n| n.to_string_lossy().to_string());
+        match &self.sheet {
+            Some(sheet) => format!("{}#{}", name, sheet),
+            None => name,
+        }
+    }
+
+    fn parse(&mut self, raw: RawLiCorFile) -> Result<(), ParseError> {
+        let mut best: Option<(Config, ValidationReport)> = None;
+        let mut last_error = None;
+        for config in self.device.configs() {
+            match validate(self.device, *config, raw.clone()) {
+                Ok(report) => {
+                    let fewer = best.as_ref()
+                        .is_none_or(|(_, current)| report.missing_variables.len() < current.missing_variables.len());
+                    if fewer {
+                        best = Some((*config, report));
+                    }
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        let Some((config, report)) = best else {
+            return Err(last_error.unwrap_or(ParseError::EmptyDataSection));
+        };
+        self.data = Some(parse(self.device, config, raw)?);
+        self.config = Some(config);
+        self.report = Some(report);
+        Ok(())
+    }
+
+    fn convert(&mut self, output_dir: &Path, format: OutputFormat) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let stem = self.path.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+        // Named like `licor convert` names the outputs of each sheet
+        let stem = match &self.sheet {
+            Some(sheet) => format!("{}_{}", stem, sheet.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_")),
+            None => stem.to_string(),
+        };
+        let output = output_dir.join(format!("{}.{}", stem, format.extension()));
+        match write(data, format, &output) {
+            Ok(()) => {
+                self.status = format!("Wrote {}", output.display());
+                self.failed = false;
+            }
+            Err(e) => {
+                self.status = e.to_string();
+                self.failed = true;
+            }
+        }
+    }
+}
+
+/// The data tables of a file: each sheet of a workbook, labelled by sheet, or
+/// the single table of a text log
+fn read_tables(path: &Path, device: Device) -> Result<Vec<(Option<String>, RawLiCorFile)>, ParseError> {
+    match device {
+        Device::Li6400 => Ok(licor_core::xls::read_xls_sheets(&path.to_string_lossy())?
+            .into_iter()
+            .map(|(sheet, raw)| (Some(sheet), raw))
+            .collect()),
+        Device::Li6800 => Ok(vec![(None, RawLiCorFile::parse(&std::fs::read_to_string(path)?)?)]),
+    }
+}
+
+fn validate(device: Device, config: Config, raw: RawLiCorFile) -> Result<ValidationReport, ParseError> {
+    match (device, config) {
+        (Device::Li6800, Config::Standard) => LiCor6800Standard::new().validate_raw(raw),
+        (Device::Li6800, Config::Fluorometer) => LiCor6800Fluorometer::new().validate_raw(raw),
+        (Device::Li6800, Config::Aquatic) => LiCor6800Aquatic::new().validate_raw(raw),
+        (Device::Li6800, Config::Soil) => LiCor6800Soil::new().validate_raw(raw),
+        (Device::Li6400, Config::Fluorometer) => LiCor6400Fluorometer::new().validate_raw(raw),
+        (Device::Li6400, _) => LiCor6400Standard::new().validate_raw(raw),
+    }
+}
+
+fn parse(device: Device, config: Config, raw: RawLiCorFile) -> Result<LiCorData, ParseError> {
+    match (device, config) {
+        (Device::Li6800, Config::Standard) => LiCor6800Standard::new().parse_raw(raw),
+        (Device::Li6800, Config::Fluorometer) => LiCor6800Fluorometer::new().parse_raw(raw),
+        (Device::Li6800, Config::Aquatic) => LiCor6800Aquatic::new().parse_raw(raw),
+        (Device::Li6800, Config::Soil) => LiCor6800Soil::new().parse_raw(raw),
+        (Device::Li6400, Config::Fluorometer) => LiCor6400Fluorometer::new().parse_raw(raw),
+        (Device::Li6400, _) => LiCor6400Standard::new().parse_raw(raw),
+    }
+}
+
+fn write(data: &LiCorData, format: OutputFormat, output: &Path) -> Result<(), ParseError> {
+    let path = output.to_string_lossy();
+    match format {
+        OutputFormat::Parquet => write_parquet(data, &path, &ParquetOptions::default()),
+        OutputFormat::Csv => {
+            let mut file = std::fs::File::create(output)?;
+            CsvWriter::new(&mut file)
+                .include_header(true)
+                .finish(&mut data.dataframe.clone())
+                .map_err(|e| ParseError::WriteError { format: "csv".to_string(), message: e.to_string() })
+        }
+        OutputFormat::Xlsx => write_xlsx(data, &path),
+        OutputFormat::Netcdf => write_netcdf(data, &path),
+    }
+}
+
+/// Numeric values of a column, nulls as NaN so the plot leaves gaps
+fn numeric_values(df: &DataFrame, name: &str) -> Option<Vec<f64>> {
+    let column = df.column(name).ok()?.cast(&polars::datatypes::DataType::Float64).ok()?;
+    Some(column.f64().ok()?.into_iter().map(|v| v.unwrap_or(f64::NAN)).collect())
+}
+
+struct LicorApp {
+    entries: Vec<Entry>,
+    selected: Option<usize>,
+    output_dir: String,
+    format: OutputFormat,
+    x_column: String,
+    y_column: String,
+}
+
+impl Default for LicorApp {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            selected: None,
+            output_dir: String::new(),
+            format: OutputFormat::Parquet,
+            x_column: "elapsed".to_string(),
+            y_column: "A".to_string(),
+        }
+    }
+}
+
+impl LicorApp {
+    fn add_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped: Vec<PathBuf> = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|f| f.path.clone()).collect());
+        for path in dropped {
+            if self.entries.iter().any(|entry| entry.path == path) {
+                continue;
+            }
+            self.entries.extend(Entry::load(path));
+            self.selected = Some(self.entries.len() - 1);
+        }
+    }
+
+    fn file_list(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Files");
+        if self.entries.is_empty() {
+            ui.label("Drop LI-6800 logs or LI-6400 workbooks here");
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (index, entry) in self.entries.iter().enumerate() {
+                let name = entry.name();
+                let text = if entry.failed {
+                    egui::RichText::new(name).color(egui::Color32::RED)
+                } else {
+                    egui::RichText::new(name)
+                };
+                if ui.selectable_label(self.selected == Some(index), text).clicked() {
+                    self.selected = Some(index);
+                }
+            }
+        });
+    }
+
+    fn actions(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Output folder:");
+            ui.add(egui::TextEdit::singleline(&mut self.output_dir).hint_text("next to each input file"));
+            egui::ComboBox::from_label("Format")
+                .selected_text(self.format.extension())
+                .show_ui(ui, |ui| {
+                    for format in OutputFormat::ALL {
+                        ui.selectable_value(&mut self.format, format, format.extension());
+                    }
+                });
+            let ready = self.entries.iter().any(|entry| entry.data.is_some());
+            if ui.add_enabled(ready, egui::Button::new("Convert all")).clicked() {
+                for entry in &mut self.entries {
+                    let output_dir = match self.output_dir.trim() {
+                        "" => entry.path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                        dir => PathBuf::from(dir),
+                    };
+                    entry.convert(&output_dir, self.format);
+                }
+            }
+            if ui.button("Clear").clicked() {
+                self.entries.clear();
+                self.selected = None;
+            }
+        });
+    }
+
+    fn details(&mut self, ui: &mut egui::Ui) {
+        let Some(entry) = self.selected.and_then(|index| self.entries.get(index)) else {
+            ui.centered_and_justified(|ui| ui.heading("Drop files to convert"));
+            return;
+        };
+
+        ui.heading(match &entry.sheet {
+            Some(sheet) => format!("{}#{}", entry.path.display(), sheet),
+            None => entry.path.display().to_string(),
+        });
+        let status = if entry.failed {
+            egui::RichText::new(&entry.status).color(egui::Color32::RED)
+        } else {
+            egui::RichText::new(&entry.status)
+        };
+        ui.label(status);
+        let Some(data) = &entry.data else {
+            return;
+        };
+
+        ui.separator();
+        egui::Grid::new("qc").striped(true).show(ui, |ui| {
+            let row = |ui: &mut egui::Ui, key: &str, value: String| {
+                ui.label(key);
+                ui.label(value);
+                ui.end_row();
+            };
+            row(ui, "Device", format!("{} ({}, {})", entry.device.label(), data.metadata.device_serial, data.metadata.console_version));
+            row(ui, "Configuration", entry.config.map_or("", |c| c.label()).to_string());
+            row(ui, "Observations", format!("{} rows, {} columns", data.dataframe.height(), data.dataframe.width()));
+            if let Some(report) = &entry.report {
+                row(ui, "Missing variables", list_or_none(&report.missing_variables));
+                row(ui, "Malformed rows", report.malformed_rows.len().to_string());
+                let mismatches: Vec<String> = report.unit_mismatches.iter()
+                    .map(|m| format!("{} ({} ≠ {})", m.column, m.found, m.expected))
+                    .collect();
+                row(ui, "Unit mismatches", list_or_none(&mismatches));
+                let fallbacks: Vec<String> = report.type_fallbacks.iter().map(|r| r.column.clone()).collect();
+                row(ui, "Kept as text", list_or_none(&fallbacks));
+                let rounded: Vec<String> = report.rounded_columns.iter().map(|r| r.column.clone()).collect();
+                row(ui, "Rounded to integers", list_or_none(&rounded));
+            }
+            row(ui, "Console messages", data.metadata.console_messages.len().to_string());
+        });
+
+        ui.separator();
+        let numeric: Vec<String> = data.dataframe.get_columns().iter()
+            .filter(|column| column.dtype().is_primitive_numeric())
+            .map(|column| column.name().to_string())
+            .collect();
+        ui.horizontal(|ui| {
+            for (label, value) in [("x", &mut self.x_column), ("y", &mut self.y_column)] {
+                egui::ComboBox::from_label(label)
+                    .selected_text(value.as_str())
+                    .show_ui(ui, |ui| {
+                        for name in &numeric {
+                            ui.selectable_value(value, name.clone(), name);
+                        }
+                    });
+            }
+        });
+        let points = numeric_values(&data.dataframe, &self.x_column)
+            .zip(numeric_values(&data.dataframe, &self.y_column))
+            .map(|(x, y)| x.into_iter().zip(y).map(|(x, y)| [x, y]).collect::<Vec<_>>())
+            .unwrap_or_default();
+        Plot::new("quick_plot")
+            .x_axis_label(self.x_column.clone())
+            .y_axis_label(self.y_column.clone())
+            .show(ui, |plot_ui| {
+                plot_ui.points(Points::new(PlotPoints::from(points)).radius(3.0));
+            });
+    }
+}
+
+fn list_or_none(values: &[String]) -> String {
+    if values.is_empty() { "none".to_string() } else { values.join(", ") }
+}
+
+impl eframe::App for LicorApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.add_dropped_files(ctx);
+
+        egui::TopBottomPanel::bottom("actions").show(ctx, |ui| self.actions(ui));
+        egui::SidePanel::left("files").resizable(true).show(ctx, |ui| self.file_list(ui));
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+                ui.centered_and_justified(|ui| ui.heading("Drop to add"));
+                return;
+            }
+            self.details(ui);
+        });
+    }
+}
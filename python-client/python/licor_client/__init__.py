@@ -1,6 +1,68 @@
 """Python client for LI-COR instrument data conversion."""
 
-from .licor_client import convert, file_to_dataframe
+from . import licor_client as _native
+from .licor_client import ArrowTable, LicorFile, clear_cache, convert, convert_many, file_to_dataframe, open_dataset, peek, read_metadata, recompute, search_variables, set_cache_size, summarize, variable_info
 
 __version__ = "0.1.0"
-__all__ = ["convert", "file_to_dataframe"]
\ No newline at end of file
+__all__ = ["ArrowTable", "LicorFile", "clear_cache", "convert", "convert_many", "file_to_dataframe", "open_dataset", "peek", "read_metadata", "recompute", "scan", "search_variables", "set_cache_size", "summarize", "variable_info"]
+
+
+def scan(file, device, config):
+    """Scan a LI-COR file as a polars LazyFrame.
+
+    The file is parsed once up front so the column types are exactly those of
+    ``file_to_dataframe`` (a column with a value like ``#DIV/0!`` anywhere is a
+    string column), and again when the query is collected. Filters are applied
+    after that parse rather than pushed into the parser; only the selected
+    columns cross into Python, and a query that only needs the first rows
+    (``head``, ``limit``) reads only the start of a file given by path.
+
+    Args:
+        file: LI-COR file: a path, its content as str or bytes, or a file-like
+            object with .read()
+        device: Device type ("6800")
+        config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+
+    Returns:
+        polars LazyFrame
+
+    Raises:
+        ValueError: Invalid device/config combination or malformed data
+        IOError: File read errors
+        RuntimeError: polars is not installed, or too old for IO plugins
+    """
+    try:
+        from polars.io.plugins import register_io_source
+    except ImportError:
+        raise RuntimeError(
+            "scan needs polars with IO plugins (polars.io.plugins). Install with: uv add licor-client[polars]"
+        ) from None
+
+    if hasattr(file, "read"):
+        file = file.read()
+    schema = _native._scan(file, device, config).schema
+
+    def source(with_columns, predicate, n_rows, batch_size):
+        columns = with_columns if with_columns is not None else list(schema)
+        if predicate is None:
+            df = _native._scan(file, device, config, columns, n_rows)
+            if n_rows is not None and any(df.schema[name] != schema[name] for name in columns):
+                # A value past the first rows changes a column's type; read it all
+                df = _native._scan(file, device, config, columns).head(n_rows)
+        else:
+            df = _native._scan(file, device, config).filter(predicate).select(columns)
+            if n_rows is not None:
+                df = df.head(n_rows)
+        df = df.cast({name: schema[name] for name in columns})
+        step = batch_size or max(df.height, 1)
+        for offset in range(0, max(df.height, 1), step):
+            yield df.slice(offset, step)
+
+    return register_io_source(source, schema=schema)
+
+
+# Built with the `hooks` cargo feature (as the published wheel is)
+if hasattr(_native, "register_hook"):
+    from .licor_client import clear_hooks, register_hook
+
+    __all__ += ["clear_hooks", "register_hook"]
\ No newline at end of file
@@ -1,48 +1,518 @@
-"""Type stubs for licor_client."""
+"""Type stubs for licor_client.
 
-from typing import Any, Literal, Union
+These stubs are written by hand, not generated: pyo3 only records each
+function's parameter names (its ``__text_signature__``), not their types.
+Update them with every change to the module's functions; the
+``TestTypeStubs`` tests fail when a function or parameter is missing.
+"""
+
+import os
+from typing import IO, Any, Callable, Literal, TypedDict, Union
+
+# A path, the file's content as str (with line breaks) or bytes, or a file-like
+# object with .read()
+FileSource = Union[str, bytes, os.PathLike[str], IO[str], IO[bytes]]
 
 def convert(
-    file: str,
+    file: FileSource,
     output: str, 
-    device: Literal["6800", "6400"],
-    config: Literal["standard", "fluorometer", "aquatic", "soil"]
-) -> None:
+    device: Literal["6800"],
+    config: Literal["standard", "fluorometer", "aquatic", "soil"],
+    *,
+    compression: Literal["zstd", "snappy", "uncompressed"] = "zstd",
+    compression_level: Union[int, None] = None,
+    layout: Literal["wide", "long"] = "wide",
+    partition: Union[Literal["date", "date_device"], None] = None,
+    metadata_columns: Union[bool, list[str], None] = None,
+    overwrite: bool = False,
+) -> list[str]:
     """Convert a LI-COR file to Parquet format.
     
+    The options match those of `licor convert`.
+    
     Args:
-        file: Path to the input LI-COR file
-        output: Path for the output Parquet file  
-        device: Device type ("6800" or "6400")
+        file: LI-COR file: a path, its content as str or bytes, or a file-like
+            object with .read()
+        output: Path for the output Parquet file, or the dataset directory
+            when partitioned
+        device: Device type ("6800")
         config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+        compression: Parquet codec ("zstd", "snappy" or "uncompressed")
+        compression_level: zstd level (1-22; the codec's default when omitted)
+        layout: "wide" for a column per variable, or "long" (tidy) for a row
+            per observation and variable
+        partition: None for one file; "date" for a Hive dataset of
+            `date=YYYY-MM-DD/<stem>.parquet` under `output`, or "date_device"
+            to also partition by console serial
+        metadata_columns: True to repeat the instrument metadata (serials,
+            console version, chamber type, calibration date) as constant
+            columns, or a list of metadata fields and raw header keys
+        overwrite: Replace existing outputs; by default an existing output
+            raises FileExistsError and nothing is written
+        
+    Returns:
+        list of the paths written
         
     Raises:
-        ValueError: Invalid device/config combination or malformed data
+        ValueError: Invalid device/config combination, option or malformed data
         IOError: File read/write errors
+        FileExistsError: An output exists and `overwrite` is not set
         RuntimeError: Other parsing errors
     """
     ...
 
 def file_to_dataframe(
-    file: str,
-    format: Literal["polars", "pandas"],
-    device: Literal["6800", "6400"], 
+    file: FileSource,
+    format: Literal["polars", "pandas", "xarray", "numpy", "arrow"],
+    device: Literal["6800"], 
     config: Literal["standard", "fluorometer", "aquatic", "soil"]
 ) -> Any:
     """Convert a LI-COR file directly to a DataFrame.
     
     Args:
-        file: Path to the input LI-COR file
-        format: Output format ("polars" or "pandas")
-        device: Device type ("6800" or "6400") 
+        file: LI-COR file: a path, its content as str or bytes, or a file-like
+            object with .read()
+        format: Output format ("polars", "pandas", "xarray", "numpy" or "arrow")
+        device: Device type ("6800")
         config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
         
     Returns:
-        DataFrame in the requested format
+        DataFrame in the requested format; for "xarray", a Dataset along
+        `time` (UTC datetimes, or `obs` when the file has no `time` column)
+        with `units` and `long_name` attrs on each variable; for "numpy", a
+        structured array with a field per column (needs only numpy); for
+        "arrow", an ArrowTable read by any Arrow consumer (needs nothing)
         
     Raises:
         ValueError: Invalid device/config combination, unsupported format, or malformed data
         IOError: File read errors
         RuntimeError: Missing optional dependencies or other parsing errors
     """
-    ...
\ No newline at end of file
+    ...
+
+class ConversionResult(TypedDict):
+    file: str
+    status: Literal["converted", "failed"]
+    output: Union[str, None]
+    rows: Union[int, None]
+    error: Union[str, None]
+    code: Union[str, None]
+
+def convert_many(
+    files: Union[str, list[str]],
+    output_dir: str,
+    device: Literal["6800"],
+    config: Literal["standard", "fluorometer", "aquatic", "soil"],
+    workers: Union[int, None] = None,
+) -> list[ConversionResult]:
+    """Convert many LI-COR files to Parquet in parallel.
+    
+    Files are converted on `workers` threads without holding the GIL, and a
+    file that fails does not stop the others.
+    
+    Args:
+        files: Glob pattern (e.g. "data/*.txt") or list of paths
+        output_dir: Directory for the `<stem>.parquet` outputs (created if missing)
+        device: Device type ("6800")
+        config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+        workers: Number of threads (one per CPU when omitted)
+        
+    Returns:
+        list of dicts, in input order, with "file", "status" ("converted" or
+        "failed"), "output" and "rows" (None when failed), and "error" and
+        "code" (None when converted)
+        
+    Raises:
+        ValueError: Invalid device/config combination or glob pattern
+        IOError: No files match the pattern, or the output directory cannot be created
+    """
+    ...
+
+class DatasetFile(TypedDict):
+    file: str
+    serial: str
+    config: str
+    status: Literal["parsed", "failed"]
+    rows: Union[int, None]
+    error: Union[str, None]
+    code: Union[str, None]
+
+def open_dataset(
+    directory: str,
+    device: Literal["6800"],
+    config: Union[Literal["standard", "fluorometer", "aquatic", "soil"], None] = None,
+    workers: Union[int, None] = None,
+) -> tuple[Any, list[DatasetFile]]:
+    """Parse every LI-COR file under a directory into one DataFrame.
+    
+    Files are found recursively, leaving out hidden files and files that are
+    not LI-COR logs (outputs, notes), and parsed in parallel on `workers`
+    threads without holding the GIL. Their rows are concatenated in path order
+    with the schemas aligned: a column a file lacks is null, and a column typed
+    differently across files becomes Float64 if every type is numeric and
+    text otherwise. A first `file` column holds each row's source path. A file
+    that fails to parse does not stop the others.
+    
+    Args:
+        directory: Directory to search
+        device: Device type ("6800")
+        config: Measurement configuration ("standard", "fluorometer",
+            "aquatic", "soil"), or None for each file's best-matching
+            configuration, as `licor index` reports it
+        workers: Number of threads (one per CPU when omitted)
+        
+    Returns:
+        (DataFrame, manifest): a polars DataFrame, and a list of dicts, one per
+        file found, with "file", "serial", "config", "status" ("parsed" or
+        "failed"), "rows" (None when failed), and "error" and "code" (None
+        when parsed)
+        
+    Raises:
+        ValueError: Invalid device/config combination
+        IOError: The directory cannot be read or holds no LI-COR files
+        RuntimeError: polars is not installed, or no file parses
+    """
+    ...
+
+class ConsoleMessage(TypedDict):
+    after_row: int
+    text: str
+
+class InstrumentMetadata(TypedDict):
+    device_serial: str
+    console_version: str
+    head_serial: Union[str, None]
+    head_version: Union[str, None]
+    chamber_type: Union[str, None]
+    chamber_serial: Union[str, None]
+    fluorometer_serial: Union[str, None]
+    calibration_date: Union[str, None]
+    clock_correction: Union[dict[str, Any], None]
+    content_hash: Union[str, None]
+    console_messages: list[ConsoleMessage]
+    fluorometer_settings: list[dict[str, Any]]
+    calibration_certificates: list[dict[str, Any]]
+
+def read_metadata(
+    file: FileSource,
+    device: Literal["6800"],
+    config: Literal["standard", "fluorometer", "aquatic", "soil"]
+) -> InstrumentMetadata:
+    """Read a file's instrument metadata.
+    
+    Args:
+        file: LI-COR file: a path, its content as str or bytes, or a file-like
+            object with .read()
+        device: Device type ("6800")
+        config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+        
+    Returns:
+        dict with "device_serial", "console_version", "head_serial",
+        "head_version", "chamber_type", "chamber_serial", "fluorometer_serial"
+        and "calibration_date" (None when not logged), "content_hash",
+        "clock_correction", "console_messages" (list of dicts with "after_row"
+        and "text") and "fluorometer_settings" (list of dicts)
+        
+    Raises:
+        ValueError: Invalid device/config combination or malformed data
+        IOError: File read errors
+    """
+    ...
+
+class VariableInfo(TypedDict):
+    name: str
+    label: str
+    units: Union[str, None]
+    units_pretty: Union[str, None]
+    description: str
+    dtype: Literal["float", "integer", "string", "boolean"]
+    category: str
+
+def variable_info(
+    file: FileSource,
+    device: Literal["6800"],
+    config: Literal["standard", "fluorometer", "aquatic", "soil"]
+) -> list[VariableInfo]:
+    """Describe the columns of a converted file, e.g. to label plot axes.
+    
+    Args:
+        file: LI-COR file: a path, its content as str or bytes, or a file-like
+            object with .read()
+        device: Device type ("6800")
+        config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+        
+    Returns:
+        list of dicts with "name", "label", "units", "units_pretty" (None
+        when unitless), "description", "dtype" and "category", in column order
+        
+    Raises:
+        ValueError: Invalid device/config combination or malformed data
+        IOError: File read errors
+    """
+    ...
+
+class Remark(TypedDict):
+    after_row: int
+    text: str
+
+class LicorFile:
+    """A parsed LI-COR file.
+    
+    Args:
+        file: LI-COR file: a path, its content as str or bytes, or a file-like
+            object with .read()
+        device: Device type ("6800")
+        config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+        
+    Raises:
+        ValueError: Invalid device/config combination or malformed data
+        IOError: File read errors
+    """
+    def __init__(
+        self,
+        file: FileSource,
+        device: Literal["6800"],
+        config: Literal["standard", "fluorometer", "aquatic", "soil"]
+    ) -> None: ...
+    @property
+    def path(self) -> Union[str, None]:
+        """Path the file was read from, or None when it was given as content."""
+        ...
+    @property
+    def metadata(self) -> InstrumentMetadata:
+        """Instrument metadata, as returned by `read_metadata()`."""
+        ...
+    @property
+    def variables(self) -> list[VariableInfo]:
+        """Column descriptions, as returned by `variable_info()`."""
+        ...
+    @property
+    def remarks(self) -> list[Remark]:
+        """Remarks logged among the data rows, with the rows logged before each."""
+        ...
+    def to_polars(self) -> Any:
+        """The data as a polars DataFrame."""
+        ...
+    def to_pandas(self) -> Any:
+        """The data as a pandas DataFrame (needs pandas and pyarrow)."""
+        ...
+    def to_parquet(self, path: str) -> None:
+        """Write the data to Parquet, with the metadata and column descriptions
+        in the file's key-value metadata."""
+        ...
+    def __len__(self) -> int: ...
+    def __arrow_c_stream__(self, requested_schema: Union[object, None] = None) -> object:
+        """The data as an Arrow C stream capsule, read by `pyarrow.table()`,
+        duckdb, `pandas.DataFrame.from_arrow()` and `polars.DataFrame()`.
+        
+        A requested schema selects its columns in its order, cast to its
+        types; a missing column or a failed cast raises ValueError."""
+        ...
+
+class ArrowTable:
+    """A table of parsed data, read by any Arrow consumer.
+    
+    Returned by `file_to_dataframe(format="arrow")`; it needs neither polars
+    nor pyarrow.
+    """
+    @property
+    def columns(self) -> list[str]:
+        """Column names, in order."""
+        ...
+    def __len__(self) -> int: ...
+    def __arrow_c_stream__(self, requested_schema: Union[object, None] = None) -> object:
+        """The data as an Arrow C stream, as `LicorFile.__arrow_c_stream__`."""
+        ...
+
+class PeekColumn(TypedDict):
+    name: str
+    units: str
+    category: str
+
+class PeekResult(TypedDict):
+    header: dict[str, str]
+    columns: list[PeekColumn]
+    rows: list[list[str]]
+
+def scan(
+    file: FileSource,
+    device: Literal["6800"],
+    config: Literal["standard", "fluorometer", "aquatic", "soil"]
+) -> Any:
+    """Scan a LI-COR file as a polars LazyFrame.
+    
+    The file is parsed once up front so the column types are exactly those of
+    file_to_dataframe (a column with a value like #DIV/0! anywhere is a
+    string column), and again when the query is collected. Filters are applied
+    after that parse rather than pushed into the parser; only the selected
+    columns cross into Python, and a query that only needs the first rows
+    (head, limit) reads only the start of a file given by path.
+    
+    Args:
+        file: LI-COR file: a path, its content as str or bytes, or a file-like
+            object with .read()
+        device: Device type ("6800")
+        config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+        
+    Returns:
+        polars LazyFrame
+        
+    Raises:
+        ValueError: Invalid device/config combination or malformed data
+        IOError: File read errors
+        RuntimeError: polars is not installed, or too old for IO plugins
+    """
+    ...
+
+def peek(file: str, rows: int = 5) -> PeekResult:
+    """Read a file's header, columns and first observations without parsing it all.
+    
+    Only the start of the file is read, so this is fast enough for file pickers.
+    
+    Args:
+        file: Path to the input LI-COR file
+        rows: Number of observations to return
+        
+    Returns:
+        dict with "header" (dict of header keys to values), "columns" (list of
+        dicts with "name", "units" and "category") and "rows" (list of rows,
+        each a list of unconverted cell strings)
+        
+    Raises:
+        ValueError: Malformed file
+        IOError: File read errors
+    """
+    ...
+
+class VariableMatch(TypedDict):
+    internal_name: str
+    label: str
+    units: Union[str, None]
+    type: Literal["float", "integer", "string", "boolean"]
+    category: str
+    description: str
+
+def search_variables(query: str) -> list[VariableMatch]:
+    """Find variables by name, label or description, tolerating typos.
+    
+    Args:
+        query: Words to look for, e.g. "boundary layer"
+        
+    Returns:
+        list of dicts with "internal_name", "label", "units" (None when
+        unitless), "type", "category" and "description", best match first
+    """
+    ...
+
+def set_cache_size(entries: int) -> None:
+    """Keep the last parsed files in memory.
+    
+    Functions given a file by path return the cached parse while the file is
+    unchanged on disk (same modification time and size), so a notebook or app
+    redrawing a plot does not parse a large file each time. Files are not
+    cached while parse hooks are registered.
+    
+    Args:
+        entries: Number of files to keep (0, the default, turns caching off)
+    """
+    ...
+
+def clear_cache() -> None:
+    """Drop every cached file."""
+    ...
+
+class RawTable(TypedDict):
+    header: dict[str, str]
+    categories: list[str]
+    column_names: list[str]
+    units: list[str]
+    data: list[list[str]]
+
+def register_hook(
+    stage: Literal["after_raw", "after_typing", "after_parse"],
+    func: Callable[[Any], Any],
+) -> None:
+    """Run a Python function at a stage of every parse.
+    
+    Hooks run in the order registered, for every function that parses a file;
+    the bundled `licor` command runs none.
+    
+    Args:
+        stage: "after_raw" to get and return a dict of the raw table
+            (RawTable), before validation; "after_typing" to get and return
+            a polars DataFrame of the typed columns, before clock correction
+            and derived columns; or "after_parse" for the parsed DataFrame
+        func: Callable taking and returning the stage's value (returning None
+            keeps the value, as changed in place for a dict)
+    
+    Raises:
+        ValueError: Unknown stage
+    """
+    ...
+
+def clear_hooks() -> None:
+    """Remove every registered hook."""
+    ...
+
+class VariableSummary(TypedDict):
+    name: str
+    units: Union[str, None]
+    count: int
+    missing: int
+    min: Union[float, None]
+    max: Union[float, None]
+    mean: Union[float, None]
+
+def summarize(
+    file: FileSource,
+    device: Literal["6800"],
+    config: Literal["standard", "fluorometer", "aquatic", "soil"],
+    columns: Union[list[str], None] = None,
+) -> list[VariableSummary]:
+    """Per-variable statistics of a file: count, missing, min, max and mean.
+    
+    Args:
+        file: LI-COR file: a path, its content as str or bytes, or a file-like
+            object with .read()
+        device: Device type ("6800")
+        config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+        columns: Only these columns, in this order (all when omitted)
+        
+    Returns:
+        list of dicts with "name", "units", "count", "missing", "min", "max"
+        and "mean" (None for text columns)
+        
+    Raises:
+        ValueError: Invalid device/config combination, unknown column or malformed data
+        IOError: File read errors
+    """
+    ...
+
+def recompute(
+    file: FileSource,
+    device: Literal["6800"],
+    config: Literal["standard", "fluorometer", "aquatic", "soil"],
+    oxygen: Union[float, None] = None,
+    gcw: Union[float, None] = None,
+) -> Any:
+    """Recompute the gas-exchange results from the logged measurements.
+    
+    Adds `E_rc`, `A_rc`, `gtw_rc`, `gsw_rc`, `gtc_rc`, `Ci_rc` and `GammaStar`
+    columns, as `licor convert --recompute` does.
+    
+    Args:
+        file: LI-COR file: a path, its content as str or bytes, or a file-like
+            object with .read()
+        device: Device type ("6800")
+        config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+        oxygen: Oxygen in the air stream (%), instead of the file's SysConst:Oxygen
+        gcw: Cuticular conductance to water vapor (mol m-2 s-1) to remove from gsw
+        
+    Returns:
+        polars DataFrame
+        
+    Raises:
+        ValueError: Invalid device/config combination or malformed data
+        IOError: File read errors
+    """
+    ...
@@ -0,0 +1,20 @@
+"""Entry point of the `licor` console script installed with the wheel."""
+
+import sys
+
+from . import licor_client
+
+
+def main() -> None:
+    """Run the `licor` command-line tool with this process's arguments."""
+    if not hasattr(licor_client, "cli_main"):
+        sys.exit("licor_client was built without the `cli` cargo feature; rebuild it with `maturin develop --features cli`")
+    try:
+        licor_client.cli_main(["licor", *sys.argv[1:]])
+    except RuntimeError as e:
+        # cli_main has already written the failure to stderr
+        sys.exit(getattr(e, "exit_status", 1))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,26 @@
+"""Type stubs for the compiled licor_client.licor_client module.
+
+Written by hand like the package stubs (``__init__.pyi``), whose public
+functions and classes are re-exported here; this adds the private functions
+the package wrappers call.
+"""
+
+from typing import Any, Union
+
+from . import *
+from . import FileSource
+
+def _scan(
+    file: FileSource,
+    device: str,
+    config: str,
+    columns: Union[list[str], None] = None,
+    n_rows: Union[int, None] = None,
+) -> Any:
+    """Parse a file for `scan()`: only the given columns, and with `n_rows`
+    only that many observations, read from the start of a file at a path."""
+    ...
+
+def cli_main(args: list[str]) -> None:
+    """Run the `licor` command line with `args`, the first being the program name."""
+    ...
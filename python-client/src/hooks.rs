@@ -0,0 +1,132 @@
+//! Python callables registered as parse stage hooks
+
+use licor_core::{DataType, ParseError, ParseHooks, RawLiCorFile, VariableInfo};
+use polars::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3_polars::PyDataFrame;
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Stage {
+    Raw,
+    Typed,
+    Parsed,
+}
+
+static HOOKS: Mutex<Vec<(Stage, Py<PyAny>)>> = Mutex::new(Vec::new());
+
+/// Run a Python function at a stage of every parse
+///
+/// Hooks run in the order registered, for every function that parses a file;
+/// the bundled `licor` command runs none.
+///
+/// Args:
+///     stage: "after_raw" to get and return a dict of the raw table
+///         ("header", "categories", "column_names", "units", and "data" as a
+///         list of columns of strings), before validation; "after_typing" to
+///         get and return a polars DataFrame of the typed columns, before
+///         clock correction and derived columns; or "after_parse" for the
+///         parsed DataFrame
+///     func: Callable taking and returning the stage's value (returning None
+///         keeps the value, as changed in place for a dict)
+///
+/// Raises:
+///     ValueError: Unknown stage
+#[pyfunction]
+pub fn register_hook(stage: &str, func: Py<PyAny>) -> PyResult<()> {
+    let stage = match stage {
+        "after_raw" => Stage::Raw,
+        "after_typing" => Stage::Typed,
+        "after_parse" => Stage::Parsed,
+        _ => return Err(PyValueError::new_err(format!(
+            "Unknown hook stage '{}': use after_raw, after_typing or after_parse", stage
+        ))),
+    };
+    HOOKS.lock().expect("hook registry poisoned").push((stage, func));
+    Ok(())
+}
+
+/// Remove every registered hook
+#[pyfunction]
+pub fn clear_hooks() {
+    HOOKS.lock().expect("hook registry poisoned").clear();
+}
+
+/// The registered hooks, for `ParseOptions::hooks`
+pub fn registered() -> ParseHooks {
+    let hooks = Python::with_gil(|py| {
+        HOOKS.lock().expect("hook registry poisoned").iter()
+            .map(|(stage, func)| (*stage, func.clone_ref(py)))
+            .collect::<Vec<_>>()
+    });
+    hooks.into_iter().fold(ParseHooks::default(), |hooks, (stage, func)| match stage {
+        Stage::Raw => hooks.after_raw(move |raw| Python::with_gil(|py| call_raw(py, &func, raw)).map_err(hook_error)),
+        Stage::Typed => hooks.after_typing(move |df, variable_info| {
+            Python::with_gil(|py| call_frame(py, &func, df, variable_info)).map_err(hook_error)
+        }),
+        Stage::Parsed => hooks.after_parse(move |data| {
+            Python::with_gil(|py| call_frame(py, &func, &mut data.dataframe, &mut data.variable_info)).map_err(hook_error)
+        }),
+    })
+}
+
+fn call_raw(py: Python<'_>, func: &Py<PyAny>, raw: &mut RawLiCorFile) -> PyResult<()> {
+    let table = PyDict::new(py);
+    table.set_item("header", &raw.header)?;
+    table.set_item("categories", &raw.column_categories)?;
+    table.set_item("column_names", &raw.column_names)?;
+    table.set_item("units", &raw.units)?;
+    table.set_item("data", &raw.data_columns)?;
+    let returned = func.call1(py, (&table,))?;
+    let table = if returned.is_none(py) { table.into_any() } else { returned.into_bound(py) };
+    raw.header = table.get_item("header")?.extract()?;
+    raw.column_categories = table.get_item("categories")?.extract()?;
+    raw.column_names = table.get_item("column_names")?.extract()?;
+    raw.units = table.get_item("units")?.extract()?;
+    raw.data_columns = table.get_item("data")?.extract()?;
+    Ok(())
+}
+
+/// Call a hook on a DataFrame, keeping the variable info in step with the
+/// columns it returns
+fn call_frame(py: Python<'_>, func: &Py<PyAny>, df: &mut DataFrame, variable_info: &mut Vec<VariableInfo>) -> PyResult<()> {
+    let frame = PyDataFrame(df.clone()).into_pyobject(py)?;
+    let returned = func.call1(py, (frame,))?;
+    if returned.is_none(py) {
+        return Ok(());
+    }
+    *df = returned.extract::<PyDataFrame>(py)?.0;
+    let mut described = std::mem::take(variable_info);
+    for column in df.get_columns() {
+        let name = column.name().as_str();
+        let info = match described.iter().position(|v| v.internal_name == name) {
+            Some(index) => described.swap_remove(index),
+            None => VariableInfo {
+                internal_name: name.to_string(),
+                display_label: name.to_string(),
+                units: None,
+                units_pretty: None,
+                units_ascii: None,
+                description: format!("Added by a Python hook: {}", name),
+                data_type: match column.dtype() {
+                    dtype if dtype.is_float() => DataType::Float,
+                    dtype if dtype.is_integer() => DataType::Integer,
+                    polars::datatypes::DataType::Boolean => DataType::Boolean,
+                    _ => DataType::String,
+                },
+                column_category: "Hook".to_string(),
+                derived_from: None,
+            },
+        };
+        variable_info.push(info);
+    }
+    Ok(())
+}
+
+fn hook_error(e: PyErr) -> ParseError {
+    ParseError::InvalidHeaderFormat {
+        message: format!("Python hook failed: {}", e)
+    }
+}
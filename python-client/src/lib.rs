@@ -1,151 +1,1158 @@
 use pyo3::prelude::*;
-use pyo3::exceptions::{PyValueError, PyIOError, PyRuntimeError};
+use pyo3::exceptions::{PyFileExistsError, PyValueError, PyIOError, PyRuntimeError, PyTypeError};
+use pyo3::types::{PyBytes, PyCapsule, PyDict, PyString};
 use licor_core::{
-    LiCor6800Standard, LiCor6800Fluorometer, LiCor6800Aquatic, LiCor6800Soil,
-    ParseError, LiCorData
+    LiCorParser, LiCorDevice, LiCorConfig, LiCor6800Standard, LiCor6800Fluorometer, LiCor6800Aquatic, LiCor6800Soil,
+    ParseError, ParseOptions, ParquetCodec, ParquetOptions, LiCorData, LiCorMetadata, VariableInfo, Language, Message,
+    CacheKey, ParseCache, index_file, merge_data, remark_text, search_variables as search_definitions
 };
 use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use polars::prelude::*;
 use pyo3_polars::PyDataFrame;
 
+#[cfg(feature = "hooks")]
+mod hooks;
+
+/// Metadata fields repeated as columns by `convert(metadata_columns=True)`
+const METADATA_COLUMNS: [&str; 8] = [
+    "device_serial", "console_version", "head_serial", "head_version",
+    "chamber_type", "chamber_serial", "fluorometer_serial", "calibration_date",
+];
+
 /// Convert a LI-COR file to Parquet format
+///
+/// The options match those of `licor convert`.
 /// 
 /// Args:
-///     file: Path to the input LI-COR file
-///     output: Path for the output Parquet file  
-///     device: Device type ("6800" or "6400")
+///     file: LI-COR file: a path, its content as str or bytes, or a file-like
+///         object with .read()
+///     output: Path for the output Parquet file, or the dataset directory
+///         when partitioned
+///     device: Device type ("6800")
 ///     config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+///     compression: Parquet codec ("zstd", "snappy" or "uncompressed")
+///     compression_level: zstd level (1-22; the codec's default when omitted)
+///     layout: "wide" for a column per variable, or "long" (tidy) for a row
+///         per observation and variable
+///     partition: None for one file; "date" for a Hive dataset of
+///         `date=YYYY-MM-DD/<stem>.parquet` under `output`, or "date_device"
+///         to also partition by console serial
+///     metadata_columns: True to repeat the instrument metadata (serials,
+///         console version, chamber type, calibration date) as constant
+///         columns, or a list of metadata fields and raw header keys
+///     overwrite: Replace existing outputs; by default an existing output
+///         raises FileExistsError and nothing is written
+///
+/// Returns:
+///     list of the paths written
 ///
 /// Raises:
-///     ValueError: Invalid device/config combination or malformed data
+///     ValueError: Invalid device/config combination, option or malformed data
 ///     IOError: File read/write errors
+///     FileExistsError: An output exists and `overwrite` is not set
 ///     RuntimeError: Other parsing errors
 #[pyfunction]
-fn convert(file: &str, output: &str, device: &str, config: &str) -> PyResult<()> {
-    let data = parse_file_internal(file, device, config)?;
-    
-    // Write to Parquet
-    let mut output_file = std::fs::File::create(output)
-        .map_err(|e| PyIOError::new_err(format!("Failed to create output file: {}", e)))?;
-    
-    ParquetWriter::new(&mut output_file)
-        .finish(&mut data.dataframe.clone())
-        .map_err(|e| PyIOError::new_err(format!("Failed to write Parquet file: {}", e)))?;
+#[pyo3(signature = (
+    file, output, device, config, *, compression="zstd", compression_level=None, layout="wide",
+    partition=None, metadata_columns=None, overwrite=false
+))]
+#[allow(clippy::too_many_arguments)]
+fn convert(
+    file: Source,
+    output: &str,
+    device: &str,
+    config: &str,
+    compression: &str,
+    compression_level: Option<i32>,
+    layout: &str,
+    partition: Option<&str>,
+    metadata_columns: Option<&Bound<'_, PyAny>>,
+    overwrite: bool,
+) -> PyResult<Vec<String>> {
+    let parquet = ParquetOptions {
+        codec: compression.parse::<ParquetCodec>().map_err(PyValueError::new_err)?,
+        level: compression_level,
+        ..ParquetOptions::default()
+    };
+    let long = match layout {
+        "wide" => false,
+        "long" | "tidy" => true,
+        _ => return Err(PyValueError::new_err(format!("Unknown layout '{}': use wide or long", layout))),
+    };
+    let partition_by_device = match partition {
+        None => None,
+        Some("date") => Some(false),
+        Some("date_device") => Some(true),
+        Some(other) => return Err(PyValueError::new_err(format!("Unknown partitioning '{}': use date or date_device", other))),
+    };
+    let header_columns = match metadata_columns {
+        None => Vec::new(),
+        Some(columns) => match columns.extract::<bool>() {
+            Ok(all) => if all { METADATA_COLUMNS.map(str::to_string).to_vec() } else { Vec::new() },
+            Err(_) => columns.extract::<Vec<String>>()?,
+        },
+    };
+    let stem = match &file {
+        Source::Path(path) | Source::Head(path, _) => Path::new(path).file_stem().and_then(|s| s.to_str()).unwrap_or("data").to_string(),
+        Source::Content(_) => "data".to_string(),
+    };
     
-    Ok(())
+    // Parse first, so a bad device or file is reported before an existing output
+    let options = ParseOptions { header_columns, ..parse_options() };
+    let data = parse_file_with_options(&file, device, config, options)?;
+    if !overwrite {
+        let existing = match partition_by_device {
+            None => Path::new(output).exists().then(|| output.to_string()),
+            Some(_) => partition_file(Path::new(output), &format!("{}.parquet", stem)),
+        };
+        if let Some(existing) = existing {
+            return Err(PyFileExistsError::new_err(format!("Output exists: {}", existing)));
+        }
+    }
+    let data = if long {
+        data.to_long().map_err(to_py_error)?
+    } else {
+        data
+    };
+    match partition_by_device {
+        None => {
+            licor_core::write_parquet(&data, output, &parquet).map_err(to_py_error)?;
+            Ok(vec![output.to_string()])
+        }
+        Some(by_device) => licor_core::write_hive_dataset(&data, output, &stem, by_device, &parquet).map_err(to_py_error),
+    }
+}
+
+/// A file named `name` in the partitions under `root`, if any
+fn partition_file(root: &Path, name: &str) -> Option<String> {
+    let entries = std::fs::read_dir(root).ok()?;
+    entries.flatten().map(|entry| entry.path()).find_map(|path| {
+        if path.is_dir() {
+            partition_file(&path, name)
+        } else {
+            (path.file_name()? == name).then(|| path.to_string_lossy().into_owned())
+        }
+    })
 }
 
 /// Convert a LI-COR file directly to a DataFrame
 ///
 /// Args:
-///     file: Path to the input LI-COR file
-///     format: Output format ("polars" or "pandas")
-///     device: Device type ("6800" or "6400") 
+///     file: LI-COR file: a path, its content as str or bytes, or a file-like
+///         object with .read()
+///     format: Output format ("polars", "pandas", "xarray", "numpy" or "arrow")
+///     device: Device type ("6800")
 ///     config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
 ///
 /// Returns:
-///     DataFrame in the requested format
+///     DataFrame in the requested format; for "xarray", a Dataset along
+///     `time` (UTC datetimes, or `obs` when the file has no `time` column)
+///     with `units` and `long_name` attrs on each variable; for "numpy", a
+///     structured array with a field per column (needs only numpy); for
+///     "arrow", an ArrowTable read by any Arrow consumer (needs nothing)
 ///
 /// Raises:
 ///     ValueError: Invalid device/config combination, unsupported format, or malformed data
 ///     IOError: File read errors
 ///     RuntimeError: Missing optional dependencies or other parsing errors
 #[pyfunction]
-fn file_to_dataframe(file: &str, format: &str, device: &str, config: &str) -> PyResult<PyObject> {
-    let data = parse_file_internal(file, device, config)?;
+fn file_to_dataframe(file: Source, format: &str, device: &str, config: &str) -> PyResult<PyObject> {
+    let data = parse_file_internal(&file, device, config)?;
     
     match format {
-        "polars" => {
-            // Check if polars is available
-            Python::with_gil(|py| {
-                match py.import("polars") {
-                    Ok(_) => {
-                        // Convert Polars DataFrame to Python via pyo3-polars
-                        let py_df = PyDataFrame(data.dataframe);
-                        Ok(py_df.into_pyobject(py)?.into_any().unbind())
-                    }
-                    Err(_) => Err(PyRuntimeError::new_err(
-                        "polars is not installed. Install with: uv add licor-client[polars]"
-                    ))
+        "polars" => Python::with_gil(|py| polars_frame(py, data.dataframe)),
+        "pandas" => Python::with_gil(|py| pandas_frame(py, data.dataframe)),
+        "xarray" => Python::with_gil(|py| xarray_dataset(py, &data)),
+        "numpy" => Python::with_gil(|py| numpy_array(py, &data.dataframe)),
+        "arrow" => Python::with_gil(|py| Ok(Py::new(py, ArrowTable { dataframe: data.dataframe })?.into_any())),
+        _ => Err(PyValueError::new_err(format!(
+            "Unsupported format '{}'. Supported formats: 'polars', 'pandas', 'xarray', 'numpy', 'arrow'", format
+        )))
+    }
+}
+
+/// Convert many LI-COR files to Parquet in parallel
+///
+/// Files are converted on `workers` threads without holding the GIL, and a
+/// file that fails does not stop the others.
+///
+/// Args:
+///     files: Glob pattern (e.g. "data/*.txt") or list of paths
+///     output_dir: Directory for the `<stem>.parquet` outputs (created if missing)
+///     device: Device type ("6800")
+///     config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+///     workers: Number of threads (one per CPU when omitted)
+///
+/// Returns:
+///     list of dicts, in input order, with "file", "status" ("converted" or
+///     "failed"), "output" and "rows" (None when failed), and "error" and
+///     "code" (None when converted)
+///
+/// Raises:
+///     ValueError: Invalid device/config combination or glob pattern
+///     IOError: No files match the pattern, or the output directory cannot be created
+#[pyfunction]
+#[pyo3(signature = (files, output_dir, device, config, workers=None))]
+fn convert_many(
+    py: Python<'_>,
+    files: &Bound<'_, PyAny>,
+    output_dir: &str,
+    device: &str,
+    config: &str,
+    workers: Option<usize>,
+) -> PyResult<Vec<PyObject>> {
+    let files: Vec<String> = match files.extract::<String>() {
+        Ok(pattern) => {
+            let paths = glob::glob(&pattern)
+                .map_err(|e| PyValueError::new_err(format!("Invalid pattern '{}': {}", pattern, e)))?;
+            let files: Vec<String> = paths.filter_map(Result::ok).map(|path| path.to_string_lossy().into_owned()).collect();
+            if files.is_empty() {
+                return Err(PyIOError::new_err(Message::NoFilesFound { pattern: &pattern }.text(Language::from_env())));
+            }
+            files
+        }
+        Err(_) => files.extract()?,
+    };
+    let parse = parser_for(device, config)?;
+    let options = parse_options();
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| PyIOError::new_err(format!("Failed to create output directory: {}", e)))?;
+    
+    let results = parallel_map(py, &files, workers, |file| convert_to_parquet(parse, &options, file, output_dir));
+    
+    let language = Language::from_env();
+    files.iter().zip(results)
+        .map(|(file, result)| {
+            let entry = PyDict::new(py);
+            entry.set_item("file", file)?;
+            match result {
+                Ok((output, rows)) => {
+                    entry.set_item("status", "converted")?;
+                    entry.set_item("output", output)?;
+                    entry.set_item("rows", rows)?;
+                    entry.set_item("error", py.None())?;
+                    entry.set_item("code", py.None())?;
                 }
-            })
+                Err(e) => {
+                    entry.set_item("status", "failed")?;
+                    entry.set_item("output", py.None())?;
+                    entry.set_item("rows", py.None())?;
+                    entry.set_item("error", e.localized(language))?;
+                    entry.set_item("code", e.code())?;
+                }
+            }
+            Ok(entry.into_any().unbind())
+        })
+        .collect()
+}
+
+/// Apply `f` to each file on `workers` threads (one per CPU when None)
+/// without holding the GIL, returning the results in input order
+fn parallel_map<T: Send>(py: Python<'_>, files: &[String], workers: Option<usize>, f: impl Fn(&str) -> T + Sync) -> Vec<T> {
+    let workers = workers
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+        .clamp(1, files.len().max(1));
+    py.allow_threads(|| {
+        // Workers take the next file until none are left
+        let next = AtomicUsize::new(0);
+        let mut results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(file) = files.get(index) else { break done };
+                        done.push((index, f(file)));
+                    }
+                }))
+                .collect();
+            handles.into_iter().flat_map(|handle| handle.join().expect("worker thread panicked")).collect()
+        });
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    })
+}
+
+/// Parse every LI-COR file under a directory into one DataFrame
+///
+/// Files are found recursively, leaving out hidden files and files that are
+/// not LI-COR logs (outputs, notes), and parsed in parallel on `workers`
+/// threads without holding the GIL. Their rows are concatenated in path order
+/// with the schemas aligned: a column a file lacks is null, and a column typed
+/// differently across files becomes Float64 if every type is numeric and
+/// text otherwise. A first `file` column holds each row's source path. A file
+/// that fails to parse does not stop the others.
+///
+/// Args:
+///     directory: Directory to search
+///     device: Device type ("6800")
+///     config: Measurement configuration ("standard", "fluorometer",
+///         "aquatic", "soil"), or None for each file's best-matching
+///         configuration, as `licor index` reports it
+///     workers: Number of threads (one per CPU when omitted)
+///
+/// Returns:
+///     (DataFrame, manifest): a polars DataFrame, and a list of dicts, one per
+///     file found, with "file", "serial", "config", "status" ("parsed" or
+///     "failed"), "rows" (None when failed), and "error" and "code" (None
+///     when parsed)
+///
+/// Raises:
+///     ValueError: Invalid device/config combination
+///     IOError: The directory cannot be read or holds no LI-COR files
+///     RuntimeError: polars is not installed, or no file parses
+#[pyfunction]
+#[pyo3(signature = (directory, device, config=None, workers=None))]
+fn open_dataset(
+    py: Python<'_>,
+    directory: &str,
+    device: &str,
+    config: Option<&str>,
+    workers: Option<usize>,
+) -> PyResult<(PyObject, Vec<PyObject>)> {
+    parser_for(device, config.unwrap_or("standard"))?;
+    let io_error = |e: std::io::Error| PyIOError::new_err(format!("Failed to read {}: {}", directory, e));
+    let mut paths = Vec::new();
+    let mut pending = vec![std::path::PathBuf::from(directory)];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+            if path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                paths.push(path.to_string_lossy().into_owned());
+            }
         }
-        "pandas" => {
-            // Check if pandas is available
-            Python::with_gil(|py| {
-                let _pandas = py.import("pandas")
-                    .map_err(|_| PyRuntimeError::new_err(
-                        "pandas is not installed. Install with: uv add licor-client[pandas]"
-                    ))?;
-                
-                // For now, pandas support is not implemented
-                Err(PyRuntimeError::new_err(
-                    "pandas support is not yet fully implemented. Use format='polars' instead."
-                ))
+    }
+    paths.sort();
+    
+    let device_name = format!("LI-{}", device);
+    let entries: Vec<_> = parallel_map(py, &paths, workers, |path| index_file(path).ok())
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.device == device_name)
+        .collect();
+    if entries.is_empty() {
+        return Err(PyIOError::new_err(format!("No {} files found under {}", device_name, directory)));
+    }
+    let files: Vec<String> = entries.iter().map(|entry| entry.path.clone()).collect();
+    let options = parse_options();
+    let results = parallel_map(py, &files, workers, |file| {
+        let entry = entries.iter().find(|entry| entry.path == file).expect("indexed file");
+        let parse = parser_for(device, config.unwrap_or(&entry.config)).map_err(|e| ParseError::InvalidHeaderFormat { message: e.to_string() })?;
+        let mut data = parse(&Source::Path(file.to_string()), options.clone())?;
+        let height = data.dataframe.height();
+        data.dataframe.insert_column(0, Column::new("file".into(), vec![file; height]))
+            .map_err(|e| ParseError::InvalidHeaderFormat { message: e.to_string() })?;
+        Ok::<_, ParseError>(data)
+    });
+    
+    let language = Language::from_env();
+    let mut manifest = Vec::new();
+    let mut parsed = Vec::new();
+    for (entry, result) in entries.iter().zip(results) {
+        let item = PyDict::new(py);
+        item.set_item("file", &entry.path)?;
+        item.set_item("serial", &entry.serial)?;
+        item.set_item("config", config.unwrap_or(&entry.config))?;
+        match result {
+            Ok(data) => {
+                item.set_item("status", "parsed")?;
+                item.set_item("rows", data.dataframe.height())?;
+                item.set_item("error", py.None())?;
+                item.set_item("code", py.None())?;
+                parsed.push(data);
+            }
+            Err(e) => {
+                item.set_item("status", "failed")?;
+                item.set_item("rows", py.None())?;
+                item.set_item("error", e.localized(language))?;
+                item.set_item("code", e.code())?;
+            }
+        }
+        manifest.push(item.into_any().unbind());
+    }
+    if parsed.is_empty() {
+        return Err(PyRuntimeError::new_err(format!("None of the {} files under {} parsed", entries.len(), directory)));
+    }
+    let data = py.allow_threads(|| merge_data(parsed, &[])).map_err(to_py_error)?;
+    Ok((polars_frame(py, data.dataframe)?, manifest))
+}
+
+/// Parse a file and write it to `<output_dir>/<stem>.parquet`, returning the
+/// output path and row count
+fn convert_to_parquet(parse: ParseFn, options: &ParseOptions, file: &str, output_dir: &str) -> Result<(String, usize), ParseError> {
+    let data = parse(&Source::Path(file.to_string()), options.clone())?;
+    let stem = Path::new(file).file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+    let output = Path::new(output_dir).join(format!("{}.parquet", stem)).to_string_lossy().into_owned();
+    licor_core::write_parquet(&data, &output, &ParquetOptions::default())?;
+    Ok((output, data.dataframe.height()))
+}
+
+/// A parsed LI-COR file
+///
+/// Args:
+///     file: LI-COR file: a path, its content as str or bytes, or a file-like
+///         object with .read()
+///     device: Device type ("6800")
+///     config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+///
+/// Raises:
+///     ValueError: Invalid device/config combination or malformed data
+///     IOError: File read errors
+#[pyclass(name = "LicorFile", module = "licor_client", frozen)]
+struct LicorFile {
+    path: Option<String>,
+    data: LiCorData,
+}
+
+#[pymethods]
+impl LicorFile {
+    #[new]
+    fn new(file: Source, device: &str, config: &str) -> PyResult<Self> {
+        Ok(LicorFile {
+            data: parse_file_internal(&file, device, config)?,
+            path: match file {
+                Source::Path(path) | Source::Head(path, _) => Some(path),
+                Source::Content(_) => None,
+            },
+        })
+    }
+    
+    /// Path the file was read from, or None when it was given as content
+    #[getter]
+    fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+    
+    /// Instrument metadata, as returned by `read_metadata()`
+    #[getter]
+    fn metadata(&self, py: Python<'_>) -> PyResult<PyObject> {
+        metadata_dict(py, &self.data.metadata)
+    }
+    
+    /// Column descriptions, as returned by `variable_info()`
+    #[getter]
+    fn variables(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        variable_dicts(py, &self.data.variable_info)
+    }
+    
+    /// Remarks logged among the data rows: dicts with "after_row" (rows
+    /// logged before the remark) and "text"
+    #[getter]
+    fn remarks(&self, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        self.data.metadata.console_messages.iter()
+            .filter_map(|message| Some((message.after_row, remark_text(&message.text)?)))
+            .map(|(after_row, text)| {
+                let entry = PyDict::new(py);
+                entry.set_item("after_row", after_row)?;
+                entry.set_item("text", text.trim())?;
+                Ok(entry.into_any().unbind())
             })
+            .collect()
+    }
+    
+    /// The data as a polars DataFrame
+    fn to_polars(&self, py: Python<'_>) -> PyResult<PyObject> {
+        polars_frame(py, self.data.dataframe.clone())
+    }
+    
+    /// The data as a pandas DataFrame (needs pandas and pyarrow)
+    fn to_pandas(&self, py: Python<'_>) -> PyResult<PyObject> {
+        pandas_frame(py, self.data.dataframe.clone())
+    }
+    
+    /// Write the data to Parquet, with the metadata and column descriptions
+    /// in the file's key-value metadata
+    fn to_parquet(&self, path: &str) -> PyResult<()> {
+        licor_core::write_parquet(&self.data, path, &ParquetOptions::default()).map_err(to_py_error)
+    }
+    
+    /// Number of observations
+    fn __len__(&self) -> usize {
+        self.data.dataframe.height()
+    }
+    
+    fn __repr__(&self) -> String {
+        let source = self.path.as_ref().map_or_else(|| "<content>".to_string(), |path| format!("{:?}", path));
+        format!("LicorFile({}, {} rows, {} columns)", source, self.data.dataframe.height(), self.data.dataframe.width())
+    }
+    
+    /// The data as an Arrow C stream (the Arrow PyCapsule interface), so
+    /// `pyarrow.table()`, duckdb, pandas and polars read it without a copy
+    /// through another library
+    ///
+    /// A requested schema selects its columns in its order, cast to its types;
+    /// a missing column or a failed cast raises ValueError.
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__<'py>(&self, py: Python<'py>, requested_schema: Option<Bound<'py, PyCapsule>>) -> PyResult<Bound<'py, PyCapsule>> {
+        arrow_stream(py, &self.data.dataframe, requested_schema)
+    }
+}
+
+/// A table of parsed data, read by any Arrow consumer
+///
+/// Returned by `file_to_dataframe(format="arrow")`; it needs neither polars
+/// nor pyarrow.
+#[pyclass(name = "ArrowTable", module = "licor_client", frozen)]
+struct ArrowTable {
+    dataframe: DataFrame,
+}
+
+#[pymethods]
+impl ArrowTable {
+    /// Column names, in order
+    #[getter]
+    fn columns(&self) -> Vec<String> {
+        self.dataframe.get_column_names_str().into_iter().map(str::to_string).collect()
+    }
+    
+    /// Number of rows
+    fn __len__(&self) -> usize {
+        self.dataframe.height()
+    }
+    
+    fn __repr__(&self) -> String {
+        format!("ArrowTable({} rows, {} columns)", self.dataframe.height(), self.dataframe.width())
+    }
+    
+    /// The data as an Arrow C stream, as `LicorFile.__arrow_c_stream__`
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__<'py>(&self, py: Python<'py>, requested_schema: Option<Bound<'py, PyCapsule>>) -> PyResult<Bound<'py, PyCapsule>> {
+        arrow_stream(py, &self.dataframe, requested_schema)
+    }
+}
+
+/// Export a DataFrame as a capsule holding an `ArrowArrayStream` of struct
+/// arrays, one per record batch, cast to `requested_schema` when given
+fn arrow_stream<'py>(py: Python<'py>, df: &DataFrame, requested_schema: Option<Bound<'py, PyCapsule>>) -> PyResult<Bound<'py, PyCapsule>> {
+    use polars_arrow::array::{Array, StructArray};
+    use polars_arrow::datatypes::{ArrowDataType, Field as ArrowField};
+    
+    let (mut df, compat) = match requested_schema {
+        Some(schema) => requested_frame(df, &schema)?,
+        None => (df.clone(), CompatLevel::newest()),
+    };
+    df.align_chunks_par();
+    let fields: Vec<ArrowField> = df.get_columns().iter().map(|c| c.field().to_arrow(compat)).collect();
+    let dtype = ArrowDataType::Struct(fields);
+    let batches: Vec<PolarsResult<Box<dyn Array>>> = df.iter_chunks(compat, true)
+        .map(|batch| {
+            let height = batch.height();
+            Ok(StructArray::try_new(dtype.clone(), height, batch.into_arrays(), None)?.boxed())
+        })
+        .collect();
+    let stream = polars_arrow::ffi::export_iterator(
+        Box::new(batches.into_iter()),
+        ArrowField::new("".into(), dtype, false),
+    );
+    let name = std::ffi::CString::new("arrow_array_stream").expect("no NUL in capsule name");
+    PyCapsule::new(py, stream, Some(name))
+}
+
+/// The columns of a requested Arrow schema (an `arrow_schema` capsule holding
+/// a struct), cast to its types, and the compat level that exports them as
+/// those Arrow types (e.g. `large_string` rather than `string_view`)
+fn requested_frame(df: &DataFrame, schema: &Bound<'_, PyCapsule>) -> PyResult<(DataFrame, CompatLevel)> {
+    use polars_arrow::datatypes::ArrowDataType;
+    use polars_arrow::ffi::{import_field_from_c, ArrowSchema};
+    
+    if schema.name()?.map(|name| name.to_bytes() != b"arrow_schema").unwrap_or(true) {
+        return Err(PyTypeError::new_err("requested_schema must be an 'arrow_schema' PyCapsule"));
+    }
+    // SAFETY: an `arrow_schema` capsule holds a valid ArrowSchema for its lifetime
+    let field = unsafe { import_field_from_c(&*(schema.pointer() as *const ArrowSchema)) }
+        .map_err(|e| PyValueError::new_err(format!("Invalid requested schema: {}", e)))?;
+    let ArrowDataType::Struct(fields) = field.dtype() else {
+        return Err(PyValueError::new_err("requested_schema must be a struct of the columns"));
+    };
+    
+    let columns = fields.iter()
+        .map(|field| {
+            let column = df.column(&field.name)
+                .map_err(|_| PyValueError::new_err(format!("Requested column '{}' is not in the data", field.name)))?;
+            column.strict_cast(&polars::datatypes::DataType::from_arrow_field(field))
+                .map_err(|e| PyValueError::new_err(format!("Cannot cast column '{}' to {:?}: {}", field.name, field.dtype(), e)))
+        })
+        .collect::<PyResult<Vec<Column>>>()?;
+    let requested = DataFrame::new(columns).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    
+    let mismatch = |compat: CompatLevel| requested.get_columns().iter().zip(fields)
+        .find(|(column, field)| column.field().to_arrow(compat).dtype() != field.dtype())
+        .map(|(_, field)| field);
+    match (mismatch(CompatLevel::newest()), mismatch(CompatLevel::oldest())) {
+        (None, _) => Ok((requested, CompatLevel::newest())),
+        (_, None) => Ok((requested, CompatLevel::oldest())),
+        (_, Some(field)) => Err(PyValueError::new_err(format!("Cannot export column '{}' as {:?}", field.name, field.dtype()))),
+    }
+}
+
+fn polars_frame(py: Python<'_>, df: DataFrame) -> PyResult<PyObject> {
+    py.import("polars").map_err(|_| PyRuntimeError::new_err(
+        "polars is not installed. Install with: uv add licor-client[polars]"
+    ))?;
+    Ok(PyDataFrame(df).into_pyobject(py)?.into_any().unbind())
+}
+
+/// Converted through polars, whose `to_pandas` needs pyarrow
+fn pandas_frame(py: Python<'_>, df: DataFrame) -> PyResult<PyObject> {
+    py.import("pandas").map_err(|_| PyRuntimeError::new_err(
+        "pandas is not installed. Install with: uv add licor-client[pandas]"
+    ))?;
+    polars_frame(py, df)?.call_method0(py, "to_pandas")
+}
+
+/// Converted through pandas, with the `time` column (seconds since 1970) as
+/// datetimes for the dimension and the attrs `write_netcdf` writes
+fn xarray_dataset(py: Python<'_>, data: &LiCorData) -> PyResult<PyObject> {
+    py.import("xarray").map_err(|_| PyRuntimeError::new_err(
+        "xarray is not installed. Install with: uv add licor-client[xarray]"
+    ))?;
+    let polars_error = |e: PolarsError| PyRuntimeError::new_err(format!("Failed to build the Dataset: {}", e));
+    let mut df = data.dataframe.clone();
+    let dimension = match df.column("time") {
+        Ok(time) => {
+            let seconds = time.cast(&DataType::Float64).map_err(polars_error)?;
+            let micros: Int64Chunked = seconds.f64().map_err(polars_error)?
+                .into_iter()
+                .map(|seconds| seconds.map(|seconds| (seconds * 1e6).round() as i64))
+                .collect::<Int64Chunked>()
+                .with_name("time".into());
+            df.with_column(micros.into_datetime(TimeUnit::Microseconds, None).into_column()).map_err(polars_error)?;
+            "time"
+        }
+        Err(_) => "obs",
+    };
+    let dataset = pandas_frame(py, df)?
+        .call_method1(py, "set_index", (dimension,))?
+        .call_method0(py, "to_xarray")?
+        .into_bound(py);
+    
+    for info in &data.variable_info {
+        let Ok(variable) = dataset.get_item(&info.internal_name) else { continue };
+        let attrs = variable.getattr("attrs")?;
+        if info.internal_name == "time" && dimension == "time" {
+            // xarray encodes datetimes itself and refuses a `units` attr on them
+            attrs.set_item("long_name", "Time of observation (UTC)")?;
+            continue;
+        }
+        let long_name = if info.description.is_empty() { &info.display_label } else { &info.description };
+        attrs.set_item("long_name", long_name)?;
+        if let Some(units) = info.units_ascii.as_ref().or(info.units.as_ref()) {
+            attrs.set_item("units", units)?;
+        }
+    }
+    let metadata = &data.metadata;
+    let attrs = dataset.getattr("attrs")?;
+    attrs.set_item("source", format!("LI-COR {}", metadata.console_version))?;
+    attrs.set_item("instrument_serial", &metadata.device_serial)?;
+    let optional_fields = [
+        ("head_serial", &metadata.head_serial),
+        ("head_version", &metadata.head_version),
+        ("chamber_type", &metadata.chamber_type),
+        ("chamber_serial", &metadata.chamber_serial),
+        ("fluorometer_serial", &metadata.fluorometer_serial),
+        ("calibration_date", &metadata.calibration_date),
+        ("content_hash", &metadata.content_hash),
+    ];
+    for (key, value) in optional_fields {
+        if let Some(value) = value {
+            attrs.set_item(key, value)?;
         }
-        _ => Err(PyValueError::new_err(format!(
-            "Unsupported format '{}'. Supported formats: 'polars', 'pandas'", format
-        )))
     }
+    Ok(dataset.unbind())
 }
 
-/// Internal function to parse a file with device/config validation
-fn parse_file_internal(file: &str, device: &str, config: &str) -> PyResult<LiCorData> {
-    // Validate file exists
+/// A structured array with a field per column, built without polars or
+/// pandas on the Python side
+///
+/// Floats and integer columns with missing values are float64 with NaN for
+/// missing, whole integer columns int64, `time` datetime64[us] (UTC), complete
+/// boolean columns bool and other boolean columns object with None. Anything
+/// else is text, with "" for missing.
+fn numpy_array(py: Python<'_>, df: &DataFrame) -> PyResult<PyObject> {
+    let numpy = py.import("numpy").map_err(|_| PyRuntimeError::new_err(
+        "numpy is not installed. Install with: uv add licor-client[numpy]"
+    ))?;
+    let polars_error = |e: PolarsError| PyRuntimeError::new_err(format!("Failed to build the array: {}", e));
+    let mut fields = Vec::new();
+    for column in df.get_columns() {
+        let name = column.name().as_str();
+        let dtype = column.dtype();
+        let array = if name == "time" {
+            let seconds = column.cast(&DataType::Float64).map_err(polars_error)?;
+            // i64::MIN is NaT
+            let micros: Vec<i64> = seconds.f64().map_err(polars_error)?
+                .into_iter()
+                .map(|seconds| seconds.map_or(i64::MIN, |seconds| (seconds * 1e6).round() as i64))
+                .collect();
+            numpy.call_method1("array", (micros, "datetime64[us]"))?
+        } else if dtype.is_float() || (dtype.is_integer() && column.null_count() > 0) {
+            let values = column.cast(&DataType::Float64).map_err(polars_error)?;
+            let values: Vec<f64> = values.f64().map_err(polars_error)?.into_iter().map(|v| v.unwrap_or(f64::NAN)).collect();
+            numpy.call_method1("array", (values, "float64"))?
+        } else if dtype.is_integer() {
+            let values = column.cast(&DataType::Int64).map_err(polars_error)?;
+            let values: Vec<i64> = values.i64().map_err(polars_error)?.into_no_null_iter().collect();
+            numpy.call_method1("array", (values, "int64"))?
+        } else if let (DataType::Boolean, 0) = (dtype, column.null_count()) {
+            let values: Vec<bool> = column.bool().map_err(polars_error)?.into_no_null_iter().collect();
+            numpy.call_method1("array", (values, "bool"))?
+        } else if let DataType::Boolean = dtype {
+            let values: Vec<Option<bool>> = column.bool().map_err(polars_error)?.into_iter().collect();
+            numpy.call_method1("array", (values, "object"))?
+        } else {
+            let values = column.cast(&DataType::String).map_err(polars_error)?;
+            let values: Vec<&str> = values.str().map_err(polars_error)?.into_iter().map(Option::unwrap_or_default).collect();
+            numpy.call_method1("array", (values, "str"))?
+        };
+        fields.push((name, array));
+    }
+    let dtype: Vec<(&str, Bound<'_, PyAny>)> = fields.iter()
+        .map(|(name, array)| Ok((*name, array.getattr("dtype")?)))
+        .collect::<PyResult<_>>()?;
+    let structured = numpy.call_method1("empty", (df.height(), dtype))?;
+    for (name, array) in fields {
+        structured.set_item(name, array)?;
+    }
+    Ok(structured.unbind())
+}
+
+fn metadata_dict(py: Python<'_>, metadata: &LiCorMetadata) -> PyResult<PyObject> {
+    // The metadata's serde form is the one the CLI writes, so both agree
+    let json = serde_json::to_string(metadata)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to convert metadata: {}", e)))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+fn variable_dicts(py: Python<'_>, variable_info: &[VariableInfo]) -> PyResult<Vec<PyObject>> {
+    variable_info.iter()
+        .map(|info| {
+            let entry = PyDict::new(py);
+            entry.set_item("name", &info.internal_name)?;
+            entry.set_item("label", &info.display_label)?;
+            entry.set_item("units", &info.units)?;
+            entry.set_item("units_pretty", &info.units_pretty)?;
+            entry.set_item("description", &info.description)?;
+            entry.set_item("dtype", info.data_type.name())?;
+            entry.set_item("category", &info.column_category)?;
+            Ok(entry.into_any().unbind())
+        })
+        .collect()
+}
+
+/// Read a file's instrument metadata
+///
+/// Args:
+///     file: LI-COR file: a path, its content as str or bytes, or a file-like
+///         object with .read()
+///     device: Device type ("6800")
+///     config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+///
+/// Returns:
+///     dict with "device_serial", "console_version", "head_serial",
+///     "head_version", "chamber_type", "chamber_serial", "fluorometer_serial"
+///     and "calibration_date" (None when not logged), "content_hash",
+///     "clock_correction", "console_messages" (list of dicts with "after_row"
+///     and "text") and "fluorometer_settings" (list of dicts)
+///
+/// Raises:
+///     ValueError: Invalid device/config combination or malformed data
+///     IOError: File read errors
+#[pyfunction]
+fn read_metadata(py: Python<'_>, file: Source, device: &str, config: &str) -> PyResult<PyObject> {
+    let data = parse_file_internal(&file, device, config)?;
+    metadata_dict(py, &data.metadata)
+}
+
+/// Describe the columns of a converted file, e.g. to label plot axes
+///
+/// Args:
+///     file: LI-COR file: a path, its content as str or bytes, or a file-like
+///         object with .read()
+///     device: Device type ("6800")
+///     config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+///
+/// Returns:
+///     list of dicts with "name", "label", "units", "units_pretty" (None
+///     when unitless), "description", "dtype" and "category", in column order
+///
+/// Raises:
+///     ValueError: Invalid device/config combination or malformed data
+///     IOError: File read errors
+#[pyfunction]
+fn variable_info(py: Python<'_>, file: Source, device: &str, config: &str) -> PyResult<Vec<PyObject>> {
+    let data = parse_file_internal(&file, device, config)?;
+    variable_dicts(py, &data.variable_info)
+}
+
+/// Read a file's header, columns and first observations without parsing it all
+///
+/// Only the start of the file is read, so this is fast enough for file pickers.
+///
+/// Args:
+///     file: Path to the input LI-COR file
+///     rows: Number of observations to return
+///
+/// Returns:
+///     dict with "header" (dict of header keys to values), "columns" (list of
+///     dicts with "name", "units" and "category") and "rows" (list of rows,
+///     each a list of unconverted cell strings)
+///
+/// Raises:
+///     ValueError: Malformed file
+///     IOError: File read errors
+#[pyfunction]
+#[pyo3(signature = (file, rows=5))]
+fn peek(py: Python<'_>, file: &str, rows: usize) -> PyResult<PyObject> {
     if !Path::new(file).exists() {
-        return Err(PyIOError::new_err(format!("File not found: {}", file)));
+        return Err(PyIOError::new_err(Message::FileNotFound { path: file }.text(Language::from_env())));
     }
+    let raw = licor_core::peek(file, rows).map_err(to_py_error)?;
+    
+    let columns = raw.column_names.iter()
+        .zip(&raw.units)
+        .zip(&raw.column_categories)
+        .map(|((name, units), category)| {
+            let column = PyDict::new(py);
+            column.set_item("name", name)?;
+            column.set_item("units", units)?;
+            column.set_item("category", category)?;
+            Ok(column)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    let data_rows: Vec<Vec<&String>> = (0..raw.num_rows())
+        .map(|row| raw.data_columns.iter().map(|column| &column[row]).collect())
+        .collect();
     
-    // Parse based on device/config combination
-    let data = match (device, config) {
-        ("6800", "standard") => {
-            let parser = LiCor6800Standard::new();
-            parser.parse_file(file)
+    let result = PyDict::new(py);
+    result.set_item("header", &raw.header)?;
+    result.set_item("columns", columns)?;
+    result.set_item("rows", data_rows)?;
+    Ok(result.into_any().unbind())
+}
+
+/// Parse a file for `scan()`: only the given columns, and with `n_rows` only
+/// that many observations, read from the start of a file at a path
+#[pyfunction]
+#[pyo3(name = "_scan", signature = (file, device, config, columns=None, n_rows=None))]
+fn scan_frame(file: Source, device: &str, config: &str, columns: Option<Vec<String>>, n_rows: Option<usize>) -> PyResult<PyDataFrame> {
+    let file = match (file, n_rows) {
+        (Source::Path(path), Some(rows)) => Source::Head(path, rows.max(1)),
+        (file, _) => file,
+    };
+    let mut df = parse_file_internal(&file, device, config)?.dataframe;
+    if let Some(rows) = n_rows {
+        df = df.head(Some(rows));
+    }
+    if let Some(columns) = columns {
+        if let Some(missing) = columns.iter().find(|name| df.column(name).is_err()) {
+            return Err(to_py_error(ParseError::UnknownVariable { variable: missing.clone() }));
         }
-        ("6800", "fluorometer") => {
-            let parser = LiCor6800Fluorometer::new();
-            parser.parse_file(file)
+        df = df.select(columns).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    }
+    Ok(PyDataFrame(df))
+}
+
+/// Find variables by name, label or description, tolerating typos
+///
+/// Args:
+///     query: Words to look for, e.g. "boundary layer"
+///
+/// Returns:
+///     list of dicts with "internal_name", "label", "units" (None when
+///     unitless), "type", "category" and "description", best match first
+#[pyfunction]
+fn search_variables(py: Python<'_>, query: &str) -> PyResult<Vec<PyObject>> {
+    search_definitions(query).into_iter()
+        .map(|def| {
+            let entry = PyDict::new(py);
+            entry.set_item("internal_name", def.internal_name)?;
+            entry.set_item("label", def.display_label)?;
+            entry.set_item("units", def.units)?;
+            entry.set_item("type", def.data_type.name())?;
+            entry.set_item("category", def.category)?;
+            entry.set_item("description", def.description)?;
+            Ok(entry.into_any().unbind())
+        })
+        .collect()
+}
+
+/// Run the `licor` command-line tool, as the `licor` console script does
+///
+/// Args:
+///     args: Command-line arguments, the first being the program name
+///
+/// The failure is written to stderr before this raises, as the CLI writes it,
+/// and the interpreter keeps running.
+///
+/// Raises:
+///     RuntimeError: The command failed, with the CLI's exit status as `exit_status`
+#[cfg(feature = "cli")]
+#[pyfunction]
+fn cli_main(py: Python<'_>, args: Vec<String>) -> PyResult<()> {
+    let exit = match py.allow_threads(|| licor::run_cli(args)) {
+        Ok(()) => return Ok(()),
+        Err(exit) => exit,
+    };
+    let error = PyRuntimeError::new_err(exit.message);
+    let _ = error.value(py).setattr("exit_status", exit.status);
+    Err(error)
+}
+
+/// Per-variable statistics of a file: count, missing, min, max and mean
+///
+/// Args:
+///     file: LI-COR file: a path, its content as str or bytes, or a file-like
+///         object with .read()
+///     device: Device type ("6800")
+///     config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+///     columns: Only these columns, in this order (all when omitted)
+///
+/// Returns:
+///     list of dicts with "name", "units", "count", "missing", "min", "max"
+///     and "mean" (None for text columns)
+///
+/// Raises:
+///     ValueError: Invalid device/config combination, unknown column or malformed data
+///     IOError: File read errors
+#[pyfunction]
+#[pyo3(signature = (file, device, config, columns=None))]
+fn summarize(py: Python<'_>, file: Source, device: &str, config: &str, columns: Option<Vec<String>>) -> PyResult<Vec<PyObject>> {
+    let data = parse_file_internal(&file, device, config)?;
+    licor_core::summarize(&data, &columns.unwrap_or_default())
+        .map_err(to_py_error)?
+        .into_iter()
+        .map(|v| {
+            let entry = PyDict::new(py);
+            entry.set_item("name", v.name)?;
+            entry.set_item("units", v.units)?;
+            entry.set_item("count", v.count)?;
+            entry.set_item("missing", v.missing)?;
+            entry.set_item("min", v.min)?;
+            entry.set_item("max", v.max)?;
+            entry.set_item("mean", v.mean)?;
+            Ok(entry.into_any().unbind())
+        })
+        .collect()
+}
+
+/// Recompute the gas-exchange results from the logged measurements
+///
+/// Adds `E_rc`, `A_rc`, `gtw_rc`, `gsw_rc`, `gtc_rc`, `Ci_rc` and
+/// `GammaStar` columns, as `licor convert --recompute` does.
+///
+/// Args:
+///     file: LI-COR file: a path, its content as str or bytes, or a file-like
+///         object with .read()
+///     device: Device type ("6800")
+///     config: Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+///     oxygen: Oxygen in the air stream (%), instead of the file's SysConst:Oxygen
+///     gcw: Cuticular conductance to water vapor (mol m-2 s-1) to remove from gsw
+///
+/// Returns:
+///     polars DataFrame
+///
+/// Raises:
+///     ValueError: Invalid device/config combination or malformed data
+///     IOError: File read errors
+#[pyfunction]
+#[pyo3(signature = (file, device, config, oxygen=None, gcw=None))]
+fn recompute(file: Source, device: &str, config: &str, oxygen: Option<f64>, gcw: Option<f64>) -> PyResult<PyDataFrame> {
+    let options = ParseOptions {
+        recompute: Some(licor_core::RecomputeOptions {
+            oxygen,
+            cuticular_conductance: gcw,
+            ..Default::default()
+        }),
+        ..parse_options()
+    };
+    let data = parse_file_with_options(&file, device, config, options)?;
+    Ok(PyDataFrame(data.dataframe))
+}
+
+/// Convert a ParseError to the matching Python exception, with the error's
+/// stable `code` and `number` as attributes
+fn to_py_error(e: ParseError) -> PyErr {
+    let message = e.localized(Language::from_env());
+    let error = match e {
+        ParseError::Io(_) | ParseError::WriteError { .. } => PyIOError::new_err(message),
+        _ => PyValueError::new_err(message),
+    };
+    Python::with_gil(|py| {
+        let value = error.value(py);
+        let _ = value.setattr("code", e.code());
+        let _ = value.setattr("number", e.number());
+    });
+    error
+}
+
+/// Where the data of a file comes from: a path, or content given as `str`
+/// (with line breaks, so it cannot be a path), `bytes` or a file-like object
+enum Source {
+    Path(String),
+    Content(String),
+    /// The first rows of the file at a path
+    Head(String, usize),
+}
+
+impl<'py> FromPyObject<'py> for Source {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(text) = ob.downcast::<PyString>() {
+            let text = text.to_str()?;
+            return Ok(if text.contains('\n') { Source::Content(text.to_string()) } else { Source::Path(text.to_string()) });
         }
-        ("6800", "aquatic") => {
-            let parser = LiCor6800Aquatic::new();
-            parser.parse_file(file)
+        if let Ok(bytes) = ob.downcast::<PyBytes>() {
+            return Source::from_bytes(bytes.as_bytes());
         }
-        ("6800", "soil") => {
-            let parser = LiCor6800Soil::new();
-            parser.parse_file(file)
+        if ob.hasattr("__fspath__")? {
+            let path = ob.py().import("os")?.call_method1("fspath", (ob,))?;
+            return Ok(Source::Path(path.str()?.to_str()?.to_string()));
         }
-        ("6400", _) => {
-            return Err(PyValueError::new_err("LI-6400 support not yet implemented"));
+        if ob.hasattr("read")? {
+            let content = ob.call_method0("read")?;
+            if let Ok(text) = content.downcast::<PyString>() {
+                return Ok(Source::Content(text.to_str()?.to_string()));
+            }
+            if let Ok(bytes) = content.downcast::<PyBytes>() {
+                return Source::from_bytes(bytes.as_bytes());
+            }
+            return Err(PyTypeError::new_err("read() must return str or bytes"));
         }
-        _ => {
-            return Err(PyValueError::new_err(format!(
-                "Invalid device/config combination: device='{}', config='{}'. \
-                 Supported: device='6800'|'6400', config='standard'|'fluorometer'|'aquatic'|'soil'",
-                device, config
-            )));
+        Err(PyTypeError::new_err(format!(
+            "Expected a path, str or bytes content, or a file-like object, not {}", ob.get_type().name()?
+        )))
+    }
+}
+
+impl Source {
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        String::from_utf8(bytes.to_vec())
+            .map(Source::Content)
+            .map_err(|e| PyValueError::new_err(format!("Content is not UTF-8 text: {}", e)))
+    }
+    
+    fn parse<D: LiCorDevice, C: LiCorConfig>(&self, parser: LiCorParser<D, C>) -> Result<LiCorData, ParseError> {
+        match self {
+            Source::Path(path) => parser.parse_file(path),
+            Source::Content(content) => parser.parse_content(content),
+            Source::Head(path, rows) => parser.parse_raw(licor_core::peek(path, *rows)?),
         }
+    }
+}
+
+static CACHE: Mutex<ParseCache> = Mutex::new(ParseCache::new(0));
+
+/// Keep the last parsed files in memory
+///
+/// Functions given a file by path return the cached parse while the file is
+/// unchanged on disk (same modification time and size), so a notebook or app
+/// redrawing a plot does not parse a large file each time. Files are not
+/// cached while parse hooks are registered.
+///
+/// Args:
+///     entries: Number of files to keep (0, the default, turns caching off)
+#[pyfunction]
+fn set_cache_size(entries: usize) {
+    CACHE.lock().expect("parse cache poisoned").set_capacity(entries);
+}
+
+/// Drop every cached file
+#[pyfunction]
+fn clear_cache() {
+    CACHE.lock().expect("parse cache poisoned").clear();
+}
+
+/// Internal function to parse a file with device/config validation
+fn parse_file_internal(file: &Source, device: &str, config: &str) -> PyResult<LiCorData> {
+    let options = parse_options();
+    let key = match file {
+        Source::Path(path) if options.hooks.is_empty() && CACHE.lock().expect("parse cache poisoned").capacity() > 0 => {
+            CacheKey::new(path, format!("{}/{}", device, config)).ok()
+        }
+        _ => None,
+    };
+    let Some(key) = key else {
+        return parse_file_with_options(file, device, config, options);
     };
+    if let Some(data) = CACHE.lock().expect("parse cache poisoned").get(&key) {
+        return Ok(data);
+    }
+    // Parsed without the lock, so other threads can read the cache meanwhile
+    let data = parse_file_with_options(file, device, config, options)?;
+    CACHE.lock().expect("parse cache poisoned").insert(key, data.clone());
+    Ok(data)
+}
+
+/// Default options, with the hooks registered from Python
+fn parse_options() -> ParseOptions {
+    ParseOptions {
+        #[cfg(feature = "hooks")]
+        hooks: hooks::registered(),
+        ..ParseOptions::default()
+    }
+}
+
+fn parse_file_with_options(file: &Source, device: &str, config: &str, options: ParseOptions) -> PyResult<LiCorData> {
+    // Validate file exists
+    if let Source::Path(path) | Source::Head(path, _) = file {
+        if !Path::new(path).exists() {
+            return Err(PyIOError::new_err(Message::FileNotFound { path }.text(Language::from_env())));
+        }
+    }
+    
+    let parse = parser_for(device, config)?;
     
     // Convert ParseError to appropriate Python exception
-    data.map_err(|e| match e {
-        ParseError::Io(io_err) => PyIOError::new_err(format!("IO error: {}", io_err)),
-        ParseError::InvalidFileFormat { device } => PyValueError::new_err(format!("Invalid file format for device: {}", device)),
-        ParseError::MissingRequiredHeader { field } => PyValueError::new_err(format!("Missing required header field: {}", field)),
-        ParseError::MissingRequiredVariable { variable, config } => PyValueError::new_err(format!("Missing required variable '{}' for config '{}'", variable, config)),
-        ParseError::UnknownVariable { variable } => PyValueError::new_err(format!("Unknown variable: {}", variable)),
-        ParseError::MalformedDataSection { expected, found } => PyValueError::new_err(format!("Malformed data section: expected {} columns, found {}", expected, found)),
-        ParseError::DataTypeError { value, expected_type, variable } => PyValueError::new_err(format!("Data type error in variable '{}': cannot convert '{}' to {}", variable, value, expected_type)),
-        ParseError::InvalidHeaderFormat { message } => PyValueError::new_err(format!("Invalid header format: {}", message)),
-        ParseError::EmptyDataSection => PyValueError::new_err("Empty data section"),
-        ParseError::TomlParse(e) => PyValueError::new_err(format!("TOML parsing error: {}", e)),
-    })
+    parse(file, options).map_err(to_py_error)
+}
+
+/// Parses a file with the parser of a device/config combination
+type ParseFn = fn(&Source, ParseOptions) -> Result<LiCorData, ParseError>;
+
+fn parser_for(device: &str, config: &str) -> PyResult<ParseFn> {
+    match (device, config) {
+        ("6800", "standard") => Ok(|file, options| file.parse(LiCor6800Standard::with_options(options))),
+        ("6800", "fluorometer") => Ok(|file, options| file.parse(LiCor6800Fluorometer::with_options(options))),
+        ("6800", "aquatic") => Ok(|file, options| file.parse(LiCor6800Aquatic::with_options(options))),
+        ("6800", "soil") => Ok(|file, options| file.parse(LiCor6800Soil::with_options(options))),
+        _ => Err(PyValueError::new_err(format!(
+            "Invalid device/config combination: device='{}', config='{}'. \
+             Supported: device='6800', config='standard'|'fluorometer'|'aquatic'|'soil'",
+            device, config
+        ))),
+    }
 }
 
 /// Python module definition
 #[pymodule]
 fn licor_client(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<LicorFile>()?;
+    m.add_class::<ArrowTable>()?;
     m.add_function(wrap_pyfunction!(convert, m)?)?;
     m.add_function(wrap_pyfunction!(file_to_dataframe, m)?)?;
+    m.add_function(wrap_pyfunction!(convert_many, m)?)?;
+    m.add_function(wrap_pyfunction!(open_dataset, m)?)?;
+    m.add_function(wrap_pyfunction!(read_metadata, m)?)?;
+    m.add_function(wrap_pyfunction!(variable_info, m)?)?;
+    m.add_function(wrap_pyfunction!(peek, m)?)?;
+    m.add_function(wrap_pyfunction!(scan_frame, m)?)?;
+    m.add_function(wrap_pyfunction!(search_variables, m)?)?;
+    m.add_function(wrap_pyfunction!(set_cache_size, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(summarize, m)?)?;
+    m.add_function(wrap_pyfunction!(recompute, m)?)?;
+    #[cfg(feature = "cli")]
+    m.add_function(wrap_pyfunction!(cli_main, m)?)?;
+    #[cfg(feature = "hooks")]
+    {
+        m.add_function(wrap_pyfunction!(hooks::register_hook, m)?)?;
+        m.add_function(wrap_pyfunction!(hooks::clear_hooks, m)?)?;
+    }
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,50 @@
+"""Cross-binding conformance tests.
+
+Runs the shared cases in ``conformance/cases`` (also checked by the Rust core,
+the CLI and the R package) through the Python bindings.
+"""
+
+import json
+import math
+from pathlib import Path
+
+import pytest
+
+try:
+    import licor_client
+except ImportError:
+    pytest.skip("licor_client not built, run 'maturin develop' first", allow_module_level=True)
+
+polars = pytest.importorskip("polars")
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+CASES = sorted((REPO_ROOT / "conformance" / "cases").glob("*.json"))
+
+# Relative tolerance for comparing column means
+TOLERANCE = 1e-9
+
+
+@pytest.mark.parametrize("case_path", CASES, ids=lambda p: p.stem)
+def test_conformance(case_path):
+    case = json.loads(case_path.read_text())
+
+    df = licor_client.file_to_dataframe(
+        file=str(REPO_ROOT / case["fixture"]),
+        format="polars",
+        device=case["device"],
+        config=case["config"],
+    )
+
+    assert df.height == case["rows"]
+    assert df.width == case["columns"]
+
+    for name, expected in case["column_summaries"].items():
+        values = df[name].cast(polars.Float64)
+        assert values.len() - values.null_count() == expected["non_null"], name
+        actual_mean = values.mean()
+        if expected["mean"] is None:
+            assert actual_mean is None, name
+        else:
+            assert math.isclose(
+                actual_mean, expected["mean"], rel_tol=TOLERANCE, abs_tol=TOLERANCE
+            ), name
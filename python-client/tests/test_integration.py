@@ -3,6 +3,8 @@
 import pytest
 import tempfile
 import os
+import subprocess
+import sys
 from pathlib import Path
 
 # Import the module - this will fail if not built with maturin
@@ -32,7 +34,8 @@ class TestConvert:
                     file=sample_file,
                     output=tmp.name,
                     device="6800",
-                    config="fluorometer"
+                    config="fluorometer",
+                    overwrite=True  # The temporary file already exists
                 )
                 
                 # Verify file was created and has content
@@ -41,6 +44,35 @@ class TestConvert:
             finally:
                 os.unlink(tmp.name)
     
+    def test_convert_options(self):
+        """Test the writer options: layout, partitioning, metadata columns and overwrite."""
+        pl = pytest.importorskip("polars")
+        
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        with tempfile.TemporaryDirectory() as directory:
+            wide = str(Path(directory) / "wide.parquet")
+            assert licor_client.convert(sample_file, wide, "6800", "fluorometer", compression="snappy", metadata_columns=True) == [wide]
+            assert pl.read_parquet(wide)["device_serial"][0] == "68C-901292"
+            with pytest.raises(FileExistsError):
+                licor_client.convert(sample_file, wide, "6800", "fluorometer")
+            licor_client.convert(sample_file, wide, "6800", "fluorometer", overwrite=True)
+            
+            long = str(Path(directory) / "long.parquet")
+            licor_client.convert(sample_file, long, "6800", "fluorometer", layout="long")
+            assert "variable" in pl.read_parquet(long).columns
+            
+            dataset = str(Path(directory) / "dataset")
+            paths = licor_client.convert(sample_file, dataset, "6800", "fluorometer", partition="date_device")
+            assert all("date=" in path and "device=" in path for path in paths)
+            with pytest.raises(FileExistsError):
+                licor_client.convert(sample_file, dataset, "6800", "fluorometer", partition="date", overwrite=False)
+            
+            with pytest.raises(ValueError, match="layout"):
+                licor_client.convert(sample_file, wide, "6800", "fluorometer", layout="diagonal")
+    
     def test_convert_invalid_device(self):
         """Test error handling for invalid device."""
         sample_file = SAMPLE_FILES[0]
@@ -118,6 +150,37 @@ class TestFileToDataFrame:
         # Check for expected columns
         assert "obs" in df.columns
     
+    def test_xarray_dataset(self):
+        """Test converting to an xarray Dataset along time, with attrs."""
+        pytest.importorskip("xarray")
+        
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        ds = licor_client.file_to_dataframe(sample_file, "xarray", "6800", "fluorometer")
+        assert list(ds.dims) == ["time"]
+        assert str(ds["time"].dtype).startswith("datetime64")
+        assert ds.sizes["time"] == len(licor_client.LicorFile(sample_file, "6800", "fluorometer"))
+        assert ds["Flow_s"].attrs["units"] == "umol s-1"
+        assert ds["Flow_s"].attrs["long_name"]
+        assert ds.attrs["instrument_serial"] == "68C-901292"
+    
+    def test_numpy_array(self):
+        """Test converting to a structured array, typed per column."""
+        np = pytest.importorskip("numpy")
+        
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        array = licor_client.file_to_dataframe(sample_file, "numpy", "6800", "fluorometer")
+        assert len(array) == len(licor_client.LicorFile(sample_file, "6800", "fluorometer"))
+        assert array.dtype["obs"] == np.int64
+        assert array.dtype["A"] == np.float64
+        assert str(array.dtype["time"]) == "datetime64[us]"
+        assert array["obs"][0] == 1
+    
     def test_missing_polars_dependency(self):
         """Test error when polars is not installed."""
         # This test is tricky because polars might be installed
@@ -149,6 +212,240 @@ class TestFileToDataFrame:
                 config="fluorometer"
             )
 
+class TestScan:
+    """Test the scan() function."""
+    
+    def test_scan_lazy_frame(self):
+        """Test that projections, filters and limits match the eager frame."""
+        pl = pytest.importorskip("polars")
+        pytest.importorskip("polars.io.plugins")
+        sample_file = SAMPLE_FILES[0]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        eager = licor_client.file_to_dataframe(sample_file, "polars", "6800", "fluorometer")
+        lazy = licor_client.scan(sample_file, "6800", "fluorometer")
+        assert isinstance(lazy, pl.LazyFrame)
+        assert lazy.collect_schema().names() == eager.columns
+        
+        query = lazy.filter(pl.col("A") > eager["A"].median()).select(["obs", "A"])
+        expected = eager.filter(pl.col("A") > eager["A"].median()).select(["obs", "A"])
+        assert query.collect().equals(expected)
+        assert lazy.head(3).collect()["obs"].to_list() == eager["obs"].head(3).to_list()
+        
+        with open(sample_file, "rb") as handle:
+            assert licor_client.scan(handle, "6800", "fluorometer").select("A").collect().height == eager.height
+
+    def test_scan_late_string_value(self):
+        """Test that a non-numeric value in the last row types the column as the eager frame does."""
+        pl = pytest.importorskip("polars")
+        pytest.importorskip("polars.io.plugins")
+        sample_file = SAMPLE_FILES[0]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+
+        lines = Path(sample_file).read_text().splitlines()
+        header = next(line.split("\t") for line in lines if line.startswith("obs\t"))
+        last = lines[-1].split("\t")
+        last[header.index("A")] = "#DIV/0!"
+        content = "\n".join(lines[:-1] + ["\t".join(last)]) + "\n"
+
+        eager = licor_client.file_to_dataframe(content, "polars", "6800", "fluorometer")
+        lazy = licor_client.scan(content, "6800", "fluorometer")
+        assert lazy.collect_schema()["A"] == eager.schema["A"] == pl.String
+        assert lazy.select("A").collect().equals(eager.select("A"))
+        assert lazy.head(2).collect()["A"].to_list() == eager["A"].head(2).to_list()
+
+class TestConvertMany:
+    """Test the convert_many() function."""
+    
+    def test_convert_many(self):
+        """Test that every file gets a status, in input order."""
+        files = [f for f in SAMPLE_FILES if Path(f).exists()]
+        if not files:
+            pytest.skip("Sample files not found")
+        
+        with tempfile.TemporaryDirectory() as output_dir:
+            results = licor_client.convert_many(files + ["missing.txt"], output_dir, "6800", "fluorometer", workers=2)
+            assert [r["file"] for r in results] == files + ["missing.txt"]
+            for result in results[:-1]:
+                assert result["status"] == "converted"
+                assert Path(result["output"]).stat().st_size > 0
+            assert results[-1]["status"] == "failed"
+            assert results[-1]["code"] == "io"
+
+class TestOpenDataset:
+    """Test the open_dataset() function."""
+    
+    def test_open_dataset(self):
+        """Test that every log under a directory lands in one frame, with a manifest."""
+        files = [f for f in SAMPLE_FILES if Path(f).exists()]
+        if len(files) < 2:
+            pytest.skip("Sample files not found")
+        
+        with tempfile.TemporaryDirectory() as directory:
+            for i, file in enumerate(files):
+                (Path(directory) / f"site{i}").mkdir()
+                (Path(directory) / f"site{i}" / Path(file).name).write_bytes(Path(file).read_bytes())
+            (Path(directory) / "notes.txt").write_text("not a log\n")
+            
+            df, manifest = licor_client.open_dataset(directory, "6800", workers=2)
+            assert [Path(m["file"]).parent.name for m in manifest] == ["site0", "site1"]
+            assert all(m["status"] == "parsed" for m in manifest)
+            assert df.columns[0] == "file"
+            assert df.height == sum(m["rows"] for m in manifest)
+            assert df["file"].n_unique() == 2
+            
+            with pytest.raises(IOError):
+                licor_client.open_dataset(str(Path(directory) / "site0" / "missing"), "6800")
+
+class TestCache:
+    """Test the parse cache."""
+    
+    def test_cache(self):
+        """Test that a cached file is parsed again once it changes."""
+        files = [f for f in SAMPLE_FILES if Path(f).exists()]
+        if len(files) < 2:
+            pytest.skip("Sample files not found")
+        
+        licor_client.set_cache_size(4)
+        try:
+            with tempfile.TemporaryDirectory() as directory:
+                path = Path(directory) / "log"
+                path.write_bytes(Path(files[0]).read_bytes())
+                first = licor_client.read_metadata(str(path), "6800", "fluorometer")
+                assert licor_client.read_metadata(str(path), "6800", "fluorometer") == first
+                
+                path.write_bytes(Path(files[1]).read_bytes())
+                changed = licor_client.read_metadata(str(path), "6800", "fluorometer")
+                assert changed["content_hash"] != first["content_hash"]
+        finally:
+            licor_client.clear_cache()
+            licor_client.set_cache_size(0)
+
+class TestReadMetadata:
+    """Test the read_metadata() function."""
+    
+    def test_instrument_metadata(self):
+        """Test that serials and versions come back as a dict."""
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        metadata = licor_client.read_metadata(sample_file, "6800", "fluorometer")
+        assert metadata["head_serial"] == "68H-581292"
+        assert metadata["device_serial"]
+        assert isinstance(metadata["console_messages"], list)
+        assert len(metadata["content_hash"]) == 64
+    
+    def test_variable_info(self):
+        """Test that every column is described, in column order."""
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        info = {v["name"]: v for v in licor_client.variable_info(sample_file, "6800", "fluorometer")}
+        assert info["A"]["units"] == "µmol m-2 s-1"
+        assert info["A"]["dtype"] == "float"
+        assert info["A"]["description"]
+
+class TestLicorFile:
+    """Test the LicorFile class."""
+    
+    def test_licor_file(self):
+        """Test that one parse serves the metadata, columns and data."""
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        licor = licor_client.LicorFile(sample_file, "6800", "fluorometer")
+        assert licor.metadata["head_serial"] == "68H-581292"
+        assert len(licor.variables) > 0
+        assert licor.remarks == []
+        
+        pytest.importorskip("polars")
+        df = licor.to_polars()
+        assert df.height == len(licor)
+        assert df.width == len(licor.variables)
+        
+        with tempfile.NamedTemporaryFile(suffix=".parquet", delete=False) as tmp:
+            try:
+                licor.to_parquet(tmp.name)
+                assert Path(tmp.name).stat().st_size > 0
+            finally:
+                os.unlink(tmp.name)
+    
+    def test_arrow_c_stream(self):
+        """Test that Arrow consumers read the data through the PyCapsule interface."""
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        licor = licor_client.LicorFile(sample_file, "6800", "fluorometer")
+        pa = pytest.importorskip("pyarrow")
+        table = pa.table(licor)
+        assert table.num_rows == len(licor)
+        assert table.column_names == [v["name"] for v in licor.variables]
+        
+        pl = pytest.importorskip("polars")
+        assert pl.DataFrame(table).equals(licor.to_polars())
+    
+    def test_arrow_requested_schema(self):
+        """Test that a requested schema selects and casts columns, or raises."""
+        pa = pytest.importorskip("pyarrow")
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        for data in (licor_client.LicorFile(sample_file, "6800", "fluorometer"),
+                     licor_client.file_to_dataframe(sample_file, "arrow", "6800", "fluorometer")):
+            schema = pa.schema([("A", pa.float32()), ("obs", pa.int64()), ("hhmmss", pa.large_string())])
+            table = pa.RecordBatchReader.from_stream(data, schema=schema).read_all()
+            assert table.schema == schema
+            assert table.num_rows == len(data)
+            with pytest.raises(ValueError, match="not in the data"):
+                pa.RecordBatchReader.from_stream(data, schema=pa.schema([("nope", pa.float64())]))
+            with pytest.raises(ValueError, match="Cannot cast"):
+                pa.RecordBatchReader.from_stream(data, schema=pa.schema([("hhmmss", pa.float64())]))
+    
+    def test_arrow_table(self):
+        """Test the table file_to_dataframe returns for format="arrow"."""
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        licor = licor_client.LicorFile(sample_file, "6800", "fluorometer")
+        table = licor_client.file_to_dataframe(sample_file, "arrow", "6800", "fluorometer")
+        assert isinstance(table, licor_client.ArrowTable)
+        assert len(table) == len(licor)
+        assert table.columns == [v["name"] for v in licor.variables]
+        pa = pytest.importorskip("pyarrow")
+        assert pa.table(table).equals(pa.table(licor))
+
+class TestContentSources:
+    """Test parsing content instead of a path."""
+    
+    def test_str_bytes_and_file_like(self):
+        """Test that content, bytes and file-like objects parse like the path."""
+        import io
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        content = Path(sample_file).read_bytes()
+        expected = licor_client.read_metadata(sample_file, "6800", "fluorometer")
+        for source in [content, content.decode(), io.BytesIO(content), io.StringIO(content.decode()), Path(sample_file)]:
+            assert licor_client.read_metadata(source, "6800", "fluorometer") == expected
+        
+        licor = licor_client.LicorFile(io.BytesIO(content), "6800", "fluorometer")
+        assert licor.path is None
+        assert len(licor) == len(licor_client.LicorFile(sample_file, "6800", "fluorometer"))
+        
+        with pytest.raises(TypeError):
+            licor_client.read_metadata(42, "6800", "fluorometer")
+        with pytest.raises(ValueError, match="UTF-8"):
+            licor_client.read_metadata(b"\xff\xfe\x00", "6800", "fluorometer")
+
 class TestMultipleFiles:
     """Test with multiple sample files."""
     
@@ -165,9 +462,139 @@ class TestMultipleFiles:
                         file=sample_file,
                         output=tmp.name,
                         device="6800",
-                        config="fluorometer"
+                        config="fluorometer",
+                        overwrite=True
                     )
                     assert Path(tmp.name).exists()
                     assert Path(tmp.name).stat().st_size > 0
                 finally:
-                    os.unlink(tmp.name)
\ No newline at end of file
+                    os.unlink(tmp.name)
+
+class TestTypeStubs:
+    """Test that the type stubs match the compiled module."""
+    
+    @staticmethod
+    def stub_definitions(name):
+        """Functions and class methods defined in a stub file, with their parameters."""
+        import ast
+        tree = ast.parse((Path(licor_client.__file__).parent / name).read_text())
+        parameters = lambda node: [a.arg for a in node.args.posonlyargs + node.args.args + node.args.kwonlyargs]
+        definitions = {}
+        for node in tree.body:
+            if isinstance(node, ast.FunctionDef):
+                definitions[node.name] = parameters(node)
+            elif isinstance(node, ast.ClassDef):
+                definitions[node.name] = None
+                for method in node.body:
+                    if isinstance(method, ast.FunctionDef):
+                        definitions[f"{node.name}.{method.name}"] = parameters(method)[1:]
+        return definitions
+    
+    def test_public_names_are_typed(self):
+        """Test that every exported name has a stub with the module's parameters."""
+        import inspect
+        stubs = self.stub_definitions("__init__.pyi")
+        for name in licor_client.__all__:
+            assert name in stubs, f"{name} has no type stub"
+            obj = getattr(licor_client, name)
+            if inspect.isbuiltin(obj):
+                assert list(inspect.signature(obj).parameters) == stubs[name], name
+        for cls in (licor_client.LicorFile, licor_client.ArrowTable):
+            for name in vars(cls):
+                if not name.startswith("_") or name in ("__len__", "__arrow_c_stream__"):
+                    assert f"{cls.__name__}.{name}" in stubs, f"{cls.__name__}.{name} has no type stub"
+    
+    def test_native_functions_are_typed(self):
+        """Test that every function of the compiled module has a stub with its parameters."""
+        import inspect
+        stubs = {**self.stub_definitions("__init__.pyi"), **self.stub_definitions("licor_client.pyi")}
+        native = licor_client.licor_client
+        functions = [name for name, obj in vars(native).items() if inspect.isbuiltin(obj)]
+        assert "_scan" in functions
+        for name in functions:
+            assert name in stubs, f"{name} has no type stub"
+            assert list(inspect.signature(getattr(native, name)).parameters) == stubs[name], name
+
+
+class TestBundledExtras:
+    """Test the CLI and analysis functions bundled into the wheel."""
+    
+    def test_error_codes(self):
+        """Test that library errors carry their stable code and number."""
+        with pytest.raises(ValueError) as raised:
+            licor_client.peek(str(Path(__file__)), 5)
+        assert raised.value.code == "invalid_header_format"
+        assert raised.value.number == 16
+    
+    def test_summarize_and_recompute(self):
+        """Test the analysis functions."""
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        summary = licor_client.summarize(sample_file, "6800", "fluorometer", columns=["A"])
+        assert summary[0]["name"] == "A"
+        assert summary[0]["min"] <= summary[0]["mean"] <= summary[0]["max"]
+        
+        pytest.importorskip("polars")
+        df = licor_client.recompute(sample_file, "6800", "fluorometer", oxygen=2.0)
+        assert "A_rc" in df.columns and "GammaStar" in df.columns
+    
+    def test_parse_hooks(self):
+        """Test that registered hooks transform every parse."""
+        if not hasattr(licor_client, "register_hook"):
+            pytest.skip("licor_client built without the hooks feature")
+        pl = pytest.importorskip("polars")
+        sample_file = SAMPLE_FILES[1]
+        if not Path(sample_file).exists():
+            pytest.skip(f"Sample file not found: {sample_file}")
+        
+        def tag_operator(table):
+            table["header"]["Operator"] = "RL"
+        
+        licor_client.register_hook("after_raw", tag_operator)
+        licor_client.register_hook("after_typing", lambda df: df.drop("Fan"))
+        licor_client.register_hook("after_parse", lambda df: df.with_columns(pl.lit("site_a").alias("site")))
+        try:
+            licor = licor_client.LicorFile(sample_file, "6800", "fluorometer")
+            df = licor.to_polars()
+            assert "Fan" not in df.columns
+            assert df["site"][0] == "site_a"
+            assert [v["name"] for v in licor.variables] == df.columns
+            
+            licor_client.register_hook("after_typing", lambda df: 1 / 0)
+            with pytest.raises(ValueError, match="ZeroDivisionError"):
+                licor_client.read_metadata(sample_file, "6800", "fluorometer")
+            with pytest.raises(ValueError):
+                licor_client.register_hook("before_write", print)
+        finally:
+            licor_client.clear_hooks()
+        assert "Fan" in licor_client.file_to_dataframe(sample_file, "polars", "6800", "fluorometer").columns
+    
+    def test_cli_entry_point(self):
+        """Test that the console script runs the bundled CLI."""
+        if not hasattr(licor_client.licor_client, "cli_main"):
+            pytest.skip("licor_client built without the cli feature")
+        
+        # The console script exits the process, so it runs in its own
+        result = subprocess.run(
+            [sys.executable, "-m", "licor_client.cli", "schema", "--device", "6800", "--config", "standard"],
+            capture_output=True, text=True,
+        )
+        assert result.returncode == 0
+        assert '"name": "A"' in result.stdout
+    
+    def test_cli_main_in_process(self):
+        """Test that cli_main raises instead of exiting, honoring each call's options."""
+        if not hasattr(licor_client.licor_client, "cli_main"):
+            pytest.skip("licor_client built without the cli feature")
+        
+        with pytest.raises(RuntimeError) as excinfo:
+            licor_client.licor_client.cli_main(["licor", "convert", "--no-such-flag"])
+        assert excinfo.value.exit_status == 2
+        
+        args = ["licor", "validate", "--device", "6800", "--config", "standard", "--input", "/nonexistent/*.txt"]
+        with pytest.raises(RuntimeError, match="No files found"):
+            licor_client.licor_client.cli_main([*args, "--lang", "en"])
+        with pytest.raises(RuntimeError, match="Ningún archivo"):
+            licor_client.licor_client.cli_main([*args, "--lang", "es"])
@@ -1,10 +1,13 @@
 use extendr_api::prelude::*;
 use licor_core::{
     LiCor6800Standard, LiCor6800Fluorometer, LiCor6800Aquatic, LiCor6800Soil,
-    ParseError, LiCorData
+    CacheKey, LiCorData, Language, Message, ParseCache, ParseError, VariableInfo, search_variables as search_definitions
 };
 use polars::prelude::*;
 use std::path::Path;
+use std::sync::Mutex;
+
+static CACHE: Mutex<ParseCache> = Mutex::new(ParseCache::new(0));
 
 /// Convert a LI-COR file to Parquet format
 /// 
@@ -35,7 +38,9 @@ fn convert(file: &str, output: &str, device: &str, config: &str) -> Result<()> {
 /// @param device Device type ("6800" or "6400") 
 /// @param config Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
 /// @param preserve_names Whether to preserve original LI-COR variable names (TRUE) or convert to R-friendly names (FALSE)
-/// @return data.frame or tibble with the converted data
+/// @return data.frame or tibble with the converted data. Columns carry
+///   "label" and "units" attributes, and the data.frame a named
+///   "variable.labels" vector
 /// @export
 #[extendr]
 fn file_to_dataframe(
@@ -48,7 +53,7 @@ fn file_to_dataframe(
     let data = parse_file_internal(file, device, config)?;
     
     // Convert polars DataFrame to R data.frame
-    let r_df = polars_to_r_dataframe(data.dataframe, preserve_names)?;
+    let r_df = polars_to_r_dataframe(data.dataframe, &data.variable_info, preserve_names)?;
     
     match format {
         "data.frame" => {
@@ -69,13 +74,144 @@ fn file_to_dataframe(
     }
 }
 
-/// Internal function to parse a file with device/config validation
+/// Read a file's header, columns and first observations without parsing it all
+///
+/// Only the start of the file is read, so this is fast enough for file pickers.
+///
+/// @param file Path to the input LI-COR file
+/// @param rows Number of observations to return
+/// @return list with `header` (named list of header values), `columns` (list of
+///   `name`, `units` and `category` vectors) and `data` (named list of the
+///   unconverted cells of each column)
+/// @export
+#[extendr]
+fn peek(file: &str, rows: i32) -> Result<Robj> {
+    if !Path::new(file).exists() {
+        return Err(Error::Other(Message::FileNotFound { path: file }.text(Language::from_env())));
+    }
+    let raw = licor_core::peek(file, rows.max(0) as usize)
+        .map_err(r_error)?;
+    
+    let mut header: Vec<(&String, &String)> = raw.header.iter().collect();
+    header.sort();
+    let header = List::from_names_and_values(
+        header.iter().map(|(key, _)| key.as_str()),
+        header.iter().map(|(_, value)| Robj::from(value.as_str())),
+    )?;
+    let columns = List::from_names_and_values(
+        ["name", "units", "category"],
+        [
+            Robj::from(raw.column_names.clone()),
+            Robj::from(raw.units.clone()),
+            Robj::from(raw.column_categories.clone()),
+        ],
+    )?;
+    let data = List::from_names_and_values(
+        raw.column_names.iter().map(String::as_str),
+        raw.data_columns.iter().map(|column| Robj::from(column.clone())),
+    )?;
+    
+    Ok(List::from_names_and_values(
+        ["header", "columns", "data"],
+        [Robj::from(header), Robj::from(columns), Robj::from(data)],
+    )?.into())
+}
+
+/// Read a file's instrument metadata
+///
+/// @param file Path to the input LI-COR file
+/// @param device Device type ("6800")
+/// @param config Measurement configuration ("standard", "fluorometer", "aquatic", "soil")
+/// @return named list of `device_serial`, `console_version` (firmware),
+///   `head_serial`, `head_version`, `chamber_type`, `chamber_serial`,
+///   `fluorometer_serial`, `calibration_date` and `content_hash`, NA when
+///   not logged
+/// @export
+#[extendr]
+fn licor_metadata(file: &str, device: &str, config: &str) -> Result<Robj> {
+    let data = parse_file_internal(file, device, config)?;
+    let metadata = &data.metadata;
+    let text = |value: Option<&str>| -> Robj { Strings::from_values([Rstr::from(value.map(str::to_string))]).into() };
+    let fields = [
+        "device_serial", "console_version", "head_serial", "head_version",
+        "chamber_type", "chamber_serial", "fluorometer_serial", "calibration_date",
+    ];
+    let values = fields.iter()
+        .map(|name| text(metadata.field(name)))
+        .chain([text(metadata.content_hash.as_deref())]);
+    Ok(List::from_names_and_values(fields.into_iter().chain(["content_hash"]), values)?.into())
+}
+
+/// Find variables by name, label or description, tolerating typos
+///
+/// @param query Words to look for, e.g. "boundary layer"
+/// @return list of `internal_name`, `label`, `units`, `type`, `category` and
+///   `description` vectors, best match first
+/// @export
+#[extendr]
+fn search_variables(query: &str) -> Result<Robj> {
+    let matches = search_definitions(query);
+    let field = |get: fn(&licor_core::VariableDef) -> &str| {
+        Robj::from(matches.iter().map(|def| get(def)).collect::<Vec<_>>())
+    };
+    Ok(List::from_names_and_values(
+        ["internal_name", "label", "units", "type", "category", "description"],
+        [
+            field(|def| def.internal_name),
+            field(|def| def.display_label),
+            field(|def| def.units.unwrap_or("")),
+            field(|def| def.data_type.name()),
+            field(|def| def.category),
+            field(|def| def.description),
+        ],
+    )?.into())
+}
+
+/// Keep the last parsed files in memory
+///
+/// Functions return the cached parse of a file while it is unchanged on disk
+/// (same modification time and size), so a Shiny app redrawing a plot does
+/// not parse a large file each time.
+///
+/// @param entries Number of files to keep (0, the default, turns caching off)
+/// @export
+#[extendr]
+fn set_cache_size(entries: i32) {
+    CACHE.lock().expect("parse cache poisoned").set_capacity(entries.max(0) as usize);
+}
+
+/// Drop every cached file
+///
+/// @export
+#[extendr]
+fn clear_cache() {
+    CACHE.lock().expect("parse cache poisoned").clear();
+}
+
+/// Internal function to parse a file with device/config validation, through
+/// the cache when it is on
 fn parse_file_internal(file: &str, device: &str, config: &str) -> Result<LiCorData> {
     // Validate file exists
     if !Path::new(file).exists() {
-        return Err(Error::Other(format!("File not found: {}", file)));
+        return Err(Error::Other(Message::FileNotFound { path: file }.text(Language::from_env())));
     }
     
+    let key = if CACHE.lock().expect("parse cache poisoned").capacity() > 0 {
+        CacheKey::new(file, format!("{}/{}", device, config)).ok()
+    } else {
+        None
+    };
+    if let Some(data) = key.as_ref().and_then(|key| CACHE.lock().expect("parse cache poisoned").get(key)) {
+        return Ok(data);
+    }
+    let data = parse_uncached(file, device, config)?;
+    if let Some(key) = key {
+        CACHE.lock().expect("parse cache poisoned").insert(key, data.clone());
+    }
+    Ok(data)
+}
+
+fn parse_uncached(file: &str, device: &str, config: &str) -> Result<LiCorData> {
     // Parse based on device/config combination
     let data = match (device, config) {
         ("6800", "standard") => {
@@ -107,26 +243,24 @@ fn parse_file_internal(file: &str, device: &str, config: &str) -> Result<LiCorDa
     };
     
     // Convert ParseError to R Error
-    data.map_err(|e| match e {
-        ParseError::Io(io_err) => Error::Other(format!("IO error: {}", io_err)),
-        ParseError::InvalidFileFormat { device } => Error::Other(format!("Invalid file format for device: {}", device)),
-        ParseError::MissingRequiredHeader { field } => Error::Other(format!("Missing required header field: {}", field)),
-        ParseError::MissingRequiredVariable { variable, config } => Error::Other(format!("Missing required variable '{}' for config '{}'", variable, config)),
-        ParseError::UnknownVariable { variable } => Error::Other(format!("Unknown variable: {}", variable)),
-        ParseError::MalformedDataSection { expected, found } => Error::Other(format!("Malformed data section: expected {} columns, found {}", expected, found)),
-        ParseError::DataTypeError { value, expected_type, variable } => Error::Other(format!("Data type error in variable '{}': cannot convert '{}' to {}", variable, value, expected_type)),
-        ParseError::InvalidHeaderFormat { message } => Error::Other(format!("Invalid header format: {}", message)),
-        ParseError::EmptyDataSection => Error::Other("Empty data section".to_string()),
-        ParseError::TomlParse(e) => Error::Other(format!("TOML parsing error: {}", e)),
-    })
+    data.map_err(r_error)
+}
+
+/// R error for a ParseError, its message prefixed with the error's stable code
+/// (e.g. `[missing_required_variable] ...`) so scripts can match on it
+fn r_error(e: ParseError) -> Error {
+    Error::Other(format!("[{}] {}", e.code(), e.localized(Language::from_env())))
 }
 
 /// Convert polars DataFrame to R data.frame with optional name cleaning
-fn polars_to_r_dataframe(df: DataFrame, preserve_names: bool) -> Result<Robj> {
-    // For now, let's simplify and just convert to a basic structure
-    // This will need refinement but should compile
-    let mut list_data = Vec::new();
+///
+/// Each column gets a "label" attribute (its display label) and a "units"
+/// attribute when it has units, as Hmisc and ggplot2 helpers read them, and
+/// the data.frame a "variable.labels" vector of the labels by column name.
+fn polars_to_r_dataframe(df: DataFrame, variable_info: &[VariableInfo], preserve_names: bool) -> Result<Robj> {
     let mut names_vec = Vec::new();
+    let mut r_values = Vec::new();
+    let mut labels = Vec::new();
     
     for column in df.get_columns() {
         let name = if preserve_names {
@@ -134,26 +268,59 @@ fn polars_to_r_dataframe(df: DataFrame, preserve_names: bool) -> Result<Robj> {
         } else {
             clean_name_for_r(column.name())
         };
+        let mut r_column = column_to_r(column)?;
+        let info = variable_info.iter().find(|v| v.internal_name == column.name().as_str());
+        let label = info.map_or_else(|| column.name().to_string(), |info| info.display_label.clone());
+        r_column.set_attrib("label", label.as_str())?;
+        if let Some(units) = info.and_then(|info| info.units_ascii.as_ref().or(info.units.as_ref())) {
+            r_column.set_attrib("units", units.as_str())?;
+        }
         names_vec.push(name);
-        
-        // Convert all columns to string for now to ensure it works
-        let string_values: Vec<String> = (0..column.len())
-            .map(|i| {
-                column.get(i).map(|av| format!("{}", av)).unwrap_or_else(|_| "NA".to_string())
-            })
-            .collect();
-        
-        list_data.push(string_values);
+        r_values.push(r_column);
+        labels.push(label);
     }
     
-    // Create a simple list structure for R using from_names_and_values
-    let r_values: Vec<Robj> = list_data.into_iter()
-        .map(|col| col.into())
-        .collect();
-    
-    let r_list = List::from_names_and_values(names_vec, r_values)?;
-    
-    Ok(r_list.into())
+    let mut variable_labels: Robj = Strings::from_values(labels).into();
+    variable_labels.set_names(names_vec.clone())?;
+    let mut r_df: Robj = List::from_names_and_values(names_vec, r_values)?.into();
+    r_df.set_attrib("variable.labels", variable_labels)?;
+    // Compact row names, as data.frame() sets them
+    r_df.set_attrib(row_names_symbol(), Integers::from_values([Rint::na(), Rint::from(-(df.height() as i32))]))?;
+    r_df.set_class(["data.frame"])?;
+    Ok(r_df)
+}
+
+/// A column as an R vector with NA for nulls: double for floats, integer
+/// for integers (double when a value is outside R's 32-bit range), logical
+/// for booleans and character for anything else
+fn column_to_r(column: &Column) -> Result<Robj> {
+    let polars_error = |e: PolarsError| Error::Other(format!("Failed to convert column '{}': {}", column.name(), e));
+    let dtype = column.dtype();
+    let robj = if dtype.is_float() {
+        let values = column.cast(&DataType::Float64).map_err(polars_error)?;
+        let values: Vec<Option<f64>> = values.f64().map_err(polars_error)?.into_iter().collect();
+        Doubles::from_values(values.into_iter().map(Rfloat::from)).into()
+    } else if dtype.is_integer() {
+        let values = column.cast(&DataType::Int64).map_err(polars_error)?;
+        let values: Vec<Option<i64>> = values.i64().map_err(polars_error)?.into_iter().collect();
+        // i32::MIN is R's integer NA
+        let fits = values.iter().flatten().all(|&v| v > i32::MIN as i64 && v <= i32::MAX as i64);
+        if fits {
+            Integers::from_values(values.into_iter().map(|v| Rint::from(v.map(|v| v as i32)))).into()
+        } else {
+            Doubles::from_values(values.into_iter().map(|v| Rfloat::from(v.map(|v| v as f64)))).into()
+        }
+    } else if dtype.is_bool() {
+        let values: Vec<Option<bool>> = column.bool().map_err(polars_error)?.into_iter().collect();
+        Logicals::from_values(values.into_iter().map(Rbool::from)).into()
+    } else {
+        let values = column.cast(&DataType::String).map_err(polars_error)?;
+        let values: Vec<Option<String>> = values.str().map_err(polars_error)?.into_iter()
+            .map(|v| v.map(str::to_string))
+            .collect();
+        Strings::from_values(values.into_iter().map(Rstr::from)).into()
+    };
+    Ok(robj)
 }
 
 /// Clean LI-COR variable names to be R-friendly
@@ -204,4 +371,9 @@ extendr_module! {
     mod licorclient;
     fn convert;
     fn file_to_dataframe;
+    fn peek;
+    fn licor_metadata;
+    fn search_variables;
+    fn set_cache_size;
+    fn clear_cache;
 }
\ No newline at end of file